@@ -12,7 +12,10 @@ use num_rational::Ratio;
 #[cfg(feature = "experimental")]
 use num_traits::ToPrimitive;
 #[cfg(feature = "experimental")]
-use prio::dp::distributions::DiscreteGaussian;
+use prio::dp::{
+    distributions::{DiscreteGaussian, FixedPrecisionDiscreteGaussian, ZCdpDiscreteGaussian},
+    DifferentialPrivacyStrategy, Rational, ZCdpBudget,
+};
 #[cfg(feature = "experimental")]
 use prio::idpf::test_utils::generate_zipf_distributed_batch;
 #[cfg(feature = "experimental")]
@@ -25,10 +28,18 @@ use prio::{
     flp::gadgets::Mul,
     vdaf::{prio3::Prio3, Aggregator, Client},
 };
+#[cfg(feature = "crypto-dependencies")]
+use prio::{
+    field::Field64 as F64, flp::gadgets::ParallelSum,
+    vdaf::prio3::Prio3SumVecField64MultiproofHmacSha256Aes128,
+};
 #[cfg(feature = "experimental")]
 use prio::{
     field::{Field255, Field64},
-    flp::types::fixedpoint_l2::FixedPointBoundedL2VecSum,
+    flp::{
+        types::{fixedpoint_l2::FixedPointBoundedL2VecSum, SumVec},
+        TypeWithNoise,
+    },
     idpf::{Idpf, IdpfInput, RingBufferCache},
     vdaf::poplar1::{Poplar1, Poplar1AggregationParam, Poplar1IdpfValue},
 };
@@ -77,6 +88,60 @@ pub fn dp_noise(c: &mut Criterion) {
             BenchmarkId::new("discrete_gaussian", std.to_f64().unwrap_or(f64::INFINITY)),
             |b| b.iter(|| sampler.sample(&mut rng)),
         );
+        if let Ok(sampler) = FixedPrecisionDiscreteGaussian::new(std.clone()) {
+            group.bench_function(
+                BenchmarkId::new(
+                    "fixed_precision_discrete_gaussian",
+                    std.to_f64().unwrap_or(f64::INFINITY),
+                ),
+                |b| b.iter(|| sampler.sample(&mut rng)),
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Speed test for adding discrete gaussian noise to a `SumVec` aggregate result. Noise is drawn
+/// from the fixed-precision sampler; the arbitrary-precision sampler is benchmarked with the same
+/// standard deviation for comparison.
+#[cfg(feature = "experimental")]
+pub fn sumvec_noise(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sumvec_noise");
+    let mut rng = StdRng::seed_from_u64(RNG_SEED);
+    let dp_strategy = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+        Rational::from_unsigned(1u8, 1).unwrap(),
+    ));
+
+    for length in [1_000, 10_000] {
+        let sumvec: SumVec<F, ParallelSum<F, Mul<F>>> = SumVec::new(8, length, 1).unwrap();
+        let mut agg_result = vec![F::zero(); length];
+        group.bench_with_input(
+            BenchmarkId::new("fixed_precision_discrete_gaussian", length),
+            &length,
+            |b, _| {
+                b.iter(|| {
+                    sumvec
+                        .add_noise_to_result_with_rng(&dp_strategy, &mut agg_result, 0, &mut rng)
+                        .unwrap()
+                })
+            },
+        );
+
+        // The L2 sensitivity is (2^8 - 1) * sqrt(length), with the square root rounded up.
+        let sensitivity =
+            Ratio::from_integer(BigUint::from(255 * (length as f64).sqrt().ceil() as u64));
+        let sampler = dp_strategy.create_distribution(sensitivity).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("discrete_gaussian", length),
+            &length,
+            |b, length| {
+                b.iter(|| {
+                    for _ in 0..*length {
+                        black_box(sampler.sample(&mut rng));
+                    }
+                })
+            },
+        );
     }
     group.finish();
 }
@@ -348,6 +413,75 @@ fn prio3(c: &mut Criterion) {
     }
     group.finish();
 
+    #[cfg(feature = "crypto-dependencies")]
+    {
+        type Prio3SumVecHmac =
+            Prio3SumVecField64MultiproofHmacSha256Aes128<ParallelSum<F64, Mul<F64>>>;
+
+        let mut group = c.benchmark_group("prio3sumvec_field64_multiproof_hmacsha256aes128_shard");
+        for (input_length, chunk_length) in [(10, 3), (100, 10), (1_000, 31)] {
+            group.bench_with_input(
+                BenchmarkId::new("serial", input_length),
+                &(input_length, chunk_length),
+                |b, (input_length, chunk_length)| {
+                    let vdaf = Prio3SumVecHmac::new_sum_vec_field64_multiproof_hmacsha256_aes128(
+                        num_shares,
+                        3,
+                        1,
+                        *input_length,
+                        *chunk_length,
+                    )
+                    .unwrap();
+                    let measurement = (0..u64::try_from(*input_length).unwrap())
+                        .map(|i| i & 1)
+                        .collect::<Vec<_>>();
+                    let nonce = black_box([0u8; 16]);
+                    b.iter(|| vdaf.shard(b"", &measurement, &nonce).unwrap());
+                },
+            );
+        }
+        group.finish();
+
+        let mut group =
+            c.benchmark_group("prio3sumvec_field64_multiproof_hmacsha256aes128_prepare_init");
+        for (input_length, chunk_length) in [(10, 3), (100, 10), (1_000, 31)] {
+            group.bench_with_input(
+                BenchmarkId::new("serial", input_length),
+                &(input_length, chunk_length),
+                |b, (input_length, chunk_length)| {
+                    let vdaf = Prio3SumVecHmac::new_sum_vec_field64_multiproof_hmacsha256_aes128(
+                        num_shares,
+                        3,
+                        1,
+                        *input_length,
+                        *chunk_length,
+                    )
+                    .unwrap();
+                    let measurement = (0..u64::try_from(*input_length).unwrap())
+                        .map(|i| i & 1)
+                        .collect::<Vec<_>>();
+                    let nonce = black_box([0u8; 16]);
+                    let verify_key = black_box([0u8; 32]);
+                    let (public_share, input_shares) =
+                        vdaf.shard(b"", &measurement, &nonce).unwrap();
+                    b.iter(|| {
+                        vdaf.prepare_init(
+                            &verify_key,
+                            b"",
+                            0,
+                            &(),
+                            &nonce,
+                            &public_share,
+                            &input_shares[0],
+                        )
+                        .unwrap()
+                    });
+                },
+            );
+        }
+        group.finish();
+    }
+
     let mut group = c.benchmark_group("prio3histogram_shard");
     for (input_length, chunk_length) in [
         (10, 3),
@@ -950,7 +1084,18 @@ fn vidpf(c: &mut Criterion) {
 }
 
 #[cfg(feature = "experimental")]
-criterion_group!(benches, poplar1, prio3, prio2, poly_mul, prng, idpf, dp_noise, vidpf);
+criterion_group!(
+    benches,
+    poplar1,
+    prio3,
+    prio2,
+    poly_mul,
+    prng,
+    idpf,
+    dp_noise,
+    sumvec_noise,
+    vidpf
+);
 #[cfg(not(feature = "experimental"))]
 criterion_group!(benches, prio3, prng, poly_mul);
 
@@ -6,7 +6,7 @@
 //!
 //!  - `DifferentialPrivacyBudget`: Implementors should be types of DP-budgets,
 //!    i.e., methods to measure the amount of privacy provided by DP-mechanisms.
-//!    Examples: zCDP, ApproximateDP (Epsilon-Delta), PureDP
+//!    Examples: zCDP, ApproximateDP (Epsilon-Delta), PureDP, Rényi DP at integer orders
 //!
 //!  - `DifferentialPrivacyDistribution`: Distribution from which noise is sampled.
 //!    Examples: DiscreteGaussian, DiscreteLaplace
@@ -14,8 +14,10 @@
 //!  - `DifferentialPrivacyStrategy`: This is a combination of choices for budget and distribution.
 //!    Examples: zCDP-DiscreteGaussian, EpsilonDelta-DiscreteGaussian
 //!
+use crate::vdaf::xof::{Seed, Xof};
 use num_bigint::{BigInt, BigUint, TryFromBigIntError};
 use num_rational::{BigRational, Ratio};
+use rand_core::CryptoRng;
 use serde::{Deserialize, Serialize};
 
 /// Errors propagated by methods in this module.
@@ -40,6 +42,10 @@ pub enum DpError {
     /// Invalid parameter value.
     #[error("invalid parameter: {0}")]
     InvalidParameter(String),
+
+    /// Recording a privacy loss would exceed the total privacy budget.
+    #[error("DP error: privacy budget exceeded")]
+    BudgetExceeded,
 }
 
 /// Positive arbitrary precision rational number to represent DP and noise distribution parameters in
@@ -121,10 +127,68 @@ impl PureDpBudget {
         }
         Ok(Self { epsilon: epsilon.0 })
     }
+
+    /// The privacy parameter `epsilon`.
+    pub(crate) fn epsilon(&self) -> &Ratio<BigUint> {
+        &self.epsilon
+    }
 }
 
 impl DifferentialPrivacyBudget for PureDpBudget {}
 
+/// Approximate differential privacy budget. ((&epsilon;, &delta;)-DP)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Ord, PartialOrd)]
+pub struct EpsilonDeltaBudget {
+    epsilon: Ratio<BigUint>,
+    delta: Ratio<BigUint>,
+}
+
+impl EpsilonDeltaBudget {
+    /// Create a budget for parameters `epsilon` and `delta`. Errors if `epsilon` is zero, or if
+    /// `delta` is not strictly between zero and one.
+    pub fn new(epsilon: Rational, delta: Rational) -> Result<Self, DpError> {
+        if epsilon.0.numer() == &BigUint::ZERO {
+            return Err(DpError::InvalidParameter("epsilon cannot be zero".into()));
+        }
+        if delta.0.numer() == &BigUint::ZERO || delta.0 >= Ratio::from_integer(BigUint::from(1u8)) {
+            return Err(DpError::InvalidParameter(
+                "delta must be greater than zero and less than one".into(),
+            ));
+        }
+        Ok(Self {
+            epsilon: epsilon.0,
+            delta: delta.0,
+        })
+    }
+}
+
+impl DifferentialPrivacyBudget for EpsilonDeltaBudget {}
+
+/// Rényi differential privacy budget at integer orders, as defined in [[Mir17]], given by a
+/// parameter `rho`. A mechanism satisfies it if it is `(alpha, alpha * rho)`-RDP for every integer
+/// order `alpha >= 2`.
+///
+/// This is weaker than `rho`-zero-concentrated DP, which requires the same bound at every real
+/// order `alpha > 1`.
+///
+/// [Mir17]: https://arxiv.org/pdf/1702.07476.pdf
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Ord, PartialOrd)]
+pub struct IntegerRenyiDpBudget {
+    rho: Ratio<BigUint>,
+}
+
+impl IntegerRenyiDpBudget {
+    /// Create a budget for parameter `rho`. Errors if `rho` is zero.
+    pub fn new(rho: Rational) -> Result<Self, DpError> {
+        if rho.0.numer() == &BigUint::ZERO {
+            return Err(DpError::InvalidParameter("rho cannot be zero".into()));
+        }
+        Ok(Self { rho: rho.0 })
+    }
+}
+
+impl DifferentialPrivacyBudget for IntegerRenyiDpBudget {}
+
 /// This module encapsulates a deserialization helper struct. It is needed so we can wrap its
 /// derived `Deserialize` implementation in a customized `Deserialize` implementation, which makes
 /// use of the budget's constructor to enforce input validation invariants.
@@ -148,6 +212,41 @@ mod budget_serde {
                 .map_err(|_| de::Error::custom("epsilon cannot be zero"))
         }
     }
+
+    #[derive(Deserialize)]
+    pub struct EpsilonDeltaBudget {
+        epsilon: Ratio<BigUint>,
+        delta: Ratio<BigUint>,
+    }
+
+    impl<'de> Deserialize<'de> for super::EpsilonDeltaBudget {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let helper = EpsilonDeltaBudget::deserialize(deserializer)?;
+            super::EpsilonDeltaBudget::new(
+                super::Rational(helper.epsilon),
+                super::Rational(helper.delta),
+            )
+            .map_err(de::Error::custom)
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct IntegerRenyiDpBudget {
+        rho: Ratio<BigUint>,
+    }
+
+    impl<'de> Deserialize<'de> for super::IntegerRenyiDpBudget {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let helper = IntegerRenyiDpBudget::deserialize(deserializer)?;
+            super::IntegerRenyiDpBudget::new(super::Rational(helper.rho)).map_err(de::Error::custom)
+        }
+    }
 }
 
 /// Strategy to make aggregate results differentially private, e.g. by adding noise from a specific
@@ -171,21 +270,96 @@ pub trait DifferentialPrivacyStrategy {
     /// with sensitivity `s` will yield differential privacy of the DP variant given in the
     /// `Budget` type. Can error upon invalid parameters.
     fn create_distribution(&self, s: Self::Sensitivity) -> Result<Self::Distribution, DpError>;
+
+    /// Create a new distribution parametrized s.t. adding a single sample to the result of a
+    /// one-dimensional function with sensitivity `s` will yield differential privacy of the DP
+    /// variant given in the `Budget` type. Some strategies admit a tighter calibration for scalar
+    /// results than for vectors. By default, this is the same as `create_distribution`.
+    fn create_scalar_distribution(
+        &self,
+        s: Self::Sensitivity,
+    ) -> Result<Self::Distribution, DpError> {
+        self.create_distribution(s)
+    }
+
+    /// Check that this strategy can be applied by a VDAF with `num_aggregators` aggregators.
+    /// Strategies that split noise across aggregators override this to reject configurations in
+    /// which there are too few aggregators to add all of the noise shares. By default, any number of
+    /// aggregators is accepted.
+    fn validate_num_aggregators(&self, _num_aggregators: usize) -> Result<(), DpError> {
+        Ok(())
+    }
+}
+
+/// Domain separation tag prefix for [`noise_rng`].
+const DST_NOISE: &[u8] = b"prio dp noise";
+
+/// Derive a random number generator for sampling noise from an XOF seed.
+///
+/// This can be passed to
+/// [`AggregatorWithNoise::add_noise_to_agg_share_with_rng`](crate::vdaf::AggregatorWithNoise::add_noise_to_agg_share_with_rng)
+/// to make noise reproducible, e.g. for test vectors or for audits by a party that holds the seed.
+/// The `domain_separator` distinguishes streams derived from the same seed, for example for
+/// different aggregators or batches. The noise is only private if the seed is secret and each pair
+/// of seed and domain separator is used for at most one aggregate share.
+pub fn noise_rng<P, const SEED_SIZE: usize>(
+    seed: &Seed<SEED_SIZE>,
+    domain_separator: &[u8],
+) -> P::SeedStream
+where
+    P: Xof<SEED_SIZE>,
+    P::SeedStream: CryptoRng,
+{
+    P::seed_stream(seed.as_ref(), &[DST_NOISE, domain_separator], &[])
 }
 
+pub mod accountant;
 pub mod distributions;
+pub mod estimation;
 mod rand_bigint;
 
 #[cfg(test)]
 mod tests {
     use serde_json::json;
 
-    use super::PureDpBudget;
+    use super::{EpsilonDeltaBudget, IntegerRenyiDpBudget, PureDpBudget, Rational};
 
     #[test]
     fn budget_deserialization() {
         serde_json::from_value::<PureDpBudget>(json!({"epsilon": [[1], [1]]})).unwrap();
         serde_json::from_value::<PureDpBudget>(json!({"epsilon": [[0], [1]]})).unwrap_err();
         serde_json::from_value::<PureDpBudget>(json!({"epsilon": [[1], [0]]})).unwrap_err();
+
+        serde_json::from_value::<EpsilonDeltaBudget>(
+            json!({"epsilon": [[1], [1]], "delta": [[1], [100000]]}),
+        )
+        .unwrap();
+        serde_json::from_value::<EpsilonDeltaBudget>(
+            json!({"epsilon": [[0], [1]], "delta": [[1], [100000]]}),
+        )
+        .unwrap_err();
+        serde_json::from_value::<EpsilonDeltaBudget>(
+            json!({"epsilon": [[1], [1]], "delta": [[0], [1]]}),
+        )
+        .unwrap_err();
+        serde_json::from_value::<EpsilonDeltaBudget>(
+            json!({"epsilon": [[1], [1]], "delta": [[1], [1]]}),
+        )
+        .unwrap_err();
+        serde_json::from_value::<EpsilonDeltaBudget>(json!({"epsilon": [[1], [1]]})).unwrap_err();
+
+        serde_json::from_value::<IntegerRenyiDpBudget>(json!({"rho": [[1], [2]]})).unwrap();
+        serde_json::from_value::<IntegerRenyiDpBudget>(json!({"rho": [[0], [1]]})).unwrap_err();
+    }
+
+    #[test]
+    fn epsilon_delta_budget() {
+        let one = || Rational::from_unsigned(1u8, 1).unwrap();
+        let small = || Rational::from_unsigned(1u8, 100).unwrap();
+        EpsilonDeltaBudget::new(one(), small()).unwrap();
+        EpsilonDeltaBudget::new(Rational::from_unsigned(0u8, 1).unwrap(), small()).unwrap_err();
+        EpsilonDeltaBudget::new(one(), Rational::from_unsigned(0u8, 1).unwrap()).unwrap_err();
+        EpsilonDeltaBudget::new(one(), one()).unwrap_err();
+        EpsilonDeltaBudget::new(one(), Rational::from_unsigned(3u8, 2).unwrap()).unwrap_err();
     }
 }
@@ -0,0 +1,751 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Tracking of cumulative privacy loss across repeated collections.
+//!
+//! Each application of a [`DifferentialPrivacyStrategy`] to an aggregate share consumes some of
+//! the privacy budget of the population that contributed to it. When the same population is
+//! queried repeatedly, e.g. once per level of a Poplar1 aggregation, or once per daily Prio3 batch,
+//! these losses compose. A [`PrivacyAccountant`] records each application, composes the losses
+//! under pure DP, zero-concentrated DP (zCDP) and Rényi DP (RDP), and refuses a new collection if
+//! it would exceed the configured total budget. Losses that are only known as
+//! `(epsilon, delta)`-DP guarantees are composed separately.
+//!
+//! Losses are converted between the notions as follows:
+//!
+//!  - `epsilon`-DP implies `1/2 epsilon^2`-zCDP, following Proposition 1.4 of [[BS16]].
+//!  - `rho`-zCDP implies `(alpha, alpha * rho)`-RDP for every order `alpha > 1`, by definition.
+//!  - `epsilon`-DP implies `(alpha, epsilon)`-RDP for every order `alpha > 1`.
+//!  - `rho`-zCDP and `(alpha, epsilon)`-RDP are converted to `(epsilon, delta)`-DP using Corollary
+//!    13 and Proposition 12 of [[CKS20]], respectively.
+//!
+//! Pure DP and zCDP losses are composed exactly, using rational arithmetic. RDP losses are
+//! composed at a fixed set of orders using floating point arithmetic. `(epsilon, delta)`-DP losses
+//! are composed by adding up their parameters (Theorem 3.16 of [[DR14]]), and the result is
+//! composed in the same way with the `(epsilon, delta)` guarantee of all other losses.
+//!
+//! [BS16]: https://arxiv.org/pdf/1605.02065.pdf
+//! [DR14]: https://www.cis.upenn.edu/~aaroth/Papers/privacybook.pdf
+//! [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+//! [`DifferentialPrivacyStrategy`]: super::DifferentialPrivacyStrategy
+
+use super::{
+    distributions::zcdp_to_approx_dp_ln_delta, DpError, EpsilonDeltaBudget, PureDpBudget, Rational,
+    ZCdpBudget,
+};
+use num_bigint::BigUint;
+use num_rational::Ratio;
+use num_traits::{ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+
+/// The Rényi DP orders at which a [`PrivacyAccountant`] tracks losses, unless others are given.
+pub const DEFAULT_RENYI_ORDERS: [f64; 25] = [
+    1.25, 1.5, 1.75, 2.0, 2.25, 2.5, 3.0, 3.5, 4.0, 4.5, 5.0, 6.0, 8.0, 10.0, 12.0, 14.0, 16.0,
+    20.0, 24.0, 28.0, 32.0, 48.0, 64.0, 128.0, 256.0,
+];
+
+/// The privacy loss incurred by a single application of a differentially private mechanism.
+#[derive(Clone, Debug)]
+pub enum PrivacyLoss {
+    /// `epsilon`-DP.
+    PureDp {
+        /// The privacy parameter.
+        epsilon: Rational,
+    },
+    /// `rho`-zero-concentrated DP, as defined in [[BS16]].
+    ///
+    /// [BS16]: https://arxiv.org/pdf/1605.02065.pdf
+    ZCdp {
+        /// The privacy parameter.
+        rho: Rational,
+    },
+    /// Rényi DP, given as a list of `(alpha, epsilon)` pairs, meaning that the mechanism satisfies
+    /// `(alpha, epsilon)`-RDP for each pair. Orders tracked by the accountant that are not present
+    /// in the list are treated as having an unbounded loss.
+    RenyiDp(Vec<(f64, f64)>),
+    /// `(epsilon, delta)`-DP.
+    ApproxDp {
+        /// The privacy parameter `epsilon`.
+        epsilon: Rational,
+        /// The privacy parameter `delta`.
+        delta: Rational,
+    },
+}
+
+/// Types, usually [`DifferentialPrivacyStrategy`] implementations, that can report the privacy
+/// loss incurred each time they are applied to an aggregate.
+///
+/// [`DifferentialPrivacyStrategy`]: super::DifferentialPrivacyStrategy
+pub trait PrivacyCost {
+    /// The privacy loss of a single application of this strategy.
+    fn privacy_loss(&self) -> Result<PrivacyLoss, DpError>;
+}
+
+/// The total privacy budget enforced by a [`PrivacyAccountant`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TotalBudget {
+    /// A total pure DP budget. Only pure DP losses can be charged against it.
+    PureDp(PureDpBudget),
+    /// A total zCDP budget of `1/2 epsilon^2`, using the same notation as [`ZCdpBudget`]. Rényi
+    /// DP and `(epsilon, delta)`-DP losses cannot be charged against it.
+    ZCdp(ZCdpBudget),
+    /// A total `(epsilon, delta)`-DP budget.
+    EpsilonDelta(EpsilonDeltaBudget),
+}
+
+/// Records applications of differential privacy strategies to aggregates computed over the same
+/// population, and tracks the cumulative privacy loss.
+///
+/// The accountant can be serialized, so that it can be persisted between collections. Unbounded
+/// Rényi DP losses are serialized as `null`.
+#[derive(Clone, Debug)]
+pub struct PrivacyAccountant {
+    budget: TotalBudget,
+    num_collections: usize,
+    /// The composed pure DP loss, or `None` if a loss that is not pure DP has been recorded.
+    /// This, `rho` and `renyi_epsilons` exclude `(epsilon, delta)`-DP losses.
+    pure_epsilon: Option<Ratio<BigUint>>,
+    /// The composed zCDP loss, or `None` if a Rényi DP loss has been recorded.
+    rho: Option<Ratio<BigUint>>,
+    /// The composed Rényi DP loss at each order in `renyi_orders`.
+    renyi_orders: Vec<f64>,
+    renyi_epsilons: Vec<f64>,
+    /// The composed `(epsilon, delta)`-DP losses.
+    approx_epsilon: Ratio<BigUint>,
+    approx_delta: Ratio<BigUint>,
+}
+
+impl PrivacyAccountant {
+    /// Construct an accountant that enforces the given total budget, tracking Rényi DP losses at
+    /// [`DEFAULT_RENYI_ORDERS`].
+    pub fn new(budget: TotalBudget) -> Self {
+        Self::with_renyi_orders(budget, DEFAULT_RENYI_ORDERS.to_vec())
+            .expect("default orders are valid")
+    }
+
+    /// Construct an accountant that enforces the given total budget, tracking Rényi DP losses at
+    /// the given orders. Errors if `renyi_orders` is empty or if any order is not a finite number
+    /// greater than one.
+    pub fn with_renyi_orders(budget: TotalBudget, renyi_orders: Vec<f64>) -> Result<Self, DpError> {
+        if renyi_orders.is_empty() {
+            return Err(DpError::InvalidParameter(
+                "at least one Renyi order is required".into(),
+            ));
+        }
+        if renyi_orders
+            .iter()
+            .any(|alpha| !alpha.is_finite() || *alpha <= 1.0)
+        {
+            return Err(DpError::InvalidParameter(
+                "Renyi orders must be finite and greater than one".into(),
+            ));
+        }
+        let renyi_epsilons = vec![0.0; renyi_orders.len()];
+        Ok(Self {
+            budget,
+            num_collections: 0,
+            pure_epsilon: Some(Ratio::zero()),
+            rho: Some(Ratio::zero()),
+            renyi_orders,
+            renyi_epsilons,
+            approx_epsilon: Ratio::zero(),
+            approx_delta: Ratio::zero(),
+        })
+    }
+
+    /// The total budget enforced by this accountant.
+    pub fn budget(&self) -> &TotalBudget {
+        &self.budget
+    }
+
+    /// The number of collections charged so far.
+    pub fn num_collections(&self) -> usize {
+        self.num_collections
+    }
+
+    /// Record one application of `strategy`. Errors, without recording anything, if this would
+    /// exceed the total budget.
+    pub fn charge<S: PrivacyCost>(&mut self, strategy: &S) -> Result<(), DpError> {
+        self.charge_loss(strategy.privacy_loss()?)
+    }
+
+    /// Record a privacy loss. Errors, without recording anything, if this would exceed the total
+    /// budget.
+    pub fn charge_loss(&mut self, loss: PrivacyLoss) -> Result<(), DpError> {
+        let mut next = self.clone();
+        next.compose(&loss)?;
+        if !next.within_budget() {
+            return Err(DpError::BudgetExceeded);
+        }
+        next.num_collections += 1;
+        *self = next;
+        Ok(())
+    }
+
+    /// Check whether a privacy loss could be recorded without exceeding the total budget.
+    pub fn can_charge(&self, loss: &PrivacyLoss) -> bool {
+        let mut next = self.clone();
+        next.compose(loss).is_ok() && next.within_budget()
+    }
+
+    /// The composed pure DP loss, or `None` if a loss that is not pure DP has been recorded.
+    pub fn pure_dp_epsilon(&self) -> Option<f64> {
+        self.pure_epsilon
+            .as_ref()
+            .filter(|_| !self.has_approx_dp_loss())
+            .and_then(ToPrimitive::to_f64)
+    }
+
+    /// The composed zCDP loss `rho`, or `None` if a Rényi DP or `(epsilon, delta)`-DP loss has been
+    /// recorded.
+    pub fn zcdp_rho(&self) -> Option<f64> {
+        self.rho
+            .as_ref()
+            .filter(|_| !self.has_approx_dp_loss())
+            .and_then(ToPrimitive::to_f64)
+    }
+
+    /// The composed Rényi DP loss, as a list of `(alpha, epsilon)` pairs. The loss is unbounded at
+    /// every order if an `(epsilon, delta)`-DP loss has been recorded.
+    pub fn renyi_dp(&self) -> Vec<(f64, f64)> {
+        self.renyi_orders
+            .iter()
+            .copied()
+            .zip(self.renyi_epsilons.iter().map(|epsilon| {
+                if self.has_approx_dp_loss() {
+                    f64::INFINITY
+                } else {
+                    *epsilon
+                }
+            }))
+            .collect()
+    }
+
+    /// The smallest `epsilon` such that the composed loss implies `(epsilon, delta)`-DP, using the
+    /// tightest conversion available.
+    pub fn epsilon_for_delta(&self, delta: f64) -> f64 {
+        let approx_delta = self.approx_delta.to_f64().unwrap_or(f64::INFINITY);
+        if approx_delta > delta {
+            return f64::INFINITY;
+        }
+        self.approx_epsilon.to_f64().unwrap_or(f64::INFINITY)
+            + self.epsilon_for_delta_excluding_approx_dp(delta - approx_delta)
+    }
+
+    fn has_approx_dp_loss(&self) -> bool {
+        !self.approx_delta.is_zero()
+    }
+
+    /// Like [`Self::epsilon_for_delta`], but ignoring `(epsilon, delta)`-DP losses.
+    fn epsilon_for_delta_excluding_approx_dp(&self, delta: f64) -> f64 {
+        let mut best = self
+            .pure_epsilon
+            .as_ref()
+            .and_then(ToPrimitive::to_f64)
+            .unwrap_or(f64::INFINITY);
+        if !(delta > 0.0 && delta < 1.0) {
+            return best;
+        }
+
+        // Convert from Rényi DP, following Proposition 12 of [CKS20].
+        for (&alpha, &renyi_epsilon) in self.renyi_orders.iter().zip(&self.renyi_epsilons) {
+            let epsilon = renyi_epsilon + ((alpha - 1.0) / alpha).ln()
+                - (delta.ln() + alpha.ln()) / (alpha - 1.0);
+            best = best.min(epsilon.max(0.0));
+        }
+
+        // Convert from zCDP, following Corollary 13 of [CKS20]. The achieved delta is decreasing
+        // in epsilon, so bisect below the best bound found so far.
+        if let Some(rho) = self.rho.as_ref().and_then(ToPrimitive::to_f64) {
+            let ln_delta = delta.ln();
+            let satisfies = |epsilon| zcdp_to_approx_dp_ln_delta(rho, epsilon) <= ln_delta;
+            let mut high = if best.is_finite() {
+                best
+            } else {
+                rho + 2.0 * (rho * -ln_delta).sqrt()
+            };
+            if satisfies(high) {
+                let mut low = 0.0;
+                for _ in 0..100 {
+                    let mid = (low + high) / 2.0;
+                    if satisfies(mid) {
+                        high = mid;
+                    } else {
+                        low = mid;
+                    }
+                }
+                best = best.min(high);
+            }
+        }
+
+        best
+    }
+
+    fn compose(&mut self, loss: &PrivacyLoss) -> Result<(), DpError> {
+        match loss {
+            PrivacyLoss::PureDp { epsilon } => {
+                let epsilon = &epsilon.0;
+                let epsilon_f64 = to_f64(epsilon)?;
+                if let Some(pure_epsilon) = self.pure_epsilon.as_mut() {
+                    *pure_epsilon += epsilon;
+                }
+                if let Some(rho) = self.rho.as_mut() {
+                    *rho += epsilon * epsilon / BigUint::from(2u8);
+                }
+                for (alpha, renyi_epsilon) in
+                    self.renyi_orders.iter().zip(self.renyi_epsilons.iter_mut())
+                {
+                    *renyi_epsilon += epsilon_f64.min(alpha * epsilon_f64 * epsilon_f64 / 2.0);
+                }
+            }
+            PrivacyLoss::ZCdp { rho } => {
+                let rho = &rho.0;
+                let rho_f64 = to_f64(rho)?;
+                self.pure_epsilon = None;
+                if let Some(total_rho) = self.rho.as_mut() {
+                    *total_rho += rho;
+                }
+                for (alpha, renyi_epsilon) in
+                    self.renyi_orders.iter().zip(self.renyi_epsilons.iter_mut())
+                {
+                    *renyi_epsilon += alpha * rho_f64;
+                }
+            }
+            PrivacyLoss::RenyiDp(curve) => {
+                if curve
+                    .iter()
+                    .any(|(alpha, epsilon)| alpha.is_nan() || epsilon.is_nan() || *epsilon < 0.0)
+                {
+                    return Err(DpError::InvalidParameter(
+                        "Renyi DP losses must be non-negative numbers".into(),
+                    ));
+                }
+                self.pure_epsilon = None;
+                self.rho = None;
+                for (alpha, renyi_epsilon) in
+                    self.renyi_orders.iter().zip(self.renyi_epsilons.iter_mut())
+                {
+                    *renyi_epsilon += curve
+                        .iter()
+                        .find(|(order, _)| order == alpha)
+                        .map_or(f64::INFINITY, |(_, epsilon)| *epsilon);
+                }
+            }
+            PrivacyLoss::ApproxDp { epsilon, delta } => {
+                if delta.0.is_zero() {
+                    return self.compose(&PrivacyLoss::PureDp {
+                        epsilon: epsilon.clone(),
+                    });
+                }
+                self.approx_epsilon += &epsilon.0;
+                self.approx_delta += &delta.0;
+            }
+        }
+        Ok(())
+    }
+
+    fn within_budget(&self) -> bool {
+        match &self.budget {
+            TotalBudget::PureDp(budget) => {
+                !self.has_approx_dp_loss()
+                    && self
+                        .pure_epsilon
+                        .as_ref()
+                        .is_some_and(|epsilon| epsilon <= &budget.epsilon)
+            }
+            TotalBudget::ZCdp(budget) => {
+                !self.has_approx_dp_loss()
+                    && self.rho.as_ref().is_some_and(|rho| {
+                        rho * BigUint::from(2u8) <= &budget.epsilon * &budget.epsilon
+                    })
+            }
+            TotalBudget::EpsilonDelta(budget) => {
+                // The `(epsilon, delta)`-DP losses are checked exactly, and the rest of the budget
+                // is left for the other losses.
+                if self.approx_epsilon > budget.epsilon || self.approx_delta > budget.delta {
+                    return false;
+                }
+                let epsilon = &budget.epsilon - &self.approx_epsilon;
+                let delta = &budget.delta - &self.approx_delta;
+                if self
+                    .pure_epsilon
+                    .as_ref()
+                    .is_some_and(|pure_epsilon| pure_epsilon <= &epsilon)
+                {
+                    return true;
+                }
+                match (epsilon.to_f64(), delta.to_f64()) {
+                    (Some(epsilon), Some(delta)) => {
+                        self.epsilon_for_delta_excluding_approx_dp(delta) <= epsilon
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+fn to_f64(value: &Ratio<BigUint>) -> Result<f64, DpError> {
+    value
+        .to_f64()
+        .filter(|value| value.is_finite())
+        .ok_or(DpError::InvalidFloat)
+}
+
+/// This module encapsulates a serialization helper struct. Its derived implementations are wrapped
+/// in customized ones, which represent unbounded Rényi DP losses explicitly, since they can't be
+/// round-tripped as floats by every format, and which enforce the invariants of
+/// [`PrivacyAccountant::with_renyi_orders`] on deserialization.
+mod accountant_serde {
+    use super::TotalBudget;
+    use num_bigint::BigUint;
+    use num_rational::Ratio;
+    use serde::{de, Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct PrivacyAccountant {
+        budget: TotalBudget,
+        num_collections: usize,
+        pure_epsilon: Option<Ratio<BigUint>>,
+        rho: Option<Ratio<BigUint>>,
+        renyi_orders: Vec<f64>,
+        /// The composed Rényi DP loss at each order, or `None` if it is unbounded.
+        renyi_epsilons: Vec<Option<f64>>,
+        #[serde(default)]
+        approx_epsilon: Ratio<BigUint>,
+        #[serde(default)]
+        approx_delta: Ratio<BigUint>,
+    }
+
+    impl Serialize for super::PrivacyAccountant {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            PrivacyAccountant {
+                budget: self.budget.clone(),
+                num_collections: self.num_collections,
+                pure_epsilon: self.pure_epsilon.clone(),
+                rho: self.rho.clone(),
+                renyi_orders: self.renyi_orders.clone(),
+                renyi_epsilons: self
+                    .renyi_epsilons
+                    .iter()
+                    .map(|epsilon| Some(*epsilon).filter(|epsilon| epsilon.is_finite()))
+                    .collect(),
+                approx_epsilon: self.approx_epsilon.clone(),
+                approx_delta: self.approx_delta.clone(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for super::PrivacyAccountant {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let helper = PrivacyAccountant::deserialize(deserializer)?;
+            if helper.renyi_epsilons.len() != helper.renyi_orders.len() {
+                return Err(de::Error::custom(
+                    "Renyi orders and losses must have the same length",
+                ));
+            }
+            let renyi_epsilons = helper
+                .renyi_epsilons
+                .into_iter()
+                .map(|epsilon| match epsilon {
+                    Some(epsilon) if epsilon.is_finite() && epsilon >= 0.0 => Ok(epsilon),
+                    Some(_) => Err(de::Error::custom(
+                        "Renyi losses must be finite and non-negative",
+                    )),
+                    None => Ok(f64::INFINITY),
+                })
+                .collect::<Result<Vec<_>, D::Error>>()?;
+
+            let accountant =
+                super::PrivacyAccountant::with_renyi_orders(helper.budget, helper.renyi_orders)
+                    .map_err(de::Error::custom)?;
+            Ok(super::PrivacyAccountant {
+                num_collections: helper.num_collections,
+                pure_epsilon: helper.pure_epsilon,
+                rho: helper.rho,
+                renyi_epsilons,
+                approx_epsilon: helper.approx_epsilon,
+                approx_delta: helper.approx_delta,
+                ..accountant
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PrivacyAccountant, PrivacyLoss, TotalBudget};
+    use crate::dp::{
+        distributions::{
+            EpsilonDeltaDiscreteGaussian, PureDpDiscreteLaplace, ZCdpDiscreteGaussian,
+        },
+        DifferentialPrivacyStrategy, DpError, EpsilonDeltaBudget, PureDpBudget, Rational,
+        ZCdpBudget,
+    };
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    fn rational(n: u32, d: u32) -> Rational {
+        Rational::from_unsigned(n, d).unwrap()
+    }
+
+    #[test]
+    fn pure_dp_composition() {
+        let mut accountant = PrivacyAccountant::new(TotalBudget::PureDp(
+            PureDpBudget::new(rational(1, 1)).unwrap(),
+        ));
+        let strategy =
+            PureDpDiscreteLaplace::from_budget(PureDpBudget::new(rational(1, 4)).unwrap());
+
+        for _ in 0..4 {
+            accountant.charge(&strategy).unwrap();
+        }
+        assert_eq!(accountant.num_collections(), 4);
+        assert_eq!(accountant.pure_dp_epsilon(), Some(1.0));
+        assert_eq!(accountant.zcdp_rho(), Some(4.0 * (0.25 * 0.25) / 2.0));
+
+        // The next collection would exceed the budget, and is not recorded.
+        assert_matches!(accountant.charge(&strategy), Err(DpError::BudgetExceeded));
+        assert_eq!(accountant.num_collections(), 4);
+        assert_eq!(accountant.pure_dp_epsilon(), Some(1.0));
+
+        // A zCDP loss can't be charged against a pure DP budget.
+        assert!(!accountant.can_charge(&PrivacyLoss::ZCdp {
+            rho: rational(0, 1)
+        }));
+    }
+
+    #[test]
+    fn zcdp_composition() {
+        // A total budget of rho = 1/2.
+        let mut accountant =
+            PrivacyAccountant::new(TotalBudget::ZCdp(ZCdpBudget::new(rational(1, 1))));
+        // Each collection costs rho = 1/8.
+        let strategy = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(rational(1, 2)));
+
+        for _ in 0..4 {
+            accountant.charge(&strategy).unwrap();
+        }
+        assert_eq!(accountant.zcdp_rho(), Some(0.5));
+        assert_eq!(accountant.pure_dp_epsilon(), None);
+        for (alpha, epsilon) in accountant.renyi_dp() {
+            assert!((epsilon - alpha * 0.5).abs() < 1e-9);
+        }
+        assert_matches!(accountant.charge(&strategy), Err(DpError::BudgetExceeded));
+
+        // Pure DP losses are converted to zCDP.
+        let mut accountant =
+            PrivacyAccountant::new(TotalBudget::ZCdp(ZCdpBudget::new(rational(1, 1))));
+        accountant
+            .charge(&PureDpDiscreteLaplace::from_budget(
+                PureDpBudget::new(rational(1, 1)).unwrap(),
+            ))
+            .unwrap();
+        assert_eq!(accountant.zcdp_rho(), Some(0.5));
+        assert!(!accountant.can_charge(&PrivacyLoss::PureDp {
+            epsilon: rational(1, 100)
+        }));
+    }
+
+    #[test]
+    fn epsilon_delta_composition() {
+        let budget = EpsilonDeltaBudget::new(rational(1, 1), rational(1, 100_000)).unwrap();
+        let mut accountant = PrivacyAccountant::new(TotalBudget::EpsilonDelta(budget.clone()));
+
+        // A strategy calibrated to the whole budget can be applied once.
+        let strategy = EpsilonDeltaDiscreteGaussian::from_budget(budget);
+        accountant.charge(&strategy).unwrap();
+        assert_eq!(accountant.epsilon_for_delta(1e-5), 1.0);
+        assert_eq!(accountant.epsilon_for_delta(1e-6), f64::INFINITY);
+        assert_eq!(accountant.pure_dp_epsilon(), None);
+        assert_eq!(accountant.zcdp_rho(), None);
+        assert_matches!(accountant.charge(&strategy), Err(DpError::BudgetExceeded));
+
+        // (epsilon, delta) losses compose by adding their parameters, and leave the rest of the
+        // budget to other losses.
+        let mut accountant = PrivacyAccountant::new(TotalBudget::EpsilonDelta(
+            EpsilonDeltaBudget::new(rational(2, 1), rational(1, 100_000)).unwrap(),
+        ));
+        let loss = PrivacyLoss::ApproxDp {
+            epsilon: rational(1, 2),
+            delta: rational(1, 400_000),
+        };
+        accountant.charge_loss(loss.clone()).unwrap();
+        accountant.charge_loss(loss.clone()).unwrap();
+        assert_eq!(accountant.epsilon_for_delta(5e-6), 1.0);
+        accountant
+            .charge(&ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+                rational(1, 10),
+            )))
+            .unwrap();
+        let epsilon = accountant.epsilon_for_delta(1e-5);
+        assert!(epsilon > 1.0 && epsilon <= 2.0, "{epsilon}");
+        assert!(!accountant.can_charge(&PrivacyLoss::PureDp {
+            epsilon: rational(1, 1)
+        }));
+
+        // An (epsilon, delta) loss can't be charged against a pure DP or zCDP budget, unless
+        // delta is zero.
+        for budget in [
+            TotalBudget::PureDp(PureDpBudget::new(rational(1, 1)).unwrap()),
+            TotalBudget::ZCdp(ZCdpBudget::new(rational(1, 1))),
+        ] {
+            let accountant = PrivacyAccountant::new(budget);
+            assert!(!accountant.can_charge(&loss));
+            assert!(accountant.can_charge(&PrivacyLoss::ApproxDp {
+                epsilon: rational(1, 10),
+                delta: rational(0, 1),
+            }));
+        }
+
+        // Many small zCDP collections compose better than the sum of their epsilons would
+        // suggest.
+        let mut accountant = PrivacyAccountant::new(TotalBudget::EpsilonDelta(
+            EpsilonDeltaBudget::new(rational(1, 1), rational(1, 100_000)).unwrap(),
+        ));
+        let strategy = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(rational(1, 20)));
+        let mut count = 0;
+        while accountant.charge(&strategy).is_ok() {
+            count += 1;
+        }
+        assert_eq!(accountant.num_collections(), count);
+        assert!(count > 20, "{count}");
+
+        // The zCDP and Renyi DP conversions agree closely.
+        let rho = accountant.zcdp_rho().unwrap();
+        let renyi_only = PrivacyLoss::RenyiDp(
+            accountant
+                .renyi_dp()
+                .into_iter()
+                .map(|(alpha, _)| (alpha, alpha * rho))
+                .collect(),
+        );
+        let mut renyi_accountant = PrivacyAccountant::new(TotalBudget::EpsilonDelta(
+            EpsilonDeltaBudget::new(rational(2, 1), rational(1, 100_000)).unwrap(),
+        ));
+        renyi_accountant.charge_loss(renyi_only).unwrap();
+        assert_eq!(renyi_accountant.zcdp_rho(), None);
+        let from_zcdp = accountant.epsilon_for_delta(1e-5);
+        let from_renyi = renyi_accountant.epsilon_for_delta(1e-5);
+        assert!(from_zcdp <= from_renyi);
+        assert!(from_renyi - from_zcdp < 0.05, "{from_zcdp} {from_renyi}");
+    }
+
+    #[test]
+    fn renyi_dp_loss() {
+        let mut accountant = PrivacyAccountant::with_renyi_orders(
+            TotalBudget::EpsilonDelta(
+                EpsilonDeltaBudget::new(rational(3, 1), rational(1, 1_000_000)).unwrap(),
+            ),
+            vec![2.0, 8.0, 32.0],
+        )
+        .unwrap();
+
+        // Orders that are missing from a loss are unbounded.
+        accountant
+            .charge_loss(PrivacyLoss::RenyiDp(vec![(8.0, 0.5), (32.0, 1.0)]))
+            .unwrap();
+        assert_eq!(
+            accountant.renyi_dp(),
+            vec![(2.0, f64::INFINITY), (8.0, 0.5), (32.0, 1.0)]
+        );
+        assert_matches!(
+            accountant.charge_loss(PrivacyLoss::RenyiDp(vec![(8.0, 10.0), (32.0, 10.0)])),
+            Err(DpError::BudgetExceeded)
+        );
+
+        // A zCDP budget can't be checked against Renyi DP losses.
+        let mut accountant =
+            PrivacyAccountant::new(TotalBudget::ZCdp(ZCdpBudget::new(rational(1, 1))));
+        assert_matches!(
+            accountant.charge_loss(PrivacyLoss::RenyiDp(vec![(2.0, 0.0)])),
+            Err(DpError::BudgetExceeded)
+        );
+        assert_matches!(
+            accountant.charge_loss(PrivacyLoss::RenyiDp(vec![(2.0, f64::NAN)])),
+            Err(DpError::InvalidParameter(_))
+        );
+
+        PrivacyAccountant::with_renyi_orders(
+            TotalBudget::ZCdp(ZCdpBudget::new(rational(1, 1))),
+            vec![],
+        )
+        .unwrap_err();
+        PrivacyAccountant::with_renyi_orders(
+            TotalBudget::ZCdp(ZCdpBudget::new(rational(1, 1))),
+            vec![1.0],
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn serialization() {
+        let mut accountant =
+            PrivacyAccountant::new(TotalBudget::ZCdp(ZCdpBudget::new(rational(1, 1))));
+        accountant
+            .charge(&ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+                rational(1, 2),
+            )))
+            .unwrap();
+
+        let mut restored: PrivacyAccountant =
+            serde_json::from_str(&serde_json::to_string(&accountant).unwrap()).unwrap();
+        assert_eq!(restored.num_collections(), 1);
+        assert_eq!(restored.zcdp_rho(), accountant.zcdp_rho());
+        assert_eq!(restored.budget(), accountant.budget());
+        restored
+            .charge(&ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+                rational(1, 2),
+            )))
+            .unwrap();
+
+        // (epsilon, delta) losses survive a round trip.
+        let mut accountant = PrivacyAccountant::new(TotalBudget::EpsilonDelta(
+            EpsilonDeltaBudget::new(rational(1, 1), rational(1, 100_000)).unwrap(),
+        ));
+        accountant
+            .charge_loss(PrivacyLoss::ApproxDp {
+                epsilon: rational(1, 2),
+                delta: rational(1, 1_000_000),
+            })
+            .unwrap();
+        let restored: PrivacyAccountant =
+            serde_json::from_str(&serde_json::to_string(&accountant).unwrap()).unwrap();
+        assert_eq!(restored.epsilon_for_delta(1e-5), 0.5);
+        assert_eq!(restored.epsilon_for_delta(1e-7), f64::INFINITY);
+
+        // Unbounded Renyi DP losses survive a round trip.
+        let mut accountant = PrivacyAccountant::with_renyi_orders(
+            TotalBudget::EpsilonDelta(
+                EpsilonDeltaBudget::new(rational(3, 1), rational(1, 1_000_000)).unwrap(),
+            ),
+            vec![2.0, 8.0],
+        )
+        .unwrap();
+        accountant
+            .charge_loss(PrivacyLoss::RenyiDp(vec![(8.0, 0.5)]))
+            .unwrap();
+        assert_eq!(accountant.renyi_dp(), [(2.0, f64::INFINITY), (8.0, 0.5)]);
+        let encoded = serde_json::to_value(&accountant).unwrap();
+        let restored: PrivacyAccountant = serde_json::from_value(encoded.clone()).unwrap();
+        assert_eq!(restored.renyi_dp(), accountant.renyi_dp());
+        assert_eq!(restored.num_collections(), 1);
+
+        // Deserialization enforces the same invariants as the constructor.
+        let mut invalid = encoded.clone();
+        invalid["renyi_orders"] = json!([2.0]);
+        serde_json::from_value::<PrivacyAccountant>(invalid).unwrap_err();
+        let mut invalid = encoded.clone();
+        invalid["renyi_orders"] = json!([1.0, 8.0]);
+        serde_json::from_value::<PrivacyAccountant>(invalid).unwrap_err();
+        let mut invalid = encoded;
+        invalid["renyi_epsilons"] = json!([-1.0, 0.5]);
+        serde_json::from_value::<PrivacyAccountant>(invalid).unwrap_err();
+    }
+}
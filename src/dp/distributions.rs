@@ -54,16 +54,27 @@ use num_bigint::{BigInt, BigUint};
 use num_integer::Integer;
 use num_iter::range_inclusive;
 use num_rational::Ratio;
-use num_traits::{One, Zero};
+use num_traits::{One, ToPrimitive, Zero};
 use rand::{distr::Distribution, Rng};
 use serde::{Deserialize, Serialize};
 
 use super::{
+    accountant::{PrivacyCost, PrivacyLoss},
+    estimation::{
+        discrete_gaussian_log_mgf_bound, discrete_laplace_log_mgf, discrete_laplace_variance,
+        NoiseDistribution,
+    },
     DifferentialPrivacyBudget, DifferentialPrivacyDistribution, DifferentialPrivacyStrategy,
-    DpError, PureDpBudget, ZCdpBudget,
+    DpError, EpsilonDeltaBudget, PureDpBudget, Rational, ZCdpBudget,
 };
 use crate::dp::rand_bigint::UniformBigUint;
 
+mod fixed_precision;
+mod skellam;
+
+pub use fixed_precision::{FixedPrecisionDiscreteGaussian, FixedPrecisionDiscreteLaplace};
+pub use skellam::{RenyiDpSkellam, Skellam, SkellamDpStrategy};
+
 /// Sample from the Bernoulli(gamma) distribution, where $gamma /leq 1$.
 ///
 /// `sample_bernoulli(gamma, rng)` returns numbers distributed as $Bernoulli(gamma)$.
@@ -114,7 +125,7 @@ fn sample_bernoulli_exp1<R: Rng + ?Sized>(gamma: &Ratio<BigUint>, rng: &mut R) -
 /// splitting the branches into two non-recursive functions. This is the `gamma > 1` branch.
 ///
 /// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
-fn sample_bernoulli_exp<R: Rng + ?Sized>(gamma: &Ratio<BigUint>, rng: &mut R) -> bool {
+pub(crate) fn sample_bernoulli_exp<R: Rng + ?Sized>(gamma: &Ratio<BigUint>, rng: &mut R) -> bool {
     assert!(!gamma.denom().is_zero());
     for _ in range_inclusive(BigUint::one(), gamma.floor().to_integer()) {
         if !sample_bernoulli_exp1(&Ratio::<BigUint>::one(), rng) {
@@ -256,6 +267,19 @@ impl Distribution<BigInt> for DiscreteGaussian {
 
 impl DifferentialPrivacyDistribution for DiscreteGaussian {}
 
+impl NoiseDistribution for DiscreteGaussian {
+    /// The variance of the discrete Gaussian is at most `std^2` [[CKS20]].
+    ///
+    /// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+    fn variance(&self) -> f64 {
+        ratio_to_f64(&self.std).powi(2)
+    }
+
+    fn log_mgf(&self, t: f64) -> f64 {
+        discrete_gaussian_log_mgf_bound(ratio_to_f64(&self.std), t)
+    }
+}
+
 /// A DP strategy using the discrete gaussian distribution.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Ord, PartialOrd)]
 pub struct DiscreteGaussianDpStrategy<B>
@@ -293,6 +317,396 @@ impl DifferentialPrivacyStrategy for DiscreteGaussianDpStrategy<ZCdpBudget> {
     }
 }
 
+impl PrivacyCost for ZCdpDiscreteGaussian {
+    /// Each application costs `rho = 1/2 epsilon^2`.
+    fn privacy_loss(&self) -> Result<PrivacyLoss, DpError> {
+        let epsilon = &self.budget.epsilon;
+        Ok(PrivacyLoss::ZCdp {
+            rho: Rational(epsilon * epsilon / BigUint::from(2u8)),
+        })
+    }
+}
+
+/// A DP strategy using the discrete gaussian distribution providing approximate
+/// (&epsilon;, &delta;)-DP.
+///
+/// This uses L2-sensitivity, with the substitution definition of neighboring datasets.
+///
+/// Noise for scalar queries is calibrated with the tight `(epsilon, delta)` bound for a
+/// one-dimensional discrete gaussian from Theorem 7 of [[CKS20]]. That bound does not apply to
+/// vector-valued queries with L2-sensitivity, such as histograms, so noise for those is calibrated
+/// through zero-concentrated DP, which is then converted into an `(epsilon, delta)` guarantee
+/// (Corollary 13 of [[CKS20]]).
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+pub type EpsilonDeltaDiscreteGaussian = DiscreteGaussianDpStrategy<EpsilonDeltaBudget>;
+
+impl DifferentialPrivacyStrategy for DiscreteGaussianDpStrategy<EpsilonDeltaBudget> {
+    type Budget = EpsilonDeltaBudget;
+    type Distribution = DiscreteGaussian;
+    type Sensitivity = Ratio<BigUint>;
+
+    fn from_budget(budget: EpsilonDeltaBudget) -> DiscreteGaussianDpStrategy<EpsilonDeltaBudget> {
+        DiscreteGaussianDpStrategy { budget }
+    }
+
+    /// Create a new sampler from the Discrete Gaussian Distribution with a standard deviation
+    /// calibrated to provide `(epsilon, delta)`-differential privacy when added to the result of an
+    /// integer-valued function with L2-sensitivity `sensitivity`.
+    ///
+    /// Adding discrete gaussian noise with standard deviation `sigma` provides
+    /// `rho = sensitivity^2 / (2 * sigma^2)` zero-concentrated differential privacy, following
+    /// Theorem 4 from [[CKS20]]. This is converted into an `(epsilon, delta)` guarantee using the
+    /// zCDP conversion from Corollary 13 of [[CKS20]]. The smallest `sigma` satisfying the budget is
+    /// found by bisection, and it is then rounded up to a multiple of `2^-32` times the
+    /// sensitivity, so that rounding errors can only increase the amount of noise.
+    ///
+    /// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+    fn create_distribution(
+        &self,
+        sensitivity: Ratio<BigUint>,
+    ) -> Result<DiscreteGaussian, DpError> {
+        let multiplier = zcdp_conversion_noise_multiplier(&self.budget)?;
+        DiscreteGaussian::new(sensitivity * multiplier)
+    }
+
+    /// Create a new sampler from the Discrete Gaussian Distribution with a standard deviation
+    /// calibrated to provide `(epsilon, delta)`-differential privacy when added to the result of a
+    /// one-dimensional integer-valued function with sensitivity `sensitivity`.
+    ///
+    /// The standard deviation is the smallest one satisfying the tight bound from Theorem 7 of
+    /// [[CKS20]], rounded up to a multiple of `2^-32`. It is never larger than the one returned
+    /// by `create_distribution` for the same sensitivity.
+    ///
+    /// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+    fn create_scalar_distribution(
+        &self,
+        sensitivity: Ratio<BigUint>,
+    ) -> Result<DiscreteGaussian, DpError> {
+        DiscreteGaussian::new(scalar_noise_std(&self.budget, &sensitivity)?)
+    }
+}
+
+impl PrivacyCost for EpsilonDeltaDiscreteGaussian {
+    /// Scalar queries are calibrated directly to the `(epsilon, delta)` budget, so each
+    /// application costs the whole budget.
+    fn privacy_loss(&self) -> Result<PrivacyLoss, DpError> {
+        Ok(PrivacyLoss::ApproxDp {
+            epsilon: Rational(self.budget.epsilon.clone()),
+            delta: Rational(self.budget.delta.clone()),
+        })
+    }
+}
+
+/// Compute the ratio of the standard deviation of discrete gaussian noise to the L2-sensitivity
+/// that is needed to satisfy the given `(epsilon, delta)` budget, rounded up to a multiple of
+/// `2^-32`.
+///
+/// This is the smallest multiplier for which the zCDP guarantee of the discrete gaussian, converted
+/// with [`zcdp_to_approx_dp_ln_delta`], meets the budget. One-dimensional queries use the tighter
+/// calibration in [`scalar_noise_std`] instead.
+fn zcdp_conversion_noise_multiplier(
+    budget: &EpsilonDeltaBudget,
+) -> Result<Ratio<BigUint>, DpError> {
+    let epsilon = budget.epsilon.to_f64().filter(|e| e.is_finite());
+    let delta = budget.delta.to_f64().filter(|d| *d > 0.0);
+    let (Some(epsilon), Some(delta)) = (epsilon, delta) else {
+        return Err(DpError::InvalidParameter(
+            "epsilon and delta must be representable as floats".into(),
+        ));
+    };
+    let ln_delta = delta.ln();
+
+    // Find an upper bound on the noise multiplier, then bisect. The delta achieved by a given
+    // multiplier is decreasing in the multiplier.
+    let satisfies = |multiplier: f64| {
+        zcdp_to_approx_dp_ln_delta(1.0 / (2.0 * multiplier * multiplier), epsilon) <= ln_delta
+    };
+    let mut high = 1.0;
+    while !satisfies(high) {
+        high *= 2.0;
+        if !high.is_finite() {
+            return Err(DpError::InvalidParameter(
+                "could not calibrate noise for the privacy budget".into(),
+            ));
+        }
+    }
+    let mut low = 0.0;
+    for _ in 0..128 {
+        let mid = (low + high) / 2.0;
+        if satisfies(mid) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    let scale = 2f64.powi(32);
+    let numer = (high * scale).ceil().to_u128().ok_or_else(|| {
+        DpError::InvalidParameter("could not calibrate noise for the privacy budget".into())
+    })?;
+    Ok(Ratio::new(BigUint::from(numer), BigUint::from(1u64 << 32)))
+}
+
+/// Compute the natural logarithm of the smallest `delta` such that `rho`-zero-concentrated
+/// differential privacy implies `(epsilon, delta)`-differential privacy, following Corollary 13
+/// of [[CKS20]]:
+///
+/// `delta = inf_{alpha > 1} exp((alpha - 1) * (alpha * rho - epsilon)) / (alpha - 1) * (1 - 1/alpha)^alpha`
+///
+/// The objective is unimodal in `alpha`, so the infimum is found by a golden section search over
+/// `ln(alpha - 1)`.
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+pub(super) fn zcdp_to_approx_dp_ln_delta(rho: f64, epsilon: f64) -> f64 {
+    let objective = |t: f64| {
+        let alpha_minus_one = t.exp();
+        let alpha = alpha_minus_one + 1.0;
+        alpha_minus_one * (alpha * rho - epsilon) - t + alpha * (alpha_minus_one / alpha).ln()
+    };
+
+    let inv_phi = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = (-40.0, 40.0);
+    let mut c = b - (b - a) * inv_phi;
+    let mut d = a + (b - a) * inv_phi;
+    let (mut fc, mut fd) = (objective(c), objective(d));
+    for _ in 0..200 {
+        if fc < fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = b - (b - a) * inv_phi;
+            fc = objective(c);
+        } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = a + (b - a) * inv_phi;
+            fd = objective(d);
+        }
+    }
+
+    // delta is a probability, so it never needs to exceed one.
+    fc.min(fd).min(0.0)
+}
+
+/// Compute the standard deviation of discrete gaussian noise needed to satisfy the given
+/// `(epsilon, delta)` budget for a one-dimensional integer-valued function with sensitivity
+/// `sensitivity`, rounded up to a multiple of `2^-32`.
+///
+/// Following Theorem 7 of [[CKS20]], adding noise `Y` from the discrete gaussian with parameter
+/// `sigma` to a function whose values on neighboring datasets differ by at most an integer
+/// `Delta` provides `(epsilon, delta)`-DP exactly when
+///
+/// `delta >= P[Y > epsilon * sigma^2 / Delta - Delta / 2] - e^epsilon * P[Y > epsilon * sigma^2 / Delta + Delta / 2]`.
+///
+/// Since the function is integer-valued, `Delta` is the sensitivity rounded up to an integer. The
+/// smallest `sigma` satisfying this bound is found by bisection.
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+fn scalar_noise_std(
+    budget: &EpsilonDeltaBudget,
+    sensitivity: &Ratio<BigUint>,
+) -> Result<Ratio<BigUint>, DpError> {
+    let epsilon = budget.epsilon.to_f64().filter(|e| e.is_finite());
+    let delta = budget.delta.to_f64().filter(|d| *d > 0.0);
+    let (Some(epsilon), Some(delta)) = (epsilon, delta) else {
+        return Err(DpError::InvalidParameter(
+            "epsilon and delta must be representable as floats".into(),
+        ));
+    };
+    let sensitivity = sensitivity
+        .ceil()
+        .to_integer()
+        .to_f64()
+        .filter(|s| s.is_finite())
+        .ok_or(DpError::InvalidFloat)?;
+    if sensitivity == 0.0 {
+        return Ok(Ratio::zero());
+    }
+
+    // Find an upper bound on the standard deviation, then bisect. The delta achieved by a given
+    // standard deviation is decreasing in the standard deviation.
+    let satisfies = |sigma: f64| theorem_7_delta(sigma, epsilon, sensitivity) <= delta;
+    let mut high = sensitivity;
+    while !satisfies(high) {
+        high *= 2.0;
+        if !high.is_finite() {
+            return Err(DpError::InvalidParameter(
+                "could not calibrate noise for the privacy budget".into(),
+            ));
+        }
+    }
+    let mut low = 0.0;
+    for _ in 0..128 {
+        let mid = (low + high) / 2.0;
+        if satisfies(mid) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    let scale = 2f64.powi(32);
+    let numer = (high * scale).ceil().to_u128().ok_or_else(|| {
+        DpError::InvalidParameter("could not calibrate noise for the privacy budget".into())
+    })?;
+    Ok(Ratio::new(BigUint::from(numer), BigUint::from(1u64 << 32)))
+}
+
+/// Compute an upper bound on the `delta` of Theorem 7 of [[CKS20]], for the discrete gaussian with
+/// parameter `sigma`, privacy parameter `epsilon`, and integer sensitivity `sensitivity`. See
+/// [`scalar_noise_std`].
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+fn theorem_7_delta(sigma: f64, epsilon: f64, sensitivity: f64) -> f64 {
+    let tails = DiscreteGaussianTails::new(sigma);
+    // `P[Y > t] = P[Y >= floor(t) + 1]` for integer-valued `Y`.
+    let n = (epsilon * sigma * sigma / sensitivity - sensitivity / 2.0).floor() + 1.0;
+    // The second term is computed in log space, since `e^epsilon` may overflow when the tail
+    // probability is zero.
+    tails.upper_bound(n) - (epsilon + tails.lower_bound(n + sensitivity).ln()).exp()
+}
+
+/// Bounds on the tail probabilities `P[Y >= n]` of the discrete gaussian with parameter `sigma`.
+///
+/// For small `sigma`, the probability mass function is summed exactly, up to the point where it
+/// underflows. For large `sigma`, the tail sum of `f(y) = exp(-y^2 / (2 * sigma^2))` is bounded
+/// on either side by integrals, `int_n^inf f <= sum_{y >= n} f(y) <= f(n) + int_n^inf f` for
+/// `n >= 0`, and the normalizing constant is `sqrt(2 * pi) * sigma` up to a relative error of
+/// `exp(-2 * pi^2 * sigma^2)`, which is far below floating point precision.
+enum DiscreteGaussianTails {
+    /// `suffix_sums[m]` is the sum of `f(y)` for `y >= m`, and `normalizer` is the sum of `f(y)`
+    /// over all integers.
+    Exact {
+        suffix_sums: Vec<f64>,
+        normalizer: f64,
+    },
+    Approximate {
+        sigma: f64,
+    },
+}
+
+impl DiscreteGaussianTails {
+    /// The largest `sigma` for which tail probabilities are computed by exact summation.
+    const MAX_EXACT_SIGMA: f64 = 1024.0;
+
+    fn new(sigma: f64) -> Self {
+        if sigma > Self::MAX_EXACT_SIGMA {
+            return Self::Approximate { sigma };
+        }
+        // `f(y)` underflows to zero for `y > 40 * sigma`.
+        let len = (40.0 * sigma).ceil() as usize + 2;
+        let mut suffix_sums = vec![0.0; len + 1];
+        for y in (0..len).rev() {
+            let y_f64 = y as f64;
+            suffix_sums[y] = suffix_sums[y + 1] + (-y_f64 * y_f64 / (2.0 * sigma * sigma)).exp();
+        }
+        let normalizer = suffix_sums[0] + suffix_sums[1];
+        Self::Exact {
+            suffix_sums,
+            normalizer,
+        }
+    }
+
+    /// A lower bound on `P[Y >= n]`, for an integer `n`.
+    fn lower_bound(&self, n: f64) -> f64 {
+        if n <= 0.0 {
+            return 1.0 - self.upper_bound(1.0 - n);
+        }
+        match self {
+            Self::Exact {
+                suffix_sums,
+                normalizer,
+            } => Self::exact_tail(suffix_sums, n) / normalizer,
+            Self::Approximate { sigma } => erfc(n / (sigma * std::f64::consts::SQRT_2)) / 2.0,
+        }
+    }
+
+    /// An upper bound on `P[Y >= n]`, for an integer `n`.
+    fn upper_bound(&self, n: f64) -> f64 {
+        if n <= 0.0 {
+            return 1.0 - self.lower_bound(1.0 - n);
+        }
+        match self {
+            Self::Exact {
+                suffix_sums,
+                normalizer,
+            } => Self::exact_tail(suffix_sums, n) / normalizer,
+            Self::Approximate { sigma } => {
+                let pmf_bound = (-n * n / (2.0 * sigma * sigma)).exp()
+                    / ((2.0 * std::f64::consts::PI).sqrt() * sigma);
+                (erfc(n / (sigma * std::f64::consts::SQRT_2)) / 2.0 + pmf_bound).min(1.0)
+            }
+        }
+    }
+
+    fn exact_tail(suffix_sums: &[f64], n: f64) -> f64 {
+        if n >= suffix_sums.len() as f64 {
+            0.0
+        } else {
+            suffix_sums[n as usize]
+        }
+    }
+}
+
+/// The complementary error function, for `x >= 0`. This is computed through the regularized upper
+/// incomplete gamma function, `erfc(x) = Q(1/2, x^2)`, using its series expansion for small
+/// arguments, and its continued fraction expansion otherwise.
+fn erfc(x: f64) -> f64 {
+    const A: f64 = 0.5;
+    let x2 = x * x;
+    if x2 == 0.0 {
+        return 1.0;
+    }
+    // The logarithm of the common prefactor `x^a * e^-x / Gamma(a)`, with `Gamma(1/2) = sqrt(pi)`.
+    let ln_prefactor = A * x2.ln() - x2 - 0.5 * std::f64::consts::PI.ln();
+
+    if x2 < A + 1.0 {
+        // Series expansion of the lower incomplete gamma function.
+        let mut term = 1.0 / A;
+        let mut sum = term;
+        let mut a = A;
+        for _ in 0..1000 {
+            a += 1.0;
+            term *= x2 / a;
+            sum += term;
+            if term.abs() < sum.abs() * f64::EPSILON {
+                break;
+            }
+        }
+        1.0 - sum * ln_prefactor.exp()
+    } else {
+        // Continued fraction for the upper incomplete gamma function, evaluated with the modified
+        // Lentz method.
+        const TINY: f64 = 1e-300;
+        let mut b = x2 + 1.0 - A;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let i = f64::from(i);
+            let an = -i * (i - A);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < f64::EPSILON {
+                break;
+            }
+        }
+        ln_prefactor.exp() * h
+    }
+}
+
 /// Samples `BigInt` numbers according to the discrete Laplace distribution, with the given scale
 /// parameter. The distribution is defined over the integers, represented by arbitrary-precision
 /// integers. The sampling procedure follows [[CKS20]].
@@ -327,6 +741,26 @@ impl Distribution<BigInt> for DiscreteLaplace {
 
 impl DifferentialPrivacyDistribution for DiscreteLaplace {}
 
+impl NoiseDistribution for DiscreteLaplace {
+    fn variance(&self) -> f64 {
+        discrete_laplace_variance(ratio_to_f64(&self.scale))
+    }
+
+    fn log_mgf(&self, t: f64) -> f64 {
+        discrete_laplace_log_mgf(ratio_to_f64(&self.scale), t)
+    }
+
+    fn max_mgf_parameter(&self) -> f64 {
+        1.0 / ratio_to_f64(&self.scale)
+    }
+}
+
+/// Convert a distribution parameter to a float for error estimation. Parameters too large to
+/// represent become infinite, which gives trivial, but valid, upper bounds.
+fn ratio_to_f64(value: &Ratio<BigUint>) -> f64 {
+    value.to_f64().unwrap_or(f64::INFINITY)
+}
+
 /// A DP strategy using the discrete Laplace distribution.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Ord, PartialOrd)]
 pub struct DiscreteLaplaceDpStrategy<B>
@@ -376,65 +810,27 @@ impl DifferentialPrivacyStrategy for PureDpDiscreteLaplace {
     }
 }
 
-#[cfg(test)]
-mod tests {
+impl PrivacyCost for PureDpDiscreteLaplace {
+    /// Each application costs `epsilon`.
+    fn privacy_loss(&self) -> Result<PrivacyLoss, DpError> {
+        Ok(PrivacyLoss::PureDp {
+            epsilon: Rational(self.budget.epsilon.clone()),
+        })
+    }
+}
 
-    use super::*;
-    use crate::dp::Rational;
+#[cfg(test)]
+pub(crate) mod test_utils {
     use crate::vdaf::xof::SeedStreamTurboShake128;
-
-    use num_bigint::{BigUint, Sign, ToBigInt, ToBigUint};
-    use num_traits::{One, Signed, ToPrimitive};
-    use rand::{distr::Distribution, SeedableRng};
+    use num_bigint::{BigInt, BigUint, Sign};
+    use num_iter::range_inclusive;
+    use num_rational::Ratio;
+    use num_traits::{Signed, ToPrimitive, Zero};
+    use rand::SeedableRng;
     use statrs::distribution::{ChiSquared, ContinuousCDF, Normal};
     use std::collections::HashMap;
 
-    #[test]
-    fn test_discrete_gaussian() {
-        let sampler =
-            DiscreteGaussian::new(Ratio::<BigUint>::from_integer(BigUint::from(5u8))).unwrap();
-
-        // check samples are consistent
-        let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
-        let samples: Vec<i8> = (0..10)
-            .map(|_| i8::try_from(sampler.sample(&mut rng)).unwrap())
-            .collect();
-        let samples1: Vec<i8> = (0..10)
-            .map(|_| i8::try_from(sampler.sample(&mut rng)).unwrap())
-            .collect();
-        assert_eq!(samples, [10, 7, 2, 1, -1, -2, -1, 3, -3, -1]);
-        assert_eq!(samples1, [3, 6, 3, -7, -8, -1, 2, -4, -11, -4]);
-    }
-
-    #[test]
-    /// Make sure that the distribution created by `create_distribution`
-    /// of `ZCdpDicreteGaussian` is the same one as manually creating one
-    /// by using the constructor of `DiscreteGaussian` directly.
-    fn test_zcdp_discrete_gaussian() {
-        // sample from a manually created distribution
-        let sampler1 =
-            DiscreteGaussian::new(Ratio::<BigUint>::from_integer(BigUint::from(4u8))).unwrap();
-        let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
-        let samples1: Vec<i8> = (0..10)
-            .map(|_| i8::try_from(sampler1.sample(&mut rng)).unwrap())
-            .collect();
-
-        // sample from the distribution created by the `zcdp` strategy
-        let zcdp = ZCdpDiscreteGaussian {
-            budget: ZCdpBudget::new(Rational::try_from(0.25).unwrap()),
-        };
-        let sampler2 = zcdp
-            .create_distribution(Ratio::<BigUint>::from_integer(1u8.into()))
-            .unwrap();
-        let mut rng2 = SeedStreamTurboShake128::from_seed([0u8; 32]);
-        let samples2: Vec<i8> = (0..10)
-            .map(|_| i8::try_from(sampler2.sample(&mut rng2)).unwrap())
-            .collect();
-
-        assert_eq!(samples2, samples1);
-    }
-
-    pub fn test_mean<FS: FnMut() -> BigInt>(
+    pub(crate) fn test_mean<FS: FnMut() -> BigInt>(
         mut sampler: FS,
         hyp_mean: f64,
         hyp_var: f64,
@@ -462,7 +858,7 @@ mod tests {
         (emp_mean - Ratio::<BigInt>::from_float(hyp_mean).unwrap()).abs() < abs_p_tol
     }
 
-    fn histogram(
+    pub(crate) fn histogram(
         d: &Vec<BigInt>,
         bin_bounds: &[Option<(BigInt, BigInt)>],
         smallest: BigInt,
@@ -502,7 +898,7 @@ mod tests {
         bin_hist
     }
 
-    fn discrete_gauss_cdf_approx(
+    pub(crate) fn discrete_gauss_cdf_approx(
         sigma: &BigUint,
         bin_bounds: &[Option<(BigInt, BigInt)>],
     ) -> HashMap<Option<(BigInt, BigInt)>, f64> {
@@ -540,9 +936,14 @@ mod tests {
         cdf
     }
 
-    fn chi_square(sigma: &BigUint, n_bins: usize, alpha: f64) -> bool {
-        // perform pearsons chi-squared test on the discrete gaussian sampler
-
+    /// Perform Pearson's chi-squared test on a sampler for the discrete gaussian distribution
+    /// with standard deviation `sigma`.
+    pub(crate) fn chi_square_with(
+        sigma: &BigUint,
+        n_bins: usize,
+        alpha: f64,
+        mut sampler: impl FnMut(&mut SeedStreamTurboShake128) -> BigInt,
+    ) -> bool {
         let sigma_signed = BigInt::from_biguint(Sign::Plus, sigma.clone());
 
         // cut off at 3 times the std. and collect all outliers in a seperate bin
@@ -575,11 +976,7 @@ mod tests {
 
         // collect that number of samples
         let mut rng = SeedStreamTurboShake128::from_seed([1u8; 32]);
-        let samples: Vec<BigInt> = (1..n_samples)
-            .map(|_| {
-                sample_discrete_gaussian(&Ratio::<BigUint>::from_integer(sigma.clone()), &mut rng)
-            })
-            .collect();
+        let samples: Vec<BigInt> = (1..n_samples).map(|_| sampler(&mut rng)).collect();
 
         // make a histogram from the samples
         let hist = histogram(&samples, &bin_bounds, -global_bound.clone(), global_bound);
@@ -604,6 +1001,250 @@ mod tests {
 
         p > alpha
     }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::test_utils::{chi_square_with, test_mean};
+    use super::*;
+    use crate::dp::Rational;
+    use crate::vdaf::xof::SeedStreamTurboShake128;
+
+    use num_bigint::{BigUint, ToBigInt, ToBigUint};
+    use num_traits::{One, ToPrimitive};
+    use rand::{distr::Distribution, SeedableRng};
+
+    #[test]
+    fn test_discrete_gaussian() {
+        let sampler =
+            DiscreteGaussian::new(Ratio::<BigUint>::from_integer(BigUint::from(5u8))).unwrap();
+
+        // check samples are consistent
+        let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+        let samples: Vec<i8> = (0..10)
+            .map(|_| i8::try_from(sampler.sample(&mut rng)).unwrap())
+            .collect();
+        let samples1: Vec<i8> = (0..10)
+            .map(|_| i8::try_from(sampler.sample(&mut rng)).unwrap())
+            .collect();
+        assert_eq!(samples, [10, 7, 2, 1, -1, -2, -1, 3, -3, -1]);
+        assert_eq!(samples1, [3, 6, 3, -7, -8, -1, 2, -4, -11, -4]);
+    }
+
+    #[test]
+    /// Make sure that the distribution created by `create_distribution`
+    /// of `ZCdpDicreteGaussian` is the same one as manually creating one
+    /// by using the constructor of `DiscreteGaussian` directly.
+    fn test_zcdp_discrete_gaussian() {
+        // sample from a manually created distribution
+        let sampler1 =
+            DiscreteGaussian::new(Ratio::<BigUint>::from_integer(BigUint::from(4u8))).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+        let samples1: Vec<i8> = (0..10)
+            .map(|_| i8::try_from(sampler1.sample(&mut rng)).unwrap())
+            .collect();
+
+        // sample from the distribution created by the `zcdp` strategy
+        let zcdp = ZCdpDiscreteGaussian {
+            budget: ZCdpBudget::new(Rational::try_from(0.25).unwrap()),
+        };
+        let sampler2 = zcdp
+            .create_distribution(Ratio::<BigUint>::from_integer(1u8.into()))
+            .unwrap();
+        let mut rng2 = SeedStreamTurboShake128::from_seed([0u8; 32]);
+        let samples2: Vec<i8> = (0..10)
+            .map(|_| i8::try_from(sampler2.sample(&mut rng2)).unwrap())
+            .collect();
+
+        assert_eq!(samples2, samples1);
+    }
+
+    #[test]
+    fn test_epsilon_delta_discrete_gaussian() {
+        // Reference values were computed by minimizing the bound from Corollary 13 of [CKS20]
+        // over a fine grid of alpha values.
+        for (epsilon, delta, expected) in [
+            ((1u32, 1u32), (1u32, 100_000u32), 4.04513),
+            ((1, 2), (1, 1_000_000), 8.67663),
+            ((2, 1), (1, 1_000), 1.60826),
+        ] {
+            let strategy = EpsilonDeltaDiscreteGaussian::from_budget(
+                EpsilonDeltaBudget::new(
+                    Rational::from_unsigned(epsilon.0, epsilon.1).unwrap(),
+                    Rational::from_unsigned(delta.0, delta.1).unwrap(),
+                )
+                .unwrap(),
+            );
+
+            let sigma = strategy
+                .create_distribution(Ratio::from_integer(1u8.into()))
+                .unwrap()
+                .std
+                .to_f64()
+                .unwrap();
+            assert!(
+                (sigma - expected).abs() < 1e-3,
+                "sigma {sigma}, expected {expected}"
+            );
+
+            // The noise must be at least as large as the zCDP noise that the bound is derived
+            // from, and smaller than that of the classical Gaussian mechanism.
+            let (epsilon, delta) = (
+                f64::from(epsilon.0) / f64::from(epsilon.1),
+                f64::from(delta.0) / f64::from(delta.1),
+            );
+            assert!(zcdp_to_approx_dp_ln_delta(1.0 / (2.0 * sigma * sigma), epsilon) <= delta.ln());
+            assert!(sigma < (2.0 * (1.25 / delta).ln()).sqrt() / epsilon);
+
+            // The standard deviation scales linearly with the sensitivity.
+            let sigma_3 = strategy
+                .create_distribution(Ratio::from_integer(3u8.into()))
+                .unwrap()
+                .std
+                .to_f64()
+                .unwrap();
+            assert!((sigma_3 - 3.0 * sigma).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_zcdp_to_approx_dp() {
+        // Decreasing rho or increasing epsilon can only decrease delta.
+        let mut prev = 0.0;
+        for rho in [2.0, 1.0, 0.5, 0.1, 0.01] {
+            let ln_delta = zcdp_to_approx_dp_ln_delta(rho, 1.0);
+            assert!(ln_delta <= prev);
+            prev = ln_delta;
+        }
+        assert!(zcdp_to_approx_dp_ln_delta(0.1, 2.0) < zcdp_to_approx_dp_ln_delta(0.1, 1.0));
+
+        // The bound is always at least as good as the simpler conversion from Proposition 1.3 of
+        // [BS16], `epsilon = rho + 2 * sqrt(rho * ln(1/delta))`.
+        let rho: f64 = 0.05;
+        let delta: f64 = 1e-6;
+        let epsilon = rho + 2.0 * (rho * (1.0 / delta).ln()).sqrt();
+        assert!(zcdp_to_approx_dp_ln_delta(rho, epsilon) <= delta.ln());
+    }
+
+    #[test]
+    fn test_scalar_epsilon_delta_discrete_gaussian() {
+        // Reference values were computed by evaluating the bound from Theorem 7 of [CKS20] with
+        // exact summation of the probability mass function, and bisecting.
+        for (epsilon, delta, sensitivity, expected) in [
+            ((1u32, 1u32), (1u32, 100_000u32), 1u8, 3.74048),
+            ((1, 2), (1, 1_000_000), 1, 8.05248),
+            ((2, 1), (1, 1_000), 1, 1.46441),
+            ((1, 1), (1, 100_000), 10, 37.30596),
+        ] {
+            let strategy = EpsilonDeltaDiscreteGaussian::from_budget(
+                EpsilonDeltaBudget::new(
+                    Rational::from_unsigned(epsilon.0, epsilon.1).unwrap(),
+                    Rational::from_unsigned(delta.0, delta.1).unwrap(),
+                )
+                .unwrap(),
+            );
+            let sensitivity = Ratio::from_integer(sensitivity.into());
+
+            let sigma = ratio_to_f64(
+                &strategy
+                    .create_scalar_distribution(sensitivity.clone())
+                    .unwrap()
+                    .std,
+            );
+            assert!(
+                (sigma - expected).abs() < 1e-3,
+                "sigma {sigma}, expected {expected}"
+            );
+
+            // The scalar calibration never needs more noise than the one for vectors.
+            let vector_sigma =
+                ratio_to_f64(&strategy.create_distribution(sensitivity).unwrap().std);
+            assert!(sigma <= vector_sigma, "{sigma} {vector_sigma}");
+        }
+
+        // A sensitivity of zero needs no noise.
+        let strategy = EpsilonDeltaDiscreteGaussian::from_budget(
+            EpsilonDeltaBudget::new(
+                Rational::from_unsigned(1u8, 1).unwrap(),
+                Rational::from_unsigned(1u32, 100_000).unwrap(),
+            )
+            .unwrap(),
+        );
+        assert!(strategy
+            .create_scalar_distribution(Ratio::zero())
+            .unwrap()
+            .std
+            .is_zero());
+    }
+
+    #[test]
+    fn test_theorem_7_delta() {
+        // The bound from Theorem 7 of [CKS20] is the hockey-stick divergence between the noise
+        // distribution and its shift by the sensitivity, which is the exact delta of the mechanism
+        // at the given epsilon.
+        for (sigma, epsilon, sensitivity) in [(1.0, 1.0f64, 1.0), (3.5, 1.0, 1.0), (12.0, 0.5, 4.0)]
+        {
+            let pmf = |y: f64| (-y * y / (2.0 * sigma * sigma)).exp();
+            let support = (40.0f64 * sigma).ceil() + sensitivity;
+            let normalizer = (-support as i64..=support as i64)
+                .map(|y| pmf(y as f64))
+                .sum::<f64>();
+            let hockey_stick = (-support as i64..=support as i64)
+                .map(|y| {
+                    let y = y as f64;
+                    (pmf(y) - epsilon.exp() * pmf(y - sensitivity)).max(0.0) / normalizer
+                })
+                .sum::<f64>();
+
+            let delta = theorem_7_delta(sigma, epsilon, sensitivity);
+            assert!(
+                (delta - hockey_stick).abs() < 1e-12,
+                "{delta} {hockey_stick}"
+            );
+        }
+
+        // For large standard deviations, the discrete gaussian behaves like the continuous one,
+        // and the bound converges to that of the analytic Gaussian mechanism of [BW18]:
+        // `Phi(Delta / (2 * sigma) - epsilon * sigma / Delta) - e^epsilon * Phi(-Delta / (2 * sigma) - epsilon * sigma / Delta)`.
+        //
+        // [BW18]: https://arxiv.org/pdf/1805.06530.pdf
+        let phi = |x: f64| erfc(-x / std::f64::consts::SQRT_2) / 2.0;
+        let (epsilon, sensitivity) = (1.0f64, 1000.0);
+        for sigma in [2000.0, 3730.0, 5000.0] {
+            let continuous = phi(sensitivity / (2.0 * sigma) - epsilon * sigma / sensitivity)
+                - epsilon.exp() * phi(-sensitivity / (2.0 * sigma) - epsilon * sigma / sensitivity);
+            let delta = theorem_7_delta(sigma, epsilon, sensitivity);
+            assert!(
+                delta >= continuous && delta < 1.05 * continuous,
+                "{delta} {continuous}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_erfc() {
+        for (x, expected) in [
+            (0.0, 1.0),
+            (0.5, 0.4795001221869535),
+            (1.0, 0.15729920705028513),
+            (1.2, 0.08968602177036465),
+            (3.0, 2.2090496998585438e-05),
+            (10.0, 2.088487583762545e-45),
+        ] {
+            let value = erfc(x);
+            assert!(
+                (value - expected).abs() <= 1e-13 * expected,
+                "erfc({x}) = {value}, expected {expected}"
+            );
+        }
+    }
+
+    fn chi_square(sigma: &BigUint, n_bins: usize, alpha: f64) -> bool {
+        chi_square_with(sigma, n_bins, alpha, |rng| {
+            sample_discrete_gaussian(&Ratio::<BigUint>::from_integer(sigma.clone()), rng)
+        })
+    }
 
     #[test]
     fn empirical_test_gauss() {
@@ -0,0 +1,512 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Samplers for the discrete Gaussian and discrete Laplace distributions that use fixed-width
+//! integer arithmetic.
+//!
+//! These follow the same algorithms from [[CKS20]] as the samplers in the parent module, and are
+//! exact, but they represent parameters and intermediate values as `u128`s instead of `BigUint`s
+//! and `Ratio<BigUint>`s. This is much faster for parameters in the typical range, whose
+//! numerators and denominators fit in a `u64`. Bernoulli trials with rational probabilities are
+//! decomposed into products of independent trials, so that no table of precomputed values is
+//! needed, and so that intermediate values don't overflow. In the rare cases where an
+//! intermediate value would still overflow, the sampler falls back to arbitrary-precision
+//! arithmetic for that step.
+//!
+//! The samplers consume randomness differently from those in the parent module, so the two
+//! produce different samples from the same random number generator.
+//!
+//! [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+
+use super::{sample_bernoulli_exp, DiscreteGaussian, DiscreteLaplace};
+use crate::dp::{
+    estimation::{
+        discrete_gaussian_log_mgf_bound, discrete_laplace_log_mgf, discrete_laplace_variance,
+        NoiseDistribution,
+    },
+    DifferentialPrivacyDistribution, DpError,
+};
+use num_bigint::{BigInt, BigUint};
+use num_rational::Ratio;
+use num_traits::{ToPrimitive, Zero};
+use rand::{
+    distr::{Distribution, Uniform},
+    Rng,
+};
+
+/// Split a non-negative rational number into its numerator and denominator, if both fit in a
+/// `u64`.
+fn to_u64_parts(value: &Ratio<BigUint>) -> Result<(u128, u128), DpError> {
+    if value.denom().is_zero() {
+        return Err(DpError::ZeroDenominator);
+    }
+    match (value.numer().to_u64(), value.denom().to_u64()) {
+        (Some(numer), Some(denom)) => Ok((numer.into(), denom.into())),
+        _ => Err(DpError::InvalidParameter(
+            "numerator and denominator must fit in a u64 for fixed-precision sampling".into(),
+        )),
+    }
+}
+
+/// Sample uniformly from `{0, ..., bound - 1}`, where `bound > 0`.
+pub(super) fn sample_uniform<R: Rng + ?Sized>(bound: u128, rng: &mut R) -> u128 {
+    // Unwrap safety: the range is non-empty because `bound` is nonzero.
+    Uniform::new(0, bound).unwrap().sample(rng)
+}
+
+/// Sample from the Bernoulli(numer/denom) distribution, where `numer <= denom` and `denom > 0`.
+pub(super) fn sample_bernoulli<R: Rng + ?Sized>(numer: u128, denom: u128, rng: &mut R) -> bool {
+    sample_uniform(denom, rng) < numer
+}
+
+/// Sample from the Bernoulli(exp(-numer/denom)) distribution, where `numer <= denom`.
+///
+/// This follows the `gamma in [0,1]` branch of Algorithm 1 of [[CKS20]]. Each Bernoulli(gamma/k)
+/// trial is performed as the conjunction of independent Bernoulli(gamma) and Bernoulli(1/k)
+/// trials, so that `denom * k` need not be computed.
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+fn sample_bernoulli_exp1<R: Rng + ?Sized>(numer: u128, denom: u128, rng: &mut R) -> bool {
+    let mut k = 1u128;
+    loop {
+        if sample_bernoulli(numer, denom, rng) && sample_bernoulli(1, k, rng) {
+            k += 1;
+        } else {
+            return k % 2 == 1;
+        }
+    }
+}
+
+/// Sample from the Bernoulli(exp(-numer/denom)) distribution.
+///
+/// This follows the `gamma > 1` branch of Algorithm 1 of [[CKS20]].
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+fn sample_bernoulli_exp_u128<R: Rng + ?Sized>(numer: u128, denom: u128, rng: &mut R) -> bool {
+    for _ in 0..numer / denom {
+        if !sample_bernoulli_exp1(1, 1, rng) {
+            return false;
+        }
+    }
+    sample_bernoulli_exp1(numer % denom, denom, rng)
+}
+
+/// Sample from the Bernoulli(exp(-numer/denom)) distribution, falling back to arbitrary-precision
+/// arithmetic if `numer` could not be computed without overflowing.
+fn sample_bernoulli_exp_checked<R: Rng + ?Sized>(
+    numer: Option<u128>,
+    denom: u128,
+    big_numer: impl FnOnce() -> BigUint,
+    rng: &mut R,
+) -> bool {
+    match numer {
+        Some(numer) => sample_bernoulli_exp_u128(numer, denom, rng),
+        None => sample_bernoulli_exp(&Ratio::new(big_numer(), BigUint::from(denom)), rng),
+    }
+}
+
+/// Sample from the Bernoulli(exp(-x^2/2)) distribution, where `x = numer/denom`.
+///
+/// The exponent is split as `x^2/2 = q^2/2 + q*r + r^2/2`, where `q` is the integer part of `x`
+/// and `r` is its fractional part, and the three factors of the probability are sampled
+/// independently. The last factor is sampled as in Algorithm 1 of [[CKS20]], where each
+/// Bernoulli(r^2/(2k)) trial is the conjunction of two Bernoulli(r) trials and a Bernoulli(1/(2k))
+/// trial.
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+fn sample_bernoulli_exp_half_square<R: Rng + ?Sized>(
+    numer: u128,
+    denom: u128,
+    rng: &mut R,
+) -> bool {
+    let (q, r) = (numer / denom, numer % denom);
+
+    // exp(-q^2/2)
+    if !sample_bernoulli_exp_checked(
+        q.checked_mul(q),
+        2,
+        || BigUint::from(q) * BigUint::from(q),
+        rng,
+    ) {
+        return false;
+    }
+
+    // exp(-q*r/denom)
+    if !sample_bernoulli_exp_checked(
+        q.checked_mul(r),
+        denom,
+        || BigUint::from(q) * BigUint::from(r),
+        rng,
+    ) {
+        return false;
+    }
+
+    // exp(-(r/denom)^2/2)
+    let mut k = 1u128;
+    loop {
+        if sample_bernoulli(r, denom, rng)
+            && sample_bernoulli(r, denom, rng)
+            && sample_bernoulli(1, 2 * k, rng)
+        {
+            k += 1;
+        } else {
+            return k % 2 == 1;
+        }
+    }
+}
+
+/// Sample from the geometric distribution with parameter `1 - exp(-numer/denom)`.
+///
+/// This follows all but the last three lines of Algorithm 2 in [[CKS20]].
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+fn sample_geometric_exp<R: Rng + ?Sized>(numer: u128, denom: u128, rng: &mut R) -> u128 {
+    if numer == 0 {
+        return 0;
+    }
+
+    let mut u = sample_uniform(denom, rng);
+    while !sample_bernoulli_exp1(u, denom, rng) {
+        u = sample_uniform(denom, rng);
+    }
+
+    let mut v = 0u128;
+    while sample_bernoulli_exp1(1, 1, rng) {
+        v += 1;
+    }
+
+    (u + denom * v) / numer
+}
+
+/// Sample from the discrete Laplace distribution with scale `numer/denom`, returning the sign and
+/// the magnitude separately.
+///
+/// This follows Algorithm 2 of [[CKS20]].
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+fn sample_discrete_laplace<R: Rng + ?Sized>(numer: u128, denom: u128, rng: &mut R) -> (bool, u128) {
+    if numer == 0 {
+        return (false, 0);
+    }
+
+    loop {
+        let negative = sample_bernoulli(1, 2, rng);
+        let y = sample_geometric_exp(denom, numer, rng);
+        if !(negative && y == 0) {
+            return (negative, y);
+        }
+    }
+}
+
+fn to_bigint((negative, magnitude): (bool, u128)) -> BigInt {
+    let value = BigInt::from(magnitude);
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Samples `BigInt` numbers according to the discrete Laplace distribution, with the given scale
+/// parameter, using fixed-width integer arithmetic. See [`DiscreteLaplace`] for details on the
+/// distribution.
+#[derive(Clone, Debug)]
+pub struct FixedPrecisionDiscreteLaplace {
+    /// The scale parameter of the distribution, as a numerator and denominator.
+    numer: u128,
+    denom: u128,
+}
+
+impl FixedPrecisionDiscreteLaplace {
+    /// Create a new sampler from the discrete Laplace distribution with the given scale parameter.
+    /// Errors if the scale has denominator zero, or if its numerator or denominator does not fit
+    /// in a `u64`.
+    pub fn new(scale: Ratio<BigUint>) -> Result<Self, DpError> {
+        let (numer, denom) = to_u64_parts(&scale)?;
+        Ok(Self { numer, denom })
+    }
+}
+
+impl TryFrom<&DiscreteLaplace> for FixedPrecisionDiscreteLaplace {
+    type Error = DpError;
+
+    fn try_from(distribution: &DiscreteLaplace) -> Result<Self, DpError> {
+        Self::new(distribution.scale.clone())
+    }
+}
+
+impl Distribution<BigInt> for FixedPrecisionDiscreteLaplace {
+    fn sample<R>(&self, rng: &mut R) -> BigInt
+    where
+        R: Rng + ?Sized,
+    {
+        to_bigint(sample_discrete_laplace(self.numer, self.denom, rng))
+    }
+}
+
+impl DifferentialPrivacyDistribution for FixedPrecisionDiscreteLaplace {}
+
+impl NoiseDistribution for FixedPrecisionDiscreteLaplace {
+    fn variance(&self) -> f64 {
+        discrete_laplace_variance(self.numer as f64 / self.denom as f64)
+    }
+
+    fn log_mgf(&self, t: f64) -> f64 {
+        discrete_laplace_log_mgf(self.numer as f64 / self.denom as f64, t)
+    }
+
+    fn max_mgf_parameter(&self) -> f64 {
+        self.denom as f64 / self.numer as f64
+    }
+}
+
+/// Samples `BigInt` numbers according to the discrete Gaussian distribution with mean zero, using
+/// fixed-width integer arithmetic. See [`DiscreteGaussian`] for details on the distribution.
+#[derive(Clone, Debug)]
+pub struct FixedPrecisionDiscreteGaussian {
+    /// The standard deviation of the distribution, as a numerator and denominator.
+    numer: u128,
+    denom: u128,
+    /// The scale of the discrete Laplace distribution used for rejection sampling.
+    laplace_scale: u128,
+}
+
+impl FixedPrecisionDiscreteGaussian {
+    /// Create a new sampler from the discrete Gaussian distribution with the given standard
+    /// deviation and mean zero. Errors if the standard deviation has denominator zero, if its
+    /// numerator or denominator does not fit in a `u64`, or if the intermediate values used during
+    /// sampling would not fit in a `u128`.
+    pub fn new(std: Ratio<BigUint>) -> Result<Self, DpError> {
+        let (numer, denom) = to_u64_parts(&std)?;
+        let laplace_scale = numer / denom + 1;
+
+        // Check that the quantities computed in `sample()` fit.
+        denom
+            .checked_mul(denom)
+            .and_then(|x| x.checked_mul(laplace_scale))
+            .and_then(|_| numer.checked_mul(denom))
+            .and_then(|x| x.checked_mul(laplace_scale))
+            .ok_or_else(|| {
+                DpError::InvalidParameter(
+                    "standard deviation is too large for fixed-precision sampling".into(),
+                )
+            })?;
+
+        Ok(Self {
+            numer,
+            denom,
+            laplace_scale,
+        })
+    }
+
+    /// Decide whether to accept the discrete Laplace sample `y`, with probability
+    /// `exp(-(|y| - sigma^2/t)^2 / (2 * sigma^2))`, where `t` is the Laplace scale. With
+    /// `sigma = n/d`, the square root of twice the exponent is
+    /// `||y| * d^2 * t - n^2| / (n * d * t)`.
+    fn accept<R: Rng + ?Sized>(&self, y: u128, rng: &mut R) -> bool {
+        let (n, d, t) = (self.numer, self.denom, self.laplace_scale);
+        // These were checked in the constructor.
+        let d2t = d * d * t;
+        let n2 = n * n;
+        let ndt = n * d * t;
+
+        match y.checked_mul(d2t) {
+            Some(scaled) => sample_bernoulli_exp_half_square(scaled.abs_diff(n2), ndt, rng),
+            None => {
+                // Fall back to arbitrary-precision arithmetic for very large samples.
+                let scaled = BigUint::from(y) * BigUint::from(d2t);
+                let n2 = BigUint::from(n2);
+                let diff = if scaled > n2 {
+                    scaled - n2
+                } else {
+                    n2 - scaled
+                };
+                let ndt = BigUint::from(ndt);
+                sample_bernoulli_exp(
+                    &Ratio::new(&diff * &diff, BigUint::from(2u8) * &ndt * &ndt),
+                    rng,
+                )
+            }
+        }
+    }
+}
+
+impl TryFrom<&DiscreteGaussian> for FixedPrecisionDiscreteGaussian {
+    type Error = DpError;
+
+    fn try_from(distribution: &DiscreteGaussian) -> Result<Self, DpError> {
+        Self::new(distribution.std.clone())
+    }
+}
+
+impl Distribution<BigInt> for FixedPrecisionDiscreteGaussian {
+    /// This follows Algorithm 3 of [[CKS20]].
+    ///
+    /// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+    fn sample<R>(&self, rng: &mut R) -> BigInt
+    where
+        R: Rng + ?Sized,
+    {
+        if self.numer == 0 {
+            return BigInt::zero();
+        }
+
+        loop {
+            let (negative, y) = sample_discrete_laplace(self.laplace_scale, 1, rng);
+            if self.accept(y, rng) {
+                return to_bigint((negative, y));
+            }
+        }
+    }
+}
+
+impl DifferentialPrivacyDistribution for FixedPrecisionDiscreteGaussian {}
+
+impl NoiseDistribution for FixedPrecisionDiscreteGaussian {
+    /// The variance of the discrete Gaussian is at most `std^2`.
+    fn variance(&self) -> f64 {
+        (self.numer as f64 / self.denom as f64).powi(2)
+    }
+
+    fn log_mgf(&self, t: f64) -> f64 {
+        discrete_gaussian_log_mgf_bound(self.numer as f64 / self.denom as f64, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        sample_bernoulli_exp_half_square, sample_bernoulli_exp_u128,
+        FixedPrecisionDiscreteGaussian, FixedPrecisionDiscreteLaplace,
+    };
+    use crate::{
+        dp::distributions::{
+            test_utils::{chi_square_with, test_mean},
+            DiscreteGaussian, DiscreteLaplace,
+        },
+        vdaf::xof::SeedStreamTurboShake128,
+    };
+    use num_bigint::{BigInt, BigUint};
+    use num_rational::Ratio;
+    use num_traits::{One, Zero};
+    use rand::{distr::Distribution, SeedableRng};
+
+    fn bool_to_bigint(b: bool) -> BigInt {
+        if b {
+            BigInt::one()
+        } else {
+            BigInt::zero()
+        }
+    }
+
+    #[test]
+    fn empirical_test_bernoulli_exp() {
+        for (numer, denom) in [(1u128, 3u128), (5, 2), (7, 1), (0, 1)] {
+            let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+            let sampler = || bool_to_bigint(sample_bernoulli_exp_u128(numer, denom, &mut rng));
+            let mean = (-(numer as f64) / denom as f64).exp();
+            let var = mean * (1. - mean);
+            assert!(
+                test_mean(sampler, mean, var.max(1e-9), 0.00001, 1000),
+                "Empirical evaluation of the Bernoulli(exp(-{numer}/{denom})) distribution mean \
+                 failed"
+            );
+        }
+
+        for (numer, denom) in [(1u128, 2u128), (3, 2), (7, 3)] {
+            let mut rng = SeedStreamTurboShake128::from_seed([1u8; 32]);
+            let sampler =
+                || bool_to_bigint(sample_bernoulli_exp_half_square(numer, denom, &mut rng));
+            let x = numer as f64 / denom as f64;
+            let mean = (-x * x / 2.).exp();
+            let var = mean * (1. - mean);
+            assert!(
+                test_mean(sampler, mean, var, 0.00001, 1000),
+                "Empirical evaluation of the Bernoulli(exp(-({numer}/{denom})^2/2)) distribution \
+                 mean failed"
+            );
+        }
+    }
+
+    #[test]
+    fn empirical_test_laplace() {
+        for (numer, denom) in [(1u32, 2u32), (5, 1), (100, 7)] {
+            let sampler = FixedPrecisionDiscreteLaplace::new(Ratio::new(
+                BigUint::from(numer),
+                BigUint::from(denom),
+            ))
+            .unwrap();
+            let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+            let p = (-f64::from(denom) / f64::from(numer)).exp();
+            let var = 2. * p / (1. - p).powi(2);
+            assert!(
+                test_mean(|| sampler.sample(&mut rng), 0.0, var, 0.0001, 1000),
+                "Empirical evaluation of the fixed-precision Laplace({numer}/{denom}) sampler \
+                 mean failed"
+            );
+        }
+    }
+
+    #[test]
+    fn empirical_test_gauss() {
+        for std in [
+            Ratio::from_integer(BigUint::from(100u8)),
+            Ratio::from_integer(BigUint::from(2000u16)),
+            Ratio::new(BigUint::from(10000u32), BigUint::from(23u32)),
+            Ratio::new(BigUint::from(17u64 << 32), BigUint::from(1u64 << 32) + 1u8),
+        ] {
+            let sampler = FixedPrecisionDiscreteGaussian::new(std.clone()).unwrap();
+            let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+            let std_f64 = num_traits::ToPrimitive::to_f64(&std).unwrap();
+            assert!(
+                test_mean(
+                    || sampler.sample(&mut rng),
+                    0.0,
+                    std_f64 * std_f64,
+                    0.00001,
+                    1000
+                ),
+                "Empirical evaluation of the fixed-precision discrete Gaussian({std_f64}) sampler \
+                 mean failed"
+            );
+        }
+
+        // Check the shape of the distribution against the same approximation of the cumulative
+        // distribution function used for the arbitrary-precision sampler.
+        let sampler =
+            FixedPrecisionDiscreteGaussian::new(Ratio::from_integer(BigUint::from(100u8))).unwrap();
+        assert!(chi_square_with(&BigUint::from(100u8), 10, 0.05, |rng| {
+            sampler.sample(rng)
+        }));
+    }
+
+    #[test]
+    fn parameters() {
+        FixedPrecisionDiscreteGaussian::new(Ratio::from_integer(BigUint::from(u64::MAX))).unwrap();
+        FixedPrecisionDiscreteGaussian::new(Ratio::from_integer(BigUint::from(u64::MAX) + 1u8))
+            .unwrap_err();
+        FixedPrecisionDiscreteGaussian::new(Ratio::new(
+            BigUint::from(u64::MAX),
+            BigUint::from(u64::MAX - 1),
+        ))
+        .unwrap_err();
+        FixedPrecisionDiscreteLaplace::new(Ratio::from_integer(BigUint::from(u64::MAX) + 1u8))
+            .unwrap_err();
+
+        // A standard deviation of zero always samples zero.
+        let sampler = FixedPrecisionDiscreteGaussian::new(Ratio::zero()).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+        assert_eq!(sampler.sample(&mut rng), BigInt::zero());
+        let sampler = FixedPrecisionDiscreteLaplace::new(Ratio::zero()).unwrap();
+        assert_eq!(sampler.sample(&mut rng), BigInt::zero());
+
+        // Conversions from the arbitrary-precision distributions.
+        FixedPrecisionDiscreteGaussian::try_from(
+            &DiscreteGaussian::new(Ratio::from_integer(BigUint::from(5u8))).unwrap(),
+        )
+        .unwrap();
+        FixedPrecisionDiscreteLaplace::try_from(
+            &DiscreteLaplace::new(Ratio::from_integer(BigUint::from(5u8))).unwrap(),
+        )
+        .unwrap();
+    }
+}
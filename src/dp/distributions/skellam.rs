@@ -0,0 +1,576 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Distributed noise generation using the Skellam distribution.
+//!
+//! The Skellam distribution with parameter `mu` is the distribution of the difference of two
+//! independent Poisson(`mu`) random variables. It is closed under summation: the sum of
+//! independent Skellam random variables with parameters `mu_1, ..., mu_n` is Skellam distributed
+//! with parameter `mu_1 + ... + mu_n`. This lets each aggregator add a share of the noise to its
+//! aggregate share, so that the released aggregate result carries exactly the noise required by
+//! the privacy budget, instead of one full noise sample per aggregator.
+//!
+//! Following [[AKL21]], adding Skellam noise with parameter `mu` to an integer-valued function
+//! with L2-sensitivity `delta_2` and L1-sensitivity `delta_1` satisfies `(alpha, epsilon(alpha))`
+//! Rényi differential privacy at every integer order `alpha >= 2`, with
+//!
+//! `epsilon(alpha) <= alpha * delta_2^2 / (4 * mu) + ((2 * alpha - 1) * delta_2^2 + 6 * delta_1) /
+//! (16 * mu^2)`.
+//!
+//! Since the function is integer-valued, `delta_1 <= delta_2^2`, so
+//! `epsilon(alpha) <= alpha * rho` with `rho = delta_2^2 / (4 * mu) + 7 * delta_2^2 / (16 * mu^2)`.
+//! The bound is not known to hold at non-integer orders, so this is not a zero-concentrated DP
+//! guarantee, and the budget is given as an [`IntegerRenyiDpBudget`] instead.
+//!
+//! [AKL21]: https://arxiv.org/pdf/2110.04995.pdf
+
+use super::{fixed_precision, sample_bernoulli as sample_bernoulli_ratio};
+use crate::dp::{
+    accountant::{PrivacyCost, PrivacyLoss},
+    estimation::NoiseDistribution,
+    DifferentialPrivacyBudget, DifferentialPrivacyDistribution, DifferentialPrivacyStrategy,
+    DpError, IntegerRenyiDpBudget,
+};
+use num_bigint::{BigInt, BigUint};
+use num_integer::Roots;
+use num_rational::Ratio;
+use num_traits::{One, ToPrimitive, Zero};
+use rand::{distr::Distribution, Rng};
+use serde::{Deserialize, Serialize};
+
+/// The largest supported Poisson parameter is `2^32`.
+const MAX_POISSON_PARAMETER_BITS: u64 = 32;
+
+/// The largest integer Rényi order at which the privacy loss of [`RenyiDpSkellam`] is reported.
+/// This covers all of the integer orders in [`DEFAULT_RENYI_ORDERS`].
+///
+/// [`DEFAULT_RENYI_ORDERS`]: crate::dp::accountant::DEFAULT_RENYI_ORDERS
+const MAX_RENYI_ORDER: u32 = 256;
+
+/// Sample from the Bernoulli((a1 * a2) / (b1 * b2)) distribution, where the probability is at
+/// most one, using fixed-width arithmetic unless the products overflow.
+fn sample_bernoulli_product<R: Rng + ?Sized>(
+    (a1, a2): (u128, u128),
+    (b1, b2): (u128, u128),
+    rng: &mut R,
+) -> bool {
+    match (a1.checked_mul(a2), b1.checked_mul(b2)) {
+        (Some(numer), Some(denom)) => fixed_precision::sample_bernoulli(numer, denom, rng),
+        _ => sample_bernoulli_ratio(
+            &Ratio::new(
+                BigUint::from(a1) * BigUint::from(a2),
+                BigUint::from(b1) * BigUint::from(b2),
+            ),
+            rng,
+        ),
+    }
+}
+
+/// Samples from the Poisson distribution with a rational parameter `lambda = a / b`.
+///
+/// This uses rejection sampling. Let `m = floor(lambda)` be the mode of the distribution and
+/// `p(k)` its probability mass function. The ratio `p(k) / p(m)` is a product of `|k - m|` rational
+/// factors, each at most one. The proposal distribution is flat on a window of half-width
+/// `l = floor(sqrt(m)) + 1` around the mode, and decays geometrically outside of it, with ratios
+/// chosen so that it dominates `p(k) / p(m)`. The acceptance probability is then also a product of
+/// rational factors that are each at most one, and it is sampled exactly as a conjunction of
+/// independent Bernoulli trials.
+#[derive(Clone, Debug)]
+struct Poisson {
+    a: u128,
+    b: u128,
+    /// The mode, `floor(a / b)`.
+    m: u128,
+    /// The half-width of the flat part of the proposal distribution.
+    l: u128,
+    /// The smallest value covered by the flat part of the proposal distribution.
+    lo: u128,
+    /// The probability of drawing from the flat part of the proposal distribution.
+    flat_probability: Ratio<BigUint>,
+    /// The probability of drawing from the upper tail, conditioned on not drawing from the flat
+    /// part.
+    upper_tail_probability: Ratio<BigUint>,
+}
+
+impl Poisson {
+    /// The parameter of the distribution, `a / b`, as a float.
+    fn parameter(&self) -> f64 {
+        self.a as f64 / self.b as f64
+    }
+
+    fn new(lambda: &Ratio<BigUint>) -> Result<Self, DpError> {
+        if lambda.denom().is_zero() {
+            return Err(DpError::ZeroDenominator);
+        }
+        if lambda.to_integer().bits() > MAX_POISSON_PARAMETER_BITS {
+            return Err(DpError::InvalidParameter(
+                "Poisson parameter is too large".into(),
+            ));
+        }
+        let (Some(a), Some(b)) = (lambda.numer().to_u64(), lambda.denom().to_u64()) else {
+            return Err(DpError::InvalidParameter(
+                "numerator and denominator of the Poisson parameter must fit in a u64".into(),
+            ));
+        };
+        let (a, b) = (u128::from(a), u128::from(b));
+
+        let m = a / b;
+        let l = m.sqrt() + 1;
+        let lo = m.saturating_sub(l);
+
+        // The flat part has weight one for each value. The upper tail has total weight
+        // `rho / (1 - rho)`, where `rho = lambda / (m + l + 1)`, and the lower tail, if there is
+        // one, has weight `tau / (1 - tau)`, where `tau = (m - l) / lambda`.
+        let flat_weight = Ratio::from_integer(BigUint::from(m + l - lo + 1));
+        let upper_weight = Ratio::new(BigUint::from(a), BigUint::from(b * (m + l + 1) - a));
+        let lower_weight = if m > l {
+            Ratio::new(BigUint::from(b * (m - l)), BigUint::from(a - b * (m - l)))
+        } else {
+            Ratio::zero()
+        };
+        let tail_weight = &upper_weight + &lower_weight;
+
+        Ok(Self {
+            a,
+            b,
+            m,
+            l,
+            lo,
+            flat_probability: &flat_weight / (&flat_weight + &tail_weight),
+            upper_tail_probability: upper_weight / tail_weight,
+        })
+    }
+
+    /// Sample a proposal.
+    fn sample_proposal<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<u128> {
+        let (a, b, m, l) = (self.a, self.b, self.m, self.l);
+        if sample_bernoulli_ratio(&self.flat_probability, rng) {
+            return Some(self.lo + fixed_precision::sample_uniform(m + l - self.lo + 1, rng));
+        }
+
+        // Sample `j >= 1` with probability proportional to `ratio^j`.
+        let mut j = 1u128;
+        if sample_bernoulli_ratio(&self.upper_tail_probability, rng) {
+            while sample_bernoulli_product((a, 1), (b, m + l + 1), rng) {
+                j += 1;
+            }
+            (m + l).checked_add(j)
+        } else {
+            while sample_bernoulli_product((b, m - l), (a, 1), rng) {
+                j += 1;
+            }
+            // Values below zero have probability zero, and are rejected.
+            (m - l).checked_sub(j)
+        }
+    }
+
+    /// Accept the proposal `k` with probability `p(k) / (p(m) * g(k))`, where `g(k)` is the
+    /// unnormalized proposal probability.
+    fn accept<R: Rng + ?Sized>(&self, k: u128, rng: &mut R) -> bool {
+        let (a, b, m, l) = (self.a, self.b, self.m, self.l);
+        if k >= m {
+            // p(k) / p(m) is the product of lambda / (m + i), for i from 1 to k - m. Beyond the
+            // flat part, each factor is divided by the proposal's ratio, lambda / (m + l + 1).
+            (1..=k - m).all(|i| {
+                if i <= l {
+                    sample_bernoulli_product((a, 1), (b, m + i), rng)
+                } else {
+                    sample_bernoulli_product((m + l + 1, 1), (m + i, 1), rng)
+                }
+            })
+        } else {
+            // p(k) / p(m) is the product of (m - i) / lambda, for i from 0 to m - k - 1. Beyond the
+            // flat part, each factor is divided by the proposal's ratio, (m - l) / lambda.
+            (0..m - k).all(|i| {
+                if i < l {
+                    sample_bernoulli_product((b, m - i), (a, 1), rng)
+                } else {
+                    sample_bernoulli_product((m - i, 1), (m - l, 1), rng)
+                }
+            })
+        }
+    }
+}
+
+impl Distribution<u128> for Poisson {
+    fn sample<R>(&self, rng: &mut R) -> u128
+    where
+        R: Rng + ?Sized,
+    {
+        if self.a == 0 {
+            return 0;
+        }
+
+        loop {
+            if let Some(k) = self.sample_proposal(rng) {
+                if self.accept(k, rng) {
+                    return k;
+                }
+            }
+        }
+    }
+}
+
+/// Samples `BigInt` numbers according to the Skellam distribution with parameter `mu`, which is
+/// the distribution of the difference of two independent Poisson(`mu`) random variables. It has
+/// mean zero and variance `2 * mu`.
+#[derive(Clone, Debug)]
+pub struct Skellam {
+    poisson: Poisson,
+}
+
+impl Skellam {
+    /// Create a new sampler from the Skellam distribution with parameter `mu`. Errors if `mu` has
+    /// denominator zero, if its numerator or denominator does not fit in a `u64`, or if `mu` is
+    /// larger than `2^32`.
+    pub fn new(mu: Ratio<BigUint>) -> Result<Self, DpError> {
+        Ok(Self {
+            poisson: Poisson::new(&mu)?,
+        })
+    }
+}
+
+impl Distribution<BigInt> for Skellam {
+    fn sample<R>(&self, rng: &mut R) -> BigInt
+    where
+        R: Rng + ?Sized,
+    {
+        let x = BigInt::from(self.poisson.sample(rng));
+        let y = BigInt::from(self.poisson.sample(rng));
+        x - y
+    }
+}
+
+impl DifferentialPrivacyDistribution for Skellam {}
+
+impl NoiseDistribution for Skellam {
+    /// The variance of the Skellam distribution is `2 * mu`.
+    fn variance(&self) -> f64 {
+        2.0 * self.poisson.parameter()
+    }
+
+    /// The moment generating function of the Skellam distribution is
+    /// `exp(mu * (exp(t) + exp(-t) - 2))`.
+    fn log_mgf(&self, t: f64) -> f64 {
+        2.0 * self.poisson.parameter() * (t.cosh() - 1.0)
+    }
+}
+
+/// A DP strategy that splits Skellam noise across aggregators.
+///
+/// Each aggregator that applies this strategy adds one share of the noise, and the privacy
+/// guarantee holds for the sum of `num_noise_shares` shares. An aggregator knows its own noise
+/// share, so if it colludes with the collector, it can remove that share from the aggregate
+/// result. Thus, `num_noise_shares` is the number of aggregators that are assumed to be honest.
+/// Setting it to one gives the guarantee even if all but one aggregator is corrupt, at the cost of
+/// each aggregator adding the full amount of noise. Setting it to the number of aggregators adds
+/// the least noise, but requires that every aggregator is honest. Adding noise fails if
+/// `num_noise_shares` exceeds the number of aggregators.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Ord, PartialOrd)]
+pub struct SkellamDpStrategy<B>
+where
+    B: DifferentialPrivacyBudget,
+{
+    budget: B,
+    num_noise_shares: u8,
+}
+
+impl<B> SkellamDpStrategy<B>
+where
+    B: DifferentialPrivacyBudget,
+{
+    /// Create a strategy for the given budget, where the privacy guarantee holds if
+    /// `num_noise_shares` aggregators add their noise shares honestly. Errors if `num_noise_shares`
+    /// is zero.
+    pub fn new(budget: B, num_noise_shares: u8) -> Result<Self, DpError> {
+        if num_noise_shares == 0 {
+            return Err(DpError::InvalidParameter(
+                "number of noise shares must be positive".into(),
+            ));
+        }
+        Ok(Self {
+            budget,
+            num_noise_shares,
+        })
+    }
+
+    /// The number of noise shares that together satisfy the privacy budget.
+    pub fn num_noise_shares(&self) -> u8 {
+        self.num_noise_shares
+    }
+}
+
+/// A DP strategy using distributed Skellam noise providing Rényi DP at integer orders.
+///
+/// This uses L2-sensitivity, with the substitution definition of neighboring datasets, and
+/// requires that the function being noised is integer-valued.
+pub type RenyiDpSkellam = SkellamDpStrategy<IntegerRenyiDpBudget>;
+
+impl DifferentialPrivacyStrategy for SkellamDpStrategy<IntegerRenyiDpBudget> {
+    type Budget = IntegerRenyiDpBudget;
+    type Distribution = Skellam;
+    type Sensitivity = Ratio<BigUint>;
+
+    /// Create a strategy in which a single noise share satisfies the budget.
+    fn from_budget(budget: IntegerRenyiDpBudget) -> SkellamDpStrategy<IntegerRenyiDpBudget> {
+        SkellamDpStrategy {
+            budget,
+            num_noise_shares: 1,
+        }
+    }
+
+    /// Create a new sampler for one noise share. The sum of `num_noise_shares` samples is Skellam
+    /// distributed with a parameter calibrated to provide `(alpha, alpha * rho)` Rényi differential
+    /// privacy at every integer order `alpha >= 2` when added to the result of an integer-valued
+    /// function with L2-sensitivity `sensitivity`.
+    fn create_distribution(&self, sensitivity: Ratio<BigUint>) -> Result<Skellam, DpError> {
+        let mu = skellam_parameter(&self.budget, &sensitivity)?;
+        let scale: BigUint = BigUint::one() << 32usize;
+        let share: Ratio<BigUint> =
+            (mu * &scale / BigUint::from(self.num_noise_shares)).ceil() / scale;
+        Skellam::new(share)
+    }
+
+    /// The privacy guarantee requires `num_noise_shares` shares of the noise, and each aggregator
+    /// adds one, so there must be at least that many aggregators.
+    fn validate_num_aggregators(&self, num_aggregators: usize) -> Result<(), DpError> {
+        if usize::from(self.num_noise_shares) > num_aggregators {
+            return Err(DpError::InvalidParameter(format!(
+                "{} noise shares are needed, but there are only {num_aggregators} aggregators",
+                self.num_noise_shares
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl PrivacyCost for RenyiDpSkellam {
+    /// Each application costs `(alpha, alpha * rho)` Rényi DP at the integer orders
+    /// `2 <= alpha <= 256`, provided that `num_noise_shares` shares of the noise are added honestly.
+    /// The loss at other orders is unbounded.
+    fn privacy_loss(&self) -> Result<PrivacyLoss, DpError> {
+        let rho = self.budget.rho.to_f64().ok_or(DpError::InvalidFloat)?;
+        Ok(PrivacyLoss::RenyiDp(
+            (2..=MAX_RENYI_ORDER)
+                .map(|alpha| (f64::from(alpha), f64::from(alpha) * rho))
+                .collect(),
+        ))
+    }
+}
+
+/// Compute the smallest Skellam parameter `mu`, up to a multiple of `2^-32`, such that
+/// `delta^2 / (4 * mu) + 7 * delta^2 / (16 * mu^2) <= rho`.
+///
+/// This solves the quadratic in floating point, and then checks the result with exact arithmetic,
+/// increasing it if rounding errors made it too small.
+fn skellam_parameter(
+    budget: &IntegerRenyiDpBudget,
+    sensitivity: &Ratio<BigUint>,
+) -> Result<Ratio<BigUint>, DpError> {
+    if sensitivity.denom().is_zero() {
+        return Err(DpError::ZeroDenominator);
+    }
+
+    let rho = &budget.rho;
+    let delta_squared = sensitivity * sensitivity;
+    let satisfies_budget = |mu: &Ratio<BigUint>| {
+        &delta_squared / (mu * BigUint::from(4u8))
+            + &delta_squared * BigUint::from(7u8) / (mu * mu * BigUint::from(16u8))
+            <= *rho
+    };
+
+    let (Some(rho_f64), Some(delta_squared_f64)) = (rho.to_f64(), delta_squared.to_f64()) else {
+        return Err(DpError::InvalidParameter(
+            "privacy parameters are out of range".into(),
+        ));
+    };
+    let mu_f64 = (4.0 * delta_squared_f64
+        + (16.0 * delta_squared_f64.powi(2) + 448.0 * rho_f64 * delta_squared_f64).sqrt())
+        / (32.0 * rho_f64);
+    if !mu_f64.is_finite() || mu_f64 >= 2f64.powi(MAX_POISSON_PARAMETER_BITS as i32) {
+        return Err(DpError::InvalidParameter(
+            "privacy parameters require too much noise".into(),
+        ));
+    }
+
+    let scale: BigUint = BigUint::one() << 32usize;
+    // Unwrap safety: `mu_f64` is finite and non-negative.
+    let mut numer = BigUint::from((mu_f64 * 2f64.powi(32)).ceil() as u64).max(BigUint::one());
+    loop {
+        let mu = Ratio::new(numer.clone(), scale.clone());
+        if satisfies_budget(&mu) {
+            return Ok(mu);
+        }
+        numer += (&numer >> 20usize).max(BigUint::one());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Poisson, RenyiDpSkellam, Skellam};
+    use crate::{
+        dp::{
+            accountant::{PrivacyAccountant, PrivacyCost, PrivacyLoss, TotalBudget},
+            distributions::test_utils::test_mean,
+            DifferentialPrivacyStrategy, EpsilonDeltaBudget, IntegerRenyiDpBudget, Rational,
+            ZCdpBudget,
+        },
+        vdaf::xof::SeedStreamTurboShake128,
+    };
+    use num_bigint::{BigInt, BigUint};
+    use num_rational::Ratio;
+    use num_traits::ToPrimitive;
+    use rand::{distr::Distribution, SeedableRng};
+    use statrs::distribution::{ChiSquared, ContinuousCDF, Discrete};
+
+    #[test]
+    fn poisson_moments() {
+        for (numer, denom) in [
+            (3u64, 10u64),
+            (1, 1),
+            (15, 2),
+            (100_001, 100),
+            (1 << 32, 1 << 20),
+        ] {
+            let lambda = numer as f64 / denom as f64;
+            let poisson =
+                Poisson::new(&Ratio::new(BigUint::from(numer), BigUint::from(denom))).unwrap();
+            let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+            let samples = (0..10_000)
+                .map(|_| poisson.sample(&mut rng) as f64)
+                .collect::<Vec<_>>();
+            let mut iter = samples.iter();
+            assert!(
+                test_mean(
+                    || BigInt::from(*iter.next().unwrap() as u128),
+                    lambda,
+                    lambda,
+                    0.00001,
+                    10_000,
+                ),
+                "Empirical evaluation of the Poisson({lambda}) distribution mean failed"
+            );
+            let variance = samples.iter().map(|x| (x - lambda).powi(2)).sum::<f64>() / 10_000.0;
+            assert!(
+                (variance - lambda).abs() < 0.1 * lambda,
+                "empirical variance {variance}, expected {lambda}"
+            );
+        }
+    }
+
+    #[test]
+    fn poisson_chi_square() {
+        // Compare a histogram of samples to the probability mass function, collecting the values
+        // above `cutoff` into a single bin.
+        for (lambda, cutoff) in [(Ratio::new(7u8, 2u8), 12u128), (Ratio::new(40u8, 1u8), 60)] {
+            let lambda = Ratio::new(
+                BigUint::from(*lambda.numer()),
+                BigUint::from(*lambda.denom()),
+            );
+            let lambda_f64 = lambda.to_f64().unwrap();
+            let poisson = Poisson::new(&lambda).unwrap();
+            let reference = statrs::distribution::Poisson::new(lambda_f64).unwrap();
+
+            let num_samples = 20_000;
+            let mut rng = SeedStreamTurboShake128::from_seed([1u8; 32]);
+            let mut hist = vec![0u64; cutoff as usize + 2];
+            for _ in 0..num_samples {
+                hist[poisson.sample(&mut rng).min(cutoff + 1) as usize] += 1;
+            }
+
+            let mut probabilities = (0..=cutoff as u64)
+                .map(|k| reference.pmf(k))
+                .collect::<Vec<_>>();
+            probabilities.push(1.0 - probabilities.iter().sum::<f64>());
+
+            let stat = hist
+                .iter()
+                .zip(probabilities.iter())
+                .map(|(observed, p)| {
+                    let expected = p * num_samples as f64;
+                    (*observed as f64 - expected).powi(2) / expected
+                })
+                .sum::<f64>();
+            let chi2 = ChiSquared::new((hist.len() - 1) as f64).unwrap();
+            assert!(1.0 - chi2.cdf(stat) > 0.01, "chi-squared statistic {stat}");
+        }
+    }
+
+    #[test]
+    fn skellam_shares_sum() {
+        // The sum of four shares with parameter 2.5 is distributed as a single sample with
+        // parameter 10, which has variance 20.
+        let skellam = Skellam::new(Ratio::new(BigUint::from(5u8), BigUint::from(2u8))).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([2u8; 32]);
+        assert!(test_mean(
+            || (0..4).map(|_| skellam.sample(&mut rng)).sum::<BigInt>(),
+            0.0,
+            20.0,
+            0.00001,
+            10_000,
+        ));
+    }
+
+    #[test]
+    fn skellam_strategy() {
+        let budget = IntegerRenyiDpBudget::new(Rational::from_unsigned(1u8, 8).unwrap()).unwrap();
+        let sensitivity = Ratio::from_integer(BigUint::from(3u8));
+
+        // With rho = 1/8 and delta = 3, mu is approximately 19.6.
+        let mu = super::skellam_parameter(&budget, &sensitivity).unwrap();
+        let mu_f64 = mu.to_f64().unwrap();
+        let rho = 0.125;
+        assert!(9.0 / (4.0 * mu_f64) + 63.0 / (16.0 * mu_f64 * mu_f64) <= rho);
+        assert!(9.0 / (4.0 * (mu_f64 - 0.01)) + 63.0 / (16.0 * (mu_f64 - 0.01).powi(2)) > rho);
+
+        let single = RenyiDpSkellam::from_budget(budget.clone());
+        assert_eq!(single.num_noise_shares(), 1);
+        let split = RenyiDpSkellam::new(budget.clone(), 2).unwrap();
+        RenyiDpSkellam::new(budget.clone(), 0).unwrap_err();
+        split.validate_num_aggregators(2).unwrap();
+        split.validate_num_aggregators(3).unwrap();
+        split.validate_num_aggregators(1).unwrap_err();
+
+        // Each of two shares has half the variance of a single share.
+        let mut rng = SeedStreamTurboShake128::from_seed([3u8; 32]);
+        let sampler = split.create_distribution(sensitivity.clone()).unwrap();
+        assert!(test_mean(
+            || sampler.sample(&mut rng),
+            0.0,
+            mu_f64,
+            0.00001,
+            10_000,
+        ));
+        let sampler = single.create_distribution(sensitivity).unwrap();
+        assert!(test_mean(
+            || sampler.sample(&mut rng),
+            0.0,
+            2.0 * mu_f64,
+            0.00001,
+            10_000,
+        ));
+
+        IntegerRenyiDpBudget::new(Rational::from_unsigned(0u8, 1).unwrap()).unwrap_err();
+
+        // The privacy loss is only bounded at integer orders, so it can be charged against an
+        // (epsilon, delta) budget, but not against a zCDP budget.
+        let loss = single.privacy_loss().unwrap();
+        let PrivacyLoss::RenyiDp(curve) = &loss else {
+            panic!("unexpected privacy loss {loss:?}");
+        };
+        assert!(curve
+            .iter()
+            .all(|(alpha, epsilon)| alpha.fract() == 0.0 && *epsilon == alpha * rho));
+        assert!(!PrivacyAccountant::new(TotalBudget::ZCdp(ZCdpBudget::new(
+            Rational::from_unsigned(1u8, 1).unwrap()
+        )))
+        .can_charge(&loss));
+        assert!(PrivacyAccountant::new(TotalBudget::EpsilonDelta(
+            EpsilonDeltaBudget::new(
+                Rational::from_unsigned(4u8, 1).unwrap(),
+                Rational::from_unsigned(1u32, 100_000).unwrap()
+            )
+            .unwrap()
+        ))
+        .can_charge(&loss));
+    }
+}
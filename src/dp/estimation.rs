@@ -0,0 +1,362 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Estimates of the error that differential privacy noise introduces into aggregate results.
+//!
+//! After unsharding, each coordinate of a noised aggregate result is the true value plus the sum
+//! of the noise added by each aggregator. A [`NoiseEstimate`] describes that sum: its standard
+//! deviation, and confidence intervals that contain the true value with at least a given
+//! probability.
+//!
+//! Confidence intervals are computed from rigorous tail bounds, not from a normal approximation,
+//! so they are conservative. The half-width of each interval is the smaller of the one given by
+//! Chebyshev's inequality and the one given by a Chernoff bound on the moment generating function
+//! of the noise.
+
+use super::{DifferentialPrivacyDistribution, DifferentialPrivacyStrategy, DpError};
+
+/// A noise distribution whose spread is known, so that the error it introduces can be estimated.
+///
+/// The distribution must be symmetric around zero.
+pub trait NoiseDistribution: DifferentialPrivacyDistribution {
+    /// The variance of the distribution, or an upper bound on it.
+    fn variance(&self) -> f64;
+
+    /// An upper bound on the natural logarithm of the moment generating function of the
+    /// distribution, `ln E[exp(t * X)]`, for `0 <= t < self.max_mgf_parameter()`.
+    fn log_mgf(&self, t: f64) -> f64;
+
+    /// The supremum of the parameters at which the moment generating function is finite.
+    fn max_mgf_parameter(&self) -> f64 {
+        f64::INFINITY
+    }
+}
+
+/// An interval that contains the true value of a noised quantity with at least the requested
+/// probability.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConfidenceInterval {
+    /// The lower end of the interval.
+    pub lower: f64,
+    /// The upper end of the interval.
+    pub upper: f64,
+}
+
+impl ConfidenceInterval {
+    /// Returns true if the interval contains `value`.
+    pub fn contains(&self, value: f64) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+}
+
+/// A noised value, along with an estimate of its error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoisyValue {
+    /// The noised value, which is an unbiased estimate of the true value.
+    pub value: f64,
+    /// The standard deviation of the noise.
+    pub standard_deviation: f64,
+    /// A confidence interval for the true value.
+    pub confidence_interval: ConfidenceInterval,
+}
+
+/// An estimate of the noise in each coordinate of an aggregate result, when each of
+/// `num_noise_shares` aggregators adds independent noise from the same distribution.
+#[derive(Clone, Debug)]
+pub struct NoiseEstimate<D> {
+    distribution: D,
+    num_noise_shares: usize,
+}
+
+impl<D: NoiseDistribution> NoiseEstimate<D> {
+    /// Estimate the noise added by `num_noise_shares` aggregators, each of which adds noise from
+    /// the distribution that `dp_strategy` creates for `sensitivity`.
+    pub fn new<S>(
+        dp_strategy: &S,
+        sensitivity: S::Sensitivity,
+        num_noise_shares: usize,
+    ) -> Result<Self, DpError>
+    where
+        S: DifferentialPrivacyStrategy<Distribution = D>,
+    {
+        Self::from_distribution(
+            dp_strategy.create_distribution(sensitivity)?,
+            num_noise_shares,
+        )
+    }
+
+    /// Estimate the noise added by `num_noise_shares` aggregators, each of which adds noise from
+    /// `distribution`. Errors if `num_noise_shares` is zero.
+    pub fn from_distribution(distribution: D, num_noise_shares: usize) -> Result<Self, DpError> {
+        if num_noise_shares == 0 {
+            return Err(DpError::InvalidParameter(
+                "at least one aggregator must add noise".into(),
+            ));
+        }
+        Ok(Self {
+            distribution,
+            num_noise_shares,
+        })
+    }
+
+    /// The variance of the noise in each coordinate, or an upper bound on it.
+    pub fn variance(&self) -> f64 {
+        self.num_noise_shares as f64 * self.distribution.variance()
+    }
+
+    /// The standard deviation of the noise in each coordinate, or an upper bound on it.
+    pub fn standard_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// The half-width of a confidence interval at the given confidence level, i.e., a bound `a`
+    /// such that the noise in each coordinate is larger than `a` in absolute value with
+    /// probability at most `1 - confidence`. Errors if `confidence` is not strictly between zero
+    /// and one.
+    pub fn margin_of_error(&self, confidence: f64) -> Result<f64, DpError> {
+        if !(confidence > 0.0 && confidence < 1.0) {
+            return Err(DpError::InvalidParameter(
+                "the confidence level must be strictly between zero and one".into(),
+            ));
+        }
+        let alpha = 1.0 - confidence;
+
+        // Chebyshev's inequality: P(|X| >= a) <= Var[X] / a^2.
+        let chebyshev = (self.variance() / alpha).sqrt();
+
+        // Chernoff bound: P(|X| >= a) <= 2 * exp(inf_t (n * ln E[exp(t * X)] - t * a)), by
+        // symmetry. The exponent is non-increasing in `a`, so find the smallest `a` at which the
+        // bound is at most `alpha` by bisection, looking no further than the Chebyshev bound.
+        let target = (alpha / 2.0).ln();
+        let exponent = self.chernoff_exponent(chebyshev);
+        if exponent.is_nan() || exponent > target {
+            return Ok(chebyshev);
+        }
+        let (mut lower, mut upper) = (0.0, chebyshev);
+        for _ in 0..128 {
+            let mid = (lower + upper) / 2.0;
+            if self.chernoff_exponent(mid) <= target {
+                upper = mid;
+            } else {
+                lower = mid;
+            }
+        }
+        Ok(upper)
+    }
+
+    /// A confidence interval for the true value of a coordinate whose noised value is `value`.
+    pub fn confidence_interval(
+        &self,
+        value: f64,
+        confidence: f64,
+    ) -> Result<ConfidenceInterval, DpError> {
+        let margin = self.margin_of_error(confidence)?;
+        Ok(ConfidenceInterval {
+            lower: value - margin,
+            upper: value + margin,
+        })
+    }
+
+    /// Attach the standard deviation and a confidence interval at the given confidence level to
+    /// each coordinate of a noised aggregate result.
+    pub fn estimate(&self, values: &[f64], confidence: f64) -> Result<Vec<NoisyValue>, DpError> {
+        let standard_deviation = self.standard_deviation();
+        let margin = self.margin_of_error(confidence)?;
+        Ok(values
+            .iter()
+            .map(|&value| NoisyValue {
+                value,
+                standard_deviation,
+                confidence_interval: ConfidenceInterval {
+                    lower: value - margin,
+                    upper: value + margin,
+                },
+            })
+            .collect())
+    }
+
+    /// Computes `inf_t (n * ln E[exp(t * X)] - t * a)` over the domain of the moment generating
+    /// function, using ternary search. The objective is convex in `t`.
+    fn chernoff_exponent(&self, a: f64) -> f64 {
+        let n = self.num_noise_shares as f64;
+        let objective = |t: f64| n * self.distribution.log_mgf(t) - t * a;
+
+        // Find an upper end for the search. The moment generating function may diverge at its
+        // supremum, so stay slightly below it.
+        let max = self.distribution.max_mgf_parameter();
+        let mut hi = if max.is_finite() {
+            max * (1.0 - 1e-9)
+        } else {
+            let mut hi = 1.0;
+            while hi < 1e12 && objective(2.0 * hi) < objective(hi) {
+                hi *= 2.0;
+            }
+            2.0 * hi
+        };
+
+        let mut lo = 0.0;
+        for _ in 0..200 {
+            let m1 = lo + (hi - lo) / 3.0;
+            let m2 = hi - (hi - lo) / 3.0;
+            if objective(m1) <= objective(m2) {
+                hi = m2;
+            } else {
+                lo = m1;
+            }
+        }
+        objective((lo + hi) / 2.0).min(0.0)
+    }
+}
+
+/// The variance of the discrete Laplace distribution with the given scale, `2p / (1 - p)^2` with
+/// `p = exp(-1 / scale)`.
+pub(crate) fn discrete_laplace_variance(scale: f64) -> f64 {
+    let p = (-1.0 / scale).exp();
+    2.0 * p / (-(-1.0 / scale).exp_m1()).powi(2)
+}
+
+/// The natural logarithm of the moment generating function of the discrete Laplace distribution
+/// with the given scale, `ln((1 - p)^2 / ((1 - p * exp(t)) * (1 - p * exp(-t))))` with
+/// `p = exp(-1 / scale)`. This is finite for `|t| < 1 / scale`.
+pub(crate) fn discrete_laplace_log_mgf(scale: f64, t: f64) -> f64 {
+    2.0 * (-(-1.0 / scale).exp_m1()).ln()
+        - (-(t - 1.0 / scale).exp_m1()).ln()
+        - (-(-t - 1.0 / scale).exp_m1()).ln()
+}
+
+/// The natural logarithm of the moment generating function of the discrete Gaussian distribution
+/// with the given standard deviation is at most `t^2 * std^2 / 2`, because the distribution is
+/// subgaussian [[CKS20]].
+///
+/// [CKS20]: https://arxiv.org/pdf/2004.00010.pdf
+pub(crate) fn discrete_gaussian_log_mgf_bound(std: f64, t: f64) -> f64 {
+    t * t * std * std / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NoiseDistribution, NoiseEstimate};
+    use crate::{
+        dp::{
+            distributions::{
+                DiscreteGaussian, DiscreteLaplace, PureDpDiscreteLaplace, Skellam,
+                ZCdpDiscreteGaussian,
+            },
+            DifferentialPrivacyStrategy, PureDpBudget, Rational, ZCdpBudget,
+        },
+        vdaf::xof::SeedStreamTurboShake128,
+    };
+    use num_bigint::{BigInt, BigUint};
+    use num_rational::Ratio;
+    use num_traits::ToPrimitive;
+    use rand::distr::Distribution;
+    use rand_core::SeedableRng;
+
+    /// Checks that at most a `1 - confidence` fraction of sums of `num_noise_shares` samples fall
+    /// outside of the margin of error.
+    fn check_coverage<D>(
+        estimate: &NoiseEstimate<D>,
+        distribution: &D,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) where
+        D: Distribution<BigInt> + NoiseDistribution,
+    {
+        let margin = estimate.margin_of_error(confidence).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+        let num_samples = 2000;
+        let num_outside = (0..num_samples)
+            .filter(|_| {
+                let noise: BigInt = (0..num_noise_shares)
+                    .map(|_| distribution.sample(&mut rng))
+                    .sum();
+                noise.to_f64().unwrap().abs() > margin
+            })
+            .count();
+        assert!(
+            (num_outside as f64) < (1.0 - confidence) * num_samples as f64,
+            "{num_outside} of {num_samples} samples are outside of the margin {margin}",
+        );
+    }
+
+    #[test]
+    fn laplace() {
+        let scale = 3u8;
+        let estimate = NoiseEstimate::from_distribution(
+            DiscreteLaplace::new(Ratio::from_integer(BigUint::from(scale))).unwrap(),
+            2,
+        )
+        .unwrap();
+        let p = (-1.0 / f64::from(scale)).exp();
+        let variance = 2.0 * 2.0 * p / (1.0 - p).powi(2);
+        assert!((estimate.variance() - variance).abs() < 1e-9);
+        assert!((estimate.standard_deviation() - variance.sqrt()).abs() < 1e-9);
+
+        let distribution = DiscreteLaplace::new(Ratio::from_integer(BigUint::from(scale))).unwrap();
+        check_coverage(&estimate, &distribution, 2, 0.9);
+        check_coverage(&estimate, &distribution, 2, 0.99);
+
+        // The Chernoff bound is tighter than Chebyshev's inequality at high confidence levels.
+        let margin = estimate.margin_of_error(0.999).unwrap();
+        assert!(margin < (variance / 0.001).sqrt());
+    }
+
+    #[test]
+    fn gaussian() {
+        let std = 5u8;
+        let distribution = DiscreteGaussian::new(Ratio::from_integer(BigUint::from(std))).unwrap();
+        let estimate = NoiseEstimate::from_distribution(distribution.clone(), 1).unwrap();
+        assert_eq!(estimate.variance(), 25.0);
+
+        // The Chernoff bound for a subgaussian distribution is `std * sqrt(2 * ln(2 / alpha))`.
+        let margin = estimate.margin_of_error(0.95).unwrap();
+        let expected = 5.0 * (2.0 * (2.0f64 / 0.05).ln()).sqrt();
+        assert!((margin - expected).abs() < 1e-6 * expected, "{margin}");
+
+        check_coverage(&estimate, &distribution, 1, 0.95);
+    }
+
+    #[test]
+    fn skellam() {
+        let mu = Ratio::new(BigUint::from(25u8), BigUint::from(2u8));
+        let distribution = Skellam::new(mu).unwrap();
+        let estimate = NoiseEstimate::from_distribution(distribution.clone(), 2).unwrap();
+        assert!((estimate.variance() - 50.0).abs() < 1e-9);
+        check_coverage(&estimate, &distribution, 2, 0.95);
+    }
+
+    #[test]
+    fn from_strategy() {
+        let laplace = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1u8, 2).unwrap()).unwrap(),
+        );
+        let estimate =
+            NoiseEstimate::new(&laplace, Ratio::from_integer(BigUint::from(1u8)), 2).unwrap();
+        let p = (-0.5f64).exp();
+        assert!((estimate.variance() - 4.0 * p / (1.0 - p).powi(2)).abs() < 1e-9);
+
+        let gaussian = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+            Rational::from_unsigned(1u8, 1).unwrap(),
+        ));
+        let estimate =
+            NoiseEstimate::new(&gaussian, Ratio::from_integer(BigUint::from(2u8)), 1).unwrap();
+        assert_eq!(estimate.variance(), 4.0);
+
+        let noisy = estimate.estimate(&[10.0, -3.0], 0.9).unwrap();
+        let margin = estimate.margin_of_error(0.9).unwrap();
+        assert_eq!(noisy[0].value, 10.0);
+        assert_eq!(noisy[0].standard_deviation, 2.0);
+        assert_eq!(noisy[1].confidence_interval.lower, -3.0 - margin);
+        assert_eq!(noisy[1].confidence_interval.upper, -3.0 + margin);
+        assert!(noisy[1].confidence_interval.contains(-3.0));
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let distribution = DiscreteGaussian::new(Ratio::from_integer(BigUint::from(1u8))).unwrap();
+        assert!(NoiseEstimate::from_distribution(distribution.clone(), 0).is_err());
+        let estimate = NoiseEstimate::from_distribution(distribution, 1).unwrap();
+        for confidence in [0.0, 1.0, -0.5, 2.0, f64::NAN] {
+            assert!(estimate.margin_of_error(confidence).is_err());
+        }
+    }
+}
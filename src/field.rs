@@ -877,6 +877,58 @@ pub(crate) fn merge_vector<F: FieldElement>(
     Ok(())
 }
 
+/// Sum the vector of field elements encoded in `encoded` into accumulator. This is equivalent to
+/// decoding `encoded` and calling [`merge_vector`], but without allocating a vector for the decoded
+/// elements. If `encoded` is rejected, accumulator is left unchanged.
+///
+/// # Errors
+///
+/// Fails if `encoded` is not the encoding of exactly `accumulator.len()` field elements, or if any
+/// of the elements is not canonically encoded.
+pub(crate) fn merge_encoded_vector<F: FieldElement>(
+    accumulator: &mut [F],
+    encoded: &[u8],
+) -> Result<(), FieldError> {
+    if encoded.len() != accumulator.len() * F::ENCODED_SIZE {
+        return Err(FieldError::InputSizeMismatch);
+    }
+
+    for (i, chunk) in encoded.chunks_exact(F::ENCODED_SIZE).enumerate() {
+        match F::try_from(chunk) {
+            Ok(elem) => accumulator[i] += elem,
+            Err(e) => {
+                // Roll back the elements added so far, which are known to decode successfully.
+                for (x, chunk) in accumulator[..i]
+                    .iter_mut()
+                    .zip(encoded.chunks_exact(F::ENCODED_SIZE))
+                {
+                    *x -= F::try_from(chunk)?;
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Subtract other_vector from accumulator. This is the inverse of [`merge_vector`].
+///
+/// # Errors
+///
+/// Fails if the two vectors do not have the same length.
+pub(crate) fn unmerge_vector<F: FieldElement>(
+    accumulator: &mut [F],
+    other_vector: &[F],
+) -> Result<(), FieldError> {
+    if accumulator.len() != other_vector.len() {
+        return Err(FieldError::InputSizeMismatch);
+    }
+    sub_assign_vector(accumulator, other_vector.iter().copied());
+
+    Ok(())
+}
+
 /// Outputs an additive secret sharing of the input.
 #[cfg(test)]
 pub(crate) fn split_vector<F: FieldElement>(inp: &[F], num_shares: usize) -> Vec<Vec<F>> {
@@ -47,11 +47,17 @@
 //! [draft-irtf-cfrg-vdaf-08]: https://datatracker.ietf.org/doc/draft-irtf-cfrg-vdaf/08/
 
 #[cfg(feature = "experimental")]
-use crate::dp::DifferentialPrivacyStrategy;
+use crate::dp::{estimation::NoisyValue, DifferentialPrivacyStrategy};
 use crate::field::{FieldElement, FieldElementWithInteger, FieldError, NttFriendlyFieldElement};
 use crate::fp::log2;
 use crate::ntt::{ntt, ntt_inv_finish, NttError};
 use crate::polynomial::poly_eval;
+#[cfg(feature = "experimental")]
+use crate::vdaf::xof::SeedStreamTurboShake128;
+#[cfg(feature = "experimental")]
+use rand::SeedableRng;
+#[cfg(feature = "experimental")]
+use rand_core::{CryptoRng, RngCore};
 use std::any::Any;
 use std::convert::TryFrom;
 use std::fmt::Debug;
@@ -486,6 +492,11 @@ pub trait Flp: Sized + Eq + Clone + Debug {
     }
 
     /// Returns true if the verifier message indicates that the input from which it was generated is valid.
+    ///
+    /// Verifier messages must be decided one at a time. The check that each gadget's output
+    /// matches the proof is not linear in the verifier message, so a random linear combination of
+    /// the verifier messages of several reports cannot be decided in one call without evaluating
+    /// every gadget on every message, which is all this method does.
     fn decide(&self, verifier: &[Self::Field]) -> Result<bool, FlpError> {
         if verifier.len() != self.verifier_len() {
             return Err(FlpError::Decide(format!(
@@ -517,6 +528,23 @@ pub trait Flp: Sized + Eq + Clone + Debug {
         Ok(true)
     }
 
+    /// Evaluates the validity circuit on the plaintext `input` and explains why it is invalid.
+    ///
+    /// For each non-zero output of [`Self::valid`], the explanation lists the gadget calls and
+    /// the input coordinates that the output depends on. Dependencies are found by perturbing
+    /// each input coordinate and each gadget call output by a random value and checking whether
+    /// the output changes, so this is only meant for debugging and testing. A dependency is
+    /// missed with negligible probability.
+    #[cfg(feature = "test-util")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    fn explain(
+        &self,
+        input: &[Self::Field],
+        joint_rand: &[Self::Field],
+    ) -> Result<test_utils::Explanation<Self::Field>, FlpError> {
+        test_utils::explain(self, input, joint_rand)
+    }
+
     /// Check whether `input` and `joint_rand` have the length expected by `self`,
     /// return [`FlpError::Valid`] otherwise.
     fn valid_call_check(
@@ -599,14 +627,72 @@ pub trait TypeWithNoise<S>: Type
 where
     S: DifferentialPrivacyStrategy,
 {
-    /// Add noise to the aggregate share to obtain differential privacy.
+    /// Add noise to the aggregate share to obtain differential privacy, using randomness from the
+    /// operating system.
+    ///
+    /// Implementations must override at least one of this method and
+    /// [`Self::add_noise_to_result_with_rng`], since their default implementations call each other.
+    /// The default implementation calls [`Self::add_noise_to_result_with_rng`] with an RNG seeded
+    /// by the operating system.
     // TODO(#1073): Rename to add_noise_to_agg_share.
     fn add_noise_to_result(
         &self,
         dp_strategy: &S,
         agg_result: &mut [Self::Field],
         num_measurements: usize,
-    ) -> Result<(), FlpError>;
+    ) -> Result<(), FlpError> {
+        self.add_noise_to_result_with_rng(
+            dp_strategy,
+            agg_result,
+            num_measurements,
+            &mut SeedStreamTurboShake128::from_os_rng(),
+        )
+    }
+
+    /// Add noise to the aggregate share to obtain differential privacy, using randomness from
+    /// `rng`.
+    ///
+    /// The default implementation ignores `rng` and calls [`Self::add_noise_to_result`], so the
+    /// noise is not reproducible. Implementations that support caller-supplied randomness should
+    /// override this method instead.
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &S,
+        agg_result: &mut [Self::Field],
+        num_measurements: usize,
+        _rng: &mut R,
+    ) -> Result<(), FlpError> {
+        self.add_noise_to_result(dp_strategy, agg_result, num_measurements)
+    }
+}
+
+/// A type whose noised aggregate results can be decoded along with an estimate of the error
+/// introduced by the noise.
+///
+/// [`SumVec`](crate::flp::types::SumVec) and [`Histogram`](crate::flp::types::Histogram)
+/// implement this for the discrete Laplace, discrete Gaussian and Skellam strategies.
+/// [`FixedPointBoundedL2VecSum`](crate::flp::types::fixedpoint_l2::FixedPointBoundedL2VecSum)
+/// implements it for the discrete Gaussian strategies only, as those are the only strategies with
+/// which it adds noise.
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub trait TypeWithNoiseEstimate<S>: TypeWithNoise<S>
+where
+    S: DifferentialPrivacyStrategy,
+{
+    /// Decode an aggregate result to which each of `num_noise_shares` aggregators added noise
+    /// using `dp_strategy`. Each decoded value is accompanied by the standard deviation of its
+    /// noise and by a confidence interval at the given `confidence` level.
+    ///
+    /// Unlike [`Type::decode_result`], the noise may make decoded values negative.
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &S,
+        data: &[Self::Field],
+        num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError>;
 }
 
 /// A gadget, a non-affine arithmetic circuit that is called when evaluating a validity circuit.
@@ -804,6 +890,7 @@ pub(crate) fn gadget_poly_len(gadget_degree: usize, wire_poly_len: usize) -> usi
 pub mod test_utils {
     use super::*;
     use crate::field::{add_vector, sub_assign_vector, FieldElement, FieldElementWithInteger};
+    use std::fmt::{Display, Formatter};
 
     /// Various tests for an FLP.
     #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
@@ -901,10 +988,16 @@ pub mod test_utils {
                 .flp
                 .valid(&mut gadgets, self.input, &joint_rand, 1)
                 .unwrap();
+            let explanation = self.flp.explain(self.input, &joint_rand).unwrap();
             assert_eq!(
                 v.iter().all(|f| f == &T::Field::zero()),
                 self.expect_valid,
-                "{name}: unexpected output of valid() returned {v:?}",
+                "{name}: unexpected output of valid() returned {v:?}\n{explanation}",
+            );
+            assert_eq!(
+                explanation.is_valid(),
+                self.expect_valid,
+                "{name}: unexpected explanation {explanation}"
             );
 
             // Generate the proof.
@@ -991,6 +1084,256 @@ pub mod test_utils {
         }
     }
 
+    /// Explanation of why an input is rejected by a validity circuit, as returned by
+    /// [`Flp::explain`].
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Explanation<F> {
+        /// The non-zero outputs of the validity circuit, in order. This is empty if the input is
+        /// valid.
+        pub failures: Vec<ConstraintFailure<F>>,
+    }
+
+    impl<F> Explanation<F> {
+        /// Returns true if every output of the validity circuit is zero.
+        pub fn is_valid(&self) -> bool {
+            self.failures.is_empty()
+        }
+    }
+
+    impl<F: FieldElement> Display for Explanation<F> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            if self.failures.is_empty() {
+                return write!(f, "all outputs of the validity circuit are zero");
+            }
+            for failure in &self.failures {
+                writeln!(
+                    f,
+                    "output {} of the validity circuit is {}; it depends on input coordinates \
+                    {:?}",
+                    failure.output, failure.value, failure.inputs
+                )?;
+                for call in &failure.gadget_calls {
+                    writeln!(
+                        f,
+                        "  gadget {} call {}: [{}] -> {} (input coordinates {:?})",
+                        call.gadget,
+                        call.call,
+                        call.inputs
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        call.output,
+                        call.input_coordinates
+                    )?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// A non-zero output of a validity circuit.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct ConstraintFailure<F> {
+        /// The position of the output among the outputs of [`Flp::valid`].
+        pub output: usize,
+
+        /// The value of the output.
+        pub value: F,
+
+        /// The gadget calls that the output depends on.
+        pub gadget_calls: Vec<GadgetCall<F>>,
+
+        /// The input coordinates that the output depends on.
+        pub inputs: Vec<usize>,
+    }
+
+    /// A gadget call made while evaluating a validity circuit.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct GadgetCall<F> {
+        /// The index of the gadget, in the order returned by [`Flp::gadget`].
+        pub gadget: usize,
+
+        /// The number of previous calls to the same gadget.
+        pub call: usize,
+
+        /// The input wires of the call.
+        pub inputs: Vec<F>,
+
+        /// The output of the call.
+        pub output: F,
+
+        /// The input coordinates that the input wires of the call depend on.
+        pub input_coordinates: Vec<usize>,
+    }
+
+    /// A gadget that records each call, and optionally adds a value to the output of one call.
+    #[derive(Debug)]
+    struct TracingGadget<F: NttFriendlyFieldElement> {
+        inner: Box<dyn Gadget<F>>,
+        calls: Vec<(Vec<F>, F)>,
+        perturbation: Option<(usize, F)>,
+    }
+
+    impl<F: NttFriendlyFieldElement> Gadget<F> for TracingGadget<F> {
+        fn call(&mut self, inp: &[F]) -> Result<F, FlpError> {
+            let mut output = self.inner.call(inp)?;
+            if let Some((call, delta)) = self.perturbation {
+                if call == self.calls.len() {
+                    output += delta;
+                }
+            }
+            self.calls.push((inp.to_vec(), output));
+            Ok(output)
+        }
+
+        fn call_poly(&mut self, outp: &mut [F], inp: &[Vec<F>]) -> Result<(), FlpError> {
+            self.inner.call_poly(outp, inp)
+        }
+
+        fn arity(&self) -> usize {
+            self.inner.arity()
+        }
+
+        fn degree(&self) -> usize {
+            self.inner.degree()
+        }
+
+        fn calls(&self) -> usize {
+            self.inner.calls()
+        }
+
+        fn as_any(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    /// Evaluates the validity circuit with tracing gadgets. If `perturbation` is `(gadget, call,
+    /// delta)`, then `delta` is added to the output of that gadget call. Returns the outputs of
+    /// the circuit and the calls made to each gadget.
+    #[allow(clippy::type_complexity)]
+    fn eval_traced<T: Flp>(
+        flp: &T,
+        input: &[T::Field],
+        joint_rand: &[T::Field],
+        perturbation: Option<(usize, usize, T::Field)>,
+    ) -> Result<(Vec<T::Field>, Vec<Vec<(Vec<T::Field>, T::Field)>>), FlpError> {
+        let mut gadgets = flp
+            .gadget()
+            .into_iter()
+            .enumerate()
+            .map(|(gadget, inner)| {
+                Box::new(TracingGadget {
+                    inner,
+                    calls: Vec::new(),
+                    perturbation: perturbation
+                        .filter(|(perturbed_gadget, _, _)| *perturbed_gadget == gadget)
+                        .map(|(_, call, delta)| (call, delta)),
+                }) as Box<dyn Gadget<T::Field>>
+            })
+            .collect();
+        let outputs = flp.valid(&mut gadgets, input, joint_rand, 1)?;
+        let calls = gadgets
+            .iter_mut()
+            .map(|gadget| {
+                std::mem::take(
+                    &mut gadget
+                        .as_any()
+                        .downcast_mut::<TracingGadget<T::Field>>()
+                        .unwrap()
+                        .calls,
+                )
+            })
+            .collect();
+        Ok((outputs, calls))
+    }
+
+    pub(super) fn explain<T: Flp>(
+        flp: &T,
+        input: &[T::Field],
+        joint_rand: &[T::Field],
+    ) -> Result<Explanation<T::Field>, FlpError> {
+        flp.valid_call_check(input, joint_rand)?;
+        let (outputs, calls) = eval_traced(flp, input, joint_rand, None)?;
+        let mut failures = outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != T::Field::zero())
+            .map(|(output, value)| ConstraintFailure {
+                output,
+                value: *value,
+                gadget_calls: Vec::new(),
+                inputs: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        if failures.is_empty() {
+            return Ok(Explanation { failures });
+        }
+
+        let delta = loop {
+            let delta = T::Field::random_vector(1)[0];
+            if delta != T::Field::zero() {
+                break delta;
+            }
+        };
+
+        let mut call_input_coordinates = calls
+            .iter()
+            .map(|gadget_calls| vec![Vec::new(); gadget_calls.len()])
+            .collect::<Vec<_>>();
+        let mut perturbed_input = input.to_vec();
+        for i in 0..input.len() {
+            perturbed_input[i] += delta;
+            let (perturbed_outputs, perturbed_calls) =
+                eval_traced(flp, &perturbed_input, joint_rand, None)?;
+            perturbed_input[i] = input[i];
+            for failure in failures.iter_mut() {
+                if perturbed_outputs[failure.output] != outputs[failure.output] {
+                    failure.inputs.push(i);
+                }
+            }
+            for ((coordinates, gadget_calls), perturbed_gadget_calls) in call_input_coordinates
+                .iter_mut()
+                .zip(calls.iter())
+                .zip(perturbed_calls.iter())
+            {
+                for ((coordinates, (inputs, _)), (perturbed_inputs, _)) in coordinates
+                    .iter_mut()
+                    .zip(gadget_calls)
+                    .zip(perturbed_gadget_calls)
+                {
+                    if inputs != perturbed_inputs {
+                        coordinates.push(i);
+                    }
+                }
+            }
+        }
+
+        for (gadget, (gadget_calls, coordinates)) in
+            calls.into_iter().zip(call_input_coordinates).enumerate()
+        {
+            for (call, ((inputs, output), input_coordinates)) in
+                gadget_calls.into_iter().zip(coordinates).enumerate()
+            {
+                let (perturbed_outputs, _) =
+                    eval_traced(flp, input, joint_rand, Some((gadget, call, delta)))?;
+                for failure in failures.iter_mut() {
+                    if perturbed_outputs[failure.output] != outputs[failure.output] {
+                        failure.gadget_calls.push(GadgetCall {
+                            gadget,
+                            call,
+                            inputs: inputs.clone(),
+                            output,
+                            input_coordinates: input_coordinates.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(Explanation { failures })
+    }
+
     fn split_vector<F: FieldElement, const SHARES: usize>(inp: &[F]) -> [Vec<F>; SHARES] {
         let mut outp = Vec::with_capacity(SHARES);
         outp.push(inp.to_vec());
@@ -1008,6 +1351,8 @@ pub mod test_utils {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "experimental")]
+    use crate::dp::{distributions::PureDpDiscreteLaplace, PureDpBudget, Rational};
     use crate::field::{add_vector, split_vector, Field128};
     use crate::flp::gadgets::{Mul, PolyEval};
     use crate::polynomial::poly_range_check;
@@ -1056,6 +1401,68 @@ mod tests {
         assert!(typ.decide(&verifier).unwrap());
     }
 
+    #[test]
+    fn test_explain() {
+        use crate::field::Field64;
+        use crate::flp::gadgets::ParallelSum;
+        use crate::flp::types::{Count, Histogram};
+
+        let count = Count::<Field64>::new();
+        let explanation = count
+            .explain(&count.encode_measurement(&true).unwrap(), &[])
+            .unwrap();
+        assert!(explanation.is_valid());
+
+        // x^2 - x for x = 2.
+        let explanation = count.explain(&[Field64::from(2)], &[]).unwrap();
+        assert_eq!(
+            explanation.failures,
+            [test_utils::ConstraintFailure {
+                output: 0,
+                value: Field64::from(2),
+                gadget_calls: vec![test_utils::GadgetCall {
+                    gadget: 0,
+                    call: 0,
+                    inputs: vec![Field64::from(2); 2],
+                    output: Field64::from(4),
+                    input_coordinates: vec![0],
+                }],
+                inputs: vec![0],
+            }]
+        );
+
+        let histogram = Histogram::<Field128, ParallelSum<_, _>>::new(4, 2).unwrap();
+        let joint_rand = Field128::random_vector(histogram.joint_rand_len());
+
+        // Two buckets are set: only the sum check fails, and no gadget is involved.
+        let input = [1, 1, 0, 0].map(Field128::from);
+        let explanation = histogram.explain(&input, &joint_rand).unwrap();
+        assert_eq!(explanation.failures.len(), 1);
+        assert_eq!(explanation.failures[0].output, 1);
+        assert_eq!(explanation.failures[0].inputs, [0, 1, 2, 3]);
+        assert!(explanation.failures[0].gadget_calls.is_empty());
+
+        // The first two buckets are out of range: only the range check fails, and the gadget call
+        // on the first chunk is the culprit.
+        let input = [
+            Field128::from(2),
+            -Field128::one(),
+            Field128::zero(),
+            Field128::zero(),
+        ];
+        let explanation = histogram.explain(&input, &joint_rand).unwrap();
+        assert_eq!(explanation.failures.len(), 1);
+        let failure = &explanation.failures[0];
+        assert_eq!(failure.output, 0);
+        assert_eq!(failure.inputs, [0, 1, 2, 3]);
+        assert_eq!(failure.gadget_calls.len(), 2);
+        assert_ne!(failure.gadget_calls[0].output, Field128::zero());
+        assert_eq!(failure.gadget_calls[0].input_coordinates, [0, 1]);
+        assert_eq!(failure.gadget_calls[1].output, Field128::zero());
+        assert_eq!(failure.gadget_calls[1].input_coordinates, [2, 3]);
+        assert!(explanation.to_string().contains("output 0"));
+    }
+
     /// A toy type used for testing multiple gadgets. Valid inputs of this type consist of a pair
     /// of field elements `(x, y)` where `2 <= x < 5` and `x^3 == y`.
     #[derive(Clone, Debug, PartialEq, Eq)]
@@ -1172,6 +1579,39 @@ mod tests {
         }
     }
 
+    // Only the method without caller-supplied randomness is implemented, as in types written
+    // before `add_noise_to_result_with_rng` was added.
+    #[cfg(feature = "experimental")]
+    impl<F: NttFriendlyFieldElement> TypeWithNoise<PureDpDiscreteLaplace> for TestType<F> {
+        fn add_noise_to_result(
+            &self,
+            _dp_strategy: &PureDpDiscreteLaplace,
+            agg_result: &mut [F],
+            _num_measurements: usize,
+        ) -> Result<(), FlpError> {
+            agg_result.iter_mut().for_each(|x| *x += F::one());
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn add_noise_to_result_with_rng_default() {
+        let typ = TestType::<Field128>::new();
+        let dp_strategy = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1u8, 1).unwrap()).unwrap(),
+        );
+        let mut agg_result = vec![Field128::zero(); typ.output_len()];
+        typ.add_noise_to_result_with_rng(
+            &dp_strategy,
+            &mut agg_result,
+            1,
+            &mut SeedStreamTurboShake128::from_seed([0; 32]),
+        )
+        .unwrap();
+        assert_eq!(agg_result, vec![Field128::one(); typ.output_len()]);
+    }
+
     // In https://github.com/divviup/libprio-rs/issues/254 an out-of-bounds bug was reported that
     // gets triggered when the size of the buffer passed to `gadget.call_poly()` is larger than
     // needed for computing the gadget polynomial.
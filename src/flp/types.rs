@@ -4,7 +4,8 @@
 
 use crate::field::{FieldElementWithIntegerExt, Integer, NttFriendlyFieldElement};
 use crate::flp::gadgets::{Mul, ParallelSumGadget, PolyEval};
-use crate::flp::{Flp, FlpError, Gadget, Type};
+use crate::flp::{gadget_poly_len, wire_poly_len, Flp, FlpError, Gadget, Type};
+use crate::ntt::{ntt, ntt_inv_finish};
 use crate::polynomial::poly_range_check;
 use std::convert::TryInto;
 use std::fmt::{self, Debug};
@@ -14,6 +15,8 @@ use subtle::Choice;
 
 #[cfg(feature = "experimental")]
 mod dp;
+#[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
+pub(crate) use dp::{add_iid_noise_to_field_vec, sqrt_upper_bound};
 
 /// The counter data type. Each measurement is `0` or `1` and the aggregate result is the sum of the
 /// measurements (i.e., the total number of `1s`).
@@ -764,6 +767,373 @@ where
     }
 }
 
+/// The optional histogram type. Each measurement is either an integer in `[0, length)` or no value
+/// at all, and the aggregate is a histogram counting the number of occurrences of each measurement.
+/// Measurements with no value are not counted in any bucket.
+///
+/// This is useful for optional answers, where a client may decline to choose a bucket. The encoding
+/// is a one-hot vector of length `length + 1`, where the extra, final bucket is set for
+/// measurements with no value. The validity circuit is the same as [`Histogram`]'s, and the extra
+/// bucket is removed by [`Type::truncate`].
+#[derive(PartialEq, Eq)]
+pub struct OptionalHistogram<F, S> {
+    length: usize,
+    chunk_length: usize,
+    gadget_calls: usize,
+    phantom: PhantomData<(F, S)>,
+}
+
+impl<F: NttFriendlyFieldElement, S> Debug for OptionalHistogram<F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OptionalHistogram")
+            .field("length", &self.length)
+            .field("chunk_length", &self.chunk_length)
+            .finish()
+    }
+}
+
+impl<F: NttFriendlyFieldElement, S: ParallelSumGadget<F, Mul<F>>> OptionalHistogram<F, S> {
+    /// Return a new [`OptionalHistogram`] type with the given number of buckets.
+    pub fn new(length: usize, chunk_length: usize) -> Result<Self, FlpError> {
+        if length >= u32::MAX as usize - 1 {
+            return Err(FlpError::Encode(
+                "invalid length: number of buckets exceeds maximum permitted".to_string(),
+            ));
+        }
+        if length == 0 {
+            return Err(FlpError::InvalidParameter(
+                "length cannot be zero".to_string(),
+            ));
+        }
+        if chunk_length == 0 {
+            return Err(FlpError::InvalidParameter(
+                "chunk_length cannot be zero".to_string(),
+            ));
+        }
+
+        // The encoded measurement has one more entry than the number of buckets.
+        let gadget_calls = (length + 1).div_ceil(chunk_length);
+
+        Ok(Self {
+            length,
+            chunk_length,
+            gadget_calls,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<F, S> Clone for OptionalHistogram<F, S> {
+    fn clone(&self) -> Self {
+        Self {
+            length: self.length,
+            chunk_length: self.chunk_length,
+            gadget_calls: self.gadget_calls,
+            phantom: self.phantom,
+        }
+    }
+}
+
+impl<F, S> Flp for OptionalHistogram<F, S>
+where
+    F: NttFriendlyFieldElement,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    type Field = F;
+
+    fn gadget(&self) -> Vec<Box<dyn Gadget<F>>> {
+        vec![Box::new(S::new(
+            Mul::new(self.gadget_calls),
+            self.chunk_length,
+        ))]
+    }
+
+    fn num_gadgets(&self) -> usize {
+        1
+    }
+
+    fn valid(
+        &self,
+        g: &mut Vec<Box<dyn Gadget<F>>>,
+        input: &[F],
+        joint_rand: &[F],
+        num_shares: usize,
+    ) -> Result<Vec<F>, FlpError> {
+        self.valid_call_check(input, joint_rand)?;
+
+        // Check that each element of `input`, including the "no value" bucket, is a 0 or 1.
+        let range_check =
+            parallel_sum_range_checks(&mut g[0], input, joint_rand, self.chunk_length, num_shares)?;
+
+        // Check that the elements of `input` sum to 1. Combined with the range check, this means
+        // that at most one of the first `length` buckets is set.
+        let sum_check = weight_check(input, F::one(), num_shares)?;
+
+        Ok(vec![range_check, sum_check])
+    }
+
+    fn input_len(&self) -> usize {
+        self.length + 1
+    }
+
+    fn proof_len(&self) -> usize {
+        (self.chunk_length * 2) + 2 * ((1 + self.gadget_calls).next_power_of_two() - 1) + 1
+    }
+
+    fn verifier_len(&self) -> usize {
+        2 + self.chunk_length * 2
+    }
+
+    fn joint_rand_len(&self) -> usize {
+        self.gadget_calls
+    }
+
+    fn eval_output_len(&self) -> usize {
+        2
+    }
+
+    fn prove_rand_len(&self) -> usize {
+        self.chunk_length * 2
+    }
+}
+
+impl<F, S> Type for OptionalHistogram<F, S>
+where
+    F: NttFriendlyFieldElement,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    type Measurement = Option<usize>;
+    type AggregateResult = Vec<F::Integer>;
+
+    fn encode_measurement(&self, measurement: &Option<usize>) -> Result<Vec<F>, FlpError> {
+        let index = match measurement {
+            Some(index) if *index >= self.length => {
+                return Err(FlpError::Encode(format!(
+                    "unexpected measurement: got {index}; want <{}",
+                    self.length
+                )));
+            }
+            Some(index) => *index,
+            None => self.length,
+        };
+
+        let mut data = vec![F::zero(); self.length + 1];
+        data[index] = F::one();
+        Ok(data)
+    }
+
+    fn truncate(&self, mut input: Vec<F>) -> Result<Vec<F>, FlpError> {
+        self.truncate_call_check(&input)?;
+        // Cut off the "no value" bucket
+        input.truncate(self.length);
+        Ok(input)
+    }
+
+    fn decode_result(
+        &self,
+        data: &[F],
+        _num_measurements: usize,
+    ) -> Result<Vec<F::Integer>, FlpError> {
+        decode_result_vec(data, self.length)
+    }
+
+    fn output_len(&self) -> usize {
+        self.length
+    }
+}
+
+/// The exact-weight counter data type. Each measurement is a list of booleans of length `length`,
+/// with exactly `weight` true values, and the aggregate is a histogram counting the number of true
+/// values at each position across all measurements.
+///
+/// This is useful for top-k selections, where each client picks exactly `k` of the buckets. Unlike
+/// [`MultihotCountVec`], the weight is fixed, so it need not be encoded in the measurement.
+#[derive(PartialEq, Eq)]
+pub struct ExactWeightCountVec<F, S> {
+    length: usize,
+    weight: usize,
+    chunk_length: usize,
+    gadget_calls: usize,
+    phantom: PhantomData<(F, S)>,
+}
+
+impl<F: NttFriendlyFieldElement, S> Debug for ExactWeightCountVec<F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExactWeightCountVec")
+            .field("length", &self.length)
+            .field("weight", &self.weight)
+            .field("chunk_length", &self.chunk_length)
+            .finish()
+    }
+}
+
+impl<F: NttFriendlyFieldElement, S: ParallelSumGadget<F, Mul<F>>> ExactWeightCountVec<F, S> {
+    /// Return a new [`ExactWeightCountVec`] type with the given number of buckets, of which exactly
+    /// `weight` are set in each measurement.
+    pub fn new(length: usize, weight: usize, chunk_length: usize) -> Result<Self, FlpError> {
+        if length >= u32::MAX as usize {
+            return Err(FlpError::Encode(
+                "invalid length: number of buckets exceeds maximum permitted".to_string(),
+            ));
+        }
+        if length == 0 {
+            return Err(FlpError::InvalidParameter(
+                "length cannot be zero".to_string(),
+            ));
+        }
+        if chunk_length == 0 {
+            return Err(FlpError::InvalidParameter(
+                "chunk_length cannot be zero".to_string(),
+            ));
+        }
+        if weight == 0 {
+            return Err(FlpError::InvalidParameter(
+                "weight cannot be zero".to_string(),
+            ));
+        }
+        if weight > length {
+            return Err(FlpError::InvalidParameter(format!(
+                "weight ({weight}) cannot exceed length ({length})"
+            )));
+        }
+        // The weight must be representable as a field element for the weight check.
+        F::valid_integer_try_from(weight)?;
+
+        let gadget_calls = length.div_ceil(chunk_length);
+
+        Ok(Self {
+            length,
+            weight,
+            chunk_length,
+            gadget_calls,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<F, S> Clone for ExactWeightCountVec<F, S> {
+    fn clone(&self) -> Self {
+        Self {
+            length: self.length,
+            weight: self.weight,
+            chunk_length: self.chunk_length,
+            gadget_calls: self.gadget_calls,
+            phantom: self.phantom,
+        }
+    }
+}
+
+impl<F, S> Flp for ExactWeightCountVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    type Field = F;
+
+    fn gadget(&self) -> Vec<Box<dyn Gadget<F>>> {
+        vec![Box::new(S::new(
+            Mul::new(self.gadget_calls),
+            self.chunk_length,
+        ))]
+    }
+
+    fn num_gadgets(&self) -> usize {
+        1
+    }
+
+    fn valid(
+        &self,
+        g: &mut Vec<Box<dyn Gadget<F>>>,
+        input: &[F],
+        joint_rand: &[F],
+        num_shares: usize,
+    ) -> Result<Vec<F>, FlpError> {
+        self.valid_call_check(input, joint_rand)?;
+
+        // Check that each element of `input` is a 0 or 1.
+        let range_check =
+            parallel_sum_range_checks(&mut g[0], input, joint_rand, self.chunk_length, num_shares)?;
+
+        // Check that the elements of `input` sum to `weight`.
+        let weight = F::from(F::valid_integer_try_from(self.weight)?);
+        let weight_check = weight_check(input, weight, num_shares)?;
+
+        Ok(vec![range_check, weight_check])
+    }
+
+    fn input_len(&self) -> usize {
+        self.length
+    }
+
+    fn proof_len(&self) -> usize {
+        (self.chunk_length * 2) + 2 * ((1 + self.gadget_calls).next_power_of_two() - 1) + 1
+    }
+
+    fn verifier_len(&self) -> usize {
+        2 + self.chunk_length * 2
+    }
+
+    fn joint_rand_len(&self) -> usize {
+        self.gadget_calls
+    }
+
+    fn eval_output_len(&self) -> usize {
+        2
+    }
+
+    fn prove_rand_len(&self) -> usize {
+        self.chunk_length * 2
+    }
+}
+
+impl<F, S> Type for ExactWeightCountVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    type Measurement = Vec<bool>;
+    type AggregateResult = Vec<F::Integer>;
+
+    fn encode_measurement(&self, measurement: &Vec<bool>) -> Result<Vec<F>, FlpError> {
+        if measurement.len() != self.length {
+            return Err(FlpError::Encode(format!(
+                "unexpected measurement length: got {}; want {}",
+                measurement.len(),
+                self.length
+            )));
+        }
+        let weight = measurement.iter().filter(|bit| **bit).count();
+        if weight != self.weight {
+            return Err(FlpError::Encode(format!(
+                "unexpected measurement weight: got {}; want {}",
+                weight, self.weight
+            )));
+        }
+
+        Ok(measurement
+            .iter()
+            .map(|bit| F::conditional_select(&F::zero(), &F::one(), Choice::from(u8::from(*bit))))
+            .collect())
+    }
+
+    fn truncate(&self, input: Vec<F>) -> Result<Vec<F>, FlpError> {
+        self.truncate_call_check(&input)?;
+        Ok(input)
+    }
+
+    fn decode_result(
+        &self,
+        data: &[F],
+        _num_measurements: usize,
+    ) -> Result<Vec<F::Integer>, FlpError> {
+        decode_result_vec(data, self.length)
+    }
+
+    fn output_len(&self) -> usize {
+        self.length
+    }
+}
+
 /// A sequence of integers in range `[0, 2^bits)`. This type uses a neat trick from [[BBCG+19],
 /// Corollary 4.9] to reduce the proof size to roughly the square root of the input size.
 ///
@@ -938,12 +1308,7 @@ where
 
         let mut flattened = Vec::with_capacity(self.flattened_len);
         for summand in measurement.iter() {
-            if summand > &self.max {
-                return Err(FlpError::Encode(format!(
-                    "summand exceeds maximum of 2^{}-1",
-                    self.bits
-                )));
-            }
+            self.check_summand(summand)?;
             flattened.extend(F::encode_as_bitvector(*summand, self.bits)?);
         }
 
@@ -972,6 +1337,124 @@ where
     }
 }
 
+impl<F, S> SumVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    /// Returns an error if `summand` can't be encoded, i.e., it is larger than `2^bits - 1`.
+    pub(crate) fn check_summand(&self, summand: &F::Integer) -> Result<(), FlpError> {
+        if summand > &self.max {
+            return Err(FlpError::Encode(format!(
+                "summand exceeds maximum of 2^{}-1",
+                self.bits
+            )));
+        }
+        Ok(())
+    }
+
+    /// Generates a proof of validity for `summands`. The output is the same as that of
+    /// [`Flp::prove`] applied to the encoding of `summands`.
+    ///
+    /// Unlike [`Flp::prove`], this neither encodes the measurement nor records the inputs of each
+    /// gadget call. Instead, each pair of wire polynomials of the `ParallelSum` gadget is
+    /// interpolated directly from `summands` and folded into the gadget polynomial before moving on
+    /// to the next, so memory usage beyond `summands` is linear in the number of gadget calls
+    /// rather than in the length of the encoded measurement.
+    pub(crate) fn prove_summands(
+        &self,
+        summands: &[F::Integer],
+        prove_rand: &[F],
+        joint_rand: &[F],
+    ) -> Result<Vec<F>, FlpError> {
+        if summands.len() != self.len {
+            return Err(FlpError::Prove(format!(
+                "unexpected measurement length: got {}; want {}",
+                summands.len(),
+                self.len
+            )));
+        }
+
+        if prove_rand.len() != self.prove_rand_len() {
+            return Err(FlpError::Prove(format!(
+                "unexpected prove randomness length: got {}; want {}",
+                prove_rand.len(),
+                self.prove_rand_len()
+            )));
+        }
+
+        if joint_rand.len() != self.joint_rand_len() {
+            return Err(FlpError::Prove(format!(
+                "unexpected joint randomness length: got {}; want {}",
+                joint_rand.len(),
+                self.joint_rand_len()
+            )));
+        }
+
+        let wire_len = wire_poly_len(self.gadget_calls);
+        let wire_len_inv = F::from(F::valid_integer_try_from(wire_len)?).inv();
+        let ntt_len = gadget_poly_len(2, wire_len).next_power_of_two();
+        let ntt_len_inv = F::from(F::valid_integer_try_from(ntt_len)?).inv();
+
+        let bit =
+            |i: usize| F::from((summands[i / self.bits] >> (i % self.bits)) & F::Integer::one());
+
+        let mut r_powers = joint_rand.to_vec();
+        let mut wire_vals = vec![F::zero(); wire_len];
+        let mut wire_coeffs = vec![F::zero(); wire_len];
+        let mut left = vec![F::zero(); ntt_len];
+        let mut right = vec![F::zero(); ntt_len];
+        let mut gadget_evals = vec![F::zero(); ntt_len];
+        for j in 0..self.chunk_length {
+            // The left input of the `j`-th multiplication gate of call `k` is `r_k^(j+1) * x`,
+            // where `x` is the `j`-th element of the `k`-th chunk of the encoded measurement.
+            wire_vals[0] = prove_rand[2 * j];
+            for (k, (val, r_power)) in wire_vals[1..=self.gadget_calls]
+                .iter_mut()
+                .zip(r_powers.iter_mut())
+                .enumerate()
+            {
+                let i = k * self.chunk_length + j;
+                *val = if i < self.flattened_len {
+                    let val = *r_power * bit(i);
+                    *r_power *= joint_rand[k];
+                    val
+                } else {
+                    F::zero()
+                };
+            }
+            ntt(&mut wire_coeffs, &wire_vals, wire_len)?;
+            ntt_inv_finish(&mut wire_coeffs, wire_len, wire_len_inv);
+            ntt(&mut left, &wire_coeffs, ntt_len)?;
+
+            // The right input is `x - 1`.
+            wire_vals[0] = prove_rand[2 * j + 1];
+            for (k, val) in wire_vals[1..=self.gadget_calls].iter_mut().enumerate() {
+                let i = k * self.chunk_length + j;
+                *val = if i < self.flattened_len {
+                    bit(i) - F::one()
+                } else {
+                    -F::one()
+                };
+            }
+            ntt(&mut wire_coeffs, &wire_vals, wire_len)?;
+            ntt_inv_finish(&mut wire_coeffs, wire_len, wire_len_inv);
+            ntt(&mut right, &wire_coeffs, ntt_len)?;
+
+            for (y, (a, b)) in gadget_evals.iter_mut().zip(left.iter().zip(right.iter())) {
+                *y += *a * *b;
+            }
+        }
+
+        let mut proof = Vec::with_capacity(self.proof_len());
+        proof.extend_from_slice(prove_rand);
+        ntt(&mut left, &gadget_evals, ntt_len)?;
+        ntt_inv_finish(&mut left, ntt_len, ntt_len_inv);
+        proof.extend_from_slice(&left[..gadget_poly_len(2, wire_len)]);
+        Ok(proof)
+    }
+}
+
 /// Given a vector `data` of field elements which should contain exactly one entry, return the
 /// integer representation of that entry.
 pub(crate) fn decode_result<F: NttFriendlyFieldElement>(
@@ -995,6 +1478,20 @@ pub(crate) fn decode_result_vec<F: NttFriendlyFieldElement>(
     Ok(data.iter().map(|elem| F::Integer::from(*elem)).collect())
 }
 
+/// Compute (a share of) the difference between the sum of the elements of `input` and `weight`.
+/// If `input` is a share of a vector whose elements sum to `weight`, then the shares of the output
+/// sum to zero.
+fn weight_check<F: NttFriendlyFieldElement>(
+    input: &[F],
+    weight: F,
+    num_shares: usize,
+) -> Result<F, FlpError> {
+    let shares_inv = F::from(F::valid_integer_try_from(num_shares)?).inv();
+    Ok(input
+        .iter()
+        .fold(-(weight * shares_inv), |acc, val| acc + *val))
+}
+
 /// This evaluates range checks on a slice of field elements, using a ParallelSum gadget evaluating
 /// many multiplication gates.
 ///
@@ -1375,6 +1872,115 @@ mod tests {
         test_multihot(MultihotCountVec::<TestField, ParallelSum<TestField, Mul<TestField>>>::new);
     }
 
+    #[test]
+    fn test_optional_histogram() {
+        let hist: OptionalHistogram<TestField, ParallelSum<TestField, Mul<TestField>>> =
+            OptionalHistogram::new(3, 2).unwrap();
+        let zero = TestField::zero();
+        let one = TestField::one();
+        let nine = TestField::from(9);
+
+        assert_eq!(
+            &hist.encode_measurement(&Some(0)).unwrap(),
+            &[one, zero, zero, zero]
+        );
+        assert_eq!(
+            &hist.encode_measurement(&Some(2)).unwrap(),
+            &[zero, zero, one, zero]
+        );
+        assert_eq!(
+            &hist.encode_measurement(&None).unwrap(),
+            &[zero, zero, zero, one]
+        );
+        hist.encode_measurement(&Some(3)).unwrap_err();
+
+        // Round trip
+        assert_eq!(
+            hist.decode_result(
+                &hist
+                    .truncate(hist.encode_measurement(&Some(1)).unwrap())
+                    .unwrap(),
+                1
+            )
+            .unwrap(),
+            [0, 1, 0]
+        );
+
+        // Test valid inputs.
+        TypeTest::expect_valid::<3>(
+            &hist,
+            &hist.encode_measurement(&Some(1)).unwrap(),
+            &[zero, one, zero],
+        );
+        TypeTest::expect_valid::<3>(
+            &hist,
+            &hist.encode_measurement(&None).unwrap(),
+            &[zero, zero, zero],
+        );
+
+        // Test invalid inputs.
+        TypeTest::expect_invalid::<3>(&hist, &[zero, zero, nine, zero]);
+        TypeTest::expect_invalid::<3>(&hist, &[zero, one, one, zero]);
+        TypeTest::expect_invalid::<3>(&hist, &[zero, one, zero, one]);
+        TypeTest::expect_invalid::<3>(&hist, &[zero, zero, zero, zero]);
+    }
+
+    #[test]
+    fn test_exact_weight_count_vec() {
+        let typ: ExactWeightCountVec<TestField, ParallelSum<TestField, Mul<TestField>>> =
+            ExactWeightCountVec::new(4, 2, 3).unwrap();
+        let zero = TestField::zero();
+        let one = TestField::one();
+        let nine = TestField::from(9);
+
+        assert_eq!(
+            &typ.encode_measurement(&vec![true, false, false, true])
+                .unwrap(),
+            &[one, zero, zero, one]
+        );
+        typ.encode_measurement(&vec![true, false, false, false])
+            .unwrap_err();
+        typ.encode_measurement(&vec![true, true, true, false])
+            .unwrap_err();
+        typ.encode_measurement(&vec![true, true, false])
+            .unwrap_err();
+
+        // Round trip
+        assert_eq!(
+            typ.decode_result(
+                &typ.truncate(
+                    typ.encode_measurement(&vec![false, true, true, false])
+                        .unwrap()
+                )
+                .unwrap(),
+                1
+            )
+            .unwrap(),
+            [0, 1, 1, 0]
+        );
+
+        // Test valid inputs.
+        TypeTest::expect_valid::<3>(
+            &typ,
+            &typ.encode_measurement(&vec![false, true, false, true])
+                .unwrap(),
+            &[zero, one, zero, one],
+        );
+
+        // Test invalid inputs.
+        TypeTest::expect_invalid::<3>(&typ, &[zero, zero, nine, zero]);
+        TypeTest::expect_invalid::<3>(&typ, &[zero, one, zero, zero]);
+        TypeTest::expect_invalid::<3>(&typ, &[zero, one, one, one]);
+        // The weight is right, but the input is not binary.
+        TypeTest::expect_invalid::<3>(&typ, &[one + one, zero, zero, zero]);
+
+        // Invalid parameters.
+        ExactWeightCountVec::<TestField, ParallelSum<TestField, Mul<TestField>>>::new(4, 0, 1)
+            .unwrap_err();
+        ExactWeightCountVec::<TestField, ParallelSum<TestField, Mul<TestField>>>::new(4, 5, 1)
+            .unwrap_err();
+    }
+
     fn test_sum_vec<F, S>(f: F)
     where
         F: Fn(usize, usize, usize) -> Result<SumVec<TestField, S>, FlpError>,
@@ -1462,6 +2068,37 @@ mod tests {
         assert!(typ.decide(&verifier).unwrap());
     }
 
+    #[test]
+    fn test_sum_vec_prove_summands() {
+        for (bits, len, chunk_length) in
+            [(1, 1, 1), (1, 10, 3), (3, 7, 4), (2, 100, 10), (5, 3, 16)]
+        {
+            let typ: SumVec<TestField, ParallelSum<TestField, _>> =
+                SumVec::new(bits, len, chunk_length).unwrap();
+            let max = (1 << bits) - 1;
+            let summands = (0..len as u64)
+                .map(|i| (i * 7) % (max + 1))
+                .collect::<Vec<_>>();
+            let input = typ.encode_measurement(&summands).unwrap();
+            let joint_rand = TestField::random_vector(typ.joint_rand_len());
+            let prove_rand = TestField::random_vector(typ.prove_rand_len());
+            assert_eq!(
+                typ.prove_summands(&summands, &prove_rand, &joint_rand)
+                    .unwrap(),
+                typ.prove(&input, &prove_rand, &joint_rand).unwrap(),
+                "bits = {bits}, len = {len}, chunk_length = {chunk_length}"
+            );
+        }
+
+        let typ: SumVec<TestField, ParallelSum<TestField, _>> = SumVec::new(2, 4, 3).unwrap();
+        let joint_rand = TestField::random_vector(typ.joint_rand_len());
+        let prove_rand = TestField::random_vector(typ.prove_rand_len());
+        typ.prove_summands(&[0; 3], &prove_rand, &joint_rand)
+            .unwrap_err();
+        typ.prove_summands(&[0; 4], &prove_rand[1..], &joint_rand)
+            .unwrap_err();
+    }
+
     #[test]
     #[cfg(feature = "multithreaded")]
     fn sum_vec_parallel_long() {
@@ -1484,3 +2121,11 @@ mod tests {
 #[cfg(feature = "experimental")]
 #[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
 pub mod fixedpoint_l2;
+
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub mod fixedpoint_sum;
+
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub mod batch;
@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A [`Type`] that validates a batch of up to `batch_len` measurements of some other [`Type`] with
+//! a single proof.
+//!
+//! [`Batch`] lets a client submit several measurements in one report. The encoded input consists
+//! of `batch_len` "slots", each of which is a presence bit followed by the encoding of a
+//! measurement. The validity circuit evaluates the inner circuit once per slot, each with its own
+//! slice of the joint randomness. The slots share the inner type's gadgets: each gadget is wrapped
+//! so that it is called `batch_len` times as often, which means the proof grows only with the
+//! number of gadget calls rather than with the number of slots.
+//!
+//! A report may carry any number of measurements up to `batch_len`; unused slots are encoded as
+//! all zeros. The circuit checks that each presence bit is a bit, that the inner circuit accepts
+//! every present slot, and that every absent slot is zero. The output of [`Batch::truncate`] is
+//! the sum of the truncated slots followed by the sum of the presence bits, so aggregating a batch
+//! report yields the same output share as aggregating its measurements individually, and
+//! [`Batch::decode_result`] passes the true number of measurements to the inner type rather than
+//! the number of reports.
+
+use crate::field::{
+    FieldElement, FieldElementWithInteger, FieldElementWithIntegerExt, NttFriendlyFieldElement,
+};
+use crate::flp::gadgets::{gadget_call_poly_check, Mul};
+use crate::flp::{gadget_poly_len, wire_poly_len, Flp, FlpError, Gadget, Type};
+use crate::ntt::{ntt, ntt_inv_finish};
+use std::any::Any;
+use std::convert::{TryFrom, TryInto};
+use std::fmt::Debug;
+
+/// A batch of up to `batch_len` measurements of the inner type `T`, validated with a single proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Batch<T: Type> {
+    inner: T,
+    batch_len: usize,
+}
+
+impl<T: Type> Batch<T> {
+    /// Returns a new [`Batch`] of up to `batch_len` measurements of `inner`. Errors if
+    /// `batch_len == 0`.
+    pub fn new(inner: T, batch_len: usize) -> Result<Self, FlpError> {
+        if batch_len == 0 {
+            return Err(FlpError::InvalidParameter(
+                "batch length must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(Self { inner, batch_len })
+    }
+
+    /// Returns the inner type.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the maximum number of measurements in each batch.
+    pub fn batch_len(&self) -> usize {
+        self.batch_len
+    }
+
+    /// Returns the length of the encoding of a single slot: the presence bit followed by the
+    /// encoding of the inner measurement.
+    fn slot_len(&self) -> usize {
+        1 + self.inner.input_len()
+    }
+
+    /// Returns the number of calls made to the [`Mul`] gadget used to check the presence bits.
+    fn mul_calls(&self) -> usize {
+        (self.inner.eval_output_len() + 2) * self.batch_len
+    }
+}
+
+impl<T: Type> Flp for Batch<T> {
+    type Field = T::Field;
+
+    fn gadget(&self) -> Vec<Box<dyn Gadget<T::Field>>> {
+        let mut gadgets: Vec<Box<dyn Gadget<T::Field>>> = self
+            .inner
+            .gadget()
+            .into_iter()
+            .map(|inner| {
+                Box::new(BatchGadget::new(inner, self.batch_len)) as Box<dyn Gadget<T::Field>>
+            })
+            .collect();
+        gadgets.push(Box::new(Mul::new(self.mul_calls())));
+        gadgets
+    }
+
+    fn num_gadgets(&self) -> usize {
+        self.inner.num_gadgets() + 1
+    }
+
+    fn valid(
+        &self,
+        gadgets: &mut Vec<Box<dyn Gadget<T::Field>>>,
+        input: &[T::Field],
+        joint_rand: &[T::Field],
+        num_shares: usize,
+    ) -> Result<Vec<T::Field>, FlpError> {
+        self.valid_call_check(input, joint_rand)?;
+
+        let shares_inv = T::Field::from(T::Field::valid_integer_try_from(num_shares)?).inv();
+        let inner_joint_rand_len = self.inner.joint_rand_len();
+        // The last element of the joint randomness is used to compress each absent slot into a
+        // single element that is zero if (and, with high probability, only if) the slot is zero.
+        let r = joint_rand[joint_rand.len() - 1];
+        let mul = self.inner.num_gadgets();
+
+        let mut output = Vec::with_capacity(self.eval_output_len());
+        for (i, slot) in input.chunks_exact(self.slot_len()).enumerate() {
+            let (present, slot) = (slot[0], &slot[1..]);
+            let slot_joint_rand =
+                &joint_rand[i * inner_joint_rand_len..(i + 1) * inner_joint_rand_len];
+
+            // The presence bit must be a bit.
+            output.push(gadgets[mul].call(&[present, present])? - present);
+
+            // The inner circuit must accept the slot if it is present.
+            for inner_output in self
+                .inner
+                .valid(gadgets, slot, slot_joint_rand, num_shares)?
+            {
+                output.push(gadgets[mul].call(&[present, inner_output])?);
+            }
+
+            // The slot must be zero if it is absent.
+            let mut compressed = T::Field::zero();
+            let mut r_power = r;
+            for x in slot {
+                compressed += r_power * *x;
+                r_power *= r;
+            }
+            output.push(gadgets[mul].call(&[shares_inv - present, compressed])?);
+        }
+
+        Ok(output)
+    }
+
+    fn input_len(&self) -> usize {
+        self.slot_len() * self.batch_len
+    }
+
+    fn proof_len(&self) -> usize {
+        self.gadget()
+            .iter()
+            .map(|gadget| {
+                gadget.arity() + gadget_poly_len(gadget.degree(), wire_poly_len(gadget.calls()))
+            })
+            .sum()
+    }
+
+    fn verifier_len(&self) -> usize {
+        1 + self
+            .gadget()
+            .iter()
+            .map(|gadget| gadget.arity() + 1)
+            .sum::<usize>()
+    }
+
+    fn joint_rand_len(&self) -> usize {
+        self.inner.joint_rand_len() * self.batch_len + 1
+    }
+
+    fn eval_output_len(&self) -> usize {
+        (self.inner.eval_output_len() + 2) * self.batch_len
+    }
+
+    fn prove_rand_len(&self) -> usize {
+        self.inner.prove_rand_len() + 2
+    }
+}
+
+impl<T: Type> Type for Batch<T> {
+    type Measurement = Vec<T::Measurement>;
+    type AggregateResult = T::AggregateResult;
+
+    fn encode_measurement(
+        &self,
+        measurements: &Vec<T::Measurement>,
+    ) -> Result<Vec<T::Field>, FlpError> {
+        if measurements.len() > self.batch_len {
+            return Err(FlpError::Encode(format!(
+                "batch too long: got {} measurements; want at most {}",
+                measurements.len(),
+                self.batch_len
+            )));
+        }
+
+        let mut encoded = Vec::with_capacity(self.input_len());
+        for measurement in measurements {
+            encoded.push(T::Field::one());
+            encoded.append(&mut self.inner.encode_measurement(measurement)?);
+        }
+        encoded.resize(self.input_len(), T::Field::zero());
+        Ok(encoded)
+    }
+
+    fn truncate(&self, input: Vec<T::Field>) -> Result<Vec<T::Field>, FlpError> {
+        self.truncate_call_check(&input)?;
+
+        let mut output = vec![T::Field::zero(); self.output_len()];
+        for slot in input.chunks_exact(self.slot_len()) {
+            let truncated = self.inner.truncate(slot[1..].to_vec())?;
+            for (x, y) in output.iter_mut().zip(truncated) {
+                *x += y;
+            }
+            output[self.inner.output_len()] += slot[0];
+        }
+        Ok(output)
+    }
+
+    /// Decodes the aggregate result. The number of measurements passed to the inner type is taken
+    /// from the sum of the presence bits, so `_num_measurements` (the number of reports) is
+    /// ignored.
+    fn decode_result(
+        &self,
+        data: &[T::Field],
+        _num_measurements: usize,
+    ) -> Result<T::AggregateResult, FlpError> {
+        if data.len() != self.output_len() {
+            return Err(FlpError::Decode(format!(
+                "unexpected output length: got {}; want {}",
+                data.len(),
+                self.output_len()
+            )));
+        }
+
+        let (data, count) = data.split_at(self.inner.output_len());
+        let count = <T::Field as FieldElementWithInteger>::Integer::from(count[0]);
+        let num_measurements = count
+            .try_into()
+            .ok()
+            .and_then(|count: u64| usize::try_from(count).ok())
+            .ok_or_else(|| FlpError::Decode(format!("failed to convert {count:?} to usize")))?;
+        self.inner.decode_result(data, num_measurements)
+    }
+
+    fn output_len(&self) -> usize {
+        self.inner.output_len() + 1
+    }
+}
+
+/// Wraps a gadget of the inner type so that it can be called once per slot of the batch.
+///
+/// The inner gadget sizes its polynomial buffers for the number of calls made by a single slot,
+/// so [`Gadget::call_poly`] can't be delegated. Instead, the wire polynomials are evaluated at
+/// the roots of unity, the inner gadget is called on each point, and the gadget polynomial is
+/// recovered by interpolation.
+#[derive(Debug)]
+struct BatchGadget<F: NttFriendlyFieldElement> {
+    inner: Box<dyn Gadget<F>>,
+    num_calls: usize,
+}
+
+impl<F: NttFriendlyFieldElement> BatchGadget<F> {
+    fn new(inner: Box<dyn Gadget<F>>, batch_len: usize) -> Self {
+        let num_calls = inner.calls() * batch_len;
+        Self { inner, num_calls }
+    }
+}
+
+impl<F: NttFriendlyFieldElement> Gadget<F> for BatchGadget<F> {
+    fn call(&mut self, inp: &[F]) -> Result<F, FlpError> {
+        self.inner.call(inp)
+    }
+
+    fn call_poly(&mut self, outp: &mut [F], inp: &[Vec<F>]) -> Result<(), FlpError> {
+        gadget_call_poly_check(self, outp, inp)?;
+
+        // The output buffer is large enough to hold the gadget polynomial and its length is a
+        // power of two, so we can use it as the size of the NTT.
+        let n = outp.len();
+        let n_inv = F::from(F::Integer::try_from(n).map_err(|_| {
+            FlpError::Gadget("NTT size does not fit in a field element".to_string())
+        })?)
+        .inv();
+
+        let mut wire_evals = Vec::with_capacity(inp.len());
+        for wire_poly in inp {
+            let mut evals = vec![F::zero(); n];
+            ntt(&mut evals, wire_poly, n)?;
+            wire_evals.push(evals);
+        }
+
+        let mut gadget_evals = vec![F::zero(); n];
+        let mut point = vec![F::zero(); inp.len()];
+        for (i, gadget_eval) in gadget_evals.iter_mut().enumerate() {
+            for (x, evals) in point.iter_mut().zip(wire_evals.iter()) {
+                *x = evals[i];
+            }
+            *gadget_eval = self.inner.call(&point)?;
+        }
+
+        ntt(outp, &gadget_evals, n)?;
+        ntt_inv_finish(outp, n, n_inv);
+        Ok(())
+    }
+
+    fn arity(&self) -> usize {
+        self.inner.arity()
+    }
+
+    fn degree(&self) -> usize {
+        self.inner.degree()
+    }
+
+    fn calls(&self) -> usize {
+        self.num_calls
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Field64;
+    use crate::flp::gadgets::{Mul, ParallelSum, ParallelSumGadget, PolyEval};
+    use crate::flp::test_utils::TypeTest;
+    use crate::flp::types::{Average, Count, Histogram, Sum, SumVec};
+    use crate::polynomial::{poly_eval, poly_range_check};
+
+    type TestField = Field64;
+
+    #[test]
+    fn test_batch_gadget_call_poly() {
+        let batch_len = 5;
+        let gadgets: Vec<Box<dyn Gadget<TestField>>> = vec![
+            Box::new(Mul::new(3)),
+            Box::new(PolyEval::new(poly_range_check(0, 2), 3)),
+            Box::new(ParallelSum::new(Mul::new(3), 4)),
+        ];
+
+        for inner in gadgets {
+            let mut gadget = BatchGadget::new(inner, batch_len);
+            let wire_poly_len = wire_poly_len(gadget.calls());
+            let outp_len = gadget_poly_len(gadget.degree(), wire_poly_len).next_power_of_two();
+            let inp: Vec<Vec<TestField>> = (0..gadget.arity())
+                .map(|_| TestField::random_vector(wire_poly_len))
+                .collect();
+            let mut outp = vec![TestField::zero(); outp_len];
+            gadget.call_poly(&mut outp, &inp).unwrap();
+
+            // The gadget polynomial must agree with the gadget at a random point.
+            let r = TestField::from(1337);
+            let point: Vec<TestField> = inp.iter().map(|poly| poly_eval(poly, r)).collect();
+            assert_eq!(
+                poly_eval(&outp, r),
+                gadget.call(&point).unwrap(),
+                "{gadget:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_count() {
+        let batch = Batch::new(Count::<TestField>::new(), 4).unwrap();
+        TypeTest::expect_valid::<3>(
+            &batch,
+            &batch
+                .encode_measurement(&vec![true, false, true, true])
+                .unwrap(),
+            &[3, 4].map(TestField::from),
+        );
+        TypeTest::expect_valid::<3>(
+            &batch,
+            &batch.encode_measurement(&vec![false; 4]).unwrap(),
+            &[0, 4].map(TestField::from),
+        );
+
+        // A batch may have fewer than `batch_len` measurements.
+        TypeTest::expect_valid::<3>(
+            &batch,
+            &batch.encode_measurement(&vec![true, true]).unwrap(),
+            &[2, 2].map(TestField::from),
+        );
+        TypeTest::expect_valid::<3>(
+            &batch,
+            &batch.encode_measurement(&vec![]).unwrap(),
+            &[0, 0].map(TestField::from),
+        );
+
+        // One invalid slot invalidates the whole batch.
+        let mut input = batch.encode_measurement(&vec![true; 4]).unwrap();
+        input[3] = TestField::from(2);
+        TypeTest::expect_invalid::<3>(&batch, &input);
+
+        // A presence bit that isn't a bit.
+        let mut input = batch.encode_measurement(&vec![true; 4]).unwrap();
+        input[2] = TestField::from(2);
+        TypeTest::expect_invalid::<3>(&batch, &input);
+
+        // An absent slot that isn't zero.
+        let mut input = batch.encode_measurement(&vec![true; 3]).unwrap();
+        input[7] = TestField::one();
+        TypeTest::expect_invalid::<3>(&batch, &input);
+
+        batch.encode_measurement(&vec![true; 5]).unwrap_err();
+        Batch::new(Count::<TestField>::new(), 0).unwrap_err();
+    }
+
+    #[test]
+    fn test_batch_sum() {
+        let batch = Batch::new(Sum::<TestField>::new(10).unwrap(), 3).unwrap();
+        TypeTest::expect_valid::<3>(
+            &batch,
+            &batch.encode_measurement(&vec![10, 0, 7]).unwrap(),
+            &[17, 3].map(TestField::from),
+        );
+
+        // A slot whose encoded bits are out of range.
+        let mut input = batch.encode_measurement(&vec![1, 2, 3]).unwrap();
+        input[batch.slot_len() + 1] = TestField::from(3);
+        TypeTest::expect_invalid::<3>(&batch, &input);
+
+        // An absent slot that encodes a valid measurement.
+        let mut input = batch.encode_measurement(&vec![1, 2]).unwrap();
+        input[2 * batch.slot_len() + 1] = TestField::one();
+        TypeTest::expect_invalid::<3>(&batch, &input);
+    }
+
+    #[test]
+    fn test_batch_sum_vec() {
+        let batch = Batch::new(
+            SumVec::<TestField, ParallelSum<_, _>>::new(2, 5, 2).unwrap(),
+            3,
+        )
+        .unwrap();
+        TypeTest::expect_valid::<3>(
+            &batch,
+            &batch
+                .encode_measurement(&vec![
+                    vec![0, 1, 2, 3, 0],
+                    vec![3, 3, 3, 3, 3],
+                    vec![1, 0, 0, 0, 2],
+                ])
+                .unwrap(),
+            &[4, 4, 5, 6, 5, 3].map(TestField::from),
+        );
+    }
+
+    #[test]
+    fn test_batch_histogram() {
+        let batch = Batch::new(
+            Histogram::<TestField, ParallelSum<_, _>>::new(4, 2).unwrap(),
+            3,
+        )
+        .unwrap();
+        TypeTest::expect_valid::<3>(
+            &batch,
+            &batch.encode_measurement(&vec![0, 3, 3]).unwrap(),
+            &[1, 0, 0, 2, 3].map(TestField::from),
+        );
+
+        // Each slot is checked independently, so the buckets of two slots can't be combined into
+        // one.
+        let mut input = batch.encode_measurement(&vec![0, 0, 0]).unwrap();
+        input[batch.slot_len() + 1] = TestField::zero();
+        input[2] = TestField::one();
+        TypeTest::expect_invalid::<3>(&batch, &input);
+
+        // A present slot with no bucket set.
+        let mut input = batch.encode_measurement(&vec![0, 0]).unwrap();
+        input[1] = TestField::zero();
+        TypeTest::expect_invalid::<3>(&batch, &input);
+    }
+
+    #[test]
+    fn test_batch_decode_result() {
+        let batch = Batch::new(Average::<TestField>::new(10).unwrap(), 4).unwrap();
+        // Two full reports of four measurements each, summing to 20.
+        assert_eq!(
+            batch
+                .decode_result(&[20, 8].map(TestField::from), 2)
+                .unwrap(),
+            2.5
+        );
+        // Two reports of five measurements in total, summing to 20.
+        assert_eq!(
+            batch
+                .decode_result(&[20, 5].map(TestField::from), 2)
+                .unwrap(),
+            4.0
+        );
+        batch.decode_result(&[TestField::from(20)], 2).unwrap_err();
+    }
+}
@@ -1,15 +1,23 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::dp::{distributions::PureDpDiscreteLaplace, DifferentialPrivacyStrategy};
+use crate::dp::{
+    distributions::{
+        DiscreteGaussian, DiscreteGaussianDpStrategy, FixedPrecisionDiscreteGaussian,
+        PureDpDiscreteLaplace, RenyiDpSkellam,
+    },
+    estimation::{NoiseDistribution, NoiseEstimate, NoisyValue},
+    DifferentialPrivacyBudget, DifferentialPrivacyStrategy,
+};
 use crate::dp::{DifferentialPrivacyDistribution, DpError};
 use crate::field::{Field128, Field64, NttFriendlyFieldElement};
 use crate::flp::gadgets::{Mul, ParallelSumGadget};
-use crate::flp::types::{Histogram, SumVec};
-use crate::flp::{FlpError, TypeWithNoise};
-use crate::vdaf::xof::SeedStreamTurboShake128;
+use crate::flp::types::{Average, Count, Histogram, MultihotCountVec, Sum, SumVec};
+use crate::flp::{FlpError, Type, TypeWithNoise, TypeWithNoiseEstimate};
 use num_bigint::{BigInt, BigUint, TryFromBigIntError};
 use num_integer::Integer;
-use rand::{distr::Distribution, Rng, SeedableRng};
+use num_rational::Ratio;
+use num_traits::ToPrimitive;
+use rand::{distr::Distribution, CryptoRng, Rng, RngCore};
 
 // TODO(#1071): This is implemented for the concrete fields `Field64` and `Field128` in order to
 // avoid imposing the `BigInt: From<F::Integer>` bound on all callers. In the future, we may want to
@@ -19,17 +27,14 @@ impl<S> TypeWithNoise<PureDpDiscreteLaplace> for SumVec<Field64, S>
 where
     S: ParallelSumGadget<Field64, Mul<Field64>> + Eq + 'static,
 {
-    fn add_noise_to_result(
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
         &self,
         dp_strategy: &PureDpDiscreteLaplace,
         agg_result: &mut [Self::Field],
         _num_measurements: usize,
+        rng: &mut R,
     ) -> Result<(), FlpError> {
-        self.add_noise(
-            dp_strategy,
-            agg_result,
-            &mut SeedStreamTurboShake128::from_os_rng(),
-        )
+        self.add_noise(dp_strategy, agg_result, rng)
     }
 }
 
@@ -37,16 +42,57 @@ impl<S> TypeWithNoise<PureDpDiscreteLaplace> for SumVec<Field128, S>
 where
     S: ParallelSumGadget<Field128, Mul<Field128>> + Eq + 'static,
 {
-    fn add_noise_to_result(
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
         &self,
         dp_strategy: &PureDpDiscreteLaplace,
         agg_result: &mut [Self::Field],
         _num_measurements: usize,
+        rng: &mut R,
     ) -> Result<(), FlpError> {
-        self.add_noise(
-            dp_strategy,
+        self.add_noise(dp_strategy, agg_result, rng)
+    }
+}
+
+impl<S, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>> for SumVec<Field64, S>
+where
+    S: ParallelSumGadget<Field64, Mul<Field64>> + Eq + 'static,
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_gaussian_noise(
+            &dp_strategy.create_distribution(self.l2_sensitivity()?)?,
+            agg_result,
+            rng,
+        )
+    }
+}
+
+impl<S, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>> for SumVec<Field128, S>
+where
+    S: ParallelSumGadget<Field128, Mul<Field128>> + Eq + 'static,
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_gaussian_noise(
+            &dp_strategy.create_distribution(self.l2_sensitivity()?)?,
             agg_result,
-            &mut SeedStreamTurboShake128::from_os_rng(),
+            rng,
         )
     }
 }
@@ -66,11 +112,19 @@ where
     where
         R: Rng,
     {
-        // Compute the global sensitivity of the aggregation function, using the L1 norm as a
-        // distance metric, and using the substitution-DP model. The worst case is when one
-        // individual's measurement changes such that each vector element flips from 0 to 2^bits -
-        // 1, or vice versa. Then, the l1 distance from the initial query result to the new query
-        // result will be (2^bits - 1) * length.
+        // Initialize sampler.
+        let sampler = dp_strategy.create_distribution(self.l1_sensitivity()?)?;
+
+        // Generate noise for each vector coordinate and apply it.
+        add_iid_noise_to_field_vec(agg_result, rng, &sampler)
+    }
+
+    /// Compute the global sensitivity of the aggregation function, using the L1 norm as a
+    /// distance metric, and using the substitution-DP model. The worst case is when one
+    /// individual's measurement changes such that each vector element flips from 0 to 2^bits - 1,
+    /// or vice versa. Then, the l1 distance from the initial query result to the new query result
+    /// will be (2^bits - 1) * length.
+    fn l1_sensitivity(&self) -> Result<Ratio<BigUint>, FlpError> {
         let length = BigUint::from(self.len);
         let sensitivity = BigUint::from(
             1u128
@@ -80,12 +134,78 @@ where
                 ))?
                 - 1,
         ) * length;
+        Ok(sensitivity.into())
+    }
 
-        // Initialize sampler.
-        let sampler = dp_strategy.create_distribution(sensitivity.into())?;
+    /// Compute the global sensitivity of the aggregation function, using the L2 norm as a
+    /// distance metric, and using the substitution-DP model. In the same worst case as above, the
+    /// l2 distance is (2^bits - 1) * sqrt(length). The square root is rounded up to a multiple of
+    /// `2^-32`, so that the result is an upper bound on the true sensitivity.
+    fn l2_sensitivity(&self) -> Result<Ratio<BigUint>, FlpError> {
+        let max_entry = BigUint::from(
+            1u128
+                .checked_shl(self.bits as u32)
+                .ok_or(FlpError::InvalidParameter(
+                    "bits must be less than 128".into(),
+                ))?
+                - 1,
+        );
+        Ok(sqrt_upper_bound(BigUint::from(self.len)) * max_entry)
+    }
+}
 
-        // Generate noise for each vector coordinate and apply it.
-        add_iid_noise_to_field_vec(agg_result, rng, &sampler)
+impl<F, S> TypeWithNoiseEstimate<PureDpDiscreteLaplace> for SumVec<F, S>
+where
+    Self: Type<Field = F> + TypeWithNoise<PureDpDiscreteLaplace>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        data: &[F],
+        _num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError> {
+        decode_result_with_sensitivity(
+            dp_strategy,
+            self.l1_sensitivity()?,
+            data,
+            self.len,
+            num_noise_shares,
+            confidence,
+        )
+    }
+}
+
+impl<F, S, B> TypeWithNoiseEstimate<DiscreteGaussianDpStrategy<B>> for SumVec<F, S>
+where
+    Self: Type<Field = F> + TypeWithNoise<DiscreteGaussianDpStrategy<B>>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+{
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        data: &[F],
+        _num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError> {
+        decode_result_with_sensitivity(
+            dp_strategy,
+            self.l2_sensitivity()?,
+            data,
+            self.len,
+            num_noise_shares,
+            confidence,
+        )
     }
 }
 
@@ -93,17 +213,14 @@ impl<S> TypeWithNoise<PureDpDiscreteLaplace> for Histogram<Field64, S>
 where
     S: ParallelSumGadget<Field64, Mul<Field64>> + Eq + 'static,
 {
-    fn add_noise_to_result(
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
         &self,
         dp_strategy: &PureDpDiscreteLaplace,
         agg_result: &mut [Self::Field],
         _num_measurements: usize,
+        rng: &mut R,
     ) -> Result<(), FlpError> {
-        self.add_noise(
-            dp_strategy,
-            agg_result,
-            &mut SeedStreamTurboShake128::from_os_rng(),
-        )
+        self.add_noise(dp_strategy, agg_result, rng)
     }
 }
 
@@ -111,17 +228,14 @@ impl<S> TypeWithNoise<PureDpDiscreteLaplace> for Histogram<Field128, S>
 where
     S: ParallelSumGadget<Field128, Mul<Field128>> + Eq + 'static,
 {
-    fn add_noise_to_result(
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
         &self,
         dp_strategy: &PureDpDiscreteLaplace,
         agg_result: &mut [Self::Field],
         _num_measurements: usize,
+        rng: &mut R,
     ) -> Result<(), FlpError> {
-        self.add_noise(
-            dp_strategy,
-            agg_result,
-            &mut SeedStreamTurboShake128::from_os_rng(),
-        )
+        self.add_noise(dp_strategy, agg_result, rng)
     }
 }
 
@@ -140,23 +254,665 @@ where
     where
         R: Rng,
     {
-        // The global sensitivity of the aggregation function is two, using the L1 norm as a
-        // distance metric, and using the substitution-DP model. Substituting a measurement may, at
-        // worst, cause one cell of the query result to be incremented by one, and another to be
-        // decremented by one.
-        let sensitivity = BigUint::from(2u64);
-
         // Initialize sampler.
-        let sampler = dp_strategy.create_distribution(sensitivity.into())?;
+        let sampler = dp_strategy.create_distribution(self.l1_sensitivity())?;
 
         // Generate noise for each vector coordinate and apply it.
         add_iid_noise_to_field_vec(agg_result, rng, &sampler)
     }
+
+    /// The global sensitivity of the aggregation function is two, using the L1 norm as a distance
+    /// metric, and using the substitution-DP model. Substituting a measurement may, at worst,
+    /// cause one cell of the query result to be incremented by one, and another to be decremented
+    /// by one.
+    fn l1_sensitivity(&self) -> Ratio<BigUint> {
+        BigUint::from(2u64).into()
+    }
+
+    /// The global sensitivity of the aggregation function using the L2 norm as a distance metric,
+    /// and using the substitution-DP model. In the same worst case as above, the L2 distance is
+    /// the square root of two, rounded up to a multiple of `2^-32`.
+    fn l2_sensitivity(&self) -> Ratio<BigUint> {
+        sqrt_upper_bound(BigUint::from(2u8))
+    }
+}
+
+impl<F, S> TypeWithNoiseEstimate<PureDpDiscreteLaplace> for Histogram<F, S>
+where
+    Self: Type<Field = F> + TypeWithNoise<PureDpDiscreteLaplace>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        data: &[F],
+        _num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError> {
+        decode_result_with_sensitivity(
+            dp_strategy,
+            self.l1_sensitivity(),
+            data,
+            self.length,
+            num_noise_shares,
+            confidence,
+        )
+    }
+}
+
+impl<F, S, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>> for Histogram<F, S>
+where
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_gaussian_noise(
+            &dp_strategy.create_distribution(self.l2_sensitivity())?,
+            agg_result,
+            rng,
+        )
+    }
+}
+
+impl<F, S, B> TypeWithNoiseEstimate<DiscreteGaussianDpStrategy<B>> for Histogram<F, S>
+where
+    Self: Type<Field = F> + TypeWithNoise<DiscreteGaussianDpStrategy<B>>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+{
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        data: &[F],
+        _num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError> {
+        decode_result_with_sensitivity(
+            dp_strategy,
+            self.l2_sensitivity(),
+            data,
+            self.length,
+            num_noise_shares,
+            confidence,
+        )
+    }
+}
+
+// Unlike the implementations above, the following implementations are generic over the field,
+// with the conversion bounds placed on each implementation. They are only available for fields
+// whose integer type converts to and from `BigInt`, which includes `Field64` and `Field128`.
+
+impl<F> TypeWithNoise<PureDpDiscreteLaplace> for Count<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.sensitivity(), agg_result, rng)
+    }
+}
+
+impl<F, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>> for Count<F>
+where
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        // The result is a single integer, so the tighter scalar calibration applies.
+        add_gaussian_noise(
+            &dp_strategy.create_scalar_distribution(self.sensitivity())?,
+            agg_result,
+            rng,
+        )
+    }
+}
+
+impl<F: NttFriendlyFieldElement> Count<F> {
+    /// The global sensitivity of the aggregation function is one, using either the L1 or the L2
+    /// norm as a distance metric, and using the substitution-DP model. Substituting a measurement
+    /// may, at worst, change the count by one.
+    fn sensitivity(&self) -> Ratio<BigUint> {
+        Ratio::from_integer(BigUint::from(1u8))
+    }
+}
+
+impl<F> TypeWithNoise<PureDpDiscreteLaplace> for Sum<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.sensitivity()?, agg_result, rng)
+    }
+}
+
+impl<F, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>> for Sum<F>
+where
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_gaussian_noise(
+            &dp_strategy.create_scalar_distribution(self.sensitivity()?)?,
+            agg_result,
+            rng,
+        )
+    }
+}
+
+impl<F> Sum<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+{
+    /// The global sensitivity of the aggregation function is `max_measurement`, using either the
+    /// L1 or the L2 norm as a distance metric, and using the substitution-DP model. The worst case
+    /// is when one individual's measurement changes from 0 to `max_measurement`, or vice versa.
+    fn sensitivity(&self) -> Result<Ratio<BigUint>, FlpError> {
+        let max_measurement = BigInt::from(self.max_measurement)
+            .to_biguint()
+            .ok_or_else(|| FlpError::InvalidParameter("max_measurement is negative".into()))?;
+        Ok(Ratio::from_integer(max_measurement))
+    }
+}
+
+impl<F> TypeWithNoise<PureDpDiscreteLaplace> for Average<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        agg_result: &mut [Self::Field],
+        num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        // The aggregate result is the sum of the measurements, which is only divided by the number
+        // of measurements when it is decoded. Noising the sum is sufficient, since the division is
+        // a post-processing step.
+        self.summer
+            .add_noise_to_result_with_rng(dp_strategy, agg_result, num_measurements, rng)
+    }
+}
+
+impl<F, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>> for Average<F>
+where
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_result: &mut [Self::Field],
+        num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        // See the comment in the `PureDpDiscreteLaplace` implementation above.
+        self.summer
+            .add_noise_to_result_with_rng(dp_strategy, agg_result, num_measurements, rng)
+    }
+}
+
+impl<F, S> TypeWithNoise<PureDpDiscreteLaplace> for MultihotCountVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.l1_sensitivity(), agg_result, rng)
+    }
+}
+
+impl<F, S, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>> for MultihotCountVec<F, S>
+where
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_gaussian_noise(
+            &dp_strategy.create_distribution(self.l2_sensitivity())?,
+            agg_result,
+            rng,
+        )
+    }
+}
+
+// The Skellam strategy adds one share of the noise per aggregator. Like the discrete gaussian
+// strategies, it is calibrated to the L2 sensitivity.
+
+impl<F> TypeWithNoise<RenyiDpSkellam> for Count<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.sensitivity(), agg_result, rng)
+    }
+}
+
+impl<F> TypeWithNoise<RenyiDpSkellam> for Sum<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.sensitivity()?, agg_result, rng)
+    }
+}
+
+impl<F> TypeWithNoise<RenyiDpSkellam> for Average<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        agg_result: &mut [Self::Field],
+        num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        // See the comment in the `PureDpDiscreteLaplace` implementation above.
+        self.summer
+            .add_noise_to_result_with_rng(dp_strategy, agg_result, num_measurements, rng)
+    }
+}
+
+impl<F, S> TypeWithNoise<RenyiDpSkellam> for MultihotCountVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.l2_sensitivity(), agg_result, rng)
+    }
+}
+
+impl<F, S> TypeWithNoise<RenyiDpSkellam> for SumVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.l2_sensitivity()?, agg_result, rng)
+    }
+}
+
+impl<F, S> TypeWithNoiseEstimate<RenyiDpSkellam> for SumVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        data: &[F],
+        _num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError> {
+        decode_result_with_sensitivity(
+            dp_strategy,
+            self.l2_sensitivity()?,
+            data,
+            self.len,
+            num_noise_shares,
+            confidence,
+        )
+    }
+}
+
+impl<F, S> TypeWithNoise<RenyiDpSkellam> for Histogram<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        agg_result: &mut [Self::Field],
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), FlpError> {
+        add_noise_with_sensitivity(dp_strategy, self.l2_sensitivity(), agg_result, rng)
+    }
+}
+
+impl<F, S> TypeWithNoiseEstimate<RenyiDpSkellam> for Histogram<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &RenyiDpSkellam,
+        data: &[F],
+        _num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError> {
+        decode_result_with_sensitivity(
+            dp_strategy,
+            self.l2_sensitivity(),
+            data,
+            self.length,
+            num_noise_shares,
+            confidence,
+        )
+    }
+}
+
+impl<F, S> MultihotCountVec<F, S> {
+    /// The maximum number of vector elements that may change when one measurement is substituted
+    /// for another. Each measurement has at most `max_weight` true values, so in the worst case,
+    /// `max_weight` elements are decremented by one and a disjoint set of `max_weight` elements are
+    /// incremented by one. This is also bounded by the length of the vector.
+    fn max_changed_elements(&self) -> BigUint {
+        BigUint::from(self.max_weight.saturating_mul(2).min(self.length))
+    }
+
+    /// The global sensitivity of the aggregation function using the L1 norm as a distance metric,
+    /// and using the substitution-DP model. Each changed element changes by one.
+    fn l1_sensitivity(&self) -> Ratio<BigUint> {
+        Ratio::from_integer(self.max_changed_elements())
+    }
+
+    /// The global sensitivity of the aggregation function using the L2 norm as a distance metric,
+    /// and using the substitution-DP model. This is the square root of the number of changed
+    /// elements. The square root is rounded up to a multiple of `2^-32`, so that the result is an
+    /// upper bound on the true sensitivity.
+    fn l2_sensitivity(&self) -> Ratio<BigUint> {
+        sqrt_upper_bound(self.max_changed_elements())
+    }
+}
+
+// The following bounds on the outputs of a single measurement are used by Mastic to calibrate
+// noise for its weights.
+#[cfg(feature = "crypto-dependencies")]
+impl<F: NttFriendlyFieldElement> Count<F> {
+    /// The largest L1 norm of the output of a single measurement.
+    pub(crate) fn max_output_l1_norm(&self) -> Result<BigUint, FlpError> {
+        Ok(BigUint::from(1u8))
+    }
+
+    /// The largest squared L2 norm of the output of a single measurement.
+    pub(crate) fn max_output_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        Ok(BigUint::from(1u8))
+    }
+}
+
+#[cfg(feature = "crypto-dependencies")]
+impl<F> Sum<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+{
+    /// The largest L1 norm of the output of a single measurement, which is `max_measurement`.
+    pub(crate) fn max_output_l1_norm(&self) -> Result<BigUint, FlpError> {
+        Ok(self.sensitivity()?.to_integer())
+    }
+
+    /// The largest squared L2 norm of the output of a single measurement, which is
+    /// `max_measurement^2`.
+    pub(crate) fn max_output_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        Ok(self.max_output_l1_norm()?.pow(2))
+    }
+}
+
+#[cfg(feature = "crypto-dependencies")]
+impl<F: NttFriendlyFieldElement, S> Histogram<F, S> {
+    /// The largest L1 norm of the output of a single measurement. The output is one-hot.
+    pub(crate) fn max_output_l1_norm(&self) -> Result<BigUint, FlpError> {
+        Ok(BigUint::from(1u8))
+    }
+
+    /// The largest squared L2 norm of the output of a single measurement. The output is one-hot.
+    pub(crate) fn max_output_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        Ok(BigUint::from(1u8))
+    }
+}
+
+#[cfg(feature = "crypto-dependencies")]
+impl<F, S> SumVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+{
+    /// The largest value of a single vector element.
+    fn max_element(&self) -> Result<BigUint, FlpError> {
+        BigInt::from(self.max)
+            .to_biguint()
+            .ok_or_else(|| FlpError::InvalidParameter("max is negative".into()))
+    }
+
+    /// The largest L1 norm of the output of a single measurement, which is attained when every
+    /// element is `2^bits - 1`.
+    pub(crate) fn max_output_l1_norm(&self) -> Result<BigUint, FlpError> {
+        Ok(self.max_element()? * BigUint::from(self.len))
+    }
+
+    /// The largest squared L2 norm of the output of a single measurement, which is attained when
+    /// every element is `2^bits - 1`.
+    pub(crate) fn max_output_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        Ok(self.max_element()?.pow(2) * BigUint::from(self.len))
+    }
+}
+
+/// Returns the square root of `value`, rounded up to a multiple of `2^-32`, so that the result is
+/// an upper bound on the true square root. This is used to compute L2 sensitivities.
+pub(crate) fn sqrt_upper_bound(value: BigUint) -> Ratio<BigUint> {
+    let scaled: BigUint = value << 64usize;
+    let mut root = scaled.sqrt();
+    if &root * &root < scaled {
+        root += 1u8;
+    }
+    Ratio::new(root, BigUint::from(1u8) << 32)
+}
+
+/// Calibrate a distribution from `dp_strategy` to the given sensitivity, then add independent,
+/// identically-distributed noise from it to each element of `agg_result`.
+fn add_noise_with_sensitivity<F, St, R>(
+    dp_strategy: &St,
+    sensitivity: Ratio<BigUint>,
+    agg_result: &mut [F],
+    rng: &mut R,
+) -> Result<(), FlpError>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    St: DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>>,
+    St::Distribution: Distribution<BigInt>,
+    R: Rng,
+{
+    let sampler = dp_strategy.create_distribution(sensitivity)?;
+    add_iid_noise_to_field_vec(agg_result, rng, &sampler)
+}
+
+/// Decode a noised aggregate result of length `len`, attaching to each element an estimate of the
+/// noise that `num_noise_shares` aggregators added with `dp_strategy` calibrated to `sensitivity`.
+fn decode_result_with_sensitivity<F, St>(
+    dp_strategy: &St,
+    sensitivity: Ratio<BigUint>,
+    data: &[F],
+    len: usize,
+    num_noise_shares: usize,
+    confidence: f64,
+) -> Result<Vec<NoisyValue>, FlpError>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    St: DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>>,
+    St::Distribution: NoiseDistribution,
+{
+    if data.len() != len {
+        return Err(FlpError::Decode("unexpected input length".into()));
+    }
+    let estimate = NoiseEstimate::new(dp_strategy, sensitivity, num_noise_shares)?;
+    let values = data.iter().copied().map(to_signed_f64).collect::<Vec<_>>();
+    Ok(estimate.estimate(&values, confidence)?)
+}
+
+/// Add independent, identically-distributed noise from the discrete gaussian distribution `sampler`
+/// to each element of `agg_result`.
+///
+/// Noise is sampled with [`FixedPrecisionDiscreteGaussian`] whenever the standard deviation fits in
+/// its machine-word representation, which is much faster for the long vectors produced by types
+/// like [`SumVec`]. Otherwise, this falls back to the arbitrary-precision [`DiscreteGaussian`]
+/// sampler. Both samplers draw from exactly the same distribution.
+fn add_gaussian_noise<F, R>(
+    sampler: &DiscreteGaussian,
+    agg_result: &mut [F],
+    rng: &mut R,
+) -> Result<(), FlpError>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    R: Rng,
+{
+    match FixedPrecisionDiscreteGaussian::try_from(sampler) {
+        Ok(fixed_precision) => add_iid_noise_to_field_vec(agg_result, rng, &fixed_precision),
+        Err(_) => add_iid_noise_to_field_vec(agg_result, rng, sampler),
+    }
+}
+
+/// Interpret a field element as a signed integer, and convert it to a float. Noise may make an
+/// aggregate result negative, in which case it wraps around the field modulus, so elements larger
+/// than half of the modulus are taken to be negative.
+pub(super) fn to_signed_f64<F>(element: F) -> f64
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+{
+    let value = BigInt::from(F::Integer::from(element));
+    let modulus = BigInt::from(F::modulus());
+    let signed = if &value * 2u8 > modulus {
+        value - modulus
+    } else {
+        value
+    };
+    signed.to_f64().unwrap_or(f64::NAN)
 }
 
 /// This generates independent, identically-distributed noise, and adds it to a vector of field
 /// elements after projecting it into the field.
-pub(super) fn add_iid_noise_to_field_vec<F, R, D>(
+pub(crate) fn add_iid_noise_to_field_vec<F, R, D>(
     field_vec: &mut [F],
     rng: &mut R,
     distribution: &D,
@@ -198,18 +954,29 @@ where
 
 #[cfg(test)]
 mod tests {
+    use super::{add_noise_with_sensitivity, to_signed_f64};
     use crate::{
         dp::{
-            distributions::PureDpDiscreteLaplace, DifferentialPrivacyStrategy, PureDpBudget,
-            Rational,
+            distributions::{
+                test_utils::test_mean, EpsilonDeltaDiscreteGaussian, PureDpDiscreteLaplace,
+                RenyiDpSkellam, ZCdpDiscreteGaussian,
+            },
+            DifferentialPrivacyStrategy, EpsilonDeltaBudget, IntegerRenyiDpBudget, PureDpBudget,
+            Rational, ZCdpBudget,
+        },
+        field::{
+            merge_vector, split_vector, Field128, Field64, FieldElement, FieldElementWithInteger,
         },
-        field::{merge_vector, split_vector, Field128, FieldElement},
         flp::{
             gadgets::ParallelSum,
-            types::{Histogram, SumVec},
+            types::{Average, Count, Histogram, MultihotCountVec, Sum, SumVec},
+            TypeWithNoise, TypeWithNoiseEstimate,
         },
         vdaf::xof::{Xof, XofTurboShake128},
     };
+    use num_bigint::{BigInt, BigUint};
+    use num_rational::Ratio;
+    use num_traits::ToPrimitive;
 
     #[test]
     fn sumvec_laplace_noise() {
@@ -289,6 +1056,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sumvec_gaussian_noise() {
+        let sumvec: SumVec<Field128, ParallelSum<_, _>> = SumVec::new(2, 4, 1).unwrap();
+        assert_eq!(
+            sumvec.l2_sensitivity().unwrap(),
+            Ratio::from_integer(6u8.into())
+        );
+
+        // The standard deviation fits the fixed-precision sampler, so noise is drawn from it. Its
+        // variance should still match that of the discrete Gaussian.
+        let mut rng = XofTurboShake128::init(&[10; 32], &[]).into_seed_stream();
+        let mut samples = Vec::with_capacity(NUM_SAMPLES);
+        while samples.len() < NUM_SAMPLES {
+            let mut noise = vec![Field128::zero(); 4];
+            sumvec
+                .add_noise_to_result_with_rng(&gaussian(1), &mut noise, 0, &mut rng)
+                .unwrap();
+            samples.extend(noise.into_iter().map(to_signed_f64));
+        }
+        let variance = samples.iter().map(|x| x.powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(
+            (variance - 36.0).abs() < 3.6,
+            "empirical variance {variance}, expected 36"
+        );
+    }
+
     #[test]
     fn histogram_laplace_noise() {
         let dp_strategy = PureDpDiscreteLaplace::from_budget(
@@ -329,4 +1122,262 @@ mod tests {
             ]
         );
     }
+
+    /// Number of noise samples drawn by each statistical test.
+    const NUM_SAMPLES: usize = 10_000;
+
+    /// Add noise calibrated to `sensitivity` to a vector of zeros, and check that the empirical mean
+    /// and variance of the noise, after lifting it out of the field, match `expected_variance`.
+    fn check_noise_moments<St>(
+        dp_strategy: &St,
+        sensitivity: Ratio<BigUint>,
+        expected_variance: f64,
+        seed: u8,
+    ) where
+        St: DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>>,
+        St::Distribution: rand::distr::Distribution<BigInt>,
+    {
+        let mut rng = XofTurboShake128::init(&[seed; 32], &[]).into_seed_stream();
+        let mut noise = vec![Field128::zero(); NUM_SAMPLES];
+        add_noise_with_sensitivity(dp_strategy, sensitivity, &mut noise, &mut rng).unwrap();
+
+        let modulus = Field128::modulus();
+        let samples = noise
+            .into_iter()
+            .map(|x| {
+                let x = u128::from(x);
+                if x > modulus / 2 {
+                    -BigInt::from(modulus - x)
+                } else {
+                    BigInt::from(x)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut iter = samples.iter().cloned();
+        assert!(test_mean(
+            || iter.next().unwrap(),
+            0.0,
+            expected_variance,
+            0.00001,
+            NUM_SAMPLES.try_into().unwrap(),
+        ));
+
+        let variance = samples
+            .iter()
+            .map(|x| x.to_f64().unwrap().powi(2))
+            .sum::<f64>()
+            / NUM_SAMPLES as f64;
+        assert!(
+            (variance - expected_variance).abs() < 0.1 * expected_variance,
+            "empirical variance {variance}, expected {expected_variance}"
+        );
+    }
+
+    /// The variance of the discrete Laplace distribution with scale parameter `scale`.
+    fn discrete_laplace_variance(scale: f64) -> f64 {
+        let p = (-1.0 / scale).exp();
+        2.0 * p / (1.0 - p).powi(2)
+    }
+
+    fn laplace(epsilon: u8) -> PureDpDiscreteLaplace {
+        PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(epsilon, 1).unwrap()).unwrap(),
+        )
+    }
+
+    fn gaussian(epsilon: u8) -> ZCdpDiscreteGaussian {
+        ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+            Rational::from_unsigned(epsilon, 1).unwrap(),
+        ))
+    }
+
+    /// An (epsilon, delta) strategy with epsilon one and delta `10^-5`. With unit L2-sensitivity,
+    /// the standard deviation of its noise for vectors is approximately 4.045.
+    fn epsilon_delta() -> EpsilonDeltaDiscreteGaussian {
+        EpsilonDeltaDiscreteGaussian::from_budget(
+            EpsilonDeltaBudget::new(
+                Rational::from_unsigned(1u32, 1).unwrap(),
+                Rational::from_unsigned(1u32, 100_000).unwrap(),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn epsilon_delta_noise() {
+        let count = Count::<Field128>::new();
+        check_noise_moments(&epsilon_delta(), count.sensitivity(), 4.045f64.powi(2), 8);
+
+        let sum = Sum::<Field128>::new(10).unwrap();
+        check_noise_moments(
+            &epsilon_delta(),
+            sum.sensitivity().unwrap(),
+            40.45f64.powi(2),
+            9,
+        );
+    }
+
+    #[test]
+    fn sensitivity() {
+        let count = Count::<Field128>::new();
+        assert_eq!(count.sensitivity(), Ratio::from_integer(1u8.into()));
+
+        let sum = Sum::<Field128>::new(10).unwrap();
+        assert_eq!(sum.sensitivity().unwrap(), Ratio::from_integer(10u8.into()));
+
+        // The average type adds noise to the sum, so it has the same sensitivity as the sum.
+        let average = Average::<Field128>::new(10).unwrap();
+        assert_eq!(
+            average.summer.sensitivity().unwrap(),
+            Ratio::from_integer(10u8.into())
+        );
+
+        let multihot: MultihotCountVec<Field128, ParallelSum<_, _>> =
+            MultihotCountVec::new(10, 2, 3).unwrap();
+        assert_eq!(multihot.l1_sensitivity(), Ratio::from_integer(4u8.into()));
+        assert_eq!(multihot.l2_sensitivity(), Ratio::from_integer(2u8.into()));
+
+        // The number of changed elements is bounded by the length.
+        let multihot: MultihotCountVec<Field128, ParallelSum<_, _>> =
+            MultihotCountVec::new(3, 2, 1).unwrap();
+        assert_eq!(multihot.l1_sensitivity(), Ratio::from_integer(3u8.into()));
+
+        // The L2 sensitivity is rounded up when it is irrational.
+        let multihot: MultihotCountVec<Field128, ParallelSum<_, _>> =
+            MultihotCountVec::new(10, 1, 1).unwrap();
+        let l2 = multihot.l2_sensitivity().to_f64().unwrap();
+        assert!(l2 >= 2f64.sqrt());
+        assert!(l2 - 2f64.sqrt() < 1e-9);
+    }
+
+    #[test]
+    fn skellam_noise() {
+        // With rho = 1/2 and unit sensitivity, the Skellam parameter is the positive root of
+        // 8 mu^2 - 4 mu - 7 = 0.
+        let mu = (4.0 + (16.0f64 + 224.0).sqrt()) / 16.0;
+        let budget = IntegerRenyiDpBudget::new(Rational::from_unsigned(1u8, 2).unwrap()).unwrap();
+
+        let count = Count::<Field128>::new();
+        check_noise_moments(
+            &RenyiDpSkellam::from_budget(budget.clone()),
+            count.sensitivity(),
+            2.0 * mu,
+            10,
+        );
+
+        // With sensitivity ten, mu is the positive root of 8 mu^2 - 400 mu - 700 = 0. When the
+        // noise is split into two shares, each share has half of the variance.
+        let sum = Sum::<Field128>::new(10).unwrap();
+        check_noise_moments(
+            &RenyiDpSkellam::new(budget, 2).unwrap(),
+            sum.sensitivity().unwrap(),
+            (400.0 + (160000.0f64 + 22400.0).sqrt()) / 16.0,
+            11,
+        );
+    }
+
+    /// Adds noise from two aggregators to an all-zero aggregate result, then checks that fewer
+    /// than 10% of the 90% confidence intervals miss zero.
+    fn check_noise_estimate<T, St>(typ: &T, dp_strategy: &St, expected_std: f64)
+    where
+        T: TypeWithNoiseEstimate<St>,
+        St: DifferentialPrivacyStrategy,
+    {
+        let mut rng = XofTurboShake128::init(&[0; 32], &[]).into_seed_stream();
+        let mut agg_result = vec![T::Field::zero(); typ.output_len()];
+        for _ in 0..2 {
+            typ.add_noise_to_result_with_rng(dp_strategy, &mut agg_result, 1, &mut rng)
+                .unwrap();
+        }
+
+        let estimates = typ
+            .decode_result_with_error(dp_strategy, &agg_result, 1, 2, 0.9)
+            .unwrap();
+        assert_eq!(estimates.len(), typ.output_len());
+        assert!(estimates.iter().any(|estimate| estimate.value < 0.0));
+        let num_missed = estimates
+            .iter()
+            .filter(|estimate| {
+                assert!((estimate.standard_deviation - expected_std).abs() < 1e-6 * expected_std);
+                !estimate.confidence_interval.contains(0.0)
+            })
+            .count();
+        assert!(
+            num_missed * 10 < estimates.len(),
+            "{num_missed} intervals do not contain zero"
+        );
+    }
+
+    #[test]
+    fn noise_estimate() {
+        let dp_strategy = laplace(1);
+        const SIZE: usize = 500;
+
+        // The sensitivity of a vector of bits is its length. Two aggregators each add discrete
+        // Laplace noise with scale equal to the sensitivity.
+        let sumvec: SumVec<Field128, ParallelSum<_, _>> = SumVec::new(1, SIZE, 1).unwrap();
+        check_noise_estimate(
+            &sumvec,
+            &dp_strategy,
+            (2.0 * discrete_laplace_variance(SIZE as f64)).sqrt(),
+        );
+
+        // The sensitivity of a histogram is two.
+        let histogram: Histogram<Field64, ParallelSum<_, _>> = Histogram::new(SIZE, 1).unwrap();
+        check_noise_estimate(
+            &histogram,
+            &dp_strategy,
+            (2.0 * discrete_laplace_variance(2.0)).sqrt(),
+        );
+
+        // Values that wrapped around the field modulus decode as negative.
+        let estimates = histogram
+            .decode_result_with_error(
+                &dp_strategy,
+                &[Field64::from(5), -Field64::from(2)]
+                    .into_iter()
+                    .chain(std::iter::repeat(Field64::zero()))
+                    .take(SIZE)
+                    .collect::<Vec<_>>(),
+                1,
+                1,
+                0.9,
+            )
+            .unwrap();
+        assert_eq!(estimates[0].value, 5.0);
+        assert_eq!(estimates[1].value, -2.0);
+        assert!(histogram
+            .decode_result_with_error(&dp_strategy, &[Field64::zero()], 1, 1, 0.9)
+            .is_err());
+    }
+
+    #[test]
+    fn noise_estimate_gaussian() {
+        // With a zCDP budget of epsilon one, the standard deviation of the noise equals the L2
+        // sensitivity. The L2 sensitivity of a vector of bits is the square root of its length,
+        // and the L2 sensitivity of a histogram is the square root of two.
+        let dp_strategy = gaussian(1);
+        let sumvec: SumVec<Field128, ParallelSum<_, _>> = SumVec::new(1, 500, 1).unwrap();
+        check_noise_estimate(&sumvec, &dp_strategy, (2.0 * 500.0f64).sqrt());
+
+        let histogram: Histogram<Field64, ParallelSum<_, _>> = Histogram::new(500, 1).unwrap();
+        check_noise_estimate(&histogram, &dp_strategy, 2.0);
+    }
+
+    #[test]
+    fn noise_estimate_skellam() {
+        // With rho = 1/2 and squared L2 sensitivity `d`, the Skellam parameter is the positive
+        // root of 8 mu^2 - 4 d mu - 7 d = 0, and each of the two aggregators adds noise with
+        // variance 2 mu.
+        let mu = |d: f64| (4.0 * d + (16.0 * d * d + 224.0 * d).sqrt()) / 16.0;
+        let dp_strategy = RenyiDpSkellam::from_budget(
+            IntegerRenyiDpBudget::new(Rational::from_unsigned(1u8, 2).unwrap()).unwrap(),
+        );
+        let sumvec: SumVec<Field128, ParallelSum<_, _>> = SumVec::new(1, 500, 1).unwrap();
+        check_noise_estimate(&sumvec, &dp_strategy, (4.0 * mu(500.0)).sqrt());
+
+        let histogram: Histogram<Field64, ParallelSum<_, _>> = Histogram::new(500, 1).unwrap();
+        check_noise_estimate(&histogram, &dp_strategy, (4.0 * mu(2.0)).sqrt());
+    }
 }
@@ -157,7 +157,12 @@
 //! `sigma = `(2^n)/epsilon` for some `epsilon` will make the procedure [`(epsilon^2)/2`
 //! zero-concentrated differentially private](https://arxiv.org/abs/2004.00010).
 //! `epsilon` is given as a parameter to the `add_noise_to_result` function, as part of the
-//! `dp_strategy` argument of type [`ZCdpDiscreteGaussian`].
+//! `dp_strategy` argument of type [`ZCdpDiscreteGaussian`]. Alternatively, a `dp_strategy` of
+//! type [`EpsilonDeltaDiscreteGaussian`] calibrates the noise to satisfy
+//! `(epsilon, delta)`-differential privacy instead.
+//!
+//! [`ZCdpDiscreteGaussian`]: crate::dp::distributions::ZCdpDiscreteGaussian
+//! [`EpsilonDeltaDiscreteGaussian`]: crate::dp::distributions::EpsilonDeltaDiscreteGaussian
 //!
 //! ### Differences in the computation because of distribution
 //!
@@ -180,21 +185,24 @@
 
 pub mod compatible_float;
 
-use crate::dp::{distributions::ZCdpDiscreteGaussian, DifferentialPrivacyStrategy};
+use crate::dp::{
+    distributions::{DiscreteGaussian, DiscreteGaussianDpStrategy},
+    estimation::{ConfidenceInterval, NoiseEstimate, NoisyValue},
+    DifferentialPrivacyBudget, DifferentialPrivacyStrategy,
+};
 use crate::field::{
     Field128, FieldElement, FieldElementWithInteger, FieldElementWithIntegerExt, Integer,
 };
 use crate::flp::gadgets::{Mul, ParallelSumGadget, PolyEval};
-use crate::flp::types::dp::add_iid_noise_to_field_vec;
+use crate::flp::types::dp::{add_iid_noise_to_field_vec, to_signed_f64};
 use crate::flp::types::fixedpoint_l2::compatible_float::CompatibleFloat;
 use crate::flp::types::parallel_sum_range_checks;
-use crate::flp::{Flp, FlpError, Gadget, Type, TypeWithNoise};
-use crate::vdaf::xof::SeedStreamTurboShake128;
+use crate::flp::{Flp, FlpError, Gadget, Type, TypeWithNoise, TypeWithNoiseEstimate};
 use fixed::traits::Fixed;
 use num_bigint::BigUint;
 use num_rational::Ratio;
 use rand::Rng;
-use rand_core::SeedableRng;
+use rand_core::{CryptoRng, RngCore};
 use std::{convert::TryFrom, convert::TryInto, fmt::Debug, marker::PhantomData};
 
 /// The fixed point vector sum data type. Each measurement is a vector of fixed point numbers of
@@ -208,7 +216,8 @@ use std::{convert::TryFrom, convert::TryInto, fmt::Debug, marker::PhantomData};
 ///
 /// The type implements the [`TypeWithNoise`] trait. The `add_noise_to_result` function adds
 /// discrete Gaussian noise to an aggregate share, calibrated to the passed privacy budget.
-/// This will result in the aggregate satisfying zero-concentrated differential privacy.
+/// This will result in the aggregate satisfying zero-concentrated differential privacy, or
+/// approximate differential privacy, depending on the budget of the strategy.
 ///
 /// Depending on the size of the vector that needs to be transmitted, a corresponding field type has
 /// to be chosen for `F`. For a `n`-bit fixed point type and a `d`-dimensional vector, the field
@@ -358,26 +367,35 @@ where
 
     /// This noising function can be called on the aggregate share to make
     /// the entire aggregation process differentially private. The noise is
-    /// calibrated to result in a guarantee of `1/2 * epsilon^2` zero-concentrated
-    /// differential privacy, where `epsilon` is given by `dp_strategy.budget`.
-    fn add_noise<R: Rng>(
+    /// calibrated to the budget of `dp_strategy`, e.g. to result in a guarantee of
+    /// `1/2 * epsilon^2` zero-concentrated differential privacy for a `ZCdpBudget`.
+    fn add_noise<B, R>(
         &self,
-        dp_strategy: &ZCdpDiscreteGaussian,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
         agg_result: &mut [Field128],
         rng: &mut R,
-    ) -> Result<(), FlpError> {
+    ) -> Result<(), FlpError>
+    where
+        B: DifferentialPrivacyBudget,
+        DiscreteGaussianDpStrategy<B>: DifferentialPrivacyStrategy<
+            Sensitivity = Ratio<BigUint>,
+            Distribution = DiscreteGaussian,
+        >,
+        R: Rng,
+    {
         // generate and add discrete gaussian noise for each entry
 
-        // 0. Compute sensitivity of aggregation, namely 2^n.
-        let sensitivity = BigUint::from(2u128).pow(self.bits_per_entry as u32);
-        // Also create a BigInt containing the field modulus.
-
         // 1. initialize sampler
-        let sampler = dp_strategy.create_distribution(Ratio::from_integer(sensitivity))?;
+        let sampler = dp_strategy.create_distribution(self.sensitivity())?;
 
         // 2. Generate noise for each slice entry and apply it.
         add_iid_noise_to_field_vec(agg_result, rng, &sampler)
     }
+
+    /// The sensitivity of the aggregation, using the L2 norm as a distance metric, is 2^n.
+    fn sensitivity(&self) -> Ratio<BigUint> {
+        Ratio::from_integer(BigUint::from(2u128).pow(self.bits_per_entry as u32))
+    }
 }
 
 impl<T, SPoly, SMul> Flp for FixedPointBoundedL2VecSum<T, SPoly, SMul>
@@ -619,24 +637,71 @@ where
     }
 }
 
-impl<T, SPoly, SMul> TypeWithNoise<ZCdpDiscreteGaussian>
+impl<T, SPoly, SMul, B> TypeWithNoise<DiscreteGaussianDpStrategy<B>>
     for FixedPointBoundedL2VecSum<T, SPoly, SMul>
 where
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
     T: Fixed + CompatibleFloat,
     SPoly: ParallelSumGadget<Field128, PolyEval<Field128>> + Eq + Clone + 'static,
     SMul: ParallelSumGadget<Field128, Mul<Field128>> + Eq + Clone + 'static,
 {
-    fn add_noise_to_result(
+    fn add_noise_to_result_with_rng<R: RngCore + CryptoRng>(
         &self,
-        dp_strategy: &ZCdpDiscreteGaussian,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
         agg_result: &mut [Self::Field],
         _num_measurements: usize,
+        rng: &mut R,
     ) -> Result<(), FlpError> {
-        self.add_noise(
-            dp_strategy,
-            agg_result,
-            &mut SeedStreamTurboShake128::from_os_rng(),
-        )
+        self.add_noise(dp_strategy, agg_result, rng)
+    }
+}
+
+impl<T, SPoly, SMul, B> TypeWithNoiseEstimate<DiscreteGaussianDpStrategy<B>>
+    for FixedPointBoundedL2VecSum<T, SPoly, SMul>
+where
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+    T: Fixed + CompatibleFloat,
+    SPoly: ParallelSumGadget<Field128, PolyEval<Field128>> + Eq + Clone + 'static,
+    SMul: ParallelSumGadget<Field128, Mul<Field128>> + Eq + Clone + 'static,
+{
+    fn decode_result_with_error(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        data: &[Field128],
+        num_measurements: usize,
+        num_noise_shares: usize,
+        confidence: f64,
+    ) -> Result<Vec<NoisyValue>, FlpError> {
+        if data.len() != self.entries {
+            return Err(FlpError::Decode("unexpected input length".into()));
+        }
+
+        // The noise is added to the field representation, in which one unit is 2^(1-n) in the
+        // decoded representation. As in `decode_result`, an entry decodes as
+        // (s - c*2^(n-1)) * 2^(1-n), except that `s` may be negative due to the noise.
+        let unit = 2f64.powi(1 - self.bits_per_entry as i32);
+        let offset = num_measurements as f64 * 2f64.powi(self.bits_per_entry as i32 - 1);
+        let estimate = NoiseEstimate::new(dp_strategy, self.sensitivity(), num_noise_shares)?;
+        let standard_deviation = estimate.standard_deviation() * unit;
+        let margin = estimate.margin_of_error(confidence)? * unit;
+        Ok(data
+            .iter()
+            .map(|&d| {
+                let value = (to_signed_f64(d) - offset) * unit;
+                NoisyValue {
+                    value,
+                    standard_deviation,
+                    confidence_interval: ConfidenceInterval {
+                        lower: value - margin,
+                        upper: value + margin,
+                    },
+                }
+            })
+            .collect())
     }
 }
 
@@ -675,7 +740,10 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dp::{Rational, ZCdpBudget};
+    use crate::dp::{
+        distributions::{EpsilonDeltaDiscreteGaussian, ZCdpDiscreteGaussian},
+        EpsilonDeltaBudget, Rational, ZCdpBudget,
+    };
     use crate::field::{Field128, FieldElement};
     use crate::flp::gadgets::ParallelSum;
     use crate::flp::test_utils::TypeTest;
@@ -727,6 +795,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bounded_fpvec_sum_noise_estimate() {
+        type Ps = ParallelSum<Field128, PolyEval<Field128>>;
+        type Psm = ParallelSum<Field128, Mul<Field128>>;
+
+        let vsum: FixedPointBoundedL2VecSum<I1F15, Ps, Psm> =
+            FixedPointBoundedL2VecSum::new(3).unwrap();
+        let fp_vec = vec![FP16_4_INV, FP16_8_INV, FP16_16_INV];
+        let mut v = vsum
+            .truncate(vsum.encode_measurement(&fp_vec).unwrap())
+            .unwrap();
+        let strategy = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+            Rational::from_unsigned(100u8, 3u8).unwrap(),
+        ));
+        vsum.add_noise(
+            &strategy,
+            &mut v,
+            &mut SeedStreamTurboShake128::from_seed([0u8; 32]),
+        )
+        .unwrap();
+
+        let estimates = vsum
+            .decode_result_with_error(&strategy, &v, 1, 1, 0.99)
+            .unwrap();
+        let decoded = vsum.decode_result(&v, 1).unwrap();
+        for ((estimate, decoded), expected) in
+            estimates.iter().zip(decoded).zip([0.25, 0.125, 0.0625])
+        {
+            assert_eq!(estimate.value, decoded);
+            // The sensitivity is 2^16 in the field representation, so the standard deviation of
+            // the noise is 2^16 * 3/100 there, and 2^16 * 3/100 * 2^-15 after decoding.
+            assert!((estimate.standard_deviation - 0.06).abs() < 1e-9);
+            assert!(estimate.confidence_interval.contains(expected));
+        }
+
+        // Noise may make the sum of encoded entries negative, but it still decodes correctly.
+        let estimates = vsum
+            .decode_result_with_error(&strategy, &[-Field128::one(); 3], 0, 1, 0.99)
+            .unwrap();
+        assert_eq!(estimates[0].value, -(2f64.powi(-15)));
+    }
+
     fn test_fixed<F>(fp_vec: Vec<F>, enc_vec: Vec<u128>)
     where
         F: Fixed + CompatibleFloat,
@@ -783,6 +893,33 @@ mod tests {
             }
         );
 
+        // Noise calibrated to an (epsilon, delta) budget. The budget is large, so the noise is
+        // small relative to the measurement.
+        let mut v = vsum
+            .truncate(vsum.encode_measurement(&fp_vec).unwrap())
+            .unwrap();
+        let strategy = EpsilonDeltaDiscreteGaussian::from_budget(
+            EpsilonDeltaBudget::new(
+                Rational::from_unsigned(1000u16, 1u16).unwrap(),
+                Rational::from_unsigned(1u32, 100_000u32).unwrap(),
+            )
+            .unwrap(),
+        );
+        vsum.add_noise(
+            &strategy,
+            &mut v,
+            &mut SeedStreamTurboShake128::from_seed([0u8; 32]),
+        )
+        .unwrap();
+        for (noised, expected) in vsum
+            .decode_result(&v, 1)
+            .unwrap()
+            .into_iter()
+            .zip([0.25, 0.125, 0.0625])
+        {
+            assert!((noised - expected).abs() < 0.1);
+        }
+
         // encoded norm does not match computed norm
         let mut input: Vec<Field128> = vsum.encode_measurement(&fp_vec).unwrap();
         assert_eq!(input[0], Field128::zero());
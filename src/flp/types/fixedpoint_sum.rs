@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A [`Type`] for summing and averaging floating point scalars by quantising them to a fixed point
+//! grid.
+//!
+//! Each measurement is an `f64`. The client clamps it to a configured range `[lower, upper]` and
+//! rounds it to a value of the fixed point type `T` of the [*fixed*
+//! crate](https://crates.io/crates/fixed), so the resolution of the grid is `T::DELTA`, i.e.,
+//! `2^-T::FRAC_NBITS`. The bounds of the clamp range are values of `T` as well. The offset of the
+//! quantised value from the lower bound, in units of the resolution, is a non-negative integer,
+//! which is then encoded and range checked exactly like a measurement of the [`Sum`] type, i.e.,
+//! with a bit decomposition. The collector decodes the sum of these offsets back into an `f64` sum
+//! and mean.
+//!
+//! ### Quantisation
+//!
+//! Let `bits(x)` denote the underlying integer of a fixed point number `x`, i.e., `x / T::DELTA`.
+//! A measurement `m` is encoded as
+//!
+//! ```text
+//! q(m) = bits(round(clamp(m, lower, upper))) - bits(lower)
+//! ```
+//!
+//! which lies in `[0, bits(upper) - bits(lower)]`. Given the sum `S` of `n` encoded measurements,
+//! the decoded sum is `(S + n * bits(lower)) * T::DELTA`. The offset is undone on integers, and
+//! only the final result is converted to a float.
+//!
+//! ### Rounding
+//!
+//! With [`Rounding::Nearest`], `round` is the conversion from `f64` of the `fixed` crate, which
+//! rounds to the nearest value of `T`, with ties rounded to even. This introduces a bias of up to
+//! half the resolution per measurement. With [`Rounding::Stochastic`], `round` rounds up with
+//! probability equal to the distance from the grid point below, in units of the resolution, and
+//! down otherwise, so that the quantised value is an unbiased estimate of the clamped measurement.
+//! The decoded mean is then unbiased as well, at the cost of additional variance.
+
+use crate::field::NttFriendlyFieldElement;
+use crate::flp::types::Sum;
+use crate::flp::{Flp, FlpError, Gadget, Type};
+use fixed::traits::Fixed;
+use rand::{rng, Rng};
+use std::fmt::{self, Debug};
+
+/// How [`FixedPointSum`] rounds a measurement to the fixed point grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round to the nearest grid point, with ties rounded to even.
+    Nearest,
+
+    /// Round up with probability equal to the distance from the grid point below, and down
+    /// otherwise. The quantised measurement is an unbiased estimate of the clamped measurement.
+    Stochastic,
+}
+
+/// The aggregate result of the [`FixedPointSum`] type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedPointSumResult {
+    /// The sum of the quantised measurements.
+    pub sum: f64,
+
+    /// The arithmetic mean of the quantised measurements.
+    pub mean: f64,
+}
+
+/// The fixed point sum type. Each measurement is an `f64` that is clamped to `[lower, upper]` and
+/// quantised to the fixed point type `T`. The aggregate result is the sum and mean of the
+/// quantised measurements.
+///
+/// The [*fixed* crate](https://crates.io/crates/fixed) is used for fixed point numbers. The
+/// resolution of the quantisation is that of `T`, e.g., `2^-8` for `FixedI32<U8>`.
+// This is a `Sum` object under the hood, applied to the offset of the quantised measurement from
+// the lower bound of the clamp range.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FixedPointSum<T: Fixed, F: NttFriendlyFieldElement> {
+    summer: Sum<F>,
+    lower: T,
+    upper: T,
+    rounding: Rounding,
+}
+
+impl<T: Fixed, F: NttFriendlyFieldElement> Debug for FixedPointSum<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixedPointSum")
+            .field("lower", &self.lower)
+            .field("upper", &self.upper)
+            .field("rounding", &self.rounding)
+            .finish()
+    }
+}
+
+impl<T: Fixed, F: NttFriendlyFieldElement> FixedPointSum<T, F> {
+    /// Return a new [`FixedPointSum`] type parameter. Measurements are clamped to `[lower,
+    /// upper]` and rounded to a value of `T` using the given rounding mode.
+    ///
+    /// Errors if `lower >= upper` or if the number of grid points in the clamp range is too large
+    /// for the field.
+    pub fn new(lower: T, upper: T, rounding: Rounding) -> Result<Self, FlpError> {
+        if lower >= upper {
+            return Err(FlpError::InvalidParameter(format!(
+                "lower bound ({lower}) must be less than upper bound ({upper})"
+            )));
+        }
+
+        let max_measurement = to_bits(lower)
+            .zip(to_bits(upper))
+            .and_then(|(lower, upper)| upper.checked_sub(lower))
+            .and_then(|m| usize::try_from(m).ok())
+            .and_then(|m| F::Integer::try_from(m).ok())
+            .ok_or_else(|| {
+                FlpError::InvalidParameter("clamp range too large for field".to_string())
+            })?;
+
+        Ok(Self {
+            summer: Sum::new(max_measurement)?,
+            lower,
+            upper,
+            rounding,
+        })
+    }
+
+    /// The lower bound of the clamp range.
+    pub fn lower(&self) -> T {
+        self.lower
+    }
+
+    /// The upper bound of the clamp range.
+    pub fn upper(&self) -> T {
+        self.upper
+    }
+
+    /// Clamp and quantise a measurement, returning its offset from the lower bound in units of the
+    /// resolution.
+    fn quantise<R: Rng + ?Sized>(&self, measurement: f64, rng: &mut R) -> Result<usize, FlpError> {
+        if measurement.is_nan() {
+            return Err(FlpError::Encode("measurement is NaN".to_string()));
+        }
+
+        let clamped = measurement.clamp(self.lower.to_num(), self.upper.to_num());
+        // The bounds may not be exactly representable as `f64`, so clamp again after rounding.
+        let nearest = T::saturating_from_num(clamped).clamp(self.lower, self.upper);
+        let quantised = match self.rounding {
+            Rounding::Nearest => nearest,
+            Rounding::Stochastic => {
+                // The signed distance from the nearest grid point, in units of the resolution.
+                let delta = (clamped - nearest.to_num::<f64>()) / T::DELTA.to_num::<f64>();
+                if delta > 0.0 && rng.random::<f64>() < delta {
+                    nearest.saturating_add(T::DELTA).min(self.upper)
+                } else if delta < 0.0 && rng.random::<f64>() < -delta {
+                    nearest.saturating_sub(T::DELTA).max(self.lower)
+                } else {
+                    nearest
+                }
+            }
+        };
+
+        // Unwrap safety: the constructor checked that the bounds can be converted, and the
+        // quantised value lies between them.
+        let offset = to_bits(quantised).unwrap() - to_bits(self.lower).unwrap();
+        Ok(usize::try_from(offset).unwrap())
+    }
+
+    /// Like [`Type::encode_measurement`], except that the randomness used for stochastic rounding
+    /// is drawn from `rng`.
+    pub fn encode_measurement_with_rng<R: Rng + ?Sized>(
+        &self,
+        measurement: &f64,
+        rng: &mut R,
+    ) -> Result<Vec<F>, FlpError> {
+        let quantised = F::Integer::try_from(self.quantise(*measurement, rng)?)
+            .map_err(|_| FlpError::Encode("quantised measurement too large".to_string()))?;
+        self.summer.encode_measurement(&quantised)
+    }
+}
+
+/// Return the underlying integer of a fixed point number, i.e., its value in units of the
+/// resolution, or `None` if it does not fit in an `i128`.
+fn to_bits<T: Fixed>(x: T) -> Option<i128> {
+    x.to_bits().try_into().ok()
+}
+
+impl<T: Fixed, F: NttFriendlyFieldElement> Flp for FixedPointSum<T, F> {
+    type Field = F;
+
+    fn gadget(&self) -> Vec<Box<dyn Gadget<F>>> {
+        self.summer.gadget()
+    }
+
+    fn num_gadgets(&self) -> usize {
+        self.summer.num_gadgets()
+    }
+
+    fn valid(
+        &self,
+        g: &mut Vec<Box<dyn Gadget<F>>>,
+        input: &[F],
+        joint_rand: &[F],
+        num_shares: usize,
+    ) -> Result<Vec<F>, FlpError> {
+        self.summer.valid(g, input, joint_rand, num_shares)
+    }
+
+    fn input_len(&self) -> usize {
+        self.summer.input_len()
+    }
+
+    fn proof_len(&self) -> usize {
+        self.summer.proof_len()
+    }
+
+    fn verifier_len(&self) -> usize {
+        self.summer.verifier_len()
+    }
+
+    fn joint_rand_len(&self) -> usize {
+        self.summer.joint_rand_len()
+    }
+
+    fn eval_output_len(&self) -> usize {
+        self.summer.eval_output_len()
+    }
+
+    fn prove_rand_len(&self) -> usize {
+        self.summer.prove_rand_len()
+    }
+}
+
+impl<T, F> Type for FixedPointSum<T, F>
+where
+    T: Fixed,
+    F: NttFriendlyFieldElement,
+    F::Integer: Into<u128>,
+{
+    type Measurement = f64;
+    type AggregateResult = FixedPointSumResult;
+
+    fn encode_measurement(&self, measurement: &f64) -> Result<Vec<F>, FlpError> {
+        self.encode_measurement_with_rng(measurement, &mut rng())
+    }
+
+    fn truncate(&self, input: Vec<F>) -> Result<Vec<F>, FlpError> {
+        self.summer.truncate(input)
+    }
+
+    fn decode_result(
+        &self,
+        data: &[F],
+        num_measurements: usize,
+    ) -> Result<FixedPointSumResult, FlpError> {
+        if num_measurements == 0 {
+            return Err(FlpError::Decode(
+                "the mean of zero measurements is undefined".to_string(),
+            ));
+        }
+        let sum: u128 = self.summer.decode_result(data, num_measurements)?.into();
+
+        // Undo the offset by the lower bound of each measurement. This is done on integers, and
+        // only the final result is converted to a float.
+        // Unwrap safety: the constructor checked that the lower bound can be converted.
+        let sum = i128::try_from(num_measurements)
+            .ok()
+            .and_then(|n| n.checked_mul(to_bits(self.lower).unwrap()))
+            .zip(i128::try_from(sum).ok())
+            .and_then(|(offset, sum)| sum.checked_add(offset))
+            .ok_or_else(|| FlpError::Decode("aggregate sum overflows i128".to_string()))?;
+        let sum = sum as f64 * T::DELTA.to_num::<f64>();
+
+        Ok(FixedPointSumResult {
+            sum,
+            mean: sum / num_measurements as f64,
+        })
+    }
+
+    fn output_len(&self) -> usize {
+        self.summer.output_len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field128, FieldElement};
+    use crate::flp::test_utils::TypeTest;
+    use crate::vdaf::xof::SeedStreamTurboShake128;
+    use fixed::types::{I128F0, I28F4, I30F2, I64F0, U128F0};
+    use rand::SeedableRng;
+
+    fn round_trip<T: Fixed>(
+        typ: &FixedPointSum<T, Field128>,
+        measurements: &[f64],
+    ) -> FixedPointSumResult {
+        let mut agg = Field128::zero();
+        for measurement in measurements {
+            let output = typ
+                .truncate(typ.encode_measurement(measurement).unwrap())
+                .unwrap();
+            agg += output[0];
+        }
+        typ.decode_result(&[agg], measurements.len()).unwrap()
+    }
+
+    #[test]
+    fn test_fixedpoint_sum() {
+        let typ = FixedPointSum::<I28F4, Field128>::new(
+            I28F4::from_num(-1),
+            I28F4::from_num(1),
+            Rounding::Nearest,
+        )
+        .unwrap();
+        assert_eq!(typ.lower(), -1);
+        assert_eq!(typ.upper(), 1);
+
+        // Values on the grid are represented exactly.
+        assert_eq!(
+            round_trip(&typ, &[0.5, -0.25, 0.0625, -1.0]),
+            FixedPointSumResult {
+                sum: -0.6875,
+                mean: -0.171875,
+            }
+        );
+
+        // Values off the grid are rounded to the nearest grid point, with ties rounded to even.
+        assert_eq!(round_trip(&typ, &[0.3]).sum, 0.3125);
+        assert_eq!(round_trip(&typ, &[-0.3]).sum, -0.3125);
+        assert_eq!(round_trip(&typ, &[0.09375]).sum, 0.125);
+        assert_eq!(round_trip(&typ, &[0.03125]).sum, 0.0);
+
+        // Values outside of the clamp range are clamped.
+        assert_eq!(round_trip(&typ, &[1337.0, -1337.0, 2.0]).sum, 1.0);
+        assert_eq!(round_trip(&typ, &[f64::INFINITY]).sum, 1.0);
+
+        typ.encode_measurement(&f64::NAN).unwrap_err();
+
+        // Test FLP on valid input.
+        for measurement in [-1.0, -0.5, 0.0, 0.3, 1.0] {
+            let input = typ.encode_measurement(&measurement).unwrap();
+            TypeTest::expect_valid_no_output::<3>(&typ, &input);
+        }
+
+        // Test FLP on invalid input.
+        let mut input = typ.encode_measurement(&0.0).unwrap();
+        input[0] = Field128::from(2);
+        TypeTest::expect_invalid::<3>(&typ, &input);
+    }
+
+    #[test]
+    fn test_fixedpoint_sum_large_aggregate() {
+        // The aggregate sum of the offsets exceeds 2^64.
+        let typ = FixedPointSum::<I64F0, Field128>::new(I64F0::MIN, I64F0::MAX, Rounding::Nearest)
+            .unwrap();
+        let max = i64::MAX as f64;
+        assert_eq!(round_trip(&typ, &[max; 4]).sum, 4.0 * max);
+        assert_eq!(round_trip(&typ, &[-max; 4]).sum, -4.0 * max);
+    }
+
+    #[test]
+    fn test_fixedpoint_sum_stochastic_rounding() {
+        let typ = FixedPointSum::<I30F2, Field128>::new(
+            I30F2::from_num(0),
+            I30F2::from_num(1),
+            Rounding::Stochastic,
+        )
+        .unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([0; 32]);
+
+        // Values on the grid are never rounded.
+        for _ in 0..100 {
+            let input = typ.encode_measurement_with_rng(&0.75, &mut rng).unwrap();
+            assert_eq!(typ.truncate(input).unwrap(), [Field128::from(3)]);
+        }
+
+        // Values between grid points are rounded to one of the neighbouring grid points, such that
+        // the mean is preserved.
+        for measurement in [0.3, 0.45] {
+            let n = 10_000;
+            let mut agg = Field128::zero();
+            for _ in 0..n {
+                let input = typ
+                    .encode_measurement_with_rng(&measurement, &mut rng)
+                    .unwrap();
+                let output = typ.truncate(input).unwrap();
+                assert!(output == [Field128::from(1)] || output == [Field128::from(2)]);
+                agg += output[0];
+            }
+            let result = typ.decode_result(&[agg], n).unwrap();
+            assert!(
+                (result.mean - measurement).abs() < 0.01,
+                "mean: {}",
+                result.mean
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixedpoint_sum_invalid_args() {
+        let one = I28F4::from_num(1);
+        FixedPointSum::<I28F4, Field128>::new(one, one, Rounding::Nearest).unwrap_err();
+        FixedPointSum::<I28F4, Field128>::new(one, -one, Rounding::Nearest).unwrap_err();
+
+        // The clamp range has too many grid points.
+        FixedPointSum::<I128F0, Field128>::new(I128F0::MIN, I128F0::MAX, Rounding::Nearest)
+            .unwrap_err();
+        FixedPointSum::<U128F0, Field128>::new(U128F0::ZERO, U128F0::MAX, Rounding::Nearest)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_fixedpoint_sum_no_measurements() {
+        let typ = FixedPointSum::<I28F4, Field128>::new(-I28F4::ONE, I28F4::ONE, Rounding::Nearest)
+            .unwrap();
+        typ.decode_result(&[Field128::zero()], 0).unwrap_err();
+    }
+}
@@ -256,7 +256,29 @@ fn extend(seed: &[u8; 16], xof_mode: &XofMode<'_>) -> ([[u8; 16]; 2], [Choice; 2
         }
     }
 
-    // "Steal" the control bits from the seeds.
+    steal_control_bits(seeds)
+}
+
+/// Extend each of `seeds`, as in [`extend`]. At inner levels, the fixed-key AES calls for all the
+/// seeds are batched together.
+fn extend_batch(seeds: &[[u8; 16]], xof_mode: &XofMode<'_>) -> Vec<([[u8; 16]; 2], [Choice; 2])> {
+    match xof_mode {
+        XofMode::Inner(fixed_key) => fixed_key
+            .fill_32_bytes_batch(seeds)
+            .into_iter()
+            .map(|bytes| {
+                let mut seeds = [[0u8; 16], [0u8; 16]];
+                seeds[0].copy_from_slice(&bytes[..16]);
+                seeds[1].copy_from_slice(&bytes[16..]);
+                steal_control_bits(seeds)
+            })
+            .collect(),
+        XofMode::Leaf(..) => seeds.iter().map(|seed| extend(seed, xof_mode)).collect(),
+    }
+}
+
+/// "Steal" the control bits from the seeds.
+fn steal_control_bits(mut seeds: [[u8; 16]; 2]) -> ([[u8; 16]; 2], [Choice; 2]) {
     let control_bits_0 = seeds[0].as_ref()[0] & 1;
     let control_bits_1 = seeds[1].as_ref()[0] & 1;
     seeds[0].as_mut()[0] &= 0xfe;
@@ -368,8 +390,35 @@ fn eval_next<V>(
 where
     V: IdpfValue,
 {
-    let (mut seeds, mut control_bits) = extend(key, extend_mode);
+    let extended = extend(key, extend_mode);
+    eval_next_extended(
+        is_leader,
+        parameter,
+        key,
+        control_bit,
+        correction_word,
+        input_bit,
+        extended,
+        convert_mode,
+    )
+}
 
+/// Helper function to evaluate one level of an IDPF, given the output of [`extend`] for the
+/// current node. This updates the seed and control bit arguments that are passed in.
+#[allow(clippy::too_many_arguments)]
+fn eval_next_extended<V>(
+    is_leader: bool,
+    parameter: &V::ValueParameter,
+    key: &mut [u8; 16],
+    control_bit: &mut Choice,
+    correction_word: &IdpfCorrectionWord<V>,
+    input_bit: Choice,
+    (mut seeds, mut control_bits): ([[u8; 16]; 2], [Choice; 2]),
+    convert_mode: &XofMode<'_>,
+) -> V
+where
+    V: IdpfValue,
+{
     seeds[0] = conditional_xor_seeds(&seeds[0], &correction_word.seed, *control_bit);
     control_bits[0] ^= correction_word.control_bits[0] & *control_bit;
     seeds[1] = conditional_xor_seeds(&seeds[1], &correction_word.seed, *control_bit);
@@ -387,6 +436,48 @@ where
     out
 }
 
+/// Helper function to evaluate both children of each node at one level of an IDPF. Returns the
+/// keys, control bits and output shares of the children, in lexicographic order.
+#[allow(clippy::type_complexity)]
+fn expand_level<V>(
+    is_leader: bool,
+    parameter: &V::ValueParameter,
+    keys: &[[u8; 16]],
+    control_bits: &[Choice],
+    correction_word: &IdpfCorrectionWord<V>,
+    extend_mode: &XofMode<'_>,
+    convert_mode: &XofMode<'_>,
+) -> (Vec<[u8; 16]>, Vec<Choice>, Vec<V>)
+where
+    V: IdpfValue,
+{
+    let mut next_keys = Vec::with_capacity(2 * keys.len());
+    let mut next_control_bits = Vec::with_capacity(2 * keys.len());
+    let mut values = Vec::with_capacity(2 * keys.len());
+    for ((key, control_bit), extended) in keys
+        .iter()
+        .zip(control_bits.iter())
+        .zip(extend_batch(keys, extend_mode))
+    {
+        for input_bit in [Choice::from(0), Choice::from(1)] {
+            let (mut key, mut control_bit) = (*key, *control_bit);
+            values.push(eval_next_extended(
+                is_leader,
+                parameter,
+                &mut key,
+                &mut control_bit,
+                correction_word,
+                input_bit,
+                extended,
+                convert_mode,
+            ));
+            next_keys.push(key);
+            next_control_bits.push(control_bit);
+        }
+    }
+    (next_keys, next_control_bits, values)
+}
+
 /// This defines a family of IDPFs (incremental distributed point functions) with certain types of
 /// values at inner tree nodes and at leaf tree nodes.
 ///
@@ -638,6 +729,87 @@ where
             cache,
         )
     }
+
+    /// Evaluate an IDPF share on every prefix of length `level + 1`.
+    ///
+    /// The output shares are returned in lexicographic order of their prefixes. This is equivalent
+    /// to calling [`Idpf::eval`] on each prefix, but the tree is walked breadth-first, so each
+    /// node is only expanded once, and the fixed-key AES calls for each inner level are batched.
+    pub fn eval_full_domain(
+        &self,
+        agg_id: usize,
+        public_share: &IdpfPublicShare<VI, VL>,
+        key: &Seed<16>,
+        level: usize,
+        ctx: &[u8],
+        nonce: &[u8],
+    ) -> Result<Vec<IdpfOutputShare<VI, VL>>, IdpfError> {
+        let bits = public_share.inner_correction_words.len() + 1;
+        if agg_id > 1 {
+            return Err(IdpfError::InvalidParameter(format!(
+                "invalid aggregator ID {agg_id}"
+            )));
+        }
+        let is_leader = agg_id == 0;
+        if level >= bits {
+            return Err(IdpfError::InvalidParameter(format!(
+                "level ({level}) exceeds configured number of bits ({bits})",
+            )));
+        }
+        if u32::try_from(level + 1)
+            .ok()
+            .and_then(|exp| 1usize.checked_shl(exp))
+            .is_none()
+        {
+            return Err(IdpfError::InvalidParameter(format!(
+                "level ({level}) is too large"
+            )));
+        }
+
+        let extend_xof_fixed_key = XofFixedKeyAes128Key::new(&[EXTEND_DOMAIN_SEP, ctx], nonce);
+        let convert_xof_fixed_key = XofFixedKeyAes128Key::new(&[CONVERT_DOMAIN_SEP, ctx], nonce);
+
+        // Keys and control bits of the nodes at the current level, in lexicographic order.
+        let mut keys = vec![key.0];
+        let mut control_bits = vec![Choice::from((!is_leader) as u8)];
+        for correction_word in public_share.inner_correction_words[..level].iter() {
+            (keys, control_bits, _) = expand_level(
+                is_leader,
+                &self.inner_node_value_parameter,
+                &keys,
+                &control_bits,
+                correction_word,
+                &XofMode::Inner(&extend_xof_fixed_key),
+                &XofMode::Inner(&convert_xof_fixed_key),
+            );
+        }
+
+        let out = if level == bits - 1 {
+            let (_, _, values) = expand_level(
+                is_leader,
+                &self.leaf_node_value_parameter,
+                &keys,
+                &control_bits,
+                &public_share.leaf_correction_word,
+                &XofMode::Leaf(ctx, nonce),
+                &XofMode::Leaf(ctx, nonce),
+            );
+            values.into_iter().map(IdpfOutputShare::Leaf).collect()
+        } else {
+            let (_, _, values) = expand_level(
+                is_leader,
+                &self.inner_node_value_parameter,
+                &keys,
+                &control_bits,
+                &public_share.inner_correction_words[level],
+                &XofMode::Inner(&extend_xof_fixed_key),
+                &XofMode::Inner(&convert_xof_fixed_key),
+            );
+            values.into_iter().map(IdpfOutputShare::Inner).collect()
+        };
+
+        Ok(out)
+    }
 }
 
 /// An IDPF public share. This contains the list of correction words used by all parties when
@@ -1436,6 +1608,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn idpf_poplar_eval_full_domain() {
+        const INPUT_LEN: usize = 6;
+        let bits = (0..INPUT_LEN).map(|_| random()).collect::<Vec<bool>>();
+        let input = IdpfInput::from_bools(&bits);
+        let inner_values = (1..INPUT_LEN as u64)
+            .map(|i| Poplar1IdpfValue::new([Field64::from(i), Field64::from(i * 2)]))
+            .collect::<Vec<_>>();
+        let leaf_values = Poplar1IdpfValue::new([Field255::one(), Field255::from(7)]);
+
+        let nonce: [u8; 16] = random();
+        let idpf = Idpf::new((), ());
+        let (public_share, keys) = idpf
+            .gen(&input, inner_values.clone(), leaf_values, CTX_STR, &nonce)
+            .unwrap();
+
+        for level in 0..INPUT_LEN {
+            let prefixes = (0..1usize << (level + 1))
+                .map(|i| {
+                    IdpfInput::from_bools(
+                        &(0..=level)
+                            .map(|j| (i >> (level - j)) & 1 == 1)
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let mut outputs = Vec::new();
+            for (agg_id, key) in keys.iter().enumerate() {
+                let shares = idpf
+                    .eval_full_domain(agg_id, &public_share, key, level, CTX_STR, &nonce)
+                    .unwrap();
+                assert_eq!(shares.len(), prefixes.len());
+                for (share, prefix) in shares.iter().zip(prefixes.iter()) {
+                    let want = idpf
+                        .eval(
+                            agg_id,
+                            &public_share,
+                            key,
+                            prefix,
+                            CTX_STR,
+                            &nonce,
+                            &mut NoCache::new(),
+                        )
+                        .unwrap();
+                    assert_eq!(share, &want);
+                }
+                outputs.push(shares);
+            }
+
+            let leader_shares = outputs.remove(0);
+            for ((leader_share, helper_share), prefix) in leader_shares
+                .into_iter()
+                .zip(outputs.remove(0))
+                .zip(prefixes.iter())
+            {
+                let output = leader_share.merge(helper_share).unwrap();
+                let on_path = prefix == &input.prefix(level);
+                let expected = match (inner_values.get(level), on_path) {
+                    (Some(value), true) => IdpfOutputShare::Inner(*value),
+                    (Some(_), false) => IdpfOutputShare::Inner(Poplar1IdpfValue::new([
+                        Field64::zero(),
+                        Field64::zero(),
+                    ])),
+                    (None, true) => IdpfOutputShare::Leaf(leaf_values),
+                    (None, false) => IdpfOutputShare::Leaf(Poplar1IdpfValue::new([
+                        Field255::zero(),
+                        Field255::zero(),
+                    ])),
+                };
+                assert_eq!(output, expected);
+            }
+        }
+
+        idpf.eval_full_domain(0, &public_share, &keys[0], INPUT_LEN, CTX_STR, &nonce)
+            .unwrap_err();
+        idpf.eval_full_domain(2, &public_share, &keys[0], 0, CTX_STR, &nonce)
+            .unwrap_err();
+    }
+
     #[test]
     fn idpf_poplar_cache_behavior() {
         let bits = bitbox![0, 1, 1, 1, 0, 1, 0, 0];
@@ -11,14 +11,23 @@ use crate::dp::DifferentialPrivacyStrategy;
 use crate::idpf::IdpfError;
 #[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
 use crate::vdaf::mastic::szk::SzkError;
+#[cfg(feature = "experimental")]
+use crate::vdaf::xof::SeedStreamTurboShake128;
 #[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
 use crate::vidpf::VidpfError;
 use crate::{
     codec::{CodecError, Decode, Encode, ParameterizedDecode},
-    field::{encode_fieldvec, merge_vector, FieldElement, FieldError},
+    field::{
+        encode_fieldvec, merge_encoded_vector, merge_vector, unmerge_vector, FieldElement,
+        FieldError,
+    },
     flp::FlpError,
     vdaf::xof::Seed,
 };
+#[cfg(feature = "experimental")]
+use rand::SeedableRng;
+#[cfg(feature = "experimental")]
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::{error::Error, fmt::Debug, io::Cursor};
 use subtle::{Choice, ConstantTimeEq};
@@ -309,14 +318,44 @@ pub trait AggregatorWithNoise<
 >: Aggregator<VERIFY_KEY_SIZE, NONCE_SIZE>
 {
     /// Adds noise to an aggregate share such that the aggregate result is differentially private
-    /// as long as one Aggregator is honest.
+    /// as long as one Aggregator is honest. Randomness is drawn from the operating system.
+    ///
+    /// Implementations must override at least one of this method and
+    /// [`Self::add_noise_to_agg_share_with_rng`], since their default implementations call each
+    /// other. The default implementation calls [`Self::add_noise_to_agg_share_with_rng`] with an
+    /// RNG seeded by the operating system.
     fn add_noise_to_agg_share(
         &self,
         dp_strategy: &DPStrategy,
         agg_param: &Self::AggregationParam,
         agg_share: &mut Self::AggregateShare,
         num_measurements: usize,
-    ) -> Result<(), VdafError>;
+    ) -> Result<(), VdafError> {
+        self.add_noise_to_agg_share_with_rng(
+            dp_strategy,
+            agg_param,
+            agg_share,
+            num_measurements,
+            &mut SeedStreamTurboShake128::from_os_rng(),
+        )
+    }
+
+    /// Like [`Self::add_noise_to_agg_share`], but draws randomness from `rng`. With a seeded `rng`,
+    /// such as one derived with [`crate::dp::noise_rng`], the noise is reproducible.
+    ///
+    /// The default implementation ignores `rng` and calls [`Self::add_noise_to_agg_share`], so the
+    /// noise is not reproducible. Implementations that support caller-supplied randomness should
+    /// override this method instead.
+    fn add_noise_to_agg_share_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DPStrategy,
+        agg_param: &Self::AggregationParam,
+        agg_share: &mut Self::AggregateShare,
+        num_measurements: usize,
+        _rng: &mut R,
+    ) -> Result<(), VdafError> {
+        self.add_noise_to_agg_share(dp_strategy, agg_param, agg_share, num_measurements)
+    }
 }
 
 /// The Collector's role in the execution of a VDAF.
@@ -357,6 +396,32 @@ pub trait Aggregatable: Clone + Debug + From<Self::OutputShare> {
     fn accumulate(&mut self, output_share: &Self::OutputShare) -> Result<(), VdafError>;
 }
 
+/// An aggregate share into which output shares can be accumulated from their encoding.
+pub trait EncodedAggregatable: Aggregatable {
+    /// Update an aggregate share by adding the output share encoded in `encoded_output_share`.
+    ///
+    /// This is equivalent to decoding the output share and passing it to
+    /// [`Aggregatable::accumulate`], but the output share is added directly from its encoding,
+    /// without allocating. It is meant for Aggregators that store output shares in their encoded
+    /// form. The encoding is validated on the way: if it is not the canonical encoding of an output
+    /// share that can be accumulated into this aggregate share, an error is returned and the
+    /// aggregate share is left unchanged.
+    fn accumulate_encoded(&mut self, encoded_output_share: &[u8]) -> Result<(), VdafError>;
+}
+
+/// An aggregate share from which output shares and other aggregate shares can be removed again.
+pub trait RetractableAggregatable: Aggregatable {
+    /// Update an aggregate share by removing another (`agg_share`) that was previously merged into
+    /// it. This is the inverse of [`Aggregatable::merge`].
+    fn subtract(&mut self, agg_share: &Self) -> Result<(), VdafError>;
+
+    /// Update an aggregate share by removing `output_share`, which was previously accumulated into
+    /// it. This is useful for dropping a report from an aggregate, e.g., because it turned out to
+    /// be a replay, without recomputing the aggregate from the remaining output shares. This is the
+    /// inverse of [`Aggregatable::accumulate`].
+    fn retract(&mut self, output_share: &Self::OutputShare) -> Result<(), VdafError>;
+}
+
 /// An output share comprised of a vector of field elements.
 #[derive(Clone)]
 pub struct OutputShare<F>(Vec<F>);
@@ -457,10 +522,30 @@ impl<F: FieldElement> Aggregatable for AggregateShare<F> {
     }
 }
 
+impl<F: FieldElement> EncodedAggregatable for AggregateShare<F> {
+    fn accumulate_encoded(&mut self, encoded_output_share: &[u8]) -> Result<(), VdafError> {
+        merge_encoded_vector(&mut self.0, encoded_output_share).map_err(Into::into)
+    }
+}
+
+impl<F: FieldElement> RetractableAggregatable for AggregateShare<F> {
+    fn subtract(&mut self, agg_share: &Self) -> Result<(), VdafError> {
+        self.difference(agg_share.as_ref())
+    }
+
+    fn retract(&mut self, output_share: &Self::OutputShare) -> Result<(), VdafError> {
+        self.difference(output_share.as_ref())
+    }
+}
+
 impl<F: FieldElement> AggregateShare<F> {
     fn sum(&mut self, other: &[F]) -> Result<(), VdafError> {
         merge_vector(&mut self.0, other).map_err(Into::into)
     }
+
+    fn difference(&mut self, other: &[F]) -> Result<(), VdafError> {
+        unmerge_vector(&mut self.0, other).map_err(Into::into)
+    }
 }
 
 impl<F: FieldElement> Encode for AggregateShare<F> {
@@ -1365,7 +1450,14 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::vdaf::{equality_comparison_test, xof::Seed, AggregateShare, OutputShare, Share};
+    use crate::{
+        codec::Encode,
+        field::{Field64, FieldElement},
+        vdaf::{
+            equality_comparison_test, xof::Seed, Aggregatable, AggregateShare, EncodedAggregatable,
+            OutputShare, RetractableAggregatable, Share,
+        },
+    };
 
     #[test]
     fn share_equality_test() {
@@ -1392,12 +1484,78 @@ mod tests {
             AggregateShare(Vec::from([3, 2, 1])),
         ])
     }
+
+    #[test]
+    fn aggregate_share_retract_subtract() {
+        let output_shares = [
+            OutputShare(Field64::random_vector(3)),
+            OutputShare(Field64::random_vector(3)),
+        ];
+        let mut want = AggregateShare(vec![Field64::zero(); 3]);
+        want.accumulate(&output_shares[0]).unwrap();
+
+        let mut got = want.clone();
+        got.accumulate(&output_shares[1]).unwrap();
+        assert_ne!(got, want);
+        got.retract(&output_shares[1]).unwrap();
+        assert_eq!(got, want);
+
+        let other = AggregateShare::from(output_shares[1].clone());
+        got.merge(&other).unwrap();
+        got.subtract(&other).unwrap();
+        assert_eq!(got, want);
+
+        got.retract(&OutputShare(Field64::random_vector(2)))
+            .unwrap_err();
+        got.subtract(&AggregateShare(Field64::random_vector(4)))
+            .unwrap_err();
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn aggregate_share_accumulate_encoded() {
+        let output_shares = [
+            OutputShare(Field64::random_vector(3)),
+            OutputShare(Field64::random_vector(3)),
+        ];
+        let mut want = AggregateShare(vec![Field64::zero(); 3]);
+        let mut got = want.clone();
+        for output_share in &output_shares {
+            want.accumulate(output_share).unwrap();
+            got.accumulate_encoded(&output_share.get_encoded().unwrap())
+                .unwrap();
+        }
+        assert_eq!(got, want);
+
+        // Wrong length.
+        let encoded = output_shares[0].get_encoded().unwrap();
+        got.accumulate_encoded(&encoded[..encoded.len() - 1])
+            .unwrap_err();
+        got.accumulate_encoded(&[encoded.clone(), encoded.clone()].concat())
+            .unwrap_err();
+
+        // Non-canonical encoding of the last element. The preceding elements must not be added.
+        let mut encoded = encoded;
+        let len = encoded.len();
+        encoded[len - Field64::ENCODED_SIZE..].fill(0xff);
+        got.accumulate_encoded(&encoded).unwrap_err();
+        assert_eq!(got, want);
+    }
 }
 
+#[cfg(feature = "crypto-dependencies")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crypto-dependencies")))]
+pub mod batch;
 #[cfg(feature = "test-util")]
 #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
 pub mod dummy;
 #[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "crypto-dependencies", feature = "experimental")))
+)]
+pub mod heavy_hitters;
+#[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
 pub mod mastic;
 #[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
 #[cfg_attr(
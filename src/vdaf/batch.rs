@@ -0,0 +1,569 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Bookkeeping for aggregate shares of a batch of reports.
+//!
+//! Besides its aggregate share, an Aggregator typically needs to track some metadata about the
+//! reports it aggregated into a batch: the number of reports, which is passed to
+//! [`Collector::unshard`]; a checksum of the reports' nonces, which lets the Collector detect
+//! Aggregators that disagree on which reports are in the batch; and the range of report
+//! timestamps. [`BatchAggregateShare`] carries all of these together with the aggregate share and
+//! keeps them consistent as output shares are accumulated and aggregate shares are merged.
+//!
+//! The checksum is the XOR of the SHA-256 hashes of each report's nonce, as in [DAP]. This makes it
+//! independent of the order in which reports are aggregated.
+//!
+//! [DAP]: https://datatracker.ietf.org/doc/draft-ietf-ppm-dap/
+
+use crate::{
+    codec::{CodecError, Decode, Encode, ParameterizedDecode},
+    vdaf::{Aggregatable, Collector, RetractableAggregatable, Vdaf, VdafError},
+};
+use sha2::{Digest, Sha256};
+use std::{fmt::Debug, io::Cursor};
+
+/// The length of a [`BatchAggregateShare`]'s checksum in bytes.
+pub const CHECKSUM_LEN: usize = 32;
+
+/// The range of timestamps of the reports in a batch. Both ends are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeInterval {
+    /// The earliest report timestamp.
+    pub start: u64,
+
+    /// The latest report timestamp.
+    pub end: u64,
+}
+
+impl TimeInterval {
+    /// Extends the interval so that it contains `other`.
+    fn merge(&mut self, other: &Self) {
+        self.start = self.start.min(other.start);
+        self.end = self.end.max(other.end);
+    }
+}
+
+impl Encode for TimeInterval {
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        self.start.encode(bytes)?;
+        self.end.encode(bytes)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(16)
+    }
+}
+
+impl Decode for TimeInterval {
+    fn decode(bytes: &mut Cursor<&[u8]>) -> Result<Self, CodecError> {
+        let start = u64::decode(bytes)?;
+        let end = u64::decode(bytes)?;
+        if start > end {
+            return Err(CodecError::UnexpectedValue);
+        }
+        Ok(Self { start, end })
+    }
+}
+
+/// An aggregate share together with the number of reports aggregated into it, an order-independent
+/// checksum of their nonces, and the range of their timestamps.
+#[derive(Clone, Debug)]
+pub struct BatchAggregateShare<V: Vdaf> {
+    aggregate_share: V::AggregateShare,
+    report_count: u64,
+    checksum: [u8; CHECKSUM_LEN],
+    interval: Option<TimeInterval>,
+}
+
+impl<V: Vdaf> BatchAggregateShare<V> {
+    /// Wraps an empty aggregate share, as returned by
+    /// [`Aggregator::aggregate_init`](crate::vdaf::Aggregator::aggregate_init).
+    pub fn new(aggregate_share: V::AggregateShare) -> Self {
+        Self {
+            aggregate_share,
+            report_count: 0,
+            checksum: [0; CHECKSUM_LEN],
+            interval: None,
+        }
+    }
+
+    /// Returns the aggregate share.
+    pub fn aggregate_share(&self) -> &V::AggregateShare {
+        &self.aggregate_share
+    }
+
+    /// Consumes `self` and returns the aggregate share.
+    pub fn into_aggregate_share(self) -> V::AggregateShare {
+        self.aggregate_share
+    }
+
+    /// Returns the number of reports aggregated into this share.
+    pub fn report_count(&self) -> u64 {
+        self.report_count
+    }
+
+    /// Returns the XOR of the SHA-256 hashes of the nonces of the reports aggregated into this
+    /// share.
+    pub fn checksum(&self) -> &[u8; CHECKSUM_LEN] {
+        &self.checksum
+    }
+
+    /// Returns the range of timestamps of the reports aggregated into this share, or `None` if no
+    /// report has been aggregated.
+    pub fn interval(&self) -> Option<TimeInterval> {
+        self.interval
+    }
+
+    /// Adds the output share of the report with the given nonce and timestamp.
+    ///
+    /// Note that this can't detect if the same report is accumulated twice; doing so cancels out
+    /// its contribution to the checksum.
+    pub fn accumulate<const NONCE_SIZE: usize>(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        time: u64,
+        output_share: &V::OutputShare,
+    ) -> Result<(), VdafError> {
+        let report_count = self
+            .report_count
+            .checked_add(1)
+            .ok_or_else(|| VdafError::Uncategorized("report count overflows u64".to_string()))?;
+        self.aggregate_share.accumulate(output_share)?;
+        self.report_count = report_count;
+        xor_assign(&mut self.checksum, &Sha256::digest(nonce).into());
+        self.merge_interval(&TimeInterval {
+            start: time,
+            end: time,
+        });
+        Ok(())
+    }
+
+    /// Merges another share of a disjoint set of reports into this one.
+    pub fn merge(&mut self, other: &Self) -> Result<(), VdafError> {
+        let report_count = self
+            .report_count
+            .checked_add(other.report_count)
+            .ok_or_else(|| VdafError::Uncategorized("report count overflows u64".to_string()))?;
+        self.aggregate_share.merge(&other.aggregate_share)?;
+        self.report_count = report_count;
+        xor_assign(&mut self.checksum, &other.checksum);
+        if let Some(interval) = other.interval.as_ref() {
+            self.merge_interval(interval);
+        }
+        Ok(())
+    }
+
+    /// Checks that `peer`, another Aggregator's share of the same batch, covers the same reports as
+    /// this one. That is, that the report counts, checksums, and time intervals match.
+    pub fn validate(&self, peer: &Self) -> Result<(), VdafError> {
+        if self.report_count != peer.report_count {
+            return Err(VdafError::Uncategorized(format!(
+                "report count mismatch: got {}; want {}",
+                peer.report_count, self.report_count
+            )));
+        }
+        if self.checksum != peer.checksum {
+            return Err(VdafError::Uncategorized("checksum mismatch".to_string()));
+        }
+        if self.interval != peer.interval {
+            return Err(VdafError::Uncategorized(format!(
+                "time interval mismatch: got {:?}; want {:?}",
+                peer.interval, self.interval
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validates the Aggregators' shares of a batch against one another and combines them into the
+    /// aggregate result. The number of measurements passed to [`Collector::unshard`] is the common
+    /// report count.
+    pub fn unshard<M: IntoIterator<Item = Self>>(
+        vdaf: &V,
+        agg_param: &V::AggregationParam,
+        batch_agg_shares: M,
+    ) -> Result<V::AggregateResult, VdafError>
+    where
+        V: Collector,
+    {
+        let batch_agg_shares = batch_agg_shares.into_iter().collect::<Vec<_>>();
+        if batch_agg_shares.len() != vdaf.num_aggregators() {
+            return Err(VdafError::Uncategorized(format!(
+                "unexpected number of aggregate shares: got {}; want {}",
+                batch_agg_shares.len(),
+                vdaf.num_aggregators()
+            )));
+        }
+        // The length check ensures there is at least one share, since every VDAF has at least one
+        // Aggregator.
+        let leader = &batch_agg_shares[0];
+        for peer in batch_agg_shares[1..].iter() {
+            leader.validate(peer)?;
+        }
+        let num_measurements = usize::try_from(leader.report_count)
+            .map_err(|_| VdafError::Uncategorized("report count overflows usize".to_string()))?;
+
+        vdaf.unshard(
+            agg_param,
+            batch_agg_shares
+                .into_iter()
+                .map(BatchAggregateShare::into_aggregate_share),
+            num_measurements,
+        )
+    }
+
+    fn merge_interval(&mut self, interval: &TimeInterval) {
+        match self.interval.as_mut() {
+            Some(current) => current.merge(interval),
+            None => self.interval = Some(*interval),
+        }
+    }
+
+    /// Clears the time interval if no reports remain, so that an empty share is indistinguishable
+    /// from a new one.
+    fn clear_interval_if_empty(&mut self) {
+        if self.report_count == 0 {
+            self.interval = None;
+        }
+    }
+}
+
+impl<V: Vdaf> BatchAggregateShare<V>
+where
+    V::AggregateShare: RetractableAggregatable,
+{
+    /// Removes the output share of the report with the given nonce, which was previously
+    /// accumulated into this share. This is the inverse of [`Self::accumulate`], except that the
+    /// time interval is left unchanged: it still contains the timestamps of all reports that were
+    /// ever aggregated, unless the share becomes empty, in which case it is cleared.
+    pub fn retract<const NONCE_SIZE: usize>(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        output_share: &V::OutputShare,
+    ) -> Result<(), VdafError> {
+        let report_count = self.report_count.checked_sub(1).ok_or_else(|| {
+            VdafError::Uncategorized("cannot retract a report from an empty share".to_string())
+        })?;
+        self.aggregate_share.retract(output_share)?;
+        self.report_count = report_count;
+        xor_assign(&mut self.checksum, &Sha256::digest(nonce).into());
+        self.clear_interval_if_empty();
+        Ok(())
+    }
+
+    /// Removes another share of a subset of the reports in this one. This is the inverse of
+    /// [`Self::merge`], except that the time interval is left unchanged unless the share becomes
+    /// empty.
+    pub fn subtract(&mut self, other: &Self) -> Result<(), VdafError> {
+        let report_count = self
+            .report_count
+            .checked_sub(other.report_count)
+            .ok_or_else(|| {
+                VdafError::Uncategorized(format!(
+                    "cannot subtract {} reports from a share of {} reports",
+                    other.report_count, self.report_count
+                ))
+            })?;
+        self.aggregate_share.subtract(&other.aggregate_share)?;
+        self.report_count = report_count;
+        xor_assign(&mut self.checksum, &other.checksum);
+        self.clear_interval_if_empty();
+        Ok(())
+    }
+}
+
+fn xor_assign(acc: &mut [u8; CHECKSUM_LEN], other: &[u8; CHECKSUM_LEN]) {
+    for (x, y) in acc.iter_mut().zip(other) {
+        *x ^= y;
+    }
+}
+
+impl<V: Vdaf> PartialEq for BatchAggregateShare<V>
+where
+    V::AggregateShare: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.report_count == other.report_count
+            && self.checksum == other.checksum
+            && self.interval == other.interval
+            && self.aggregate_share == other.aggregate_share
+    }
+}
+
+impl<V: Vdaf> Eq for BatchAggregateShare<V> where V::AggregateShare: Eq {}
+
+impl<V: Vdaf> Encode for BatchAggregateShare<V> {
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        self.report_count.encode(bytes)?;
+        bytes.extend_from_slice(&self.checksum);
+        match self.interval.as_ref() {
+            Some(interval) => {
+                1u8.encode(bytes)?;
+                interval.encode(bytes)?;
+            }
+            None => 0u8.encode(bytes)?,
+        }
+        self.aggregate_share.encode(bytes)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let interval_len = match self.interval.as_ref() {
+            Some(interval) => interval.encoded_len()?,
+            None => 0,
+        };
+        Some(8 + CHECKSUM_LEN + 1 + interval_len + self.aggregate_share.encoded_len()?)
+    }
+}
+
+impl<'a, V: Vdaf> ParameterizedDecode<(&'a V, &'a V::AggregationParam)> for BatchAggregateShare<V> {
+    fn decode_with_param(
+        decoding_parameter: &(&'a V, &'a V::AggregationParam),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        let report_count = u64::decode(bytes)?;
+        let mut checksum = [0; CHECKSUM_LEN];
+        for byte in checksum.iter_mut() {
+            *byte = u8::decode(bytes)?;
+        }
+        let interval = match u8::decode(bytes)? {
+            0 => None,
+            1 => Some(TimeInterval::decode(bytes)?),
+            _ => return Err(CodecError::UnexpectedValue),
+        };
+        // An empty share has no interval and vice versa.
+        if (report_count == 0) != interval.is_none() {
+            return Err(CodecError::UnexpectedValue);
+        }
+        let aggregate_share = V::AggregateShare::decode_with_param(decoding_parameter, bytes)?;
+
+        Ok(Self {
+            aggregate_share,
+            report_count,
+            checksum,
+            interval,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vdaf::{
+        prio3::{Prio3, Prio3Count, Prio3Histogram},
+        test_utils::run_vdaf_prepare,
+        Aggregator, Client,
+    };
+    use rand::random;
+
+    const CTX_STR: &[u8] = b"batch ctx";
+
+    /// Shards and prepares each measurement, returning each report's nonce, timestamp, and output
+    /// shares.
+    #[allow(clippy::type_complexity)]
+    fn prepare_reports(
+        vdaf: &Prio3Histogram,
+        measurements: &[usize],
+    ) -> Vec<([u8; 16], u64, Vec<<Prio3Histogram as Vdaf>::OutputShare>)> {
+        let verify_key = random();
+        measurements
+            .iter()
+            .zip(1000..)
+            .map(|(measurement, time)| {
+                let nonce = random();
+                let (public_share, input_shares) =
+                    vdaf.shard(CTX_STR, measurement, &nonce).unwrap();
+                let output_shares = run_vdaf_prepare(
+                    vdaf,
+                    &verify_key,
+                    CTX_STR,
+                    &(),
+                    &nonce,
+                    public_share,
+                    input_shares,
+                )
+                .unwrap();
+                (nonce, time, output_shares)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accumulate_merge_unshard() {
+        let vdaf = Prio3::new_histogram(2, 4, 2).unwrap();
+        let reports = prepare_reports(&vdaf, &[0, 1, 3, 3, 2, 3]);
+
+        // The leader accumulates all reports in order.
+        let mut leader = BatchAggregateShare::<Prio3Histogram>::new(vdaf.aggregate_init(&()));
+        for (nonce, time, output_shares) in reports.iter() {
+            leader.accumulate(nonce, *time, &output_shares[0]).unwrap();
+        }
+
+        // The helper accumulates them in reverse order, across two shares that are then merged.
+        let mut helper = BatchAggregateShare::<Prio3Histogram>::new(vdaf.aggregate_init(&()));
+        let mut other = BatchAggregateShare::<Prio3Histogram>::new(vdaf.aggregate_init(&()));
+        for (i, (nonce, time, output_shares)) in reports.iter().rev().enumerate() {
+            let share = if i % 2 == 0 { &mut helper } else { &mut other };
+            share.accumulate(nonce, *time, &output_shares[1]).unwrap();
+        }
+        helper.merge(&other).unwrap();
+
+        assert_eq!(leader.report_count(), 6);
+        assert_eq!(helper.report_count(), 6);
+        assert_eq!(leader.checksum(), helper.checksum());
+        assert_eq!(
+            leader.interval(),
+            Some(TimeInterval {
+                start: 1000,
+                end: 1005
+            })
+        );
+        leader.validate(&helper).unwrap();
+
+        assert_eq!(
+            BatchAggregateShare::unshard(&vdaf, &(), [leader.clone(), helper.clone()]).unwrap(),
+            vec![1, 1, 1, 3]
+        );
+
+        // Dropping a report from one of the shares is detected.
+        let mut short_helper = BatchAggregateShare::<Prio3Histogram>::new(vdaf.aggregate_init(&()));
+        for (nonce, time, output_shares) in reports[1..].iter() {
+            short_helper
+                .accumulate(nonce, *time, &output_shares[1])
+                .unwrap();
+        }
+        leader.validate(&short_helper).unwrap_err();
+        BatchAggregateShare::unshard(&vdaf, &(), [leader.clone(), short_helper]).unwrap_err();
+
+        // Aggregators that disagree on the last report aggregated into the batch are detected, even
+        // if the report counts and time intervals match. Here the leader aggregates a report twice
+        // while the helper aggregates a different report.
+        let mut swapped_helper = helper.clone();
+        let (_, time, output_shares) = &reports[0];
+        let (extra_nonce, _, extra_output_shares) = &prepare_reports(&vdaf, &[0])[0];
+        swapped_helper
+            .accumulate(extra_nonce, *time, &extra_output_shares[1])
+            .unwrap();
+        let mut swapped_leader = leader.clone();
+        swapped_leader
+            .accumulate(&reports[0].0, *time, &output_shares[0])
+            .unwrap();
+        assert_eq!(swapped_leader.report_count(), swapped_helper.report_count());
+        swapped_leader.validate(&swapped_helper).unwrap_err();
+
+        // The wrong number of shares is rejected.
+        BatchAggregateShare::unshard(&vdaf, &(), [leader]).unwrap_err();
+    }
+
+    #[test]
+    fn retract_subtract() {
+        let vdaf = Prio3::new_histogram(2, 4, 2).unwrap();
+        let reports = prepare_reports(&vdaf, &[0, 1, 3, 2]);
+        let new_share = || BatchAggregateShare::<Prio3Histogram>::new(vdaf.aggregate_init(&()));
+
+        let mut want = new_share();
+        for (nonce, time, output_shares) in reports[..3].iter() {
+            want.accumulate(nonce, *time, &output_shares[0]).unwrap();
+        }
+        let mut got = want.clone();
+        let (nonce, time, output_shares) = &reports[3];
+        got.accumulate(nonce, *time, &output_shares[0]).unwrap();
+        assert_ne!(got, want);
+
+        // Retracting the last report restores everything but the interval.
+        got.retract(nonce, &output_shares[0]).unwrap();
+        assert_eq!(got.aggregate_share(), want.aggregate_share());
+        assert_eq!(got.report_count(), want.report_count());
+        assert_eq!(got.checksum(), want.checksum());
+        assert_eq!(
+            got.interval(),
+            Some(TimeInterval {
+                start: 1000,
+                end: 1003
+            })
+        );
+
+        // Subtracting a merged share restores the original.
+        let mut other = new_share();
+        for (nonce, time, output_shares) in reports[..2].iter() {
+            other.accumulate(nonce, *time, &output_shares[0]).unwrap();
+        }
+        let mut got = want.clone();
+        got.merge(&other).unwrap();
+        got.subtract(&other).unwrap();
+        assert_eq!(got, want);
+
+        // Removing every report leaves an empty share with no interval, which round-trips through
+        // its encoding.
+        let mut got = want.clone();
+        for (nonce, _, output_shares) in reports[..3].iter() {
+            got.retract(nonce, &output_shares[0]).unwrap();
+        }
+        assert_eq!(got, new_share());
+        let mut other = want.clone();
+        other.subtract(&want).unwrap();
+        assert_eq!(other, new_share());
+        for share in [got, other] {
+            let encoded = share.get_encoded().unwrap();
+            assert_eq!(
+                BatchAggregateShare::get_decoded_with_param(&(&vdaf, &()), &encoded).unwrap(),
+                share
+            );
+        }
+
+        // Removing more reports than there are is an error.
+        let mut empty = new_share();
+        empty.retract(nonce, &output_shares[0]).unwrap_err();
+        empty.subtract(&want).unwrap_err();
+        assert_eq!(empty, new_share());
+    }
+
+    #[test]
+    fn roundtrip_encoding() {
+        let vdaf = Prio3::new_count(2).unwrap();
+        let verify_key = random();
+        let mut share = BatchAggregateShare::<Prio3Count>::new(vdaf.aggregate_init(&()));
+
+        for time in [7, 3, 5] {
+            let encoded = share.get_encoded().unwrap();
+            assert_eq!(encoded.len(), share.encoded_len().unwrap());
+            let decoded =
+                BatchAggregateShare::get_decoded_with_param(&(&vdaf, &()), &encoded).unwrap();
+            assert_eq!(share, decoded);
+
+            let nonce = random();
+            let (public_share, input_shares) = vdaf.shard(CTX_STR, &true, &nonce).unwrap();
+            let output_shares = run_vdaf_prepare(
+                &vdaf,
+                &verify_key,
+                CTX_STR,
+                &(),
+                &nonce,
+                public_share,
+                input_shares,
+            )
+            .unwrap();
+            share.accumulate(&nonce, time, &output_shares[0]).unwrap();
+        }
+        assert_eq!(share.interval(), Some(TimeInterval { start: 3, end: 7 }));
+
+        let encoded = share.get_encoded().unwrap();
+        assert_eq!(
+            BatchAggregateShare::get_decoded_with_param(&(&vdaf, &()), &encoded).unwrap(),
+            share
+        );
+
+        // An invalid interval flag.
+        let mut bad = encoded.clone();
+        bad[8 + CHECKSUM_LEN] = 2;
+        BatchAggregateShare::<Prio3Count>::get_decoded_with_param(&(&vdaf, &()), &bad).unwrap_err();
+
+        // An empty share with an interval.
+        let mut bad = encoded.clone();
+        bad[..8].copy_from_slice(&0u64.to_be_bytes());
+        BatchAggregateShare::<Prio3Count>::get_decoded_with_param(&(&vdaf, &()), &bad).unwrap_err();
+
+        // An interval that ends before it starts.
+        let mut bad = encoded;
+        let start = 8 + CHECKSUM_LEN + 1;
+        bad[start..start + 8].copy_from_slice(&8u64.to_be_bytes());
+        BatchAggregateShare::<Prio3Count>::get_decoded_with_param(&(&vdaf, &()), &bad).unwrap_err();
+    }
+}
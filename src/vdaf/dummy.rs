@@ -310,6 +310,26 @@ impl Aggregatable for AggregateShare {
     }
 }
 
+impl vdaf::EncodedAggregatable for AggregateShare {
+    fn accumulate_encoded(&mut self, encoded_out_share: &[u8]) -> Result<(), VdafError> {
+        let out_share = OutputShare::get_decoded(encoded_out_share)
+            .map_err(|e| VdafError::Uncategorized(format!("invalid output share: {e}")))?;
+        self.accumulate(&out_share)
+    }
+}
+
+impl vdaf::RetractableAggregatable for AggregateShare {
+    fn subtract(&mut self, other: &Self) -> Result<(), VdafError> {
+        self.0 = (self.0 + MODULUS - other.0 % MODULUS) % MODULUS;
+        Ok(())
+    }
+
+    fn retract(&mut self, out_share: &Self::OutputShare) -> Result<(), VdafError> {
+        self.0 = (self.0 + MODULUS - out_share.0 % MODULUS) % MODULUS;
+        Ok(())
+    }
+}
+
 impl From<OutputShare> for AggregateShare {
     fn from(out_share: OutputShare) -> Self {
         Self(out_share.0)
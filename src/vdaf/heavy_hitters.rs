@@ -0,0 +1,437 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Collector-side driver for computing heavy hitters with [`Poplar1`] or [`Mastic`].
+//!
+//! Both VDAFs are evaluated one level of the prefix tree at a time. For each level, the Collector
+//! picks a set of candidate prefixes, the Aggregators aggregate their reports using those prefixes
+//! as the aggregation parameter, and the Collector uses the unsharded counts to pick the
+//! candidates for the next level. [`HeavyHitters`] implements this loop: it emits the aggregation
+//! parameter for each level, consumes the resulting aggregate shares, and returns the heavy
+//! hitters once the last level has been evaluated.
+
+use crate::{
+    flp::Type,
+    idpf::IdpfInput,
+    vdaf::{
+        mastic::{Mastic, MasticAggregationParam},
+        poplar1::{Poplar1, Poplar1AggregationParam},
+        xof::Xof,
+        Aggregator, Collector, VdafError,
+    },
+};
+use num_rational::Ratio;
+use std::cmp::Reverse;
+
+/// A VDAF that can be used to compute heavy hitters.
+///
+/// The aggregate result is the count for each candidate prefix in the aggregation parameter, in
+/// the order in which the prefixes appear in the aggregation parameter.
+pub trait HeavyHittersVdaf: Collector<AggregateResult = Vec<u64>> {
+    /// Returns the bit length of each measurement.
+    fn bits(&self) -> usize;
+
+    /// Construct the aggregation parameter for the given candidate prefixes. `prev` contains the
+    /// aggregation parameters used for the previous levels, from least to most recently used.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the prefixes are not a valid set of candidate prefixes, or if the resulting
+    /// aggregation parameter may not be used after `prev`.
+    fn agg_param_for_prefixes(
+        &self,
+        prefixes: Vec<IdpfInput>,
+        prev: &[Self::AggregationParam],
+    ) -> Result<Self::AggregationParam, VdafError>;
+}
+
+impl<P: Xof<SEED_SIZE>, const SEED_SIZE: usize> HeavyHittersVdaf for Poplar1<P, SEED_SIZE> {
+    fn bits(&self) -> usize {
+        self.bits()
+    }
+
+    fn agg_param_for_prefixes(
+        &self,
+        prefixes: Vec<IdpfInput>,
+        prev: &[Poplar1AggregationParam],
+    ) -> Result<Poplar1AggregationParam, VdafError> {
+        let agg_param = Poplar1AggregationParam::try_from_prefixes(prefixes)?;
+        if !<Self as Aggregator<SEED_SIZE, 16>>::is_agg_param_valid(&agg_param, prev) {
+            return Err(VdafError::Uncategorized(
+                "invalid aggregation parameter for the next level".into(),
+            ));
+        }
+        Ok(agg_param)
+    }
+}
+
+impl<T: Type<AggregateResult = u64>> HeavyHittersVdaf for Mastic<T> {
+    fn bits(&self) -> usize {
+        self.bits()
+    }
+
+    fn agg_param_for_prefixes(
+        &self,
+        prefixes: Vec<IdpfInput>,
+        prev: &[MasticAggregationParam],
+    ) -> Result<MasticAggregationParam, VdafError> {
+        // The weight is checked the first time the reports are aggregated.
+        let agg_param = MasticAggregationParam::new(prefixes, prev.is_empty())?;
+        if !Self::is_agg_param_valid(&agg_param, prev) {
+            return Err(VdafError::Uncategorized(
+                "invalid aggregation parameter for the next level".into(),
+            ));
+        }
+        Ok(agg_param)
+    }
+}
+
+/// The rule used to decide which prefixes are heavy hitters at each level.
+///
+/// Prefixes with a count of zero are never kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThresholdPolicy {
+    /// Keep the prefixes whose count is at least the given value.
+    Absolute(u64),
+    /// Keep the prefixes whose count is at least the given fraction of the number of measurements.
+    Relative(Ratio<u64>),
+    /// Keep the given number of prefixes with the highest counts. Ties are broken in favor of the
+    /// lexicographically smaller prefix.
+    TopK(usize),
+}
+
+/// State of a heavy hitters computation.
+#[derive(Clone, Debug)]
+pub struct HeavyHitters<V: HeavyHittersVdaf> {
+    bits: usize,
+    policy: ThresholdPolicy,
+    max_candidates: usize,
+    prev_agg_params: Vec<V::AggregationParam>,
+    state: HeavyHittersState<V::AggregationParam>,
+}
+
+#[derive(Clone, Debug)]
+enum HeavyHittersState<A> {
+    /// Waiting for the aggregate shares for `agg_param`, whose candidate prefixes are `prefixes`.
+    Collecting {
+        prefixes: Vec<IdpfInput>,
+        agg_param: A,
+    },
+    /// The last level has been evaluated, or no prefix was kept at some level.
+    Finished(Vec<(IdpfInput, u64)>),
+}
+
+impl<V: HeavyHittersVdaf> HeavyHitters<V> {
+    /// Start a heavy hitters computation. At each level, the prefixes that are kept according to
+    /// `policy` are extended by one bit to obtain the candidate prefixes for the next level. If
+    /// there would be more than `max_candidates` candidates, only the prefixes with the highest
+    /// counts are extended, with ties broken in favor of the lexicographically smaller prefix.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `max_candidates` is less than 2, if `policy` keeps no prefixes, or if `vdaf` has a
+    /// bit length of 0.
+    pub fn new(
+        vdaf: &V,
+        policy: ThresholdPolicy,
+        max_candidates: usize,
+    ) -> Result<Self, VdafError> {
+        if max_candidates < 2 {
+            return Err(VdafError::Uncategorized(
+                "at least two candidate prefixes are required".into(),
+            ));
+        }
+        if policy == ThresholdPolicy::TopK(0) {
+            return Err(VdafError::Uncategorized(
+                "top-k policy must keep at least one prefix".into(),
+            ));
+        }
+        let bits = vdaf.bits();
+        if bits == 0 {
+            return Err(VdafError::Uncategorized(
+                "measurements must have at least one bit".into(),
+            ));
+        }
+
+        let prefixes = vec![
+            IdpfInput::from_bools(&[false]),
+            IdpfInput::from_bools(&[true]),
+        ];
+        let agg_param = vdaf.agg_param_for_prefixes(prefixes.clone(), &[])?;
+        Ok(Self {
+            bits,
+            policy,
+            max_candidates,
+            prev_agg_params: Vec::new(),
+            state: HeavyHittersState::Collecting {
+                prefixes,
+                agg_param,
+            },
+        })
+    }
+
+    /// Returns the aggregation parameter with which the Aggregators should aggregate the reports
+    /// next, or `None` if the computation is finished.
+    pub fn next_agg_param(&self) -> Option<&V::AggregationParam> {
+        match &self.state {
+            HeavyHittersState::Collecting { agg_param, .. } => Some(agg_param),
+            HeavyHittersState::Finished(_) => None,
+        }
+    }
+
+    /// Unshard the aggregate shares computed with [`Self::next_agg_param`] and advance to the next
+    /// level. `num_measurements` is the number of reports that were aggregated.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the computation is already finished, or if the aggregate shares can't be
+    /// unsharded.
+    pub fn unshard<M: IntoIterator<Item = V::AggregateShare>>(
+        &mut self,
+        vdaf: &V,
+        agg_shares: M,
+        num_measurements: usize,
+    ) -> Result<(), VdafError> {
+        let HeavyHittersState::Collecting {
+            prefixes,
+            agg_param,
+        } = &self.state
+        else {
+            return Err(VdafError::Uncategorized(
+                "heavy hitters computation is finished".into(),
+            ));
+        };
+
+        let counts = vdaf.unshard(agg_param, agg_shares, num_measurements)?;
+        if counts.len() != prefixes.len() {
+            return Err(VdafError::Uncategorized(format!(
+                "expected {} prefix counts, got {}",
+                prefixes.len(),
+                counts.len()
+            )));
+        }
+
+        let mut kept = self.apply_policy(prefixes.iter().cloned().zip(counts), num_measurements);
+        let level = prefixes[0].len();
+        if kept.is_empty() || level == self.bits {
+            self.state = HeavyHittersState::Finished(kept);
+            return Ok(());
+        }
+
+        // Only extend as many prefixes as fit in the candidate budget.
+        if kept.len() > self.max_candidates / 2 {
+            kept.sort_by(|(a, a_count), (b, b_count)| {
+                Reverse(a_count).cmp(&Reverse(b_count)).then(a.cmp(b))
+            });
+            kept.truncate(self.max_candidates / 2);
+            kept.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        let next_prefixes = kept
+            .iter()
+            .flat_map(|(prefix, _)| {
+                [
+                    prefix.clone_with_suffix(&[false]),
+                    prefix.clone_with_suffix(&[true]),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        self.prev_agg_params.push(agg_param.clone());
+        let agg_param =
+            vdaf.agg_param_for_prefixes(next_prefixes.clone(), &self.prev_agg_params)?;
+        self.state = HeavyHittersState::Collecting {
+            prefixes: next_prefixes,
+            agg_param,
+        };
+        Ok(())
+    }
+
+    /// Returns the heavy hitters and their counts in lexicographic order, or `None` if the
+    /// computation is not finished yet.
+    pub fn heavy_hitters(&self) -> Option<&[(IdpfInput, u64)]> {
+        match &self.state {
+            HeavyHittersState::Collecting { .. } => None,
+            HeavyHittersState::Finished(heavy_hitters) => Some(heavy_hitters),
+        }
+    }
+
+    /// Returns the prefixes that are kept according to the threshold policy, in lexicographic
+    /// order.
+    fn apply_policy(
+        &self,
+        counts: impl Iterator<Item = (IdpfInput, u64)>,
+        num_measurements: usize,
+    ) -> Vec<(IdpfInput, u64)> {
+        let counts = counts.filter(|(_, count)| *count > 0);
+        match self.policy {
+            ThresholdPolicy::Absolute(threshold) => {
+                counts.filter(|(_, count)| *count >= threshold).collect()
+            }
+            ThresholdPolicy::Relative(fraction) => counts
+                .filter(|(_, count)| {
+                    u128::from(*count) * u128::from(*fraction.denom())
+                        >= u128::from(*fraction.numer()) * num_measurements as u128
+                })
+                .collect(),
+            ThresholdPolicy::TopK(k) => {
+                let mut counts = counts.collect::<Vec<_>>();
+                counts.sort_by(|(a, a_count), (b, b_count)| {
+                    Reverse(a_count).cmp(&Reverse(b_count)).then(a.cmp(b))
+                });
+                counts.truncate(k);
+                counts.sort_by(|(a, _), (b, _)| a.cmp(b));
+                counts
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vdaf::{test_utils::run_vdaf_prepare, Aggregatable, Client};
+    use rand::random;
+
+    const CTX_STR: &[u8] = b"heavy hitters ctx";
+
+    /// Run a heavy hitters computation to completion and return the heavy hitters.
+    fn run_heavy_hitters<V>(
+        vdaf: &V,
+        measurements: Vec<V::Measurement>,
+        policy: ThresholdPolicy,
+        max_candidates: usize,
+    ) -> Vec<(IdpfInput, u64)>
+    where
+        V: HeavyHittersVdaf + Client<16> + Aggregator<32, 16>,
+    {
+        let verify_key = random();
+        let reports = measurements
+            .iter()
+            .map(|measurement| {
+                let nonce = random();
+                let (public_share, input_shares) =
+                    vdaf.shard(CTX_STR, measurement, &nonce).unwrap();
+                (nonce, public_share, input_shares)
+            })
+            .collect::<Vec<_>>();
+
+        let mut hh = HeavyHitters::new(vdaf, policy, max_candidates).unwrap();
+        let mut levels = 0;
+        while let Some(agg_param) = hh.next_agg_param().cloned() {
+            let mut agg_shares: Vec<Option<V::AggregateShare>> = vec![None, None];
+            for (nonce, public_share, input_shares) in &reports {
+                let out_shares = run_vdaf_prepare(
+                    vdaf,
+                    &verify_key,
+                    CTX_STR,
+                    &agg_param,
+                    nonce,
+                    public_share.clone(),
+                    input_shares.clone(),
+                )
+                .unwrap();
+                for (agg_share, out_share) in agg_shares.iter_mut().zip(out_shares) {
+                    match agg_share {
+                        Some(agg_share) => agg_share.accumulate(&out_share).unwrap(),
+                        None => *agg_share = Some(out_share.into()),
+                    }
+                }
+            }
+            hh.unshard(vdaf, agg_shares.into_iter().flatten(), reports.len())
+                .unwrap();
+            levels += 1;
+        }
+        assert!(levels <= vdaf.bits());
+
+        hh.heavy_hitters().unwrap().to_vec()
+    }
+
+    fn measurements(bytes: &[(&[u8], usize)]) -> Vec<IdpfInput> {
+        bytes
+            .iter()
+            .flat_map(|(bytes, count)| std::iter::repeat_n(IdpfInput::from_bytes(bytes), *count))
+            .collect()
+    }
+
+    #[test]
+    fn poplar1_absolute() {
+        let vdaf = Poplar1::new_turboshake128(8);
+        let measurements = measurements(&[(b"a", 3), (b"b", 2), (b"c", 1), (b"\xff", 3)]);
+        let got = run_heavy_hitters(&vdaf, measurements, ThresholdPolicy::Absolute(2), 8);
+        assert_eq!(
+            got,
+            [
+                (IdpfInput::from_bytes(b"a"), 3),
+                (IdpfInput::from_bytes(b"b"), 2),
+                (IdpfInput::from_bytes(b"\xff"), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn poplar1_relative() {
+        let vdaf = Poplar1::new_turboshake128(8);
+        let measurements = measurements(&[(b"a", 3), (b"b", 2), (b"c", 1), (b"\xff", 4)]);
+        let got = run_heavy_hitters(
+            &vdaf,
+            measurements,
+            ThresholdPolicy::Relative(Ratio::new(3, 10)),
+            8,
+        );
+        assert_eq!(
+            got,
+            [
+                (IdpfInput::from_bytes(b"a"), 3),
+                (IdpfInput::from_bytes(b"\xff"), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn mastic_top_k() {
+        let vdaf = Mastic::new_count(8).unwrap();
+        let measurements = measurements(&[(b"a", 4), (b"b", 1), (b"c", 1), (b"\xff", 3)])
+            .into_iter()
+            .map(|input| (input, true))
+            .collect();
+        let got = run_heavy_hitters(&vdaf, measurements, ThresholdPolicy::TopK(2), 8);
+        assert_eq!(
+            got,
+            [
+                (IdpfInput::from_bytes(b"a"), 4),
+                (IdpfInput::from_bytes(b"\xff"), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn max_candidates() {
+        // With room for only two candidates, only the prefix with the highest count is extended
+        // at each level.
+        let vdaf = Poplar1::new_turboshake128(8);
+        let measurements = measurements(&[(b"a", 3), (b"b", 2), (b"\xff", 2)]);
+        let got = run_heavy_hitters(&vdaf, measurements, ThresholdPolicy::Absolute(1), 2);
+        assert_eq!(got, [(IdpfInput::from_bytes(b"a"), 3)]);
+    }
+
+    #[test]
+    fn no_heavy_hitters() {
+        let vdaf = Poplar1::new_turboshake128(8);
+        let measurements = measurements(&[(b"a", 1), (b"b", 1)]);
+        let got = run_heavy_hitters(&vdaf, measurements, ThresholdPolicy::Absolute(3), 8);
+        assert!(got.is_empty());
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let vdaf = Poplar1::new_turboshake128(8);
+        HeavyHitters::new(&vdaf, ThresholdPolicy::Absolute(1), 1).unwrap_err();
+        HeavyHitters::new(&vdaf, ThresholdPolicy::TopK(0), 8).unwrap_err();
+
+        let mut hh = HeavyHitters::new(&vdaf, ThresholdPolicy::Absolute(1), 8).unwrap();
+        assert!(hh.heavy_hitters().is_none());
+        hh.unshard(&vdaf, [], 0).unwrap();
+        assert_eq!(hh.heavy_hitters(), Some(&[][..]));
+        assert!(hh.next_agg_param().is_none());
+        hh.unshard(&vdaf, [], 0).unwrap_err();
+    }
+}
@@ -7,13 +7,23 @@
 use crate::{
     bt::BinaryTree,
     codec::{CodecError, Decode, Encode, ParameterizedDecode},
-    field::{decode_fieldvec, Field64, FieldElement, FieldElementWithInteger},
-    flp::{types::Count, Type},
+    dp::{
+        distributions::{DiscreteGaussian, DiscreteGaussianDpStrategy, PureDpDiscreteLaplace},
+        DifferentialPrivacyBudget, DifferentialPrivacyStrategy,
+    },
+    field::{
+        decode_fieldvec, Field64, FieldElement, FieldElementWithInteger, NttFriendlyFieldElement,
+    },
+    flp::{
+        gadgets::{Mul, ParallelSumGadget},
+        types::{add_iid_noise_to_field_vec, sqrt_upper_bound, Count, Histogram, Sum, SumVec},
+        FlpError, Type,
+    },
     vdaf::{
         poplar1::{Poplar1, Poplar1AggregationParam},
         xof::{Seed, Xof},
-        Aggregatable, AggregateShare, Aggregator, Client, Collector, OutputShare,
-        PrepareTransition, Vdaf, VdafError,
+        Aggregatable, AggregateShare, Aggregator, AggregatorWithNoise, Client, Collector,
+        OutputShare, PrepareTransition, Vdaf, VdafError,
     },
     vidpf::{
         Vidpf, VidpfError, VidpfInput, VidpfKey, VidpfPublicShare, VidpfServerId, VidpfWeight,
@@ -23,7 +33,9 @@ use crate::{
 
 use szk::{Szk, SzkJointShare, SzkProofShare, SzkQueryShare, SzkQueryState};
 
-use rand::{rng, Rng};
+use num_bigint::{BigInt, BigUint, TryFromBigIntError};
+use num_rational::Ratio;
+use rand::{distr::Distribution, rng, CryptoRng, Rng, RngCore};
 use std::io::{Cursor, Read};
 use std::ops::BitAnd;
 use std::slice::from_ref;
@@ -748,6 +760,154 @@ impl<T: Type> Aggregator<SEED_SIZE, NONCE_SIZE> for Mastic<T> {
     }
 }
 
+/// Weight types for which [`Mastic`] can add noise to aggregate shares. This gives the largest
+/// norms of a single weight, from which the sensitivity of each prefix's counter and weight is
+/// derived.
+trait MasticNoiseWeight: Type {
+    /// The largest L1 norm of a single weight.
+    fn max_weight_l1_norm(&self) -> Result<BigUint, FlpError>;
+
+    /// The largest squared L2 norm of a single weight.
+    fn max_weight_l2_norm_squared(&self) -> Result<BigUint, FlpError>;
+}
+
+impl<F: NttFriendlyFieldElement> MasticNoiseWeight for Count<F> {
+    fn max_weight_l1_norm(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l1_norm()
+    }
+
+    fn max_weight_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l2_norm_squared()
+    }
+}
+
+impl<F> MasticNoiseWeight for Sum<F>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+{
+    fn max_weight_l1_norm(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l1_norm()
+    }
+
+    fn max_weight_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l2_norm_squared()
+    }
+}
+
+impl<F, S> MasticNoiseWeight for Histogram<F, S>
+where
+    F: NttFriendlyFieldElement,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn max_weight_l1_norm(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l1_norm()
+    }
+
+    fn max_weight_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l2_norm_squared()
+    }
+}
+
+impl<F, S> MasticNoiseWeight for SumVec<F, S>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    fn max_weight_l1_norm(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l1_norm()
+    }
+
+    fn max_weight_l2_norm_squared(&self) -> Result<BigUint, FlpError> {
+        self.max_output_l2_norm_squared()
+    }
+}
+
+impl<T> AggregatorWithNoise<SEED_SIZE, NONCE_SIZE, PureDpDiscreteLaplace> for Mastic<T>
+where
+    T: MasticNoiseWeight,
+    BigInt: From<<T::Field as FieldElementWithInteger>::Integer>,
+    <T::Field as FieldElementWithInteger>::Integer:
+        TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+{
+    fn add_noise_to_agg_share_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        agg_param: &MasticAggregationParam,
+        agg_share: &mut MasticAggregateShare<T::Field>,
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), VdafError> {
+        let sensitivity = l1_sensitivity(agg_param, self.szk.typ.max_weight_l1_norm()?);
+        add_noise_with_sensitivity(dp_strategy, sensitivity, agg_share, rng)
+    }
+}
+
+impl<T, B> AggregatorWithNoise<SEED_SIZE, NONCE_SIZE, DiscreteGaussianDpStrategy<B>> for Mastic<T>
+where
+    T: MasticNoiseWeight,
+    BigInt: From<<T::Field as FieldElementWithInteger>::Integer>,
+    <T::Field as FieldElementWithInteger>::Integer:
+        TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+{
+    fn add_noise_to_agg_share_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_param: &MasticAggregationParam,
+        agg_share: &mut MasticAggregateShare<T::Field>,
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), VdafError> {
+        let sensitivity = l2_sensitivity(agg_param, self.szk.typ.max_weight_l2_norm_squared()?);
+        add_noise_with_sensitivity(dp_strategy, sensitivity, agg_share, rng)
+    }
+}
+
+/// The global sensitivity of the aggregate share, using the L1 norm as a distance metric, and
+/// using the substitution-DP model. Each changed prefix has its counter changed by one and its
+/// weight changed by at most the largest L1 norm of a single weight, `max_weight_norm`.
+fn l1_sensitivity(agg_param: &MasticAggregationParam, max_weight_norm: BigUint) -> Ratio<BigUint> {
+    let num_changed = BigUint::from(agg_param.level_and_prefixes.max_changed_prefixes());
+    Ratio::from_integer(num_changed * (max_weight_norm + 1u8))
+}
+
+/// The global sensitivity of the aggregate share, using the L2 norm as a distance metric, and
+/// using the substitution-DP model. Each changed prefix has its counter changed by one and its
+/// weight changed by at most the square root of `max_weight_norm_squared`.
+fn l2_sensitivity(
+    agg_param: &MasticAggregationParam,
+    max_weight_norm_squared: BigUint,
+) -> Ratio<BigUint> {
+    let num_changed = BigUint::from(agg_param.level_and_prefixes.max_changed_prefixes());
+    sqrt_upper_bound(num_changed * (max_weight_norm_squared + 1u8))
+}
+
+/// Calibrate a distribution from `dp_strategy` to the given sensitivity, then add independent,
+/// identically-distributed noise from it to each counter and weight in `agg_share`.
+fn add_noise_with_sensitivity<F, St, R>(
+    dp_strategy: &St,
+    sensitivity: Ratio<BigUint>,
+    agg_share: &mut MasticAggregateShare<F>,
+    rng: &mut R,
+) -> Result<(), VdafError>
+where
+    F: NttFriendlyFieldElement,
+    BigInt: From<F::Integer>,
+    F::Integer: TryFrom<BigInt, Error = TryFromBigIntError<BigInt>>,
+    St: DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>>,
+    St::Distribution: Distribution<BigInt>,
+    R: Rng,
+{
+    let sampler = dp_strategy
+        .create_distribution(sensitivity)
+        .map_err(|e| VdafError::Other(Box::new(e)))?;
+    Ok(add_iid_noise_to_field_vec(&mut agg_share.0, rng, &sampler)?)
+}
+
 impl<T: Type> Collector for Mastic<T> {
     fn unshard<M: IntoIterator<Item = Self::AggregateShare>>(
         &self,
@@ -790,13 +950,72 @@ impl<T: Type> Collector for Mastic<T> {
     }
 }
 
+impl<T> Mastic<T>
+where
+    T: Type,
+    <T::Field as FieldElementWithInteger>::Integer: Into<u128>,
+{
+    /// Combines aggregate shares to which noise was added with [`AggregatorWithNoise`] into
+    /// signed aggregates. For each prefix, this returns the counter and the encoded weight.
+    ///
+    /// Noise may make the counter or an element of the weight negative, in which case it wraps
+    /// around the field modulus, so [`Collector::unshard`] would fail or decode a huge value. Here,
+    /// elements larger than half of the field modulus are taken to be negative instead. The weight
+    /// is not decoded, because decoding assumes that the aggregate is a sum of valid measurements,
+    /// and negative values are returned as is, rather than clamped to zero, so that estimates
+    /// derived from them remain unbiased.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the aggregate shares can't be combined.
+    pub fn unshard_noisy<M: IntoIterator<Item = AggregateShare<T::Field>>>(
+        &self,
+        agg_param: &MasticAggregationParam,
+        agg_shares: M,
+    ) -> Result<Vec<(i128, Vec<i128>)>, VdafError> {
+        let AggregateShare(agg) = agg_shares.into_iter().try_fold(
+            AggregateShare(vec![T::Field::zero(); self.agg_share_len(agg_param)]),
+            |mut agg, agg_share| {
+                agg.merge(&agg_share)?;
+                Result::<_, VdafError>::Ok(agg)
+            },
+        )?;
+
+        // The modulus of each field that Mastic supports is less than 2^128, so half of it fits in
+        // an `i128`.
+        let to_signed = |element: T::Field| -> i128 {
+            let value: u128 = <T::Field as FieldElementWithInteger>::Integer::from(element).into();
+            let negated: u128 =
+                <T::Field as FieldElementWithInteger>::Integer::from(-element).into();
+            if negated < value {
+                -(negated as i128)
+            } else {
+                value as i128
+            }
+        };
+
+        Ok(agg
+            .chunks(1 + self.szk.typ.output_len())
+            .map(|agg_for_prefix| {
+                (
+                    to_signed(agg_for_prefix[0]),
+                    agg_for_prefix[1..].iter().copied().map(to_signed).collect(),
+                )
+            })
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::field::{Field128, Field64};
     use crate::flp::gadgets::{Mul, ParallelSum};
     use crate::flp::types::{Count, Histogram, Sum, SumVec};
-    use crate::vdaf::test_utils::run_vdaf;
+    use crate::vdaf::{
+        test_utils::{run_vdaf, run_vdaf_prepare},
+        RetractableAggregatable,
+    };
     use rand::{rng, Rng};
 
     const CTX_STR: &[u8] = b"mastic ctx";
@@ -1030,6 +1249,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mastic_retract() {
+        let count = Count::<Field128>::new();
+        let mastic = Mastic::new(6, count, 32).unwrap();
+        let verify_key = rng().random();
+        let agg_param = MasticAggregationParam::new(
+            vec![
+                VidpfInput::from_bools(&[false]),
+                VidpfInput::from_bools(&[true]),
+            ],
+            true,
+        )
+        .unwrap();
+
+        let measurements = [
+            (VidpfInput::from_bytes(&[240u8, 0u8, 1u8, 4u8][..]), true),
+            (VidpfInput::from_bytes(&[112u8, 0u8, 1u8, 4u8][..]), true),
+            (VidpfInput::from_bytes(&[48u8, 0u8, 1u8, 4u8][..]), false),
+        ];
+        let output_shares = measurements
+            .iter()
+            .map(|measurement| {
+                let nonce = rng().random();
+                let (public_share, input_shares) =
+                    mastic.shard(CTX_STR, measurement, &nonce).unwrap();
+                run_vdaf_prepare(
+                    &mastic,
+                    &verify_key,
+                    CTX_STR,
+                    &agg_param,
+                    &nonce,
+                    public_share,
+                    input_shares,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // Retract the first report from each aggregate share.
+        let agg_shares = (0..2)
+            .map(|agg_id| {
+                let mut agg_share = mastic
+                    .aggregate(
+                        &agg_param,
+                        output_shares
+                            .iter()
+                            .map(|output_shares| output_shares[agg_id].clone()),
+                    )
+                    .unwrap();
+                agg_share.retract(&output_shares[0][agg_id]).unwrap();
+                assert_eq!(
+                    agg_share,
+                    mastic
+                        .aggregate(
+                            &agg_param,
+                            output_shares[1..]
+                                .iter()
+                                .map(|output_shares| output_shares[agg_id].clone()),
+                        )
+                        .unwrap()
+                );
+                agg_share
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            mastic.unshard(&agg_param, agg_shares, 2).unwrap(),
+            vec![1, 0]
+        );
+    }
+
     #[test]
     fn test_public_share_encoded_len() {
         let algorithm_id = 6;
@@ -1693,4 +1983,135 @@ mod tests {
             );
         }
     }
+
+    /// Returns `count` distinct prefixes of length `len`, in lexicographic order.
+    fn prefixes(len: usize, count: usize) -> Vec<VidpfInput> {
+        (0..count)
+            .map(|i| {
+                let bits = (0..len)
+                    .rev()
+                    .map(|j| (i >> j) & 1 == 1)
+                    .collect::<Vec<_>>();
+                VidpfInput::from_bools(&bits)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mastic_add_noise_huge_budget() {
+        use crate::dp::{
+            distributions::{PureDpDiscreteLaplace, ZCdpDiscreteGaussian},
+            DifferentialPrivacyStrategy, PureDpBudget, Rational, ZCdpBudget,
+        };
+
+        let laplace = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1000u16, 1).unwrap()).unwrap(),
+        );
+        let gaussian = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+            Rational::from_unsigned(1_000_000_000u32, 1).unwrap(),
+        ));
+        let agg_param = MasticAggregationParam::new(prefixes(2, 2), true).unwrap();
+
+        let mastic = Mastic::new_count(2).unwrap();
+        let mut agg_share_0 = AggregateShare(
+            [3, 3, 1, 1]
+                .into_iter()
+                .map(Field64::from)
+                .collect::<Vec<_>>(),
+        );
+        let mut agg_share_1 = AggregateShare(vec![Field64::zero(); 4]);
+        mastic
+            .add_noise_to_agg_share(&laplace, &agg_param, &mut agg_share_0, 4)
+            .unwrap();
+        mastic
+            .add_noise_to_agg_share(&gaussian, &agg_param, &mut agg_share_1, 4)
+            .unwrap();
+        assert_eq!(
+            mastic
+                .unshard(&agg_param, [agg_share_0, agg_share_1], 4)
+                .unwrap(),
+            [3, 1],
+        );
+
+        let mastic = Mastic::new(6, Sum::<Field128>::new(29).unwrap(), 2).unwrap();
+        let mut agg_share_0 = AggregateShare(
+            [2, 30, 0, 0]
+                .into_iter()
+                .map(Field128::from)
+                .collect::<Vec<_>>(),
+        );
+        let mut agg_share_1 = AggregateShare(vec![Field128::zero(); 4]);
+        mastic
+            .add_noise_to_agg_share(&laplace, &agg_param, &mut agg_share_0, 2)
+            .unwrap();
+        mastic
+            .add_noise_to_agg_share(&gaussian, &agg_param, &mut agg_share_1, 2)
+            .unwrap();
+        assert_eq!(
+            mastic
+                .unshard(&agg_param, [agg_share_0, agg_share_1], 2)
+                .unwrap(),
+            [30, 0],
+        );
+    }
+
+    #[test]
+    fn test_mastic_add_noise_variance() {
+        use crate::dp::{
+            distributions::PureDpDiscreteLaplace, noise_rng, DifferentialPrivacyStrategy,
+            PureDpBudget, Rational,
+        };
+        use crate::vdaf::xof::XofTurboShake128;
+
+        let laplace = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1u8, 1).unwrap()).unwrap(),
+        );
+        let mastic = Mastic::new_count(10).unwrap();
+        let num_prefixes = 1000;
+        let agg_param = MasticAggregationParam::new(prefixes(10, num_prefixes), true).unwrap();
+
+        // Two prefixes may change, each by one in the counter and one in the weight, so the
+        // sensitivity is four, and the noise is discrete Laplace with scale four.
+        let expected_variance = 2.0 * (-0.25f64).exp() / (1.0 - (-0.25f64).exp()).powi(2);
+
+        let mut agg_share = AggregateShare(vec![Field64::zero(); 2 * num_prefixes]);
+        let mut rng = noise_rng::<XofTurboShake128, 32>(&Seed::from_bytes([0; 32]), b"");
+        mastic
+            .add_noise_to_agg_share_with_rng(&laplace, &agg_param, &mut agg_share, 1, &mut rng)
+            .unwrap();
+
+        let variance = agg_share
+            .0
+            .iter()
+            .map(|&x| {
+                let noise = if u64::from(-x) < u64::from(x) {
+                    -(u64::from(-x) as f64)
+                } else {
+                    u64::from(x) as f64
+                };
+                noise * noise
+            })
+            .sum::<f64>()
+            / f64::from(2 * num_prefixes as u32);
+        assert!(
+            (variance - expected_variance).abs() < 0.2 * expected_variance,
+            "variance {variance}, expected {expected_variance}",
+        );
+    }
+
+    #[test]
+    fn test_mastic_unshard_noisy() {
+        let mastic = Mastic::new(6, Sum::<Field128>::new(29).unwrap(), 2).unwrap();
+        let agg_param = MasticAggregationParam::new(prefixes(1, 1), true).unwrap();
+        let agg_share = AggregateShare(vec![-Field128::one(), -Field128::from(5)]);
+        assert_eq!(
+            mastic
+                .unshard_noisy(&agg_param, [agg_share.clone()])
+                .unwrap(),
+            [(-1, vec![-5])],
+        );
+
+        // `unshard` does not interpret the counter as signed, and rejects it as out of range.
+        mastic.unshard(&agg_param, [agg_share], 1).unwrap_err();
+    }
 }
@@ -6,15 +6,26 @@
 
 use crate::{
     codec::{CodecError, Decode, Encode, ParameterizedDecode},
-    field::{decode_fieldvec, merge_vector, Field255, Field64, FieldElement},
+    dp::{
+        distributions::{DiscreteGaussian, DiscreteGaussianDpStrategy, PureDpDiscreteLaplace},
+        DifferentialPrivacyBudget, DifferentialPrivacyDistribution, DifferentialPrivacyStrategy,
+    },
+    field::{
+        decode_fieldvec, merge_encoded_vector, merge_vector, unmerge_vector, Field255, Field64,
+        FieldElement,
+    },
+    flp::types::{add_iid_noise_to_field_vec, sqrt_upper_bound},
     idpf::{Idpf, IdpfInput, IdpfOutputShare, IdpfPublicShare, IdpfValue, RingBufferCache},
     prng::Prng,
     vdaf::{
         xof::{Seed, Xof, XofTurboShake128},
-        Aggregatable, Aggregator, Client, Collector, PrepareTransition, Vdaf, VdafError, VERSION,
+        Aggregatable, Aggregator, AggregatorWithNoise, Client, Collector, EncodedAggregatable,
+        PrepareTransition, RetractableAggregatable, Vdaf, VdafError, VERSION,
     },
 };
-use rand::{rng, Rng, RngCore};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_rational::Ratio;
+use rand::{distr::Distribution, rng, CryptoRng, Rng, RngCore};
 use std::{
     collections::BTreeSet,
     convert::TryFrom,
@@ -43,6 +54,11 @@ impl<P, const SEED_SIZE: usize> Poplar1<P, SEED_SIZE> {
             phantom: PhantomData,
         }
     }
+
+    /// Returns the bit length of each measurement.
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
 }
 
 impl Poplar1<XofTurboShake128, 32> {
@@ -698,6 +714,37 @@ impl Aggregatable for Poplar1FieldVec {
     }
 }
 
+impl EncodedAggregatable for Poplar1FieldVec {
+    fn accumulate_encoded(&mut self, encoded_output_share: &[u8]) -> Result<(), VdafError> {
+        match self {
+            Self::Inner(ref mut left) => Ok(merge_encoded_vector(left, encoded_output_share)?),
+            Self::Leaf(ref mut left) => Ok(merge_encoded_vector(left, encoded_output_share)?),
+        }
+    }
+}
+
+impl RetractableAggregatable for Poplar1FieldVec {
+    fn subtract(&mut self, agg_share: &Self) -> Result<(), VdafError> {
+        match (self, agg_share) {
+            (Self::Inner(ref mut left), Self::Inner(right)) => Ok(unmerge_vector(left, right)?),
+            (Self::Leaf(ref mut left), Self::Leaf(right)) => Ok(unmerge_vector(left, right)?),
+            _ => Err(VdafError::Uncategorized(
+                "cannot subtract leaf nodes from inner nodes".into(),
+            )),
+        }
+    }
+
+    fn retract(&mut self, output_share: &Self) -> Result<(), VdafError> {
+        match (self, output_share) {
+            (Self::Inner(ref mut left), Self::Inner(right)) => Ok(unmerge_vector(left, right)?),
+            (Self::Leaf(ref mut left), Self::Leaf(right)) => Ok(unmerge_vector(left, right)?),
+            _ => Err(VdafError::Uncategorized(
+                "cannot retract leaf nodes from inner nodes".into(),
+            )),
+        }
+    }
+}
+
 /// Poplar1 aggregation parameter.
 ///
 /// This includes an indication of what level of the IDPF tree is being evaluated and the set of
@@ -762,6 +809,40 @@ impl Poplar1AggregationParam {
         Ok(Self { level, prefixes })
     }
 
+    /// Construct an aggregation parameter whose candidate prefixes are all the prefixes of length
+    /// `level + 1`, i.e., the full domain of the IDPF tree at `level`. Aggregators evaluate such
+    /// an aggregation parameter with [`Idpf::eval_full_domain`], which is faster than evaluating
+    /// each prefix individually.
+    ///
+    /// # Errors
+    ///
+    /// Fails if there would be more than 2^32 - 1 prefixes.
+    pub fn full_domain(level: usize) -> Result<Self, VdafError> {
+        if level >= 31 {
+            return Err(VdafError::Uncategorized("too many prefixes".into()));
+        }
+        let prefixes = (0..1usize << (level + 1))
+            .map(|i| {
+                IdpfInput::from_bools(
+                    &(0..=level)
+                        .map(|j| (i >> (level - j)) & 1 == 1)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        Self::try_from_prefixes(prefixes)
+    }
+
+    /// Returns true if the candidate prefixes are all the prefixes at this level of the IDPF
+    /// tree.
+    pub fn is_full_domain(&self) -> bool {
+        // The prefixes are unique and all have length `level + 1`, so there are 2^(level + 1) of
+        // them only if every prefix is a candidate.
+        1usize
+            .checked_shl(u32::from(self.level) + 1)
+            .is_some_and(|num_prefixes| num_prefixes == self.prefixes.len())
+    }
+
     /// Return the level of the IDPF tree.
     pub fn level(&self) -> usize {
         usize::from(self.level)
@@ -771,6 +852,13 @@ impl Poplar1AggregationParam {
     pub fn prefixes(&self) -> &[IdpfInput] {
         self.prefixes.as_ref()
     }
+
+    /// The number of prefix counts that may change when one measurement is substituted for
+    /// another. The prefixes are unique, so each measurement is counted under at most one of
+    /// them: in the worst case, one count is decremented and another is incremented.
+    pub(crate) fn max_changed_prefixes(&self) -> usize {
+        self.prefixes.len().min(2)
+    }
 }
 
 impl Encode for Poplar1AggregationParam {
@@ -1012,18 +1100,36 @@ impl<P: Xof<SEED_SIZE>, const SEED_SIZE: usize> Poplar1<P, SEED_SIZE> {
             corr_prng.get(), // c_share
         ];
 
-        let mut idpf_eval_cache = RingBufferCache::new(agg_param.prefixes.len());
         let idpf = Idpf::<Poplar1IdpfValue<Field64>, Poplar1IdpfValue<Field255>>::new((), ());
-        for prefix in agg_param.prefixes.iter() {
-            let share = Poplar1IdpfValue::<F>::from(idpf.eval(
+        let shares = if agg_param.is_full_domain() {
+            idpf.eval_full_domain(
                 agg_id,
                 public_share,
                 idpf_key,
-                prefix,
+                agg_param.level(),
                 ctx,
                 nonce,
-                &mut idpf_eval_cache,
-            )?);
+            )?
+        } else {
+            let mut idpf_eval_cache = RingBufferCache::new(agg_param.prefixes.len());
+            agg_param
+                .prefixes
+                .iter()
+                .map(|prefix| {
+                    idpf.eval(
+                        agg_id,
+                        public_share,
+                        idpf_key,
+                        prefix,
+                        ctx,
+                        nonce,
+                        &mut idpf_eval_cache,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        for share in shares {
+            let share = Poplar1IdpfValue::<F>::from(share);
 
             let r = verify_prng.get();
             let checked_data_share = share.0[0] * r;
@@ -1305,6 +1411,90 @@ impl<P: Xof<SEED_SIZE>, const SEED_SIZE: usize> Aggregator<SEED_SIZE, 16>
     }
 }
 
+impl<P, const SEED_SIZE: usize> AggregatorWithNoise<SEED_SIZE, 16, PureDpDiscreteLaplace>
+    for Poplar1<P, SEED_SIZE>
+where
+    P: Xof<SEED_SIZE>,
+{
+    fn add_noise_to_agg_share_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &PureDpDiscreteLaplace,
+        agg_param: &Poplar1AggregationParam,
+        agg_share: &mut Poplar1FieldVec,
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), VdafError> {
+        // The global sensitivity of the prefix counts, using the L1 norm as a distance metric, and
+        // using the substitution-DP model. Each changed count changes by one.
+        let sensitivity = BigUint::from(agg_param.max_changed_prefixes());
+        let sampler = dp_strategy
+            .create_distribution(Ratio::from_integer(sensitivity))
+            .map_err(|e| VdafError::Other(Box::new(e)))?;
+        agg_share.add_iid_noise(rng, &sampler)
+    }
+}
+
+impl<P, B, const SEED_SIZE: usize> AggregatorWithNoise<SEED_SIZE, 16, DiscreteGaussianDpStrategy<B>>
+    for Poplar1<P, SEED_SIZE>
+where
+    P: Xof<SEED_SIZE>,
+    B: DifferentialPrivacyBudget,
+    DiscreteGaussianDpStrategy<B>:
+        DifferentialPrivacyStrategy<Sensitivity = Ratio<BigUint>, Distribution = DiscreteGaussian>,
+{
+    fn add_noise_to_agg_share_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dp_strategy: &DiscreteGaussianDpStrategy<B>,
+        agg_param: &Poplar1AggregationParam,
+        agg_share: &mut Poplar1FieldVec,
+        _num_measurements: usize,
+        rng: &mut R,
+    ) -> Result<(), VdafError> {
+        // The global sensitivity of the prefix counts, using the L2 norm as a distance metric, and
+        // using the substitution-DP model. This is the square root of the number of changed
+        // counts.
+        let sensitivity = sqrt_upper_bound(BigUint::from(agg_param.max_changed_prefixes()));
+        let sampler = dp_strategy
+            .create_distribution(sensitivity)
+            .map_err(|e| VdafError::Other(Box::new(e)))?;
+        agg_share.add_iid_noise(rng, &sampler)
+    }
+}
+
+impl Poplar1FieldVec {
+    /// Adds independent, identically-distributed noise to each element, after projecting it into
+    /// the field. As with Prio3, reducing the noise modulo the field modulus is a post-processing
+    /// step, and does not affect the differential privacy guarantee.
+    fn add_iid_noise<R, D>(&mut self, rng: &mut R, distribution: &D) -> Result<(), VdafError>
+    where
+        R: Rng,
+        D: Distribution<BigInt> + DifferentialPrivacyDistribution,
+    {
+        match self {
+            Self::Inner(ref mut vec) => Ok(add_iid_noise_to_field_vec(vec, rng, distribution)?),
+            Self::Leaf(ref mut vec) => {
+                // `Field255` has no associated integer type, so the noise is converted through
+                // its little-endian encoding instead.
+                for entry in vec.iter_mut() {
+                    let noise = distribution.sample(rng);
+                    let mut bytes = noise.magnitude().to_bytes_le();
+                    if bytes.len() >= Field255::ENCODED_SIZE {
+                        return Err(VdafError::Uncategorized("noise is too large".into()));
+                    }
+                    bytes.resize(Field255::ENCODED_SIZE, 0);
+                    let magnitude = Field255::try_from(bytes.as_slice())?;
+                    if noise.sign() == Sign::Minus {
+                        *entry -= magnitude;
+                    } else {
+                        *entry += magnitude;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 impl<P: Xof<SEED_SIZE>, const SEED_SIZE: usize> Collector for Poplar1<P, SEED_SIZE> {
     fn unshard<M: IntoIterator<Item = Poplar1FieldVec>>(
         &self,
@@ -1328,6 +1518,62 @@ impl<P: Xof<SEED_SIZE>, const SEED_SIZE: usize> Collector for Poplar1<P, SEED_SI
     }
 }
 
+impl<P: Xof<SEED_SIZE>, const SEED_SIZE: usize> Poplar1<P, SEED_SIZE> {
+    /// Combines aggregate shares to which noise was added with [`AggregatorWithNoise`] into signed
+    /// prefix counts.
+    ///
+    /// Noise may make a count negative, in which case it wraps around the field modulus, so
+    /// [`Collector::unshard`] would return a huge count, or fail at the last level. Here, counts
+    /// larger than half of the field modulus are taken to be negative instead. Negative counts are
+    /// returned as is, rather than clamped to zero, so that estimates derived from them remain
+    /// unbiased.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the aggregate shares can't be combined, or if a count at the last level is out of
+    /// range for an `i64`.
+    pub fn unshard_noisy<M: IntoIterator<Item = Poplar1FieldVec>>(
+        &self,
+        agg_param: &Poplar1AggregationParam,
+        agg_shares: M,
+    ) -> Result<Vec<i64>, VdafError> {
+        let result = aggregate(
+            usize::from(agg_param.level) == self.bits - 1,
+            agg_param.prefixes.len(),
+            agg_shares,
+        )?;
+
+        match result {
+            // The modulus of `Field64` is less than 2^64, so half of it fits in an `i64`.
+            Poplar1FieldVec::Inner(vec) => Ok(vec
+                .into_iter()
+                .map(|count| {
+                    let count_int = u64::from(count);
+                    let negated = u64::from(-count);
+                    if negated < count_int {
+                        -(negated as i64)
+                    } else {
+                        count_int as i64
+                    }
+                })
+                .collect()),
+            Poplar1FieldVec::Leaf(vec) => vec
+                .into_iter()
+                .map(|count| {
+                    let (magnitude, negative) = match u64::try_from(count) {
+                        Ok(count_int) => (count_int, false),
+                        Err(_) => (u64::try_from(-count)?, true),
+                    };
+                    let magnitude = i64::try_from(magnitude).map_err(|_| {
+                        VdafError::Uncategorized("count is out of range for an i64".into())
+                    })?;
+                    Ok(if negative { -magnitude } else { magnitude })
+                })
+                .collect(),
+        }
+    }
+}
+
 impl From<IdpfOutputShare<Poplar1IdpfValue<Field64>, Poplar1IdpfValue<Field255>>>
     for Poplar1IdpfValue<Field64>
 {
@@ -1745,6 +1991,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn shard_prepare_full_domain() {
+        let mut rng = rng();
+        let vdaf = Poplar1::new_turboshake128(4);
+        let verify_key = rng.random();
+        let input = IdpfInput::from_bools(&[true, false, true, true]);
+        let nonce = rng.random();
+        let (public_share, input_shares) = vdaf.shard(CTX_STR, &input, &nonce).unwrap();
+
+        for level in 0..vdaf.bits {
+            let agg_param = Poplar1AggregationParam::full_domain(level).unwrap();
+            assert!(agg_param.is_full_domain());
+            assert_eq!(agg_param.prefixes().len(), 1 << (level + 1));
+
+            let index = input
+                .prefix(level)
+                .iter()
+                .fold(0, |index, bit| (index << 1) | usize::from(bit));
+            let mut expected_result = vec![0; 1 << (level + 1)];
+            expected_result[index] = 1;
+            test_prepare(
+                &vdaf,
+                &verify_key,
+                &nonce,
+                &public_share,
+                &input_shares,
+                &agg_param,
+                expected_result,
+            );
+        }
+
+        assert!(!Poplar1AggregationParam::try_from_prefixes(vec![
+            IdpfInput::from_bools(&[false, true]),
+            IdpfInput::from_bools(&[true, false]),
+        ])
+        .unwrap()
+        .is_full_domain());
+        Poplar1AggregationParam::full_domain(31).unwrap_err();
+    }
+
     #[test]
     fn heavy_hitters() {
         let mut rng = rng();
@@ -2441,4 +2727,213 @@ mod tests {
             Poplar1FieldVec::Leaf(Vec::from([Field255::from(1), Field255::from(0)])),
         ])
     }
+
+    #[test]
+    fn field_vec_retract_subtract() {
+        let inner = Poplar1FieldVec::Inner(Field64::random_vector(2));
+        let leaf = Poplar1FieldVec::Leaf(Field255::random_vector(2));
+
+        for (want, share) in [
+            (
+                inner.clone(),
+                Poplar1FieldVec::Inner(Field64::random_vector(2)),
+            ),
+            (
+                leaf.clone(),
+                Poplar1FieldVec::Leaf(Field255::random_vector(2)),
+            ),
+        ] {
+            let mut got = want.clone();
+            got.accumulate(&share).unwrap();
+            got.retract(&share).unwrap();
+            assert_eq!(got, want);
+
+            got.merge(&share).unwrap();
+            got.subtract(&share).unwrap();
+            assert_eq!(got, want);
+        }
+
+        let mut got = inner.clone();
+        got.retract(&leaf).unwrap_err();
+        got.subtract(&leaf).unwrap_err();
+        got.retract(&Poplar1FieldVec::Inner(Field64::random_vector(3)))
+            .unwrap_err();
+        assert_eq!(got, inner);
+    }
+
+    #[test]
+    fn field_vec_accumulate_encoded() {
+        for share in [
+            Poplar1FieldVec::Inner(Field64::random_vector(2)),
+            Poplar1FieldVec::Leaf(Field255::random_vector(2)),
+        ] {
+            let mut want = share.clone();
+            want.accumulate(&share).unwrap();
+            let mut got = share.clone();
+            got.accumulate_encoded(&share.get_encoded().unwrap())
+                .unwrap();
+            assert_eq!(got, want);
+
+            // Non-canonical encoding of the last element.
+            let mut encoded = share.get_encoded().unwrap();
+            let len = encoded.len();
+            encoded[len - 1] = 0xff;
+            encoded[len - 8..len - 1].fill(0xff);
+            got.accumulate_encoded(&encoded).unwrap_err();
+            assert_eq!(got, want);
+        }
+
+        // Leaf encodings are too long to be inner node encodings.
+        let mut inner = Poplar1FieldVec::Inner(Field64::random_vector(2));
+        inner
+            .accumulate_encoded(
+                &Poplar1FieldVec::Leaf(Field255::random_vector(2))
+                    .get_encoded()
+                    .unwrap(),
+            )
+            .unwrap_err();
+    }
+
+    /// Returns `count` distinct prefixes of length `len`, in lexicographic order.
+    fn prefixes(len: usize, count: usize) -> Vec<IdpfInput> {
+        (0..count)
+            .map(|i| {
+                let bits = (0..len)
+                    .rev()
+                    .map(|j| (i >> j) & 1 == 1)
+                    .collect::<Vec<_>>();
+                IdpfInput::from_bools(&bits)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn add_noise_huge_budget() {
+        use crate::dp::{
+            distributions::{PureDpDiscreteLaplace, ZCdpDiscreteGaussian},
+            DifferentialPrivacyStrategy, PureDpBudget, Rational, ZCdpBudget,
+        };
+
+        let laplace = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1000u16, 1).unwrap()).unwrap(),
+        );
+        let gaussian = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+            Rational::from_unsigned(1_000_000u32, 1).unwrap(),
+        ));
+        let poplar = Poplar1::new_turboshake128(4);
+
+        for level in [0, 3] {
+            let agg_param =
+                Poplar1AggregationParam::try_from_prefixes(prefixes(level + 1, 2)).unwrap();
+            let is_leaf = level == 3;
+            let mut agg_share_0 = Poplar1FieldVec::zero(is_leaf, 2);
+            agg_share_0
+                .accumulate(&if is_leaf {
+                    Poplar1FieldVec::Leaf(vec![Field255::from(3), Field255::from(1)])
+                } else {
+                    Poplar1FieldVec::Inner(vec![Field64::from(3), Field64::from(1)])
+                })
+                .unwrap();
+            let mut agg_share_1 = Poplar1FieldVec::zero(is_leaf, 2);
+
+            poplar
+                .add_noise_to_agg_share(&laplace, &agg_param, &mut agg_share_0, 4)
+                .unwrap();
+            poplar
+                .add_noise_to_agg_share(&gaussian, &agg_param, &mut agg_share_1, 4)
+                .unwrap();
+            assert_eq!(
+                poplar
+                    .unshard(&agg_param, [agg_share_0, agg_share_1], 4)
+                    .unwrap(),
+                [3, 1],
+            );
+        }
+    }
+
+    #[test]
+    fn add_noise_variance() {
+        use crate::dp::{
+            distributions::PureDpDiscreteLaplace, noise_rng, DifferentialPrivacyStrategy,
+            PureDpBudget, Rational,
+        };
+
+        let laplace = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1u8, 1).unwrap()).unwrap(),
+        );
+        let poplar = Poplar1::new_turboshake128(12);
+        let num_prefixes = 2000;
+
+        // The sensitivity is two, so the noise is discrete Laplace with scale two.
+        let expected_variance = 2.0 * (-0.5f64).exp() / (1.0 - (-0.5f64).exp()).powi(2);
+
+        for level in [10, 11] {
+            let agg_param =
+                Poplar1AggregationParam::try_from_prefixes(prefixes(level + 1, num_prefixes))
+                    .unwrap();
+            let mut agg_share = Poplar1FieldVec::zero(level == 11, num_prefixes);
+            let mut rng = noise_rng::<XofTurboShake128, 32>(&Seed::from_bytes([0; 32]), b"");
+            poplar
+                .add_noise_to_agg_share_with_rng(&laplace, &agg_param, &mut agg_share, 1, &mut rng)
+                .unwrap();
+
+            let noise: Vec<f64> = match agg_share {
+                Poplar1FieldVec::Inner(vec) => vec
+                    .into_iter()
+                    .map(|x| {
+                        if u64::from(-x) < u64::from(x) {
+                            -(u64::from(-x) as f64)
+                        } else {
+                            u64::from(x) as f64
+                        }
+                    })
+                    .collect(),
+                Poplar1FieldVec::Leaf(vec) => vec
+                    .into_iter()
+                    .map(|x| match u64::try_from(x) {
+                        Ok(x) => x as f64,
+                        Err(_) => -(u64::try_from(-x).unwrap() as f64),
+                    })
+                    .collect(),
+            };
+            let variance =
+                noise.iter().map(|x| x * x).sum::<f64>() / f64::from(num_prefixes as u32);
+            assert!(
+                (variance - expected_variance).abs() < 0.2 * expected_variance,
+                "level {level}: variance {variance}, expected {expected_variance}",
+            );
+        }
+    }
+
+    #[test]
+    fn unshard_noisy_signed_counts() {
+        let poplar = Poplar1::new_turboshake128(2);
+        let inner_param = Poplar1AggregationParam::try_from_prefixes(prefixes(1, 2)).unwrap();
+        let leaf_param = Poplar1AggregationParam::try_from_prefixes(prefixes(2, 2)).unwrap();
+        let inner = Poplar1FieldVec::Inner(vec![-Field64::from(2), Field64::from(5)]);
+        let leaf = Poplar1FieldVec::Leaf(vec![-Field255::from(2), Field255::from(5)]);
+
+        assert_eq!(
+            poplar.unshard_noisy(&inner_param, [inner.clone()]).unwrap(),
+            [-2, 5],
+        );
+        assert_eq!(
+            poplar.unshard_noisy(&leaf_param, [leaf.clone()]).unwrap(),
+            [-2, 5],
+        );
+
+        // `unshard` does not interpret counts as signed, and rejects an out-of-range count at the
+        // last level.
+        assert_eq!(
+            poplar.unshard(&inner_param, [inner], 1).unwrap(),
+            [u64::from(-Field64::from(2)), 5],
+        );
+        poplar.unshard(&leaf_param, [leaf], 1).unwrap_err();
+
+        // Counts that are out of range even when interpreted as signed are rejected.
+        let huge = Field255::from(1) + Field255::from(u64::MAX) * Field255::from(u64::MAX);
+        poplar
+            .unshard_noisy(&leaf_param, [Poplar1FieldVec::Leaf(vec![huge, huge])])
+            .unwrap_err();
+    }
 }
@@ -27,6 +27,8 @@
 //! [DPRS23]: https://ia.cr/2023/130
 //! [draft-irtf-cfrg-vdaf-08]: https://datatracker.ietf.org/doc/draft-irtf-cfrg-vdaf/08/
 
+#[cfg(feature = "crypto-dependencies")]
+use super::xof::XofHmacSha256Aes128;
 use super::xof::XofTurboShake128;
 #[cfg(feature = "experimental")]
 use super::AggregatorWithNoise;
@@ -38,20 +40,28 @@ use crate::field::{
     NttFriendlyFieldElement,
 };
 use crate::field::{Field128, Field64};
+use crate::flp::gadgets::ParallelSumGadget;
 #[cfg(feature = "multithreaded")]
 use crate::flp::gadgets::ParallelSumMultithreaded;
 #[cfg(feature = "experimental")]
 use crate::flp::gadgets::PolyEval;
 use crate::flp::gadgets::{Mul, ParallelSum};
 #[cfg(feature = "experimental")]
+use crate::flp::types::batch::Batch;
+#[cfg(feature = "experimental")]
 use crate::flp::types::fixedpoint_l2::{
     compatible_float::CompatibleFloat, FixedPointBoundedL2VecSum,
 };
 #[cfg(feature = "experimental")]
+use crate::flp::types::fixedpoint_sum::{FixedPointSum, Rounding};
+#[cfg(feature = "experimental")]
 use crate::flp::TypeWithNoise;
 use crate::flp::{
-    types::{Average, Count, Histogram, MultihotCountVec, Sum, SumVec},
-    Type,
+    types::{
+        Average, Count, ExactWeightCountVec, Histogram, MultihotCountVec, OptionalHistogram, Sum,
+        SumVec,
+    },
+    Flp, FlpError, Type,
 };
 use crate::prng::Prng;
 use crate::vdaf::xof::{IntoFieldVec, Seed, Xof};
@@ -62,14 +72,25 @@ use crate::vdaf::{
 #[cfg(feature = "experimental")]
 use fixed::traits::Fixed;
 use rand::{rng, Rng};
-use std::borrow::Cow;
+#[cfg(feature = "experimental")]
+use rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "multithreaded")]
+use rayon::prelude::*;
 use std::convert::TryFrom;
 use std::fmt::Debug;
-use std::io::Cursor;
+use std::io::{Cursor, Write};
 use std::iter::{self, IntoIterator};
 use std::marker::PhantomData;
 use subtle::{Choice, ConstantTimeEq};
 
+pub mod dynamic;
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub mod local_dp;
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub mod threshold;
+
 const DST_MEASUREMENT_SHARE: u16 = 1;
 const DST_PROOF_SHARE: u16 = 2;
 const DST_JOINT_RANDOMNESS: u16 = 3;
@@ -142,6 +163,48 @@ impl Prio3SumVecMultithreaded {
     }
 }
 
+/// Like [`Prio3SumVec`] except this type uses [`Field64`] with multiple proofs, and
+/// [`XofHmacSha256Aes128`] in place of TurboSHAKE128. This is not specified by the VDAF draft; its
+/// algorithm ID, `0xFFFF1003`, is the one DAP deployments use for this instantiation.
+///
+/// The parallel sum gadget `PS` is either [`ParallelSum`] or, with the `multithreaded` feature,
+/// `ParallelSumMultithreaded`.
+#[cfg(feature = "crypto-dependencies")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crypto-dependencies")))]
+pub type Prio3SumVecField64MultiproofHmacSha256Aes128<PS> =
+    Prio3<SumVec<Field64, PS>, XofHmacSha256Aes128, 32>;
+
+#[cfg(feature = "crypto-dependencies")]
+impl<PS> Prio3SumVecField64MultiproofHmacSha256Aes128<PS>
+where
+    PS: ParallelSumGadget<Field64, Mul<Field64>> + Eq + 'static,
+{
+    /// Construct an instance of this VDAF with the given number of aggregators and number of
+    /// proofs. `bits` defines the bit width of each summand of the measurement; `len` defines the
+    /// length of the measurement vector. Errors if `num_proofs < 2`, since a single proof over
+    /// [`Field64`] does not provide enough soundness.
+    pub fn new_sum_vec_field64_multiproof_hmacsha256_aes128(
+        num_aggregators: u8,
+        num_proofs: u8,
+        bits: usize,
+        len: usize,
+        chunk_length: usize,
+    ) -> Result<Self, VdafError> {
+        if num_proofs < 2 {
+            return Err(VdafError::Uncategorized(
+                "at least two proofs are required with Field64".to_string(),
+            ));
+        }
+
+        Prio3::new(
+            num_aggregators,
+            num_proofs,
+            0xFFFF1003,
+            SumVec::new(bits, len, chunk_length)?,
+        )
+    }
+}
+
 /// The sum type. Each measurement is an integer in `[0,2^bits)` for some `0 < bits < 64` and the
 /// aggregate is the sum.
 pub type Prio3Sum = Prio3<Sum<Field64>, XofTurboShake128, 32>;
@@ -338,6 +401,56 @@ impl Prio3MultihotCountVecMultithreaded {
     }
 }
 
+/// The optional histogram type. Each measurement is either an integer in `[0, length)` or `None`,
+/// and the aggregate is a histogram counting the number of occurrences of each measurement.
+/// Measurements of `None` are not counted.
+pub type Prio3OptionalHistogram =
+    Prio3<OptionalHistogram<Field128, ParallelSum<Field128, Mul<Field128>>>, XofTurboShake128, 32>;
+
+impl Prio3OptionalHistogram {
+    /// Constructs an instance of Prio3OptionalHistogram with the given number of aggregators,
+    /// number of buckets, and parallel sum gadget chunk length.
+    pub fn new_optional_histogram(
+        num_aggregators: u8,
+        length: usize,
+        chunk_length: usize,
+    ) -> Result<Self, VdafError> {
+        Prio3::new(
+            num_aggregators,
+            1,
+            0xFFFF0000,
+            OptionalHistogram::new(length, chunk_length)?,
+        )
+    }
+}
+
+/// The exact-weight counter data type. Each measurement is a list of booleans of length `length`,
+/// with exactly `weight` true values, and the aggregate is a histogram counting the number of true
+/// values at each position across all measurements.
+pub type Prio3ExactWeightCountVec = Prio3<
+    ExactWeightCountVec<Field128, ParallelSum<Field128, Mul<Field128>>>,
+    XofTurboShake128,
+    32,
+>;
+
+impl Prio3ExactWeightCountVec {
+    /// Constructs an instance of Prio3ExactWeightCountVec with the given number of aggregators,
+    /// number of buckets, weight, and parallel sum gadget chunk length.
+    pub fn new_exact_weight_count_vec(
+        num_aggregators: u8,
+        num_buckets: usize,
+        weight: usize,
+        chunk_length: usize,
+    ) -> Result<Self, VdafError> {
+        Prio3::new(
+            num_aggregators,
+            1,
+            0xFFFF0000,
+            ExactWeightCountVec::new(num_buckets, weight, chunk_length)?,
+        )
+    }
+}
+
 /// The average type. Each measurement is an integer in `[0,2^bits)` for some `0 < bits < 64` and
 /// the aggregate is the arithmetic average.
 pub type Prio3Average = Prio3<Average<Field128>, XofTurboShake128, 32>;
@@ -361,6 +474,66 @@ impl Prio3Average {
     }
 }
 
+/// The fixed point sum type. Each measurement is an `f64` that is clamped to a range and quantised
+/// to the fixed point type `Fx`, and the aggregate result is the sum and the mean of the quantised
+/// measurements.
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub type Prio3FixedPointSum<Fx> = Prio3<FixedPointSum<Fx, Field128>, XofTurboShake128, 32>;
+
+#[cfg(feature = "experimental")]
+impl<Fx: Fixed> Prio3FixedPointSum<Fx> {
+    /// Construct an instance of `Prio3FixedPointSum` with the given number of aggregators. Each
+    /// measurement is clamped to `[lower, upper]` and rounded to a value of `Fx` using the given
+    /// rounding mode.
+    pub fn new_fixedpoint_sum(
+        num_aggregators: u8,
+        lower: Fx,
+        upper: Fx,
+        rounding: Rounding,
+    ) -> Result<Self, VdafError> {
+        Prio3::new(
+            num_aggregators,
+            1,
+            0xFFFF0000,
+            FixedPointSum::new(lower, upper, rounding)?,
+        )
+    }
+}
+
+/// A batch of measurements of the inner type `T`, sharded into a single report and validated with
+/// a single proof. Each measurement is a vector of at most `batch_len` measurements of `T`, and
+/// the aggregate is the same as if each of them had been submitted in a separate report. See
+/// [`Batch`] for details.
+#[cfg(feature = "experimental")]
+#[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+pub type Prio3Batch<T, P, const SEED_SIZE: usize> = Prio3<Batch<T>, P, SEED_SIZE>;
+
+#[cfg(feature = "experimental")]
+impl<T, P, const SEED_SIZE: usize> Prio3Batch<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    /// Construct an instance of this VDAF with the given number of aggregators, number of proofs,
+    /// algorithm ID, inner type, and number of measurements per report. Errors if
+    /// `batch_len == 0`.
+    pub fn new_batch(
+        num_aggregators: u8,
+        num_proofs: u8,
+        algorithm_id: u32,
+        typ: T,
+        batch_len: usize,
+    ) -> Result<Self, VdafError> {
+        Prio3::new(
+            num_aggregators,
+            num_proofs,
+            algorithm_id,
+            Batch::new(typ, batch_len)?,
+        )
+    }
+}
+
 /// The base type for Prio3.
 ///
 /// An instance of Prio3 is determined by:
@@ -513,14 +686,23 @@ where
         joint_rand_parts: impl Iterator<Item = &'a Seed<SEED_SIZE>>,
     ) -> (Seed<SEED_SIZE>, Vec<T::Field>) {
         let joint_rand_seed = self.derive_joint_rand_seed(ctx, joint_rand_parts);
-        let joint_rands = P::seed_stream(
+        let joint_rands = self
+            .joint_rands_seed_stream(ctx, &joint_rand_seed)
+            .into_field_vec(self.typ.joint_rand_len() * self.num_proofs());
+
+        (joint_rand_seed, joint_rands)
+    }
+
+    fn joint_rands_seed_stream(
+        &self,
+        ctx: &[u8],
+        joint_rand_seed: &Seed<SEED_SIZE>,
+    ) -> P::SeedStream {
+        P::seed_stream(
             joint_rand_seed.as_ref(),
             &[&self.domain_separation_tag(DST_JOINT_RANDOMNESS), ctx],
             &[&[self.num_proofs]],
         )
-        .into_field_vec(self.typ.joint_rand_len() * self.num_proofs());
-
-        (joint_rand_seed, joint_rands)
     }
 
     fn derive_helper_proofs_share(
@@ -536,12 +718,12 @@ where
         ))
     }
 
-    fn derive_query_rands(
+    fn query_rands_seed_stream(
         &self,
         verify_key: &[u8; SEED_SIZE],
         ctx: &[u8],
         nonce: &[u8; 16],
-    ) -> Vec<T::Field> {
+    ) -> P::SeedStream {
         let mut xof = P::init(
             verify_key,
             &[&self.domain_separation_tag(DST_QUERY_RANDOMNESS), ctx],
@@ -549,7 +731,6 @@ where
         xof.update(&[self.num_proofs]);
         xof.update(nonce);
         xof.into_seed_stream()
-            .into_field_vec(self.typ.query_rand_len() * self.num_proofs())
     }
 
     /// Generate the domain separation tag for this VDAF. The output is used for domain separation
@@ -759,6 +940,196 @@ where
     }
 }
 
+impl<F, S, P, const SEED_SIZE: usize> Prio3<SumVec<F, S>, P, SEED_SIZE>
+where
+    F: NttFriendlyFieldElement,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+    P: Xof<SEED_SIZE>,
+{
+    /// Shards a measurement given as an iterator of summands, writing the encoded input share of
+    /// each aggregator to the corresponding sink in `input_share_sinks`. The bytes written are the
+    /// same as the encoding of the input shares output by [`Client::shard`]. Returns the public
+    /// share.
+    ///
+    /// This is intended for measurements that are too large to comfortably shard in memory. The
+    /// encoded measurement and the leader's measurement share are never materialised: each summand
+    /// is encoded, shared, and written to the leader's sink as soon as it is consumed. Helper
+    /// shares are expanded from their seeds as usual. The proof is generated from the summands
+    /// one `ParallelSum` chunk position at a time, so the only buffer proportional to the length
+    /// of the measurement is the list of summands itself.
+    ///
+    /// Writes are made once per summand, so unbuffered sinks should be wrapped in a
+    /// [`std::io::BufWriter`]. If an error is returned, the contents of the sinks are unspecified.
+    pub fn shard_streaming<I, W>(
+        &self,
+        ctx: &[u8],
+        measurement: I,
+        nonce: &[u8; 16],
+        input_share_sinks: &mut [W],
+    ) -> Result<Prio3PublicShare<SEED_SIZE>, VdafError>
+    where
+        I: IntoIterator<Item = F::Integer>,
+        W: Write,
+    {
+        let mut random = vec![0u8; self.random_size()];
+        rng().fill(&mut random[..]);
+        self.shard_streaming_with_random(ctx, measurement, nonce, &random, input_share_sinks)
+    }
+
+    pub(crate) fn shard_streaming_with_random<I, W, const N: usize>(
+        &self,
+        ctx: &[u8],
+        measurement: I,
+        nonce: &[u8; N],
+        random: &[u8],
+        input_share_sinks: &mut [W],
+    ) -> Result<Prio3PublicShare<SEED_SIZE>, VdafError>
+    where
+        I: IntoIterator<Item = F::Integer>,
+        W: Write,
+    {
+        if random.len() != self.random_size() {
+            return Err(VdafError::Uncategorized(
+                "incorrect random input length".to_string(),
+            ));
+        }
+        if input_share_sinks.len() != self.num_aggregators() {
+            return Err(VdafError::Uncategorized(format!(
+                "unexpected number of input share sinks: got {}; want {}",
+                input_share_sinks.len(),
+                self.num_aggregators
+            )));
+        }
+        let encode_err =
+            |_| VdafError::Uncategorized("failed to encode measurement share".to_string());
+
+        // Consume the randomness in the same order as `shard_with_random()`. `SumVec` always uses
+        // joint randomness, so each helper has a blind.
+        let mut random_seeds = random
+            .chunks_exact(SEED_SIZE)
+            .map(|seed| <[u8; SEED_SIZE]>::try_from(seed).unwrap());
+        let helper_seeds = (1..self.num_aggregators)
+            .map(|_| (random_seeds.next().unwrap(), random_seeds.next().unwrap()))
+            .collect::<Vec<_>>();
+        let leader_blind = random_seeds.next().unwrap();
+        let prove_rand_seed = random_seeds.next().unwrap();
+
+        let mut helper_measurement_shares = Vec::with_capacity(helper_seeds.len());
+        let mut joint_rand_part_xofs = Vec::with_capacity(self.num_aggregators());
+        let mut leader_joint_rand_part_xof = P::init(
+            &leader_blind,
+            &[&self.domain_separation_tag(DST_JOINT_RAND_PART), ctx],
+        );
+        leader_joint_rand_part_xof.update(&[0]); // Aggregator ID
+        leader_joint_rand_part_xof.update(nonce);
+        for ((meas_and_proofs_share_seed, joint_rand_blind), agg_id) in helper_seeds.iter().zip(1..)
+        {
+            helper_measurement_shares.push(Prng::<F, _>::from_seed_stream(P::seed_stream(
+                meas_and_proofs_share_seed,
+                &[&self.domain_separation_tag(DST_MEASUREMENT_SHARE), ctx],
+                &[&[agg_id]],
+            )));
+            let mut joint_rand_part_xof = P::init(
+                joint_rand_blind,
+                &[&self.domain_separation_tag(DST_JOINT_RAND_PART), ctx],
+            );
+            joint_rand_part_xof.update(&[agg_id]); // Aggregator ID
+            joint_rand_part_xof.update(nonce);
+            joint_rand_part_xofs.push(joint_rand_part_xof);
+        }
+
+        // Share each summand and stream the leader's measurement share to its sink.
+        let len = self.typ.output_len();
+        let bits = self.typ.input_len() / len;
+        let mut summands = Vec::with_capacity(len);
+        let mut leader_buffer = Vec::with_capacity(bits * F::ENCODED_SIZE);
+        let mut helper_buffer = Vec::with_capacity(F::ENCODED_SIZE);
+        for summand in measurement {
+            if summands.len() == len {
+                return Err(FlpError::Encode(format!(
+                    "measurement is longer than expected length of {len}"
+                ))
+                .into());
+            }
+            self.typ.check_summand(&summand)?;
+
+            leader_buffer.clear();
+            for x in F::encode_as_bitvector(summand, bits)? {
+                let mut leader_share = x;
+                for (helper_share, joint_rand_part_xof) in helper_measurement_shares
+                    .iter_mut()
+                    .zip(joint_rand_part_xofs.iter_mut())
+                {
+                    let y = helper_share.next().unwrap();
+                    leader_share -= y;
+                    y.encode(&mut helper_buffer).map_err(encode_err)?;
+                    joint_rand_part_xof.update(&helper_buffer);
+                    helper_buffer.clear();
+                }
+                leader_share
+                    .encode(&mut leader_buffer)
+                    .map_err(encode_err)?;
+            }
+            leader_joint_rand_part_xof.update(&leader_buffer);
+            input_share_sinks[0].write_all(&leader_buffer)?;
+            summands.push(summand);
+        }
+        if summands.len() != len {
+            return Err(FlpError::Encode(format!(
+                "unexpected measurement length: got {}; want {len}",
+                summands.len()
+            ))
+            .into());
+        }
+
+        let mut joint_rand_parts = Vec::with_capacity(self.num_aggregators());
+        joint_rand_parts.push(leader_joint_rand_part_xof.into_seed());
+        joint_rand_parts.extend(joint_rand_part_xofs.into_iter().map(Xof::into_seed));
+        let public_share = Prio3PublicShare {
+            joint_rand_parts: Some(joint_rand_parts),
+        };
+
+        // Generate the proofs and the leader's proofs share.
+        let (_, joint_rands) =
+            self.derive_joint_rands(ctx, public_share.joint_rand_parts.iter().flatten());
+        let prove_rands = self.derive_prove_rands(ctx, &Seed::from_bytes(prove_rand_seed));
+        let mut leader_proofs_share = Vec::with_capacity(self.typ.proof_len() * self.num_proofs());
+        for (prove_rand, joint_rand) in prove_rands
+            .chunks_exact(self.typ.prove_rand_len())
+            .zip(joint_rands.chunks_exact(self.typ.joint_rand_len()))
+        {
+            leader_proofs_share
+                .append(&mut self.typ.prove_summands(&summands, prove_rand, joint_rand)?);
+        }
+        for ((meas_and_proofs_share_seed, _), agg_id) in helper_seeds.iter().zip(1..) {
+            sub_assign_vector(
+                &mut leader_proofs_share,
+                self.derive_helper_proofs_share(
+                    ctx,
+                    &Seed::from_bytes(*meas_and_proofs_share_seed),
+                    agg_id,
+                )
+                .take(self.typ.proof_len() * self.num_proofs()),
+            );
+        }
+
+        leader_buffer.clear();
+        encode_fixlen_items(&mut leader_buffer, &leader_proofs_share).map_err(encode_err)?;
+        leader_buffer.extend_from_slice(&leader_blind);
+        input_share_sinks[0].write_all(&leader_buffer)?;
+
+        for ((meas_and_proofs_share_seed, joint_rand_blind), sink) in helper_seeds
+            .into_iter()
+            .zip(input_share_sinks[1..].iter_mut())
+        {
+            sink.write_all(&meas_and_proofs_share_seed)?;
+            sink.write_all(&joint_rand_blind)?;
+        }
+
+        Ok(public_share)
+    }
+}
+
 impl<T, P, const SEED_SIZE: usize> Vdaf for Prio3<T, P, SEED_SIZE>
 where
     T: Type,
@@ -1330,82 +1701,466 @@ where
     }
 }
 
-impl<T, P, const SEED_SIZE: usize> Aggregator<SEED_SIZE, 16> for Prio3<T, P, SEED_SIZE>
+/// Buffers that are reused across reports when preparing a batch of reports.
+struct PrepareScratch<F> {
+    measurement_share: Vec<F>,
+    proofs_share: Vec<F>,
+    joint_rands: Vec<F>,
+    query_rands: Vec<F>,
+    encoding_buffer: Vec<u8>,
+}
+
+impl<F> Default for PrepareScratch<F> {
+    fn default() -> Self {
+        Self {
+            measurement_share: Vec::new(),
+            proofs_share: Vec::new(),
+            joint_rands: Vec::new(),
+            query_rands: Vec::new(),
+            encoding_buffer: Vec::new(),
+        }
+    }
+}
+
+/// Replace the contents of `buf` with `len` field elements from `seed_stream`, reusing its
+/// allocation.
+fn fill_field_vec<F: FieldElement>(
+    buf: &mut Vec<F>,
+    seed_stream: impl rand_core::RngCore,
+    len: usize,
+) {
+    buf.clear();
+    buf.extend(Prng::<F, _>::from_seed_stream(seed_stream).take(len));
+}
+
+/// The nonce, public share and input share of a report, as consumed by
+/// [`Prio3::prepare_init_batch`].
+pub type Prio3ReportShare<'a, F, const SEED_SIZE: usize> = (
+    &'a [u8; 16],
+    &'a Prio3PublicShare<SEED_SIZE>,
+    &'a Prio3InputShare<F, SEED_SIZE>,
+);
+
+/// The result of [`Aggregator::prepare_init`] for one report of a batch.
+pub type Prio3PrepareInitResult<F, const SEED_SIZE: usize> = Result<
+    (
+        Prio3PrepareState<F, SEED_SIZE>,
+        Prio3PrepareShare<F, SEED_SIZE>,
+    ),
+    VdafError,
+>;
+
+/// Diagnostic returned by [`Prio3::shard_and_check`] when a freshly sharded report would be
+/// rejected by the aggregators.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Prio3ShardCheckError {
+    /// Sharding failed, e.g. because the measurement could not be encoded.
+    #[error("sharding failed: {0}")]
+    Shard(#[from] VdafError),
+
+    /// [`Aggregator::prepare_init`] failed for an aggregator.
+    #[error("prepare_init failed for aggregator {agg_id}: {source}")]
+    PrepareInit {
+        /// The aggregator whose input share was rejected.
+        agg_id: usize,
+        /// The error returned by [`Aggregator::prepare_init`].
+        source: VdafError,
+    },
+
+    /// The prepare shares could not be combined into a prepare message.
+    #[error("prepare_shares_to_prepare_message failed: {0}")]
+    PrepareMessage(#[source] VdafError),
+
+    /// The validity circuit rejected the encoded measurement, i.e., the measurement is not valid
+    /// for the [`Type`].
+    #[error("validity circuit rejected the measurement for proof {proof}: non-zero outputs at {outputs:?}")]
+    InvalidMeasurement {
+        /// The index of the proof that was rejected.
+        proof: usize,
+        /// The positions of the non-zero outputs of [`Flp::valid`](crate::flp::Flp::valid) on
+        /// the encoded measurement.
+        outputs: Vec<usize>,
+    },
+
+    /// The check of a gadget failed, i.e., the proof does not match the measurement.
+    #[error("gadget {gadget} check failed for proof {proof}")]
+    GadgetCheck {
+        /// The index of the proof that was rejected.
+        proof: usize,
+        /// The index of the gadget, in the order returned by
+        /// [`Flp::gadget`](crate::flp::Flp::gadget).
+        gadget: usize,
+    },
+
+    /// [`Aggregator::prepare_next`] failed for an aggregator.
+    #[error("prepare_next failed for aggregator {agg_id}: {source}")]
+    PrepareNext {
+        /// The aggregator that failed to compute its output share.
+        agg_id: usize,
+        /// The error returned by [`Aggregator::prepare_next`].
+        source: VdafError,
+    },
+
+    /// The output shares do not sum to the truncated measurement.
+    #[error("output shares do not match the measurement at positions {positions:?}")]
+    OutputMismatch {
+        /// The positions at which the sum of the output shares differs from the truncated
+        /// encoded measurement.
+        positions: Vec<usize>,
+    },
+}
+
+impl<T, P, const SEED_SIZE: usize> Prio3<T, P, SEED_SIZE>
 where
     T: Type,
     P: Xof<SEED_SIZE>,
 {
-    type PrepareState = Prio3PrepareState<T::Field, SEED_SIZE>;
-    type PrepareShare = Prio3PrepareShare<T::Field, SEED_SIZE>;
-    type PrepareMessage = Prio3PrepareMessage<SEED_SIZE>;
-
-    /// Begins the Prep process with the other aggregators. The result of this process is
-    /// the aggregator's output share.
-    #[allow(clippy::type_complexity)]
-    fn prepare_init(
+    /// Begins the Prep process for a batch of reports. This is equivalent to calling
+    /// [`Aggregator::prepare_init`] on each report, but reuses buffers across reports.
+    ///
+    /// The outer error is returned if the arguments shared by all reports are invalid. Otherwise,
+    /// there is one result per report, so that one invalid report does not fail the whole batch.
+    pub fn prepare_init_batch(
         &self,
         verify_key: &[u8; SEED_SIZE],
         ctx: &[u8],
         agg_id: usize,
-        _agg_param: &Self::AggregationParam,
-        nonce: &[u8; 16],
-        public_share: &Self::PublicShare,
-        msg: &Prio3InputShare<T::Field, SEED_SIZE>,
-    ) -> Result<
-        (
-            Prio3PrepareState<T::Field, SEED_SIZE>,
-            Prio3PrepareShare<T::Field, SEED_SIZE>,
-        ),
-        VdafError,
-    > {
+        reports: &[Prio3ReportShare<'_, T::Field, SEED_SIZE>],
+    ) -> Result<Vec<Prio3PrepareInitResult<T::Field, SEED_SIZE>>, VdafError> {
         let agg_id = self.role_try_from(agg_id)?;
+        let mut scratch = PrepareScratch::default();
+        Ok(reports
+            .iter()
+            .map(|(nonce, public_share, input_share)| {
+                self.prepare_init_with_scratch(
+                    &mut scratch,
+                    verify_key,
+                    ctx,
+                    agg_id,
+                    nonce,
+                    public_share,
+                    input_share,
+                )
+            })
+            .collect())
+    }
 
-        let (measurement_share, proofs_share) = match msg {
-            Prio3InputShare::Leader {
-                measurement_share,
-                proofs_share,
-                ..
-            } => {
-                let measurement_share = Cow::Borrowed(measurement_share);
-                let proof_share = Cow::Borrowed(proofs_share);
-                (measurement_share, proof_share)
-            }
-            Prio3InputShare::Helper {
-                meas_and_proofs_share,
-                ..
-            } => {
-                let measurement_share = Cow::Owned(
-                    P::seed_stream(
-                        meas_and_proofs_share.as_ref(),
-                        &[&self.domain_separation_tag(DST_MEASUREMENT_SHARE), ctx],
-                        &[&[agg_id]],
+    /// Like [`Self::prepare_init_batch`], but prepares reports in parallel. Each thread reuses its
+    /// own buffers.
+    #[cfg(feature = "multithreaded")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "multithreaded")))]
+    pub fn prepare_init_batch_multithreaded(
+        &self,
+        verify_key: &[u8; SEED_SIZE],
+        ctx: &[u8],
+        agg_id: usize,
+        reports: &[Prio3ReportShare<'_, T::Field, SEED_SIZE>],
+    ) -> Result<Vec<Prio3PrepareInitResult<T::Field, SEED_SIZE>>, VdafError>
+    where
+        T: Sync,
+        P: Sync,
+        T::Field: Send + Sync,
+    {
+        let agg_id = self.role_try_from(agg_id)?;
+        Ok(reports
+            .par_iter()
+            .map_init(
+                PrepareScratch::default,
+                |scratch, (nonce, public_share, input_share)| {
+                    self.prepare_init_with_scratch(
+                        scratch,
+                        verify_key,
+                        ctx,
+                        agg_id,
+                        nonce,
+                        public_share,
+                        input_share,
                     )
-                    .into_field_vec(self.typ.input_len()),
-                );
-                let proof_share = Cow::Owned(
-                    self.derive_helper_proofs_share(ctx, meas_and_proofs_share, agg_id)
-                        .take(self.typ.proof_len() * self.num_proofs())
-                        .collect::<Vec<_>>(),
-                );
-                (measurement_share, proof_share)
-            }
-        };
+                },
+            )
+            .collect())
+    }
 
-        // Compute the joint randomness.
-        let (joint_rand_seed, joint_rand_part, joint_rands) = if self.typ.joint_rand_len() > 0 {
-            let mut joint_rand_part_xof = P::init(
+    /// Finishes the Prep process for a batch of reports. This is equivalent to calling
+    /// [`Aggregator::prepare_next`] on each pair of prepare state and prepare message, and
+    /// returns one result per report.
+    pub fn prepare_next_batch(
+        &self,
+        ctx: &[u8],
+        steps: Vec<(
+            Prio3PrepareState<T::Field, SEED_SIZE>,
+            Prio3PrepareMessage<SEED_SIZE>,
+        )>,
+    ) -> Vec<Result<PrepareTransition<Self, SEED_SIZE, 16>, VdafError>> {
+        steps
+            .into_iter()
+            .map(|(state, msg)| self.prepare_next(ctx, state, msg))
+            .collect()
+    }
+
+    /// Like [`Self::prepare_next_batch`], but processes reports in parallel.
+    #[cfg(feature = "multithreaded")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "multithreaded")))]
+    pub fn prepare_next_batch_multithreaded(
+        &self,
+        ctx: &[u8],
+        steps: Vec<(
+            Prio3PrepareState<T::Field, SEED_SIZE>,
+            Prio3PrepareMessage<SEED_SIZE>,
+        )>,
+    ) -> Vec<Result<PrepareTransition<Self, SEED_SIZE, 16>, VdafError>>
+    where
+        T: Sync,
+        P: Sync,
+        T::Field: Send + Sync,
+    {
+        steps
+            .into_par_iter()
+            .map(|(state, msg)| self.prepare_next(ctx, state, msg))
+            .collect()
+    }
+
+    /// Sums the verifier shares of a report and collects the joint randomness parts.
+    #[allow(clippy::type_complexity)]
+    fn combine_prepare_shares<M: IntoIterator<Item = Prio3PrepareShare<T::Field, SEED_SIZE>>>(
+        &self,
+        inputs: M,
+    ) -> Result<(Vec<T::Field>, Vec<Seed<SEED_SIZE>>), VdafError> {
+        let mut verifiers = vec![T::Field::zero(); self.typ.verifier_len() * self.num_proofs()];
+        let mut joint_rand_parts = Vec::with_capacity(self.num_aggregators());
+        let mut count = 0;
+        for share in inputs.into_iter() {
+            count += 1;
+
+            if share.verifiers.len() != verifiers.len() {
+                return Err(VdafError::Uncategorized(format!(
+                    "unexpected verifier share length: got {}; want {}",
+                    share.verifiers.len(),
+                    verifiers.len(),
+                )));
+            }
+
+            if self.typ.joint_rand_len() > 0 {
+                let joint_rand_seed_part = share.joint_rand_part.unwrap();
+                joint_rand_parts.push(joint_rand_seed_part);
+            }
+
+            add_assign_vector(&mut verifiers, share.verifiers.iter().copied());
+        }
+
+        if count != self.num_aggregators {
+            return Err(VdafError::Uncategorized(format!(
+                "unexpected message count: got {}; want {}",
+                count, self.num_aggregators,
+            )));
+        }
+
+        Ok((verifiers, joint_rand_parts))
+    }
+
+    fn prepare_message(
+        &self,
+        ctx: &[u8],
+        joint_rand_parts: &[Seed<SEED_SIZE>],
+    ) -> Prio3PrepareMessage<SEED_SIZE> {
+        let joint_rand_seed = if self.typ.joint_rand_len() > 0 {
+            Some(self.derive_joint_rand_seed(ctx, joint_rand_parts.iter()))
+        } else {
+            None
+        };
+
+        Prio3PrepareMessage { joint_rand_seed }
+    }
+
+    /// Shards a measurement, then runs preparation for every aggregator with a throwaway verify
+    /// key in order to check that the report would be accepted.
+    ///
+    /// This is meant for debugging and testing client integrations: it is as expensive as sharding
+    /// and preparing the report for every aggregator. If the report would be rejected, the
+    /// returned [`Prio3ShardCheckError`] indicates which step failed and, where possible, which
+    /// output of the validity circuit, which gadget or which output position is at fault.
+    #[allow(clippy::type_complexity)]
+    pub fn shard_and_check(
+        &self,
+        ctx: &[u8],
+        measurement: &T::Measurement,
+        nonce: &[u8; 16],
+    ) -> Result<
+        (
+            Prio3PublicShare<SEED_SIZE>,
+            Vec<Prio3InputShare<T::Field, SEED_SIZE>>,
+        ),
+        Prio3ShardCheckError,
+    > {
+        let (public_share, input_shares) = self.shard(ctx, measurement, nonce)?;
+        let encoded_measurement = self
+            .typ
+            .encode_measurement(measurement)
+            .map_err(VdafError::from)?;
+        let mut verify_key = [0; SEED_SIZE];
+        rng().fill(&mut verify_key[..]);
+
+        let (states, prep_shares): (Vec<_>, Vec<_>) = input_shares
+            .iter()
+            .enumerate()
+            .map(|(agg_id, input_share)| {
+                self.prepare_init(
+                    &verify_key,
+                    ctx,
+                    agg_id,
+                    &(),
+                    nonce,
+                    &public_share,
+                    input_share,
+                )
+                .map_err(|source| Prio3ShardCheckError::PrepareInit { agg_id, source })
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        let (verifiers, joint_rand_parts) = self
+            .combine_prepare_shares(prep_shares)
+            .map_err(Prio3ShardCheckError::PrepareMessage)?;
+        let prep_msg = self.prepare_message(ctx, &joint_rand_parts);
+
+        // Check the proof verifiers, and find out which check failed.
+        let joint_rands = prep_msg
+            .joint_rand_seed
+            .as_ref()
+            .map(|seed| {
+                self.joint_rands_seed_stream(ctx, seed)
+                    .into_field_vec(self.typ.joint_rand_len() * self.num_proofs())
+            })
+            .unwrap_or_default();
+        for (proof, verifier) in verifiers.chunks(self.typ.verifier_len()).enumerate() {
+            if verifier[0] != T::Field::zero() {
+                let joint_rand = &joint_rands
+                    [proof * self.typ.joint_rand_len()..(proof + 1) * self.typ.joint_rand_len()];
+                let outputs = self
+                    .typ
+                    .valid(&mut self.typ.gadget(), &encoded_measurement, joint_rand, 1)
+                    .map_err(VdafError::from)?
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, output)| *output != T::Field::zero())
+                    .map(|(position, _)| position)
+                    .collect();
+                return Err(Prio3ShardCheckError::InvalidMeasurement { proof, outputs });
+            }
+
+            let mut offset = 1;
+            for (gadget, g) in self.typ.gadget().iter_mut().enumerate() {
+                let arity = g.arity();
+                let output = g
+                    .call(&verifier[offset..offset + arity])
+                    .map_err(VdafError::from)?;
+                if output != verifier[offset + arity] {
+                    return Err(Prio3ShardCheckError::GadgetCheck { proof, gadget });
+                }
+                offset += arity + 1;
+            }
+        }
+
+        // Compute the output shares and check that they sum to the truncated measurement.
+        let mut output = vec![T::Field::zero(); self.typ.output_len()];
+        for (agg_id, state) in states.into_iter().enumerate() {
+            match self.prepare_next(ctx, state, prep_msg.clone()) {
+                Ok(PrepareTransition::Finish(output_share)) => {
+                    add_assign_vector(&mut output, output_share.0)
+                }
+                Ok(PrepareTransition::Continue(..)) => {
+                    return Err(Prio3ShardCheckError::PrepareNext {
+                        agg_id,
+                        source: VdafError::Uncategorized("unexpected transition".into()),
+                    })
+                }
+                Err(source) => return Err(Prio3ShardCheckError::PrepareNext { agg_id, source }),
+            }
+        }
+        let expected = self
+            .typ
+            .truncate(encoded_measurement)
+            .map_err(VdafError::from)?;
+        let positions = output
+            .iter()
+            .zip(expected.iter())
+            .enumerate()
+            .filter(|(_, (x, y))| x != y)
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        if !positions.is_empty() {
+            return Err(Prio3ShardCheckError::OutputMismatch { positions });
+        }
+
+        Ok((public_share, input_shares))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_init_with_scratch(
+        &self,
+        scratch: &mut PrepareScratch<T::Field>,
+        verify_key: &[u8; SEED_SIZE],
+        ctx: &[u8],
+        agg_id: u8,
+        nonce: &[u8; 16],
+        public_share: &Prio3PublicShare<SEED_SIZE>,
+        msg: &Prio3InputShare<T::Field, SEED_SIZE>,
+    ) -> Prio3PrepareInitResult<T::Field, SEED_SIZE> {
+        let PrepareScratch {
+            measurement_share: measurement_share_buf,
+            proofs_share: proofs_share_buf,
+            joint_rands,
+            query_rands,
+            encoding_buffer,
+        } = scratch;
+
+        let (measurement_share, proofs_share) = match msg {
+            Prio3InputShare::Leader {
+                measurement_share,
+                proofs_share,
+                ..
+            } => (measurement_share.as_slice(), proofs_share.as_slice()),
+            Prio3InputShare::Helper {
+                meas_and_proofs_share,
+                ..
+            } => {
+                fill_field_vec(
+                    measurement_share_buf,
+                    P::seed_stream(
+                        meas_and_proofs_share.as_ref(),
+                        &[&self.domain_separation_tag(DST_MEASUREMENT_SHARE), ctx],
+                        &[&[agg_id]],
+                    ),
+                    self.typ.input_len(),
+                );
+                proofs_share_buf.clear();
+                proofs_share_buf.extend(
+                    self.derive_helper_proofs_share(ctx, meas_and_proofs_share, agg_id)
+                        .take(self.typ.proof_len() * self.num_proofs()),
+                );
+                (
+                    measurement_share_buf.as_slice(),
+                    proofs_share_buf.as_slice(),
+                )
+            }
+        };
+
+        // Compute the joint randomness.
+        let (joint_rand_seed, joint_rand_part) = if self.typ.joint_rand_len() > 0 {
+            let mut joint_rand_part_xof = P::init(
                 msg.joint_rand_blind().as_ref().unwrap().as_ref(),
                 &[&self.domain_separation_tag(DST_JOINT_RAND_PART), ctx],
             );
             joint_rand_part_xof.update(&[agg_id]);
             joint_rand_part_xof.update(nonce);
-            let mut encoding_buffer = Vec::with_capacity(T::Field::ENCODED_SIZE);
             for x in measurement_share.iter() {
-                x.encode(&mut encoding_buffer).map_err(|_| {
+                encoding_buffer.clear();
+                x.encode(encoding_buffer).map_err(|_| {
                     VdafError::Uncategorized("failed to encode measurement share".to_string())
                 })?;
-                joint_rand_part_xof.update(&encoding_buffer);
-                encoding_buffer.clear();
+                joint_rand_part_xof.update(encoding_buffer);
             }
             let own_joint_rand_part = joint_rand_part_xof.into_seed();
 
@@ -1430,20 +2185,25 @@ where
                         .skip(agg_id as usize + 1),
                 );
 
-            let (joint_rand_seed, joint_rands) =
-                self.derive_joint_rands(ctx, corrected_joint_rand_parts);
-
-            (
-                Some(joint_rand_seed),
-                Some(own_joint_rand_part),
+            let joint_rand_seed = self.derive_joint_rand_seed(ctx, corrected_joint_rand_parts);
+            fill_field_vec(
                 joint_rands,
-            )
+                self.joint_rands_seed_stream(ctx, &joint_rand_seed),
+                self.typ.joint_rand_len() * self.num_proofs(),
+            );
+
+            (Some(joint_rand_seed), Some(own_joint_rand_part))
         } else {
-            (None, None, Vec::new())
+            joint_rands.clear();
+            (None, None)
         };
 
         // Run the query-generation algorithm.
-        let query_rands = self.derive_query_rands(verify_key, ctx, nonce);
+        fill_field_vec(
+            query_rands,
+            self.query_rands_seed_stream(verify_key, ctx, nonce),
+            self.typ.query_rand_len() * self.num_proofs(),
+        );
         let mut verifiers_share = Vec::with_capacity(self.typ.verifier_len() * self.num_proofs());
         for p in 0..self.num_proofs() {
             let query_rand =
@@ -1454,7 +2214,7 @@ where
                 &proofs_share[p * self.typ.proof_len()..(p + 1) * self.typ.proof_len()];
 
             verifiers_share.append(&mut self.typ.query(
-                measurement_share.as_ref(),
+                measurement_share,
                 proof_share,
                 query_rand,
                 joint_rand,
@@ -1475,6 +2235,47 @@ where
             },
         ))
     }
+}
+
+impl<T, P, const SEED_SIZE: usize> Aggregator<SEED_SIZE, 16> for Prio3<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    type PrepareState = Prio3PrepareState<T::Field, SEED_SIZE>;
+    type PrepareShare = Prio3PrepareShare<T::Field, SEED_SIZE>;
+    type PrepareMessage = Prio3PrepareMessage<SEED_SIZE>;
+
+    /// Begins the Prep process with the other aggregators. The result of this process is
+    /// the aggregator's output share.
+    #[allow(clippy::type_complexity)]
+    fn prepare_init(
+        &self,
+        verify_key: &[u8; SEED_SIZE],
+        ctx: &[u8],
+        agg_id: usize,
+        _agg_param: &Self::AggregationParam,
+        nonce: &[u8; 16],
+        public_share: &Self::PublicShare,
+        msg: &Prio3InputShare<T::Field, SEED_SIZE>,
+    ) -> Result<
+        (
+            Prio3PrepareState<T::Field, SEED_SIZE>,
+            Prio3PrepareShare<T::Field, SEED_SIZE>,
+        ),
+        VdafError,
+    > {
+        let agg_id = self.role_try_from(agg_id)?;
+        self.prepare_init_with_scratch(
+            &mut PrepareScratch::default(),
+            verify_key,
+            ctx,
+            agg_id,
+            nonce,
+            public_share,
+            msg,
+        )
+    }
 
     fn prepare_shares_to_prepare_message<
         M: IntoIterator<Item = Prio3PrepareShare<T::Field, SEED_SIZE>>,
@@ -1484,34 +2285,7 @@ where
         _: &Self::AggregationParam,
         inputs: M,
     ) -> Result<Prio3PrepareMessage<SEED_SIZE>, VdafError> {
-        let mut verifiers = vec![T::Field::zero(); self.typ.verifier_len() * self.num_proofs()];
-        let mut joint_rand_parts = Vec::with_capacity(self.num_aggregators());
-        let mut count = 0;
-        for share in inputs.into_iter() {
-            count += 1;
-
-            if share.verifiers.len() != verifiers.len() {
-                return Err(VdafError::Uncategorized(format!(
-                    "unexpected verifier share length: got {}; want {}",
-                    share.verifiers.len(),
-                    verifiers.len(),
-                )));
-            }
-
-            if self.typ.joint_rand_len() > 0 {
-                let joint_rand_seed_part = share.joint_rand_part.unwrap();
-                joint_rand_parts.push(joint_rand_seed_part);
-            }
-
-            add_assign_vector(&mut verifiers, share.verifiers.iter().copied());
-        }
-
-        if count != self.num_aggregators {
-            return Err(VdafError::Uncategorized(format!(
-                "unexpected message count: got {}; want {}",
-                count, self.num_aggregators,
-            )));
-        }
+        let (verifiers, joint_rand_parts) = self.combine_prepare_shares(inputs)?;
 
         // Check the proof verifiers.
         for verifier in verifiers.chunks(self.typ.verifier_len()) {
@@ -1522,13 +2296,7 @@ where
             }
         }
 
-        let joint_rand_seed = if self.typ.joint_rand_len() > 0 {
-            Some(self.derive_joint_rand_seed(ctx, joint_rand_parts.iter()))
-        } else {
-            None
-        };
-
-        Ok(Prio3PrepareMessage { joint_rand_seed })
+        Ok(self.prepare_message(ctx, &joint_rand_parts))
     }
 
     fn prepare_next(
@@ -1591,15 +2359,23 @@ where
     P: Xof<SEED_SIZE>,
     S: DifferentialPrivacyStrategy,
 {
-    fn add_noise_to_agg_share(
+    fn add_noise_to_agg_share_with_rng<R: RngCore + CryptoRng>(
         &self,
         dp_strategy: &S,
         _agg_param: &Self::AggregationParam,
         agg_share: &mut Self::AggregateShare,
         num_measurements: usize,
+        rng: &mut R,
     ) -> Result<(), VdafError> {
-        self.typ
-            .add_noise_to_result(dp_strategy, &mut agg_share.0, num_measurements)?;
+        dp_strategy
+            .validate_num_aggregators(self.num_aggregators())
+            .map_err(FlpError::from)?;
+        self.typ.add_noise_to_result_with_rng(
+            dp_strategy,
+            &mut agg_share.0,
+            num_measurements,
+            rng,
+        )?;
         Ok(())
     }
 }
@@ -1730,7 +2506,7 @@ mod tests {
         flp::Flp,
         vdaf::{
             equality_comparison_test, fieldvec_roundtrip_test,
-            test_utils::{run_vdaf, run_vdaf_prepare},
+            test_utils::{run_vdaf, run_vdaf_prepare, run_vdaf_sharded},
         },
     };
     use assert_matches::assert_matches;
@@ -1900,6 +2676,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_prio3_sum_vec_shard_streaming() {
+        let nonce = [0x42; 16];
+        for (num_aggregators, num_proofs, bits, len, chunk_length) in
+            [(2, 1, 1, 20, 4), (3, 2, 3, 10, 7), (2, 3, 8, 33, 5)]
+        {
+            let prio3 = Prio3::<
+                SumVec<Field128, ParallelSum<Field128, Mul<Field128>>>,
+                XofTurboShake128,
+                32,
+            >::new(
+                num_aggregators,
+                num_proofs,
+                0xFFFF0000,
+                SumVec::new(bits, len, chunk_length).unwrap(),
+            )
+            .unwrap();
+            let measurement = (0..len as u128)
+                .map(|i| (i * 13) % (1 << bits))
+                .collect::<Vec<_>>();
+            let mut random = vec![0u8; prio3.random_size()];
+            rng().fill(&mut random[..]);
+
+            let (want_public_share, want_input_shares) = prio3
+                .shard_with_random(CTX_STR, &measurement, &nonce, &random)
+                .unwrap();
+            let mut sinks = vec![Vec::new(); usize::from(num_aggregators)];
+            let public_share = prio3
+                .shard_streaming_with_random(
+                    CTX_STR,
+                    measurement.iter().copied(),
+                    &nonce,
+                    &random,
+                    &mut sinks,
+                )
+                .unwrap();
+            assert_eq!(public_share, want_public_share);
+            for (sink, want_input_share) in sinks.iter().zip(want_input_shares.iter()) {
+                assert_eq!(sink, &want_input_share.get_encoded().unwrap());
+            }
+
+            // The streamed shares can be decoded and prepared.
+            let input_shares = sinks
+                .iter()
+                .enumerate()
+                .map(|(agg_id, sink)| {
+                    Prio3InputShare::get_decoded_with_param(&(&prio3, agg_id), sink).unwrap()
+                })
+                .collect::<Vec<_>>();
+            let verify_key = rng().random();
+            let output_shares = run_vdaf_prepare(
+                &prio3,
+                &verify_key,
+                CTX_STR,
+                &(),
+                &nonce,
+                public_share,
+                input_shares,
+            )
+            .unwrap();
+            let aggregate_shares = output_shares
+                .into_iter()
+                .map(|output_share| prio3.aggregate(&(), [output_share]).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                prio3.unshard(&(), aggregate_shares, 1).unwrap(),
+                measurement
+            );
+        }
+
+        let prio3 = Prio3::new_sum_vec(2, 2, 4, 2).unwrap();
+        let mut sinks = vec![Vec::new(); 2];
+        prio3
+            .shard_streaming(CTX_STR, [1, 2, 3], &nonce, &mut sinks)
+            .unwrap_err();
+        prio3
+            .shard_streaming(CTX_STR, [1, 2, 3, 4, 5], &nonce, &mut sinks)
+            .unwrap_err();
+        prio3
+            .shard_streaming(CTX_STR, [1, 2, 4, 0], &nonce, &mut sinks)
+            .unwrap_err();
+        prio3
+            .shard_streaming(CTX_STR, [1, 2, 3, 0], &nonce, &mut sinks[..1])
+            .unwrap_err();
+    }
+
     #[test]
     fn test_prio3_sum_vec_multiproof() {
         let prio3 = Prio3::<
@@ -1925,6 +2787,37 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "crypto-dependencies")]
+    fn test_prio3_sum_vec_field64_multiproof_hmac_sha256_aes128() {
+        type P = Prio3SumVecField64MultiproofHmacSha256Aes128<ParallelSum<Field64, Mul<Field64>>>;
+
+        let prio3 = P::new_sum_vec_field64_multiproof_hmacsha256_aes128(2, 3, 2, 20, 4).unwrap();
+        assert_eq!(prio3.algorithm_id(), 0xFFFF1003);
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [
+                    vec![0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 1, 0, 1, 1, 1, 0, 1],
+                    vec![0, 2, 0, 0, 1, 0, 0, 0, 1, 1, 1, 3, 0, 3, 0, 0, 0, 1, 0, 0],
+                    vec![1, 1, 1, 0, 1, 1, 0, 1, 1, 1, 1, 0, 1, 1, 1, 1, 0, 1, 0, 1],
+                ]
+            )
+            .unwrap(),
+            vec![1, 3, 1, 0, 3, 1, 0, 1, 2, 2, 3, 3, 1, 5, 1, 2, 1, 3, 0, 2],
+        );
+
+        let nonce = [0; 16];
+        test_serialization(&prio3, &vec![1; 20], &nonce).unwrap();
+
+        assert_matches!(
+            P::new_sum_vec_field64_multiproof_hmacsha256_aes128(2, 1, 2, 20, 4),
+            Err(VdafError::Uncategorized(_))
+        );
+    }
+
     #[test]
     #[cfg(feature = "multithreaded")]
     fn test_prio3_sum_vec_multithreaded() {
@@ -2223,6 +3116,51 @@ mod tests {
         test_serialization(&prio3, &3, &[0; 16]).unwrap();
     }
 
+    #[test]
+    fn test_prio3_optional_histogram() {
+        let prio3 = Prio3::new_optional_histogram(2, 4, 2).unwrap();
+
+        assert_eq!(
+            run_vdaf(CTX_STR, &prio3, &(), [Some(0), Some(1), Some(2), Some(3)]).unwrap(),
+            vec![1, 1, 1, 1]
+        );
+        assert_eq!(
+            run_vdaf(CTX_STR, &prio3, &(), [Some(2), None, Some(2), None]).unwrap(),
+            vec![0, 0, 2, 0]
+        );
+        assert_eq!(
+            run_vdaf(CTX_STR, &prio3, &(), [None]).unwrap(),
+            vec![0, 0, 0, 0]
+        );
+        prio3.shard(CTX_STR, &Some(4), &[0; 16]).unwrap_err();
+        test_serialization(&prio3, &None, &[0; 16]).unwrap();
+        test_serialization(&prio3, &Some(3), &[0; 16]).unwrap();
+    }
+
+    #[test]
+    fn test_prio3_exact_weight_count_vec() {
+        let prio3 = Prio3::new_exact_weight_count_vec(2, 4, 2, 3).unwrap();
+
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [
+                    vec![true, true, false, false],
+                    vec![false, true, true, false],
+                    vec![false, false, true, true],
+                ]
+            )
+            .unwrap(),
+            vec![1, 2, 2, 1]
+        );
+        prio3
+            .shard(CTX_STR, &vec![true, false, false, false], &[0; 16])
+            .unwrap_err();
+        test_serialization(&prio3, &vec![true, false, false, true], &[0; 16]).unwrap();
+    }
+
     #[test]
     fn test_prio3_average() {
         let max_measurement = 43_208;
@@ -2240,6 +3178,627 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn test_prio3_batch() {
+        let prio3 = Prio3Batch::<_, XofTurboShake128, 32>::new_batch(
+            2,
+            1,
+            0xFFFF0000,
+            Average::<Field128>::new(100).unwrap(),
+            3,
+        )
+        .unwrap();
+        assert_eq!(
+            run_vdaf(CTX_STR, &prio3, &(), [vec![1, 2, 3], vec![4, 5, 6]]).unwrap(),
+            3.5f64
+        );
+        // The number of measurements is counted across reports, not the number of reports.
+        assert_eq!(
+            run_vdaf(CTX_STR, &prio3, &(), [vec![1, 2, 3], vec![10], vec![]]).unwrap(),
+            4.0f64
+        );
+
+        let prio3 = Prio3Batch::<_, XofTurboShake128, 32>::new_batch(
+            3,
+            2,
+            0xFFFF0000,
+            Histogram::<Field128, ParallelSum<_, _>>::new(5, 2).unwrap(),
+            4,
+        )
+        .unwrap();
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [vec![0, 1, 1, 4], vec![4, 4, 2, 0], vec![3, 3, 3, 3]]
+            )
+            .unwrap(),
+            vec![2, 2, 1, 4, 3]
+        );
+        prio3
+            .shard(CTX_STR, &vec![0, 1, 2, 3, 4], &[0; 16])
+            .unwrap_err();
+
+        Prio3Batch::<_, XofTurboShake128, 32>::new_batch(
+            2,
+            1,
+            0xFFFF0000,
+            Count::<Field64>::new(),
+            0,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn test_prio3_fixedpoint_sum() {
+        use crate::flp::types::fixedpoint_sum::FixedPointSumResult;
+        use fixed::types::{I16F16, I24F8};
+
+        let prio3 = Prio3::new_fixedpoint_sum(
+            2,
+            I24F8::from_num(-10),
+            I24F8::from_num(10),
+            Rounding::Nearest,
+        )
+        .unwrap();
+        assert_eq!(
+            run_vdaf(CTX_STR, &prio3, &(), [1.5, -2.25, 7.0, 100.0]).unwrap(),
+            FixedPointSumResult {
+                sum: 16.25,
+                mean: 4.0625,
+            }
+        );
+
+        let prio3 =
+            Prio3::new_fixedpoint_sum(3, I16F16::ZERO, I16F16::ONE, Rounding::Stochastic).unwrap();
+        let result = run_vdaf(CTX_STR, &prio3, &(), [0.5, 0.25, 0.125, 0.0]).unwrap();
+        assert_eq!(result.sum, 0.875);
+
+        let nonce = [0; 16];
+        test_serialization(&prio3, &0.3, &nonce).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn test_prio3_add_noise() {
+        use crate::dp::{
+            distributions::{
+                EpsilonDeltaDiscreteGaussian, PureDpDiscreteLaplace, RenyiDpSkellam,
+                ZCdpDiscreteGaussian,
+            },
+            noise_rng, EpsilonDeltaBudget, IntegerRenyiDpBudget, PureDpBudget, Rational,
+            ZCdpBudget,
+        };
+
+        // These check that noise can be added to the aggregate shares of each of these types, and
+        // that the result still decodes as expected. The budgets are very large, and the noise is
+        // drawn from a fixed seed, for which every sample is zero. This keeps the test
+        // deterministic.
+        fn add_noise_and_unshard<T, S>(prio3: &Prio3<T, XofTurboShake128, 32>, dp_strategy: &S)
+        where
+            T: TypeWithNoise<S>,
+            S: DifferentialPrivacyStrategy,
+            T::AggregateResult: Debug + PartialEq,
+        {
+            let seed = Seed::from_bytes([0; 32]);
+            let mut agg_shares = Vec::new();
+            for agg_id in 0..prio3.num_aggregators() {
+                let mut agg_share = prio3.aggregate_init(&());
+                prio3
+                    .add_noise_to_agg_share_with_rng(
+                        dp_strategy,
+                        &(),
+                        &mut agg_share,
+                        1,
+                        &mut noise_rng::<XofTurboShake128, 32>(&seed, &[agg_id as u8]),
+                    )
+                    .unwrap();
+                agg_shares.push(agg_share);
+            }
+            assert_eq!(
+                prio3.unshard(&(), agg_shares, 1).unwrap(),
+                prio3.unshard(&(), [prio3.aggregate_init(&())], 1).unwrap()
+            );
+        }
+
+        let laplace = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1000u16, 1).unwrap()).unwrap(),
+        );
+        let gaussian = ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+            Rational::from_unsigned(1000u16, 1).unwrap(),
+        ));
+        let epsilon_delta = EpsilonDeltaDiscreteGaussian::from_budget(
+            EpsilonDeltaBudget::new(
+                Rational::from_unsigned(1000u16, 1).unwrap(),
+                Rational::from_unsigned(1u16, 1000).unwrap(),
+            )
+            .unwrap(),
+        );
+        // The Skellam distribution has polynomial rather than exponential tails, so it needs an
+        // even larger budget for the noise to be zero.
+        let skellam = RenyiDpSkellam::new(
+            IntegerRenyiDpBudget::new(
+                Rational::from_unsigned(500_000_000_000_000_000u64, 1).unwrap(),
+            )
+            .unwrap(),
+            2,
+        )
+        .unwrap();
+
+        let prio3 = Prio3::new_count(2).unwrap();
+        add_noise_and_unshard(&prio3, &laplace);
+        add_noise_and_unshard(&prio3, &gaussian);
+        add_noise_and_unshard(&prio3, &epsilon_delta);
+        add_noise_and_unshard(&prio3, &skellam);
+
+        let prio3 = Prio3::new_sum(2, 10).unwrap();
+        add_noise_and_unshard(&prio3, &laplace);
+        add_noise_and_unshard(&prio3, &gaussian);
+        add_noise_and_unshard(&prio3, &epsilon_delta);
+        add_noise_and_unshard(&prio3, &skellam);
+
+        let prio3 = Prio3::new_average(3, 10).unwrap();
+        add_noise_and_unshard(&prio3, &laplace);
+        add_noise_and_unshard(&prio3, &gaussian);
+        add_noise_and_unshard(&prio3, &epsilon_delta);
+        add_noise_and_unshard(&prio3, &skellam);
+
+        let prio3 = Prio3::new_multihot_count_vec(2, 10, 2, 3).unwrap();
+        add_noise_and_unshard(&prio3, &laplace);
+        add_noise_and_unshard(&prio3, &gaussian);
+        add_noise_and_unshard(&prio3, &epsilon_delta);
+        add_noise_and_unshard(&prio3, &skellam);
+    }
+
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn test_prio3_add_noise_with_rng() {
+        use crate::dp::{distributions::PureDpDiscreteLaplace, noise_rng, PureDpBudget, Rational};
+
+        let prio3 = Prio3::new_histogram(2, 5, 2).unwrap();
+        let dp_strategy = PureDpDiscreteLaplace::from_budget(
+            PureDpBudget::new(Rational::from_unsigned(1u8, 1).unwrap()).unwrap(),
+        );
+        let seed = Seed::from_bytes([1; 32]);
+        let noised_agg_share = |domain_separator: &[u8]| {
+            let mut agg_share = prio3.aggregate_init(&());
+            prio3
+                .add_noise_to_agg_share_with_rng(
+                    &dp_strategy,
+                    &(),
+                    &mut agg_share,
+                    0,
+                    &mut noise_rng::<XofTurboShake128, 32>(&seed, domain_separator),
+                )
+                .unwrap();
+            agg_share
+        };
+
+        // Noise derived from the same seed and domain separator is reproducible, so it can be
+        // checked against a known answer.
+        let agg_share = noised_agg_share(b"aggregator 0");
+        assert_eq!(agg_share, noised_agg_share(b"aggregator 0"));
+        assert_ne!(agg_share, noised_agg_share(b"aggregator 1"));
+        assert_eq!(
+            agg_share.0,
+            [
+                -Field128::from(1),
+                Field128::from(0),
+                Field128::from(3),
+                Field128::from(1),
+                -Field128::from(5),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn test_prio3_distributed_noise() {
+        use crate::dp::{distributions::RenyiDpSkellam, IntegerRenyiDpBudget, Rational};
+
+        // Each of two aggregators adds one share of Skellam noise, and the unsharded aggregate
+        // result carries the sum. With rho = 1/2 and L2 sensitivity `sqrt(2)`, the total Skellam
+        // parameter `mu` is the positive root of 8 mu^2 - 8 mu - 14 = 0, and the variance of the
+        // noise is `2 * mu`.
+        let length = 2000;
+        let prio3 = Prio3::new_multihot_count_vec(2, length, 1, 1).unwrap();
+        let dp_strategy = RenyiDpSkellam::new(
+            IntegerRenyiDpBudget::new(Rational::from_unsigned(1u8, 2).unwrap()).unwrap(),
+            2,
+        )
+        .unwrap();
+
+        let agg_shares = (0..prio3.num_aggregators())
+            .map(|_| {
+                let mut agg_share = prio3.aggregate_init(&());
+                prio3
+                    .add_noise_to_agg_share(&dp_strategy, &(), &mut agg_share, 0)
+                    .unwrap();
+                agg_share
+            })
+            .collect::<Vec<_>>();
+        let result = prio3.unshard(&(), agg_shares, 0).unwrap();
+
+        let modulus = Field128::modulus();
+        let variance = result
+            .into_iter()
+            .map(|x| {
+                let x = if x > modulus / 2 {
+                    -((modulus - x) as f64)
+                } else {
+                    x as f64
+                };
+                x * x
+            })
+            .sum::<f64>()
+            / length as f64;
+        let expected = 2.0 * (8.0 + (64.0f64 + 448.0).sqrt()) / 16.0;
+        assert!(
+            (variance - expected).abs() < 0.2 * expected,
+            "empirical variance {variance}, expected {expected}"
+        );
+
+        // The guarantee would need more noise shares than there are aggregators to add them.
+        let dp_strategy = RenyiDpSkellam::new(
+            IntegerRenyiDpBudget::new(Rational::from_unsigned(1u8, 2).unwrap()).unwrap(),
+            3,
+        )
+        .unwrap();
+        let mut agg_share = prio3.aggregate_init(&());
+        prio3
+            .add_noise_to_agg_share(&dp_strategy, &(), &mut agg_share, 0)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_prio3_prepare_batch() {
+        let prio3 = Prio3::new_count(2).unwrap();
+        test_prepare_batch(&prio3, &[true, false, true, true]);
+
+        let prio3 = Prio3::new_histogram(3, 6, 2).unwrap();
+        test_prepare_batch(&prio3, &[0, 5, 2, 2, 1]);
+    }
+
+    #[test]
+    fn test_prio3_shard_and_check() {
+        let nonce = [0; 16];
+
+        let prio3 = Prio3::new_count(2).unwrap();
+        prio3.shard_and_check(CTX_STR, &true, &nonce).unwrap();
+
+        let prio3 = Prio3::new_sum(3, 100).unwrap();
+        prio3.shard_and_check(CTX_STR, &42, &nonce).unwrap();
+        assert_matches!(
+            prio3.shard_and_check(CTX_STR, &101, &nonce),
+            Err(Prio3ShardCheckError::Shard(VdafError::Flp(_)))
+        );
+
+        let prio3 = Prio3::new_histogram(2, 4, 2).unwrap();
+        let (public_share, input_shares) = prio3.shard_and_check(CTX_STR, &1, &nonce).unwrap();
+        assert_eq!(
+            run_vdaf_sharded(CTX_STR, &prio3, &(), [(public_share, nonce, input_shares)]).unwrap(),
+            vec![0, 1, 0, 0]
+        );
+
+        // A count encoded as 2 fails the only output of the validity circuit.
+        let prio3 = Prio3::<_, XofTurboShake128, 32>::new(
+            2,
+            1,
+            0xFFFF0000,
+            Buggy::new(Count::<Field64>::new(), Bug::Encoding),
+        )
+        .unwrap();
+        assert_matches!(
+            prio3.shard_and_check(CTX_STR, &true, &nonce),
+            Err(Prio3ShardCheckError::InvalidMeasurement { proof: 0, outputs }) => {
+                assert_eq!(outputs, [0]);
+            }
+        );
+
+        // A histogram with two buckets set passes the range check but fails the sum check, which
+        // is the second output of the validity circuit.
+        let prio3 = Prio3::<_, XofTurboShake128, 32>::new(
+            2,
+            2,
+            0xFFFF0000,
+            Buggy::new(
+                Histogram::<Field128, ParallelSum<_, _>>::new(4, 2).unwrap(),
+                Bug::Encoding,
+            ),
+        )
+        .unwrap();
+        assert_matches!(
+            prio3.shard_and_check(CTX_STR, &1, &nonce),
+            Err(Prio3ShardCheckError::InvalidMeasurement { proof: 0, outputs }) => {
+                assert_eq!(outputs, [1]);
+            }
+        );
+
+        // A proof with a corrupted wire seed passes the validity circuit, but not the gadget check.
+        let prio3 = Prio3::<_, XofTurboShake128, 32>::new(
+            2,
+            1,
+            0xFFFF0000,
+            Buggy::new(Count::<Field64>::new(), Bug::Proof),
+        )
+        .unwrap();
+        assert_matches!(
+            prio3.shard_and_check(CTX_STR, &true, &nonce),
+            Err(Prio3ShardCheckError::GadgetCheck {
+                proof: 0,
+                gadget: 0
+            })
+        );
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Bug {
+        /// Adds one to the first coordinate of the encoded measurement.
+        Encoding,
+        /// Adds one to the first element of each proof.
+        Proof,
+    }
+
+    /// Wraps a [`Type`], introducing a bug in the client's computation.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Buggy<T> {
+        inner: T,
+        bug: Bug,
+    }
+
+    impl<T> Buggy<T> {
+        fn new(inner: T, bug: Bug) -> Self {
+            Self { inner, bug }
+        }
+    }
+
+    impl<T: Type> Flp for Buggy<T> {
+        type Field = T::Field;
+
+        fn gadget(&self) -> Vec<Box<dyn crate::flp::Gadget<T::Field>>> {
+            self.inner.gadget()
+        }
+
+        fn num_gadgets(&self) -> usize {
+            self.inner.num_gadgets()
+        }
+
+        fn valid(
+            &self,
+            gadgets: &mut Vec<Box<dyn crate::flp::Gadget<T::Field>>>,
+            input: &[T::Field],
+            joint_rand: &[T::Field],
+            num_shares: usize,
+        ) -> Result<Vec<T::Field>, crate::flp::FlpError> {
+            self.inner.valid(gadgets, input, joint_rand, num_shares)
+        }
+
+        fn input_len(&self) -> usize {
+            self.inner.input_len()
+        }
+
+        fn proof_len(&self) -> usize {
+            self.inner.proof_len()
+        }
+
+        fn verifier_len(&self) -> usize {
+            self.inner.verifier_len()
+        }
+
+        fn joint_rand_len(&self) -> usize {
+            self.inner.joint_rand_len()
+        }
+
+        fn eval_output_len(&self) -> usize {
+            self.inner.eval_output_len()
+        }
+
+        fn prove_rand_len(&self) -> usize {
+            self.inner.prove_rand_len()
+        }
+
+        fn prove(
+            &self,
+            input: &[T::Field],
+            prove_rand: &[T::Field],
+            joint_rand: &[T::Field],
+        ) -> Result<Vec<T::Field>, crate::flp::FlpError> {
+            let mut proof = self.inner.prove(input, prove_rand, joint_rand)?;
+            if self.bug == Bug::Proof {
+                proof[0] += T::Field::one();
+            }
+            Ok(proof)
+        }
+    }
+
+    impl<T: Type> Type for Buggy<T> {
+        type Measurement = T::Measurement;
+        type AggregateResult = T::AggregateResult;
+
+        fn encode_measurement(
+            &self,
+            measurement: &T::Measurement,
+        ) -> Result<Vec<T::Field>, crate::flp::FlpError> {
+            let mut encoded = self.inner.encode_measurement(measurement)?;
+            if self.bug == Bug::Encoding {
+                encoded[0] += T::Field::one();
+            }
+            Ok(encoded)
+        }
+
+        fn truncate(&self, input: Vec<T::Field>) -> Result<Vec<T::Field>, crate::flp::FlpError> {
+            self.inner.truncate(input)
+        }
+
+        fn decode_result(
+            &self,
+            data: &[T::Field],
+            num_measurements: usize,
+        ) -> Result<T::AggregateResult, crate::flp::FlpError> {
+            self.inner.decode_result(data, num_measurements)
+        }
+
+        fn output_len(&self) -> usize {
+            self.inner.output_len()
+        }
+    }
+
+    /// Prepares a batch of reports for `measurements`, checking that the batch APIs agree with
+    /// preparing each report individually and that invalid reports fail only their own entry.
+    fn test_prepare_batch<T, P, const SEED_SIZE: usize>(
+        prio3: &Prio3<T, P, SEED_SIZE>,
+        measurements: &[T::Measurement],
+    ) where
+        T: Type + Sync,
+        P: Xof<SEED_SIZE> + Sync,
+        T::Field: Send + Sync,
+    {
+        let mut verify_key = [0; SEED_SIZE];
+        rng().fill(&mut verify_key[..]);
+
+        let mut reports = measurements
+            .iter()
+            .map(|measurement| {
+                let nonce = rng().random::<[u8; 16]>();
+                let (public_share, input_shares) =
+                    prio3.shard(CTX_STR, measurement, &nonce).unwrap();
+                (nonce, public_share, input_shares)
+            })
+            .collect::<Vec<_>>();
+
+        // Report 0 has a malformed leader share, which is rejected in the first round.
+        let Prio3InputShare::Leader {
+            measurement_share, ..
+        } = &mut reports[0].2[0]
+        else {
+            panic!("unexpected leader input share");
+        };
+        measurement_share.pop();
+
+        let mut prep_states = Vec::new();
+        let mut prep_shares = Vec::new();
+        for agg_id in 0..prio3.num_aggregators() {
+            let report_shares = reports
+                .iter()
+                .map(|(nonce, public_share, input_shares)| {
+                    (nonce, public_share, &input_shares[agg_id])
+                })
+                .collect::<Vec<_>>();
+            let results = prio3
+                .prepare_init_batch(&verify_key, CTX_STR, agg_id, &report_shares)
+                .unwrap();
+            assert_eq!(results.len(), reports.len());
+
+            let mut states = Vec::new();
+            let mut shares = Vec::new();
+            for (result, (nonce, public_share, input_share)) in
+                results.into_iter().zip(report_shares)
+            {
+                let expected = prio3.prepare_init(
+                    &verify_key,
+                    CTX_STR,
+                    agg_id,
+                    &(),
+                    nonce,
+                    public_share,
+                    input_share,
+                );
+                match (result, expected) {
+                    (Ok((state, share)), Ok((expected_state, expected_share))) => {
+                        assert_eq!(state, expected_state);
+                        assert_eq!(share, expected_share);
+                        states.push(Some(state));
+                        shares.push(Some(share));
+                    }
+                    (Err(_), Err(_)) => {
+                        states.push(None);
+                        shares.push(None);
+                    }
+                    (result, expected) => {
+                        panic!("batch result {result:?} does not match {expected:?}")
+                    }
+                }
+            }
+            prep_states.push(states);
+            prep_shares.push(shares);
+        }
+
+        #[cfg(feature = "multithreaded")]
+        for agg_id in 0..prio3.num_aggregators() {
+            let report_shares = reports
+                .iter()
+                .map(|(nonce, public_share, input_shares)| {
+                    (nonce, public_share, &input_shares[agg_id])
+                })
+                .collect::<Vec<_>>();
+            let results = prio3
+                .prepare_init_batch_multithreaded(&verify_key, CTX_STR, agg_id, &report_shares)
+                .unwrap();
+            for (result, expected) in results.into_iter().zip(prep_shares[agg_id].iter()) {
+                assert_eq!(
+                    result.ok().map(|(_, share)| share).as_ref(),
+                    expected.as_ref()
+                );
+            }
+        }
+
+        // Only the report with the malformed share fails in the first round.
+        for (i, share) in prep_shares[0].iter().enumerate() {
+            assert_eq!(share.is_none(), i == 0);
+        }
+
+        for states in prep_states.iter() {
+            let steps = (1..reports.len())
+                .map(|i| {
+                    let mut prep_msg = prio3
+                        .prepare_shares_to_prepare_message(
+                            CTX_STR,
+                            &(),
+                            prep_shares.iter().map(|shares| shares[i].clone().unwrap()),
+                        )
+                        .unwrap();
+                    // Report 1 has a corrupted joint randomness seed, if there is one, which is
+                    // rejected in the second round.
+                    if i == 1 {
+                        if let Some(joint_rand_seed) = prep_msg.joint_rand_seed.as_mut() {
+                            *joint_rand_seed = Seed::from_bytes([0xff; SEED_SIZE]);
+                        }
+                    }
+                    (states[i].clone().unwrap(), prep_msg)
+                })
+                .collect::<Vec<_>>();
+
+            #[cfg(feature = "multithreaded")]
+            let multithreaded_results =
+                prio3.prepare_next_batch_multithreaded(CTX_STR, steps.clone());
+            let results = prio3.prepare_next_batch(CTX_STR, steps.clone());
+            assert_eq!(results.len(), steps.len());
+
+            for (i, (result, (state, prep_msg))) in results.into_iter().zip(steps).enumerate() {
+                let expected = prio3.prepare_next(CTX_STR, state, prep_msg);
+                if i == 0 && prio3.typ.joint_rand_len() > 0 {
+                    assert_matches!(result, Err(VdafError::Uncategorized(_)));
+                    assert_matches!(expected, Err(VdafError::Uncategorized(_)));
+                } else {
+                    assert_matches!(
+                        (result, expected),
+                        (Ok(PrepareTransition::Finish(out)), Ok(PrepareTransition::Finish(expected_out))) => {
+                            assert_eq!(out, expected_out);
+                        }
+                    );
+                }
+            }
+
+            #[cfg(feature = "multithreaded")]
+            for (i, result) in multithreaded_results.into_iter().enumerate() {
+                assert_eq!(result.is_err(), i == 0 && prio3.typ.joint_rand_len() > 0);
+            }
+        }
+    }
+
     #[test]
     fn test_prio3_input_share() {
         let max_measurement = 1;
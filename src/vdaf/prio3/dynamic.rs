@@ -0,0 +1,1014 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A Prio3 VDAF whose circuit and parameters are chosen at runtime.
+//!
+//! [`Prio3`] is generic over its circuit, XOF and seed size, so each configuration is a distinct
+//! type. [`Prio3Dyn`] is built from an algorithm ID and a [`Prio3DynParams`] instead, and
+//! delegates to the corresponding concrete instantiation. Measurements and aggregate results are
+//! represented by [`Prio3DynMeasurement`] and [`Prio3DynAggregateResult`], and shares by
+//! [`Prio3DynValue`], which wraps the concrete share of whichever field the circuit uses.
+
+use super::{
+    Prio3, Prio3InputShare, Prio3PrepareMessage, Prio3PrepareShare, Prio3PrepareState,
+    Prio3PublicShare,
+};
+#[cfg(feature = "crypto-dependencies")]
+use crate::vdaf::xof::XofHmacSha256Aes128;
+use crate::{
+    codec::{CodecError, Encode, ParameterizedDecode},
+    field::{Field128, Field64},
+    flp::{
+        gadgets::{Mul, ParallelSum},
+        types::{Count, Histogram, MultihotCountVec, Sum, SumVec},
+        Type,
+    },
+    vdaf::{
+        xof::{Xof, XofTurboShake128},
+        Aggregatable, AggregateShare, Aggregator, Client, Collector, EncodedAggregatable,
+        OutputShare, PrepareTransition, RetractableAggregatable, Vdaf, VdafError,
+    },
+};
+use std::io::Cursor;
+
+/// The XOF used by a [`Prio3Dyn`] instance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Prio3DynXof {
+    /// TurboSHAKE128, as specified for all Prio3 variants in the VDAF draft.
+    #[default]
+    TurboShake128,
+    /// HMAC-SHA256 and AES128. This XOF is not part of the VDAF spec, and is only used by
+    /// `Prio3SumVecField64MultiproofHmacSha256Aes128`.
+    #[cfg(feature = "crypto-dependencies")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "crypto-dependencies")))]
+    HmacSha256Aes128,
+}
+
+/// The parameters of a [`Prio3Dyn`] instance. Which of these are used depends on the algorithm
+/// ID; the rest are ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prio3DynParams {
+    /// The number of aggregators.
+    pub num_aggregators: u8,
+    /// The length of the measurement, for `Prio3SumVec`, `Prio3Histogram`,
+    /// `Prio3MultihotCountVec` and `Prio3SumVecField64MultiproofHmacSha256Aes128`.
+    pub length: usize,
+    /// The bit width of each element of the measurement, for `Prio3SumVec` and
+    /// `Prio3SumVecField64MultiproofHmacSha256Aes128`.
+    pub bits: usize,
+    /// The chunk length of the parallel sum gadget, for `Prio3SumVec`, `Prio3Histogram`,
+    /// `Prio3MultihotCountVec` and `Prio3SumVecField64MultiproofHmacSha256Aes128`.
+    pub chunk_length: usize,
+    /// The maximum measurement, for `Prio3Sum`.
+    pub max_measurement: u64,
+    /// The maximum number of true values in a measurement, for `Prio3MultihotCountVec`.
+    pub max_weight: usize,
+    /// The number of proofs generated and verified for each report. This must be at least 2 for
+    /// `Prio3SumVecField64MultiproofHmacSha256Aes128`.
+    pub num_proofs: u8,
+    /// The XOF. This must be the XOF identified by the algorithm ID.
+    pub xof: Prio3DynXof,
+}
+
+impl Default for Prio3DynParams {
+    fn default() -> Self {
+        Self {
+            num_aggregators: 2,
+            length: 0,
+            bits: 0,
+            chunk_length: 0,
+            max_measurement: 0,
+            max_weight: 0,
+            num_proofs: 1,
+            xof: Prio3DynXof::default(),
+        }
+    }
+}
+
+/// A measurement for a [`Prio3Dyn`] instance. The variant must match the circuit selected by the
+/// algorithm ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Prio3DynMeasurement {
+    /// A measurement for `Prio3Count`.
+    Count(bool),
+    /// A measurement for `Prio3Sum`.
+    Sum(u64),
+    /// A measurement for `Prio3SumVec` or `Prio3SumVecField64MultiproofHmacSha256Aes128`.
+    SumVec(Vec<u128>),
+    /// A measurement for `Prio3Histogram`.
+    Histogram(usize),
+    /// A measurement for `Prio3MultihotCountVec`.
+    MultihotCountVec(Vec<bool>),
+}
+
+/// The aggregate result of a [`Prio3Dyn`] instance. The variant matches the circuit selected by
+/// the algorithm ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Prio3DynAggregateResult {
+    /// The aggregate result of `Prio3Count`.
+    Count(u64),
+    /// The aggregate result of `Prio3Sum`.
+    Sum(u64),
+    /// The aggregate result of `Prio3SumVec` or `Prio3SumVecField64MultiproofHmacSha256Aes128`.
+    SumVec(Vec<u128>),
+    /// The aggregate result of `Prio3Histogram`.
+    Histogram(Vec<u128>),
+    /// The aggregate result of `Prio3MultihotCountVec`.
+    MultihotCountVec(Vec<u128>),
+}
+
+/// A share, prepare state or other message of a [`Prio3Dyn`] instance, whose concrete type
+/// depends on the field used by the circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Prio3DynValue<A, B> {
+    /// A value of a circuit over [`Field64`].
+    Field64(A),
+    /// A value of a circuit over [`Field128`].
+    Field128(B),
+}
+
+/// An input share of a [`Prio3Dyn`] instance.
+pub type Prio3DynInputShare =
+    Prio3DynValue<Prio3InputShare<Field64, 32>, Prio3InputShare<Field128, 32>>;
+
+/// The prepare state of a [`Prio3Dyn`] instance.
+pub type Prio3DynPrepareState =
+    Prio3DynValue<Prio3PrepareState<Field64, 32>, Prio3PrepareState<Field128, 32>>;
+
+/// A prepare share of a [`Prio3Dyn`] instance.
+pub type Prio3DynPrepareShare =
+    Prio3DynValue<Prio3PrepareShare<Field64, 32>, Prio3PrepareShare<Field128, 32>>;
+
+/// An output share of a [`Prio3Dyn`] instance.
+pub type Prio3DynOutputShare = Prio3DynValue<OutputShare<Field64>, OutputShare<Field128>>;
+
+/// An aggregate share of a [`Prio3Dyn`] instance.
+pub type Prio3DynAggregateShare = Prio3DynValue<AggregateShare<Field64>, AggregateShare<Field128>>;
+
+impl<A: Encode, B: Encode> Encode for Prio3DynValue<A, B> {
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        match self {
+            Self::Field64(value) => value.encode(bytes),
+            Self::Field128(value) => value.encode(bytes),
+        }
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        match self {
+            Self::Field64(value) => value.encoded_len(),
+            Self::Field128(value) => value.encoded_len(),
+        }
+    }
+}
+
+impl From<Prio3DynOutputShare> for Prio3DynAggregateShare {
+    fn from(output_share: Prio3DynOutputShare) -> Self {
+        match output_share {
+            Prio3DynValue::Field64(share) => Self::Field64(share.into()),
+            Prio3DynValue::Field128(share) => Self::Field128(share.into()),
+        }
+    }
+}
+
+impl Aggregatable for Prio3DynAggregateShare {
+    type OutputShare = Prio3DynOutputShare;
+
+    fn merge(&mut self, agg_share: &Self) -> Result<(), VdafError> {
+        match (self, agg_share) {
+            (Self::Field64(share), Self::Field64(other)) => share.merge(other),
+            (Self::Field128(share), Self::Field128(other)) => share.merge(other),
+            _ => Err(field_mismatch()),
+        }
+    }
+
+    fn accumulate(&mut self, output_share: &Self::OutputShare) -> Result<(), VdafError> {
+        match (self, output_share) {
+            (Self::Field64(share), Prio3DynValue::Field64(other)) => share.accumulate(other),
+            (Self::Field128(share), Prio3DynValue::Field128(other)) => share.accumulate(other),
+            _ => Err(field_mismatch()),
+        }
+    }
+}
+
+impl EncodedAggregatable for Prio3DynAggregateShare {
+    fn accumulate_encoded(&mut self, encoded_output_share: &[u8]) -> Result<(), VdafError> {
+        match self {
+            Self::Field64(share) => share.accumulate_encoded(encoded_output_share),
+            Self::Field128(share) => share.accumulate_encoded(encoded_output_share),
+        }
+    }
+}
+
+impl RetractableAggregatable for Prio3DynAggregateShare {
+    fn subtract(&mut self, agg_share: &Self) -> Result<(), VdafError> {
+        match (self, agg_share) {
+            (Self::Field64(share), Self::Field64(other)) => share.subtract(other),
+            (Self::Field128(share), Self::Field128(other)) => share.subtract(other),
+            _ => Err(field_mismatch()),
+        }
+    }
+
+    fn retract(&mut self, output_share: &Self::OutputShare) -> Result<(), VdafError> {
+        match (self, output_share) {
+            (Self::Field64(share), Prio3DynValue::Field64(other)) => share.retract(other),
+            (Self::Field128(share), Prio3DynValue::Field128(other)) => share.retract(other),
+            _ => Err(field_mismatch()),
+        }
+    }
+}
+
+impl<A, B, C, D> ParameterizedDecode<Prio3DynValue<A, B>> for Prio3DynValue<C, D>
+where
+    C: ParameterizedDecode<A>,
+    D: ParameterizedDecode<B>,
+{
+    fn decode_with_param(
+        decoding_parameter: &Prio3DynValue<A, B>,
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        match decoding_parameter {
+            Prio3DynValue::Field64(param) => C::decode_with_param(param, bytes).map(Self::Field64),
+            Prio3DynValue::Field128(param) => {
+                D::decode_with_param(param, bytes).map(Self::Field128)
+            }
+        }
+    }
+}
+
+impl ParameterizedDecode<Prio3DynPrepareState> for Prio3PrepareMessage<32> {
+    fn decode_with_param(
+        decoding_parameter: &Prio3DynPrepareState,
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        match decoding_parameter {
+            Prio3DynValue::Field64(state) => Self::decode_with_param(state, bytes),
+            Prio3DynValue::Field128(state) => Self::decode_with_param(state, bytes),
+        }
+    }
+}
+
+fn field_mismatch() -> VdafError {
+    VdafError::Uncategorized("value is for a circuit over a different field".into())
+}
+
+/// A Prio3 circuit whose measurement and aggregate result can be converted to and from
+/// [`Prio3DynMeasurement`] and [`Prio3DynAggregateResult`].
+trait DynType: Type {
+    fn measurement(measurement: &Prio3DynMeasurement) -> Result<Self::Measurement, VdafError>;
+
+    fn aggregate_result(agg_result: Self::AggregateResult) -> Prio3DynAggregateResult;
+}
+
+fn measurement_mismatch(measurement: &Prio3DynMeasurement) -> VdafError {
+    VdafError::Uncategorized(format!(
+        "measurement {measurement:?} does not match the circuit"
+    ))
+}
+
+impl DynType for Count<Field64> {
+    fn measurement(measurement: &Prio3DynMeasurement) -> Result<bool, VdafError> {
+        match measurement {
+            Prio3DynMeasurement::Count(measurement) => Ok(*measurement),
+            _ => Err(measurement_mismatch(measurement)),
+        }
+    }
+
+    fn aggregate_result(agg_result: u64) -> Prio3DynAggregateResult {
+        Prio3DynAggregateResult::Count(agg_result)
+    }
+}
+
+impl DynType for Sum<Field64> {
+    fn measurement(measurement: &Prio3DynMeasurement) -> Result<u64, VdafError> {
+        match measurement {
+            Prio3DynMeasurement::Sum(measurement) => Ok(*measurement),
+            _ => Err(measurement_mismatch(measurement)),
+        }
+    }
+
+    fn aggregate_result(agg_result: u64) -> Prio3DynAggregateResult {
+        Prio3DynAggregateResult::Sum(agg_result)
+    }
+}
+
+impl DynType for SumVec<Field128, ParallelSum<Field128, Mul<Field128>>> {
+    fn measurement(measurement: &Prio3DynMeasurement) -> Result<Vec<u128>, VdafError> {
+        match measurement {
+            Prio3DynMeasurement::SumVec(measurement) => Ok(measurement.clone()),
+            _ => Err(measurement_mismatch(measurement)),
+        }
+    }
+
+    fn aggregate_result(agg_result: Vec<u128>) -> Prio3DynAggregateResult {
+        Prio3DynAggregateResult::SumVec(agg_result)
+    }
+}
+
+impl DynType for SumVec<Field64, ParallelSum<Field64, Mul<Field64>>> {
+    fn measurement(measurement: &Prio3DynMeasurement) -> Result<Vec<u64>, VdafError> {
+        match measurement {
+            Prio3DynMeasurement::SumVec(measurement) => measurement
+                .iter()
+                .map(|element| {
+                    u64::try_from(*element).map_err(|_| {
+                        VdafError::Uncategorized(format!(
+                            "measurement element {element} is too large for Field64"
+                        ))
+                    })
+                })
+                .collect(),
+            _ => Err(measurement_mismatch(measurement)),
+        }
+    }
+
+    fn aggregate_result(agg_result: Vec<u64>) -> Prio3DynAggregateResult {
+        Prio3DynAggregateResult::SumVec(agg_result.into_iter().map(u128::from).collect())
+    }
+}
+
+impl DynType for Histogram<Field128, ParallelSum<Field128, Mul<Field128>>> {
+    fn measurement(measurement: &Prio3DynMeasurement) -> Result<usize, VdafError> {
+        match measurement {
+            Prio3DynMeasurement::Histogram(measurement) => Ok(*measurement),
+            _ => Err(measurement_mismatch(measurement)),
+        }
+    }
+
+    fn aggregate_result(agg_result: Vec<u128>) -> Prio3DynAggregateResult {
+        Prio3DynAggregateResult::Histogram(agg_result)
+    }
+}
+
+impl DynType for MultihotCountVec<Field128, ParallelSum<Field128, Mul<Field128>>> {
+    fn measurement(measurement: &Prio3DynMeasurement) -> Result<Vec<bool>, VdafError> {
+        match measurement {
+            Prio3DynMeasurement::MultihotCountVec(measurement) => Ok(measurement.clone()),
+            _ => Err(measurement_mismatch(measurement)),
+        }
+    }
+
+    fn aggregate_result(agg_result: Vec<u128>) -> Prio3DynAggregateResult {
+        Prio3DynAggregateResult::MultihotCountVec(agg_result)
+    }
+}
+
+/// The concrete instantiations of Prio3 for a given XOF.
+#[derive(Clone, Debug)]
+enum Circuit<P: Xof<32>> {
+    Count(Prio3<Count<Field64>, P, 32>),
+    Sum(Prio3<Sum<Field64>, P, 32>),
+    SumVec(Prio3<SumVec<Field128, ParallelSum<Field128, Mul<Field128>>>, P, 32>),
+    Histogram(Prio3<Histogram<Field128, ParallelSum<Field128, Mul<Field128>>>, P, 32>),
+    MultihotCountVec(
+        Prio3<MultihotCountVec<Field128, ParallelSum<Field128, Mul<Field128>>>, P, 32>,
+    ),
+    SumVecField64Multiproof(Prio3<SumVec<Field64, ParallelSum<Field64, Mul<Field64>>>, P, 32>),
+}
+
+impl<P: Xof<32>> Circuit<P> {
+    fn new(algorithm_id: u32, params: &Prio3DynParams) -> Result<Self, VdafError> {
+        let Prio3DynParams {
+            num_aggregators,
+            length,
+            bits,
+            chunk_length,
+            max_measurement,
+            max_weight,
+            num_proofs,
+            xof: _,
+        } = *params;
+
+        Ok(match algorithm_id {
+            0x00000001 => Self::Count(Prio3::new(
+                num_aggregators,
+                num_proofs,
+                algorithm_id,
+                Count::new(),
+            )?),
+            0x00000002 => Self::Sum(Prio3::new(
+                num_aggregators,
+                num_proofs,
+                algorithm_id,
+                Sum::new(max_measurement)?,
+            )?),
+            0x00000003 => Self::SumVec(Prio3::new(
+                num_aggregators,
+                num_proofs,
+                algorithm_id,
+                SumVec::new(bits, length, chunk_length)?,
+            )?),
+            0x00000004 => Self::Histogram(Prio3::new(
+                num_aggregators,
+                num_proofs,
+                algorithm_id,
+                Histogram::new(length, chunk_length)?,
+            )?),
+            0x00000005 => Self::MultihotCountVec(Prio3::new(
+                num_aggregators,
+                num_proofs,
+                algorithm_id,
+                MultihotCountVec::new(length, max_weight, chunk_length)?,
+            )?),
+            0xFFFF1003 => {
+                if num_proofs < 2 {
+                    return Err(VdafError::Uncategorized(
+                        "at least two proofs are required with Field64".to_string(),
+                    ));
+                }
+                Self::SumVecField64Multiproof(Prio3::new(
+                    num_aggregators,
+                    num_proofs,
+                    algorithm_id,
+                    SumVec::new(bits, length, chunk_length)?,
+                )?)
+            }
+            _ => {
+                return Err(VdafError::Uncategorized(format!(
+                    "unsupported algorithm ID: {algorithm_id:#010x}"
+                )))
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Inner {
+    TurboShake128(Circuit<XofTurboShake128>),
+    #[cfg(feature = "crypto-dependencies")]
+    HmacSha256Aes128(Circuit<XofHmacSha256Aes128>),
+}
+
+/// Evaluates `$body` with `$vdaf` bound to the concrete Prio3 instance of `$prio3` and `$wrap`
+/// bound to the [`Prio3DynValue`] variant for the field of its circuit.
+macro_rules! dispatch {
+    ($prio3:expr, $vdaf:ident, $wrap:ident => $body:expr) => {
+        match &$prio3.inner {
+            Inner::TurboShake128(circuit) => dispatch_circuit!(circuit, $vdaf, $wrap => $body),
+            #[cfg(feature = "crypto-dependencies")]
+            Inner::HmacSha256Aes128(circuit) => dispatch_circuit!(circuit, $vdaf, $wrap => $body),
+        }
+    };
+}
+
+macro_rules! dispatch_circuit {
+    ($circuit:expr, $vdaf:ident, $wrap:ident => $body:expr) => {
+        match $circuit {
+            Circuit::Count($vdaf) => {
+                #[allow(unused_imports)]
+                use Prio3DynValue::Field64 as $wrap;
+                $body
+            }
+            Circuit::Sum($vdaf) => {
+                #[allow(unused_imports)]
+                use Prio3DynValue::Field64 as $wrap;
+                $body
+            }
+            Circuit::SumVec($vdaf) => {
+                #[allow(unused_imports)]
+                use Prio3DynValue::Field128 as $wrap;
+                $body
+            }
+            Circuit::Histogram($vdaf) => {
+                #[allow(unused_imports)]
+                use Prio3DynValue::Field128 as $wrap;
+                $body
+            }
+            Circuit::MultihotCountVec($vdaf) => {
+                #[allow(unused_imports)]
+                use Prio3DynValue::Field128 as $wrap;
+                $body
+            }
+            Circuit::SumVecField64Multiproof($vdaf) => {
+                #[allow(unused_imports)]
+                use Prio3DynValue::Field64 as $wrap;
+                $body
+            }
+        }
+    };
+}
+
+/// A Prio3 VDAF whose circuit, parameters and XOF are chosen at runtime. This implements the same
+/// traits as [`Prio3`] by delegating to the concrete instantiation selected by the algorithm ID.
+///
+/// The supported algorithm IDs are those of `Prio3Count` (`0x00000001`), `Prio3Sum`
+/// (`0x00000002`), `Prio3SumVec` (`0x00000003`), `Prio3Histogram` (`0x00000004`),
+/// `Prio3MultihotCountVec` (`0x00000005`) and `Prio3SumVecField64MultiproofHmacSha256Aes128`
+/// (`0xFFFF1003`). Each algorithm ID identifies its XOF: the IDs from the VDAF spec require
+/// `Prio3DynXof::TurboShake128`, and `0xFFFF1003` requires `Prio3DynXof::HmacSha256Aes128`.
+#[derive(Clone, Debug)]
+pub struct Prio3Dyn {
+    inner: Inner,
+}
+
+impl Prio3Dyn {
+    /// Construct the Prio3 instance with the given algorithm ID and parameters.
+    pub fn new(algorithm_id: u32, params: &Prio3DynParams) -> Result<Self, VdafError> {
+        if algorithm_id == 0xFFFF1003 && params.xof == Prio3DynXof::TurboShake128 {
+            return Err(VdafError::Uncategorized(format!(
+                "algorithm ID {algorithm_id:#010x} requires the HmacSha256Aes128 XOF"
+            )));
+        }
+        if algorithm_id != 0xFFFF1003 && params.xof != Prio3DynXof::TurboShake128 {
+            return Err(VdafError::Uncategorized(format!(
+                "algorithm ID {algorithm_id:#010x} requires the TurboShake128 XOF"
+            )));
+        }
+
+        let inner = match params.xof {
+            Prio3DynXof::TurboShake128 => Inner::TurboShake128(Circuit::new(algorithm_id, params)?),
+            #[cfg(feature = "crypto-dependencies")]
+            Prio3DynXof::HmacSha256Aes128 => {
+                Inner::HmacSha256Aes128(Circuit::new(algorithm_id, params)?)
+            }
+        };
+        Ok(Self { inner })
+    }
+}
+
+impl Vdaf for Prio3Dyn {
+    type Measurement = Prio3DynMeasurement;
+    type AggregateResult = Prio3DynAggregateResult;
+    type AggregationParam = ();
+    type PublicShare = Prio3PublicShare<32>;
+    type InputShare = Prio3DynInputShare;
+    type OutputShare = Prio3DynOutputShare;
+    type AggregateShare = Prio3DynAggregateShare;
+
+    fn algorithm_id(&self) -> u32 {
+        dispatch!(self, vdaf, _Wrap => vdaf.algorithm_id())
+    }
+
+    fn num_aggregators(&self) -> usize {
+        dispatch!(self, vdaf, _Wrap => vdaf.num_aggregators())
+    }
+}
+
+impl ParameterizedDecode<Prio3Dyn> for Prio3PublicShare<32> {
+    fn decode_with_param(
+        decoding_parameter: &Prio3Dyn,
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        dispatch!(decoding_parameter, vdaf, _Wrap => Self::decode_with_param(vdaf, bytes))
+    }
+}
+
+impl<'a> ParameterizedDecode<(&'a Prio3Dyn, usize)> for Prio3DynInputShare {
+    fn decode_with_param(
+        (prio3, agg_id): &(&'a Prio3Dyn, usize),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        dispatch!(prio3, vdaf, Wrap => {
+            Prio3InputShare::decode_with_param(&(vdaf, *agg_id), bytes).map(Wrap)
+        })
+    }
+}
+
+impl<'a> ParameterizedDecode<(&'a Prio3Dyn, &'a ())> for Prio3DynOutputShare {
+    fn decode_with_param(
+        (prio3, agg_param): &(&'a Prio3Dyn, &'a ()),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        dispatch!(prio3, vdaf, Wrap => {
+            OutputShare::decode_with_param(&(vdaf, *agg_param), bytes).map(Wrap)
+        })
+    }
+}
+
+impl<'a> ParameterizedDecode<(&'a Prio3Dyn, &'a ())> for Prio3DynAggregateShare {
+    fn decode_with_param(
+        (prio3, agg_param): &(&'a Prio3Dyn, &'a ()),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        dispatch!(prio3, vdaf, Wrap => {
+            AggregateShare::decode_with_param(&(vdaf, *agg_param), bytes).map(Wrap)
+        })
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn shard<T: DynType, P: Xof<32>>(
+    vdaf: &Prio3<T, P, 32>,
+    ctx: &[u8],
+    measurement: &Prio3DynMeasurement,
+    nonce: &[u8; 16],
+) -> Result<(Prio3PublicShare<32>, Vec<Prio3InputShare<T::Field, 32>>), VdafError> {
+    vdaf.shard(ctx, &T::measurement(measurement)?, nonce)
+}
+
+fn unshard<T: DynType, P: Xof<32>>(
+    vdaf: &Prio3<T, P, 32>,
+    agg_shares: Vec<AggregateShare<T::Field>>,
+    num_measurements: usize,
+) -> Result<Prio3DynAggregateResult, VdafError> {
+    vdaf.unshard(&(), agg_shares, num_measurements)
+        .map(T::aggregate_result)
+}
+
+impl Client<16> for Prio3Dyn {
+    fn shard(
+        &self,
+        ctx: &[u8],
+        measurement: &Prio3DynMeasurement,
+        nonce: &[u8; 16],
+    ) -> Result<(Self::PublicShare, Vec<Self::InputShare>), VdafError> {
+        dispatch!(self, vdaf, Wrap => {
+            let (public_share, input_shares) = shard(vdaf, ctx, measurement, nonce)?;
+            Ok((public_share, input_shares.into_iter().map(Wrap).collect()))
+        })
+    }
+}
+
+impl Aggregator<32, 16> for Prio3Dyn {
+    type PrepareState = Prio3DynPrepareState;
+    type PrepareShare = Prio3DynPrepareShare;
+    type PrepareMessage = Prio3PrepareMessage<32>;
+
+    fn prepare_init(
+        &self,
+        verify_key: &[u8; 32],
+        ctx: &[u8],
+        agg_id: usize,
+        agg_param: &(),
+        nonce: &[u8; 16],
+        public_share: &Self::PublicShare,
+        input_share: &Self::InputShare,
+    ) -> Result<(Self::PrepareState, Self::PrepareShare), VdafError> {
+        dispatch!(self, vdaf, Wrap => {
+            let Wrap(input_share) = input_share else {
+                return Err(field_mismatch());
+            };
+            let (prep_state, prep_share) = vdaf.prepare_init(
+                verify_key,
+                ctx,
+                agg_id,
+                agg_param,
+                nonce,
+                public_share,
+                input_share,
+            )?;
+            Ok((Wrap(prep_state), Wrap(prep_share)))
+        })
+    }
+
+    fn prepare_shares_to_prepare_message<M: IntoIterator<Item = Self::PrepareShare>>(
+        &self,
+        ctx: &[u8],
+        agg_param: &(),
+        inputs: M,
+    ) -> Result<Self::PrepareMessage, VdafError> {
+        dispatch!(self, vdaf, Wrap => {
+            let inputs = inputs
+                .into_iter()
+                .map(|prep_share| match prep_share {
+                    Wrap(prep_share) => Ok(prep_share),
+                    _ => Err(field_mismatch()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            vdaf.prepare_shares_to_prepare_message(ctx, agg_param, inputs)
+        })
+    }
+
+    fn prepare_next(
+        &self,
+        ctx: &[u8],
+        state: Self::PrepareState,
+        input: Self::PrepareMessage,
+    ) -> Result<PrepareTransition<Self, 32, 16>, VdafError> {
+        dispatch!(self, vdaf, Wrap => {
+            let Wrap(state) = state else {
+                return Err(field_mismatch());
+            };
+            Ok(match vdaf.prepare_next(ctx, state, input)? {
+                PrepareTransition::Continue(prep_state, prep_share) => {
+                    PrepareTransition::Continue(Wrap(prep_state), Wrap(prep_share))
+                }
+                PrepareTransition::Finish(output_share) => {
+                    PrepareTransition::Finish(Wrap(output_share))
+                }
+            })
+        })
+    }
+
+    fn aggregate_init(&self, agg_param: &()) -> Self::AggregateShare {
+        dispatch!(self, vdaf, Wrap => Wrap(vdaf.aggregate_init(agg_param)))
+    }
+
+    fn is_agg_param_valid(_cur: &(), prev: &[()]) -> bool {
+        prev.is_empty()
+    }
+}
+
+impl Collector for Prio3Dyn {
+    fn unshard<M: IntoIterator<Item = Self::AggregateShare>>(
+        &self,
+        _agg_param: &(),
+        agg_shares: M,
+        num_measurements: usize,
+    ) -> Result<Prio3DynAggregateResult, VdafError> {
+        dispatch!(self, vdaf, Wrap => {
+            let agg_shares = agg_shares
+                .into_iter()
+                .map(|agg_share| match agg_share {
+                    Wrap(agg_share) => Ok(agg_share),
+                    _ => Err(field_mismatch()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            unshard(vdaf, agg_shares, num_measurements)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldElement;
+    use crate::vdaf::{prio3::Prio3Count, test_utils::run_vdaf};
+    use assert_matches::assert_matches;
+    use rand::{rng, Rng};
+
+    const CTX_STR: &[u8] = b"prio3dyn ctx";
+
+    #[test]
+    fn run_dynamic() {
+        let prio3 = Prio3Dyn::new(0x00000001, &Prio3DynParams::default()).unwrap();
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [true, false, true].map(Prio3DynMeasurement::Count)
+            )
+            .unwrap(),
+            Prio3DynAggregateResult::Count(2),
+        );
+
+        let params = Prio3DynParams {
+            num_aggregators: 3,
+            max_measurement: 100,
+            ..Default::default()
+        };
+        let prio3 = Prio3Dyn::new(0x00000002, &params).unwrap();
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [1, 100, 42].map(Prio3DynMeasurement::Sum)
+            )
+            .unwrap(),
+            Prio3DynAggregateResult::Sum(143),
+        );
+
+        let params = Prio3DynParams {
+            length: 3,
+            bits: 2,
+            chunk_length: 2,
+            num_proofs: 2,
+            ..Default::default()
+        };
+        let prio3 = Prio3Dyn::new(0x00000003, &params).unwrap();
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [vec![1, 2, 3], vec![3, 0, 1]].map(Prio3DynMeasurement::SumVec)
+            )
+            .unwrap(),
+            Prio3DynAggregateResult::SumVec(vec![4, 2, 4]),
+        );
+
+        let params = Prio3DynParams {
+            length: 4,
+            chunk_length: 2,
+            ..Default::default()
+        };
+        let prio3 = Prio3Dyn::new(0x00000004, &params).unwrap();
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [0, 3, 3].map(Prio3DynMeasurement::Histogram)
+            )
+            .unwrap(),
+            Prio3DynAggregateResult::Histogram(vec![1, 0, 0, 2]),
+        );
+
+        let params = Prio3DynParams {
+            length: 3,
+            max_weight: 2,
+            chunk_length: 2,
+            ..Default::default()
+        };
+        let prio3 = Prio3Dyn::new(0x00000005, &params).unwrap();
+        assert_eq!(
+            run_vdaf(
+                CTX_STR,
+                &prio3,
+                &(),
+                [vec![true, false, true], vec![false, false, true]]
+                    .map(Prio3DynMeasurement::MultihotCountVec)
+            )
+            .unwrap(),
+            Prio3DynAggregateResult::MultihotCountVec(vec![1, 0, 2]),
+        );
+
+        #[cfg(feature = "crypto-dependencies")]
+        {
+            let params = Prio3DynParams {
+                length: 3,
+                bits: 2,
+                chunk_length: 2,
+                num_proofs: 3,
+                xof: Prio3DynXof::HmacSha256Aes128,
+                ..Default::default()
+            };
+            let prio3 = Prio3Dyn::new(0xFFFF1003, &params).unwrap();
+            assert_eq!(
+                run_vdaf(
+                    CTX_STR,
+                    &prio3,
+                    &(),
+                    [vec![1, 2, 3], vec![3, 0, 1]].map(Prio3DynMeasurement::SumVec)
+                )
+                .unwrap(),
+                Prio3DynAggregateResult::SumVec(vec![4, 2, 4]),
+            );
+        }
+    }
+
+    #[test]
+    fn interoperates_with_concrete() {
+        let prio3 = Prio3Dyn::new(0x00000001, &Prio3DynParams::default()).unwrap();
+        let concrete = Prio3Count::new_count(2).unwrap();
+        assert_eq!(prio3.algorithm_id(), concrete.algorithm_id());
+        assert_eq!(prio3.num_aggregators(), concrete.num_aggregators());
+
+        let verify_key = rng().random();
+        let nonce = rng().random();
+        let (public_share, input_shares) = concrete.shard(CTX_STR, &true, &nonce).unwrap();
+
+        // Shares encoded by the concrete instance are decoded and prepared by the dynamic one.
+        let public_share =
+            Prio3PublicShare::get_decoded_with_param(&prio3, &public_share.get_encoded().unwrap())
+                .unwrap();
+        let mut prep_states = Vec::new();
+        let mut prep_shares = Vec::new();
+        for (agg_id, input_share) in input_shares.iter().enumerate() {
+            let input_share = Prio3DynInputShare::get_decoded_with_param(
+                &(&prio3, agg_id),
+                &input_share.get_encoded().unwrap(),
+            )
+            .unwrap();
+            let (prep_state, prep_share) = prio3
+                .prepare_init(
+                    &verify_key,
+                    CTX_STR,
+                    agg_id,
+                    &(),
+                    &nonce,
+                    &public_share,
+                    &input_share,
+                )
+                .unwrap();
+            let prep_share = Prio3DynPrepareShare::get_decoded_with_param(
+                &prep_state,
+                &prep_share.get_encoded().unwrap(),
+            )
+            .unwrap();
+            prep_states.push(prep_state);
+            prep_shares.push(prep_share);
+        }
+        let prep_msg = prio3
+            .prepare_shares_to_prepare_message(CTX_STR, &(), prep_shares)
+            .unwrap();
+
+        let agg_shares = prep_states
+            .into_iter()
+            .map(|prep_state| {
+                let PrepareTransition::Finish(output_share) = prio3
+                    .prepare_next(CTX_STR, prep_state, prep_msg.clone())
+                    .unwrap()
+                else {
+                    panic!("unexpected transition");
+                };
+                let agg_share = prio3.aggregate(&(), [output_share]).unwrap();
+                AggregateShare::get_decoded_with_param(
+                    &(&concrete, &()),
+                    &agg_share.get_encoded().unwrap(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(concrete.unshard(&(), agg_shares, 1).unwrap(), 1);
+    }
+
+    #[test]
+    fn retract_subtract() {
+        let output_share = Prio3DynValue::Field64(OutputShare::from(Field64::random_vector(3)));
+        let want = Prio3DynValue::Field64(AggregateShare::from(Field64::random_vector(3)));
+
+        let mut got = want.clone();
+        got.accumulate(&output_share).unwrap();
+        got.retract(&output_share).unwrap();
+        assert_eq!(got, want);
+
+        let other = Prio3DynAggregateShare::from(output_share);
+        got.merge(&other).unwrap();
+        got.subtract(&other).unwrap();
+        assert_eq!(got, want);
+
+        got.retract(&Prio3DynValue::Field128(OutputShare::from(
+            Field128::random_vector(3),
+        )))
+        .unwrap_err();
+        got.subtract(&Prio3DynValue::Field128(AggregateShare::from(
+            Field128::random_vector(3),
+        )))
+        .unwrap_err();
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn errors() {
+        assert_matches!(
+            Prio3Dyn::new(0xFFFF0000, &Prio3DynParams::default()),
+            Err(VdafError::Uncategorized(_))
+        );
+        assert_matches!(
+            Prio3Dyn::new(0x00000004, &Prio3DynParams::default()),
+            Err(VdafError::Flp(_))
+        );
+        let params = Prio3DynParams {
+            length: 3,
+            bits: 2,
+            chunk_length: 2,
+            num_proofs: 3,
+            ..Default::default()
+        };
+        assert_matches!(
+            Prio3Dyn::new(0xFFFF1003, &params),
+            Err(VdafError::Uncategorized(_))
+        );
+        #[cfg(feature = "crypto-dependencies")]
+        assert_matches!(
+            Prio3Dyn::new(
+                0xFFFF1003,
+                &Prio3DynParams {
+                    num_proofs: 1,
+                    xof: Prio3DynXof::HmacSha256Aes128,
+                    ..params
+                }
+            ),
+            Err(VdafError::Uncategorized(_))
+        );
+        #[cfg(feature = "crypto-dependencies")]
+        for algorithm_id in 0x00000001..=0x00000005 {
+            assert_matches!(
+                Prio3Dyn::new(
+                    algorithm_id,
+                    &Prio3DynParams {
+                        xof: Prio3DynXof::HmacSha256Aes128,
+                        ..params.clone()
+                    }
+                ),
+                Err(VdafError::Uncategorized(_))
+            );
+        }
+
+        let count = Prio3Dyn::new(0x00000001, &Prio3DynParams::default()).unwrap();
+        let nonce = rng().random();
+        assert_matches!(
+            count.shard(CTX_STR, &Prio3DynMeasurement::Sum(1), &nonce),
+            Err(VdafError::Uncategorized(_))
+        );
+
+        // Shares of a circuit over a different field are rejected.
+        let params = Prio3DynParams {
+            length: 2,
+            chunk_length: 1,
+            ..Default::default()
+        };
+        let histogram = Prio3Dyn::new(0x00000004, &params).unwrap();
+        let (public_share, input_shares) = histogram
+            .shard(CTX_STR, &Prio3DynMeasurement::Histogram(1), &nonce)
+            .unwrap();
+        assert_matches!(
+            count.prepare_init(
+                &[0; 32],
+                CTX_STR,
+                0,
+                &(),
+                &nonce,
+                &public_share,
+                &input_shares[0],
+            ),
+            Err(VdafError::Uncategorized(_))
+        );
+        let mut agg_share = count.aggregate_init(&());
+        assert_matches!(
+            agg_share.merge(&histogram.aggregate_init(&())),
+            Err(VdafError::Uncategorized(_))
+        );
+    }
+}
@@ -0,0 +1,534 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Client-side local differential privacy for Prio3 measurements.
+//!
+//! Server-side noise (see [`AggregatorWithNoise`](crate::vdaf::AggregatorWithNoise)) protects
+//! measurements as long as enough aggregators are honest. The mechanisms in this module instead
+//! randomize each measurement on the client before it is sharded, which gives each client
+//! plausible deniability even if all aggregators and the collector collude. The randomized
+//! measurement is still a valid measurement for the Prio3 type, so its validity proof passes.
+//!
+//! The aggregate of randomized measurements is a biased estimate of the true aggregate. The
+//! collector can pass the output of [`Collector::unshard`](crate::vdaf::Collector::unshard) to
+//! [`LocalDpMechanism::debias`] to obtain an unbiased estimate.
+//!
+//! Randomization is exact: probabilities involving `exp(epsilon)` are realized with the exact
+//! Bernoulli samplers from [`crate::dp::distributions`] rather than with floating point
+//! arithmetic. Debiasing is a post-processing step, and uses floating point arithmetic.
+
+use super::{Prio3, Prio3InputShare, Prio3PublicShare};
+use crate::{
+    dp::{distributions::sample_bernoulli_exp, PureDpBudget},
+    field::NttFriendlyFieldElement,
+    flp::{
+        gadgets::{Mul, ParallelSumGadget},
+        types::{Count, Histogram, MultihotCountVec},
+        Type,
+    },
+    vdaf::{xof::Xof, Client, VdafError},
+};
+use num_bigint::BigUint;
+use num_rational::Ratio;
+use num_traits::ToPrimitive;
+use rand::{
+    distr::{Distribution, Uniform},
+    rng,
+    seq::index::sample,
+    Rng,
+};
+
+/// A local differential privacy mechanism for measurements of the Prio3 type `T`.
+pub trait LocalDpMechanism<T: Type> {
+    /// The debiased estimate of the aggregate result.
+    type Estimate;
+
+    /// Randomize a measurement. The output is a valid measurement for `T`.
+    fn randomize<R: Rng + ?Sized>(
+        &self,
+        measurement: &T::Measurement,
+        rng: &mut R,
+    ) -> Result<T::Measurement, VdafError>;
+
+    /// Compute an unbiased estimate of the aggregate of the true measurements, given the aggregate
+    /// of `num_measurements` randomized measurements.
+    fn debias(
+        &self,
+        agg_result: &T::AggregateResult,
+        num_measurements: usize,
+    ) -> Result<Self::Estimate, VdafError>;
+}
+
+/// Sample from the k-ary randomized response mechanism: report `value` with probability
+/// `exp(epsilon) / (exp(epsilon) + k - 1)`, and each other value in `{0, ..., k - 1}` with
+/// probability `1 / (exp(epsilon) + k - 1)`.
+///
+/// Each round draws a uniform candidate. It is accepted unconditionally if it is `value`, and
+/// otherwise with probability `exp(-epsilon)`. Conditioned on termination, each other value is
+/// reported `exp(-epsilon)` times as often as `value`.
+fn sample_randomized_response<R: Rng + ?Sized>(
+    value: usize,
+    k: usize,
+    epsilon: &Ratio<BigUint>,
+    rng: &mut R,
+) -> usize {
+    // Unwrap safety: `k` is at least two.
+    let uniform = Uniform::new(0, k).unwrap();
+    loop {
+        let candidate = uniform.sample(rng);
+        if candidate == value || sample_bernoulli_exp(epsilon, rng) {
+            return candidate;
+        }
+    }
+}
+
+/// Invert the expected value of a randomized response count. If `true_count` of `n` measurements
+/// have a value, the count of reports of that value has expectation
+/// `n * q + (p - q) * true_count`, where `p = exp(epsilon) / (exp(epsilon) + k - 1)` and
+/// `q = 1 / (exp(epsilon) + k - 1)`.
+fn debias_count(count: f64, n: f64, k: f64, epsilon: f64) -> f64 {
+    let e = epsilon.exp();
+    let q = 1.0 / (e + k - 1.0);
+    let p_minus_q = (e - 1.0) / (e + k - 1.0);
+    (count - n * q) / p_minus_q
+}
+
+fn epsilon_f64(epsilon: &Ratio<BigUint>) -> Result<f64, VdafError> {
+    epsilon
+        .to_f64()
+        .filter(|epsilon| epsilon.is_finite())
+        .ok_or_else(|| VdafError::Uncategorized("epsilon is out of range".into()))
+}
+
+/// The k-ary randomized response mechanism, for [`Histogram`] and [`Count`] measurements.
+///
+/// Each client reports its true bucket with probability `exp(epsilon) / (exp(epsilon) + k - 1)`,
+/// and each other bucket with probability `1 / (exp(epsilon) + k - 1)`, where `k` is the number of
+/// buckets. This satisfies `epsilon`-local differential privacy. A [`Count`] measurement is
+/// treated as a choice between two buckets.
+#[derive(Clone, Debug)]
+pub struct RandomizedResponse {
+    epsilon: Ratio<BigUint>,
+    num_buckets: usize,
+}
+
+impl RandomizedResponse {
+    /// Construct the mechanism for measurements with `num_buckets` possible values. Errors if
+    /// there are fewer than two buckets.
+    pub fn new(budget: &PureDpBudget, num_buckets: usize) -> Result<Self, VdafError> {
+        if num_buckets < 2 {
+            return Err(VdafError::Uncategorized(
+                "randomized response requires at least two buckets".into(),
+            ));
+        }
+        Ok(Self {
+            epsilon: budget.epsilon().clone(),
+            num_buckets,
+        })
+    }
+
+    fn check_num_buckets(&self, num_buckets: usize) -> Result<(), VdafError> {
+        if num_buckets != self.num_buckets {
+            return Err(VdafError::Uncategorized(format!(
+                "mechanism has {} buckets, but the measurement has {num_buckets}",
+                self.num_buckets
+            )));
+        }
+        Ok(())
+    }
+
+    fn debias_vec(
+        &self,
+        agg_result: impl ExactSizeIterator<Item = u128>,
+        num_measurements: usize,
+    ) -> Result<Vec<f64>, VdafError> {
+        self.check_num_buckets(agg_result.len())?;
+        let epsilon = epsilon_f64(&self.epsilon)?;
+        Ok(agg_result
+            .map(|count| {
+                debias_count(
+                    count as f64,
+                    num_measurements as f64,
+                    self.num_buckets as f64,
+                    epsilon,
+                )
+            })
+            .collect())
+    }
+}
+
+impl<F, S> LocalDpMechanism<Histogram<F, S>> for RandomizedResponse
+where
+    F: NttFriendlyFieldElement,
+    F::Integer: Into<u128>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    type Estimate = Vec<f64>;
+
+    fn randomize<R: Rng + ?Sized>(
+        &self,
+        measurement: &usize,
+        rng: &mut R,
+    ) -> Result<usize, VdafError> {
+        if *measurement >= self.num_buckets {
+            return Err(VdafError::Uncategorized(format!(
+                "measurement {measurement} is out of range for {} buckets",
+                self.num_buckets
+            )));
+        }
+        Ok(sample_randomized_response(
+            *measurement,
+            self.num_buckets,
+            &self.epsilon,
+            rng,
+        ))
+    }
+
+    fn debias(
+        &self,
+        agg_result: &Vec<F::Integer>,
+        num_measurements: usize,
+    ) -> Result<Vec<f64>, VdafError> {
+        self.debias_vec(agg_result.iter().map(|x| (*x).into()), num_measurements)
+    }
+}
+
+impl<F> LocalDpMechanism<Count<F>> for RandomizedResponse
+where
+    F: NttFriendlyFieldElement,
+    F::Integer: Into<u128>,
+{
+    type Estimate = f64;
+
+    fn randomize<R: Rng + ?Sized>(
+        &self,
+        measurement: &bool,
+        rng: &mut R,
+    ) -> Result<bool, VdafError> {
+        self.check_num_buckets(2)?;
+        Ok(sample_randomized_response(usize::from(*measurement), 2, &self.epsilon, rng) == 1)
+    }
+
+    fn debias(&self, agg_result: &F::Integer, num_measurements: usize) -> Result<f64, VdafError> {
+        self.check_num_buckets(2)?;
+        let count: u128 = (*agg_result).into();
+        Ok(debias_count(
+            count as f64,
+            num_measurements as f64,
+            2.0,
+            epsilon_f64(&self.epsilon)?,
+        ))
+    }
+}
+
+/// The bit-flipping mechanism, for [`MultihotCountVec`] measurements.
+///
+/// Flipping every bit independently could produce more than `max_weight` true bits, which would
+/// not be a valid measurement. Instead, each client samples a uniformly random subset of
+/// `k = min(max_weight, length)` positions, independently of its measurement. Each bit in the
+/// subset is reported truthfully with probability `exp(epsilon') / (exp(epsilon') + 1)`, where
+/// `epsilon' = epsilon / k`, and every bit outside of the subset is reported as false. The
+/// randomized measurement has at most `k` true bits, so it is always valid.
+///
+/// Substituting one measurement for another changes at most `k` of the reported bits for any
+/// choice of subset, so this satisfies `epsilon`-local differential privacy. Each position is
+/// sampled with probability `k / length`, which [`LocalDpMechanism::debias`] corrects for, so the
+/// estimate is unbiased for any `max_weight`.
+#[derive(Clone, Debug)]
+pub struct BitFlipping {
+    epsilon_per_bit: Ratio<BigUint>,
+    length: usize,
+    num_sampled_bits: usize,
+}
+
+impl BitFlipping {
+    /// Construct the mechanism for measurements of `length` bits, at most `max_weight` of which
+    /// are set. Errors if `length` or `max_weight` is zero.
+    pub fn new(budget: &PureDpBudget, length: usize, max_weight: usize) -> Result<Self, VdafError> {
+        if length == 0 || max_weight == 0 {
+            return Err(VdafError::Uncategorized(
+                "length and max_weight must be positive".into(),
+            ));
+        }
+        let num_sampled_bits = max_weight.min(length);
+        Ok(Self {
+            epsilon_per_bit: budget.epsilon() / BigUint::from(num_sampled_bits),
+            length,
+            num_sampled_bits,
+        })
+    }
+}
+
+impl<F, S> LocalDpMechanism<MultihotCountVec<F, S>> for BitFlipping
+where
+    F: NttFriendlyFieldElement,
+    F::Integer: Into<u128>,
+    S: ParallelSumGadget<F, Mul<F>> + Eq + 'static,
+{
+    type Estimate = Vec<f64>;
+
+    fn randomize<R: Rng + ?Sized>(
+        &self,
+        measurement: &Vec<bool>,
+        rng: &mut R,
+    ) -> Result<Vec<bool>, VdafError> {
+        if measurement.len() != self.length {
+            return Err(VdafError::Uncategorized(format!(
+                "measurement has length {}, expected {}",
+                measurement.len(),
+                self.length
+            )));
+        }
+
+        let mut randomized = vec![false; self.length];
+        for i in sample(rng, self.length, self.num_sampled_bits) {
+            randomized[i] = sample_randomized_response(
+                usize::from(measurement[i]),
+                2,
+                &self.epsilon_per_bit,
+                rng,
+            ) == 1;
+        }
+
+        Ok(randomized)
+    }
+
+    fn debias(
+        &self,
+        agg_result: &Vec<F::Integer>,
+        num_measurements: usize,
+    ) -> Result<Vec<f64>, VdafError> {
+        if agg_result.len() != self.length {
+            return Err(VdafError::Uncategorized(format!(
+                "aggregate result has length {}, expected {}",
+                agg_result.len(),
+                self.length
+            )));
+        }
+        let epsilon = epsilon_f64(&self.epsilon_per_bit)?;
+        // Each position is only reported by the fraction `num_sampled_bits / length` of clients
+        // that sampled it, so scale the counts up before inverting randomized response.
+        let scale = self.length as f64 / self.num_sampled_bits as f64;
+        Ok(agg_result
+            .iter()
+            .map(|count| {
+                let count: u128 = (*count).into();
+                debias_count(count as f64 * scale, num_measurements as f64, 2.0, epsilon)
+            })
+            .collect())
+    }
+}
+
+impl<T, P, const SEED_SIZE: usize> Prio3<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    /// Randomize a measurement with a local differential privacy mechanism, then shard it as in
+    /// [`Client::shard`].
+    #[allow(clippy::type_complexity)]
+    pub fn shard_with_local_dp<M: LocalDpMechanism<T>>(
+        &self,
+        ctx: &[u8],
+        mechanism: &M,
+        measurement: &T::Measurement,
+        nonce: &[u8; 16],
+    ) -> Result<
+        (
+            Prio3PublicShare<SEED_SIZE>,
+            Vec<Prio3InputShare<T::Field, SEED_SIZE>>,
+        ),
+        VdafError,
+    > {
+        let randomized = mechanism.randomize(measurement, &mut rng())?;
+        self.shard(ctx, &randomized, nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitFlipping, LocalDpMechanism, RandomizedResponse};
+    use crate::{
+        dp::{PureDpBudget, Rational},
+        field::Field128,
+        flp::{
+            gadgets::{Mul, ParallelSum},
+            types::{Count, Histogram, MultihotCountVec},
+        },
+        vdaf::{prio3::Prio3, test_utils::run_vdaf, xof::SeedStreamTurboShake128},
+    };
+    use rand::SeedableRng;
+
+    const CTX_STR: &[u8] = b"prio3 local dp ctx";
+
+    type TestHistogram = Histogram<Field128, ParallelSum<Field128, Mul<Field128>>>;
+    type TestMultihot = MultihotCountVec<Field128, ParallelSum<Field128, Mul<Field128>>>;
+
+    fn budget(numer: u32, denom: u32) -> PureDpBudget {
+        PureDpBudget::new(Rational::from_unsigned(numer, denom).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn randomized_response_histogram() {
+        // With epsilon = ln(3) approximately, each other bucket is reported a third as often as
+        // the true bucket.
+        let mechanism = RandomizedResponse::new(&budget(10986, 10000), 4).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([0u8; 32]);
+
+        let num_measurements = 20_000;
+        let mut counts = vec![0u128; 4];
+        for i in 0..num_measurements {
+            // Three quarters of the measurements are in bucket 0.
+            let measurement = if i % 4 == 3 { 2 } else { 0 };
+            let randomized =
+                LocalDpMechanism::<TestHistogram>::randomize(&mechanism, &measurement, &mut rng)
+                    .unwrap();
+            counts[randomized] += 1;
+        }
+
+        // Reports of bucket 1 come only from measurements in other buckets: 1/6 of them.
+        let expected = num_measurements as f64 / 6.0;
+        assert!((counts[1] as f64 - expected).abs() < 0.05 * expected);
+
+        let estimate =
+            LocalDpMechanism::<TestHistogram>::debias(&mechanism, &counts, num_measurements)
+                .unwrap();
+        let truth = [15_000.0, 0.0, 5_000.0, 0.0];
+        for (estimate, truth) in estimate.iter().zip(truth) {
+            assert!((estimate - truth).abs() < 500.0, "{estimate} vs. {truth}");
+        }
+
+        LocalDpMechanism::<TestHistogram>::randomize(&mechanism, &4, &mut rng).unwrap_err();
+        LocalDpMechanism::<TestHistogram>::debias(&mechanism, &vec![0; 3], 0).unwrap_err();
+        RandomizedResponse::new(&budget(1, 1), 1).unwrap_err();
+    }
+
+    #[test]
+    fn randomized_response_count() {
+        let mechanism = RandomizedResponse::new(&budget(1, 1), 2).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([1u8; 32]);
+
+        let num_measurements = 20_000;
+        let count = (0..num_measurements)
+            .filter(|i| {
+                LocalDpMechanism::<Count<Field128>>::randomize(&mechanism, &(i % 5 == 0), &mut rng)
+                    .unwrap()
+            })
+            .count();
+        let estimate = LocalDpMechanism::<Count<Field128>>::debias(
+            &mechanism,
+            &(count as u128),
+            num_measurements,
+        )
+        .unwrap();
+        assert!((estimate - 4_000.0).abs() < 500.0, "{estimate}");
+
+        let mechanism = RandomizedResponse::new(&budget(1, 1), 3).unwrap();
+        LocalDpMechanism::<Count<Field128>>::randomize(&mechanism, &true, &mut rng).unwrap_err();
+    }
+
+    #[test]
+    fn bit_flipping() {
+        let mechanism = BitFlipping::new(&budget(4, 1), 8, 8).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([2u8; 32]);
+
+        let num_measurements = 10_000;
+        let mut counts = vec![0u128; 8];
+        for _ in 0..num_measurements {
+            let measurement = vec![true, true, false, false, true, false, false, false];
+            let randomized =
+                LocalDpMechanism::<TestMultihot>::randomize(&mechanism, &measurement, &mut rng)
+                    .unwrap();
+            for (count, bit) in counts.iter_mut().zip(randomized) {
+                *count += u128::from(bit);
+            }
+        }
+        let estimate =
+            LocalDpMechanism::<TestMultihot>::debias(&mechanism, &counts, num_measurements)
+                .unwrap();
+        for (i, estimate) in estimate.into_iter().enumerate() {
+            let truth = if [0, 1, 4].contains(&i) {
+                10_000.0
+            } else {
+                0.0
+            };
+            assert!((estimate - truth).abs() < 1_000.0, "{estimate} vs. {truth}");
+        }
+
+        // The randomized measurement is always valid.
+        let mechanism = BitFlipping::new(&budget(1, 100), 8, 2).unwrap();
+        for _ in 0..100 {
+            let randomized =
+                LocalDpMechanism::<TestMultihot>::randomize(&mechanism, &vec![false; 8], &mut rng)
+                    .unwrap();
+            assert!(randomized.iter().filter(|bit| **bit).count() <= 2);
+        }
+
+        LocalDpMechanism::<TestMultihot>::randomize(&mechanism, &vec![false; 7], &mut rng)
+            .unwrap_err();
+        BitFlipping::new(&budget(1, 1), 8, 0).unwrap_err();
+    }
+
+    #[test]
+    fn bit_flipping_small_max_weight() {
+        // Only two of 64 positions are reported by each client. The estimate is still unbiased.
+        let mechanism = BitFlipping::new(&budget(4, 1), 64, 2).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([4u8; 32]);
+
+        let num_measurements = 50_000;
+        let mut measurement = vec![false; 64];
+        measurement[0] = true;
+        measurement[1] = true;
+        let mut counts = vec![0u128; 64];
+        for _ in 0..num_measurements {
+            let randomized =
+                LocalDpMechanism::<TestMultihot>::randomize(&mechanism, &measurement, &mut rng)
+                    .unwrap();
+            assert!(randomized.iter().filter(|bit| **bit).count() <= 2);
+            for (count, bit) in counts.iter_mut().zip(randomized) {
+                *count += u128::from(bit);
+            }
+        }
+        let estimate =
+            LocalDpMechanism::<TestMultihot>::debias(&mechanism, &counts, num_measurements)
+                .unwrap();
+        for (i, estimate) in estimate.into_iter().enumerate() {
+            let truth = if i < 2 { 50_000.0 } else { 0.0 };
+            assert!((estimate - truth).abs() < 5_000.0, "{estimate} vs. {truth}");
+        }
+    }
+
+    #[test]
+    fn prio3_with_local_dp() {
+        // Randomized measurements pass the validity check.
+        let prio3 = Prio3::new_histogram(2, 4, 2).unwrap();
+        let mechanism = RandomizedResponse::new(&budget(1, 2), 4).unwrap();
+        let mut rng = SeedStreamTurboShake128::from_seed([3u8; 32]);
+        let measurements = (0..10)
+            .map(|i| {
+                LocalDpMechanism::<TestHistogram>::randomize(&mechanism, &(i % 4), &mut rng)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let result = run_vdaf(CTX_STR, &prio3, &(), measurements).unwrap();
+        assert_eq!(result.iter().sum::<u128>(), 10);
+
+        let prio3 = Prio3::new_multihot_count_vec(2, 8, 2, 3).unwrap();
+        let mechanism = BitFlipping::new(&budget(1, 2), 8, 2).unwrap();
+        let measurements = (0..10)
+            .map(|_| {
+                LocalDpMechanism::<TestMultihot>::randomize(
+                    &mechanism,
+                    &vec![true, false, false, false, false, false, false, true],
+                    &mut rng,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        run_vdaf(CTX_STR, &prio3, &(), measurements).unwrap();
+
+        let (_, input_shares) = prio3
+            .shard_with_local_dp(CTX_STR, &mechanism, &vec![false; 8], &[0; 16])
+            .unwrap();
+        assert_eq!(input_shares.len(), 2);
+    }
+}
@@ -0,0 +1,1642 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Threshold variant of Prio3, in which any `threshold` of the `num_aggregators` aggregators can
+//! finish preparation and unsharding.
+//!
+//! **WARNING:** This construction is not specified by [[draft-irtf-cfrg-vdaf-08]] and has not
+//! undergone any security analysis. Use at your own risk.
+//!
+//! [`Prio3`] splits each measurement into additive shares, so every aggregator has to take part
+//! in preparing every report, and losing the state of a single aggregator makes the whole batch
+//! unrecoverable. [`Prio3Threshold`] instead splits the encoded measurement and the FLP proofs
+//! into Shamir secret shares of degree `threshold - 1`: the share of aggregator `j` is the
+//! evaluation of a random polynomial at `j + 1`, whose constant term is the secret. Any
+//! `threshold` shares determine the secret, and any `threshold - 1` shares reveal nothing about
+//! it.
+//!
+//! Because the FLP query is affine in the input and the proof, each aggregator's verifier share is
+//! a Shamir share of the verifier. The verifier is recovered by Lagrange interpolation from the
+//! prepare shares of any `threshold` aggregators. Output shares and aggregate shares are Shamir
+//! shares as well, and [`Collector::unshard`] interpolates the aggregate from any `threshold`
+//! aggregate shares. Prepare shares, output shares and aggregate shares therefore carry the ID of
+//! the aggregator that produced them.
+//!
+//! A malicious client could hand out shares that do not lie on a polynomial of degree
+//! `threshold - 1`, so that different sets of aggregators reconstruct different measurements. To
+//! detect this, each prepare share also carries a random linear combination of the aggregator's
+//! input share, masked with a share of a random value picked by the client. When more than
+//! `threshold` aggregators take part in preparation, the combinations of the extra aggregators
+//! are checked against the polynomial interpolated from the first `threshold` of them. A report
+//! prepared by exactly `threshold` aggregators cannot be checked this way. For such reports, only
+//! those aggregators should aggregate the resulting output shares. Likewise, when more than
+//! `threshold` aggregate shares are passed to [`Collector::unshard`], they are checked for
+//! consistency.
+//!
+//! Input shares are not compressed into seeds as in [`Prio3`], so every input share is as large as
+//! the leader's input share in [`Prio3`].
+//!
+//! Threshold VDAFs use their own range of algorithm IDs, from [`ALGORITHM_ID_MIN`] to
+//! [`ALGORITHM_ID_MAX`], so that their reports are never confused with those of [`Prio3`].
+//!
+//! [draft-irtf-cfrg-vdaf-08]: https://datatracker.ietf.org/doc/draft-irtf-cfrg-vdaf/08/
+
+use super::{check_num_aggregators, option_ct_eq, Prio3, Prio3PrepareMessage, Prio3PublicShare};
+use crate::{
+    codec::{encode_fixlen_items, CodecError, Decode, Encode, ParameterizedDecode},
+    field::{
+        decode_fieldvec, merge_encoded_vector, merge_vector, unmerge_vector, Field128, Field64,
+        FieldElement, FieldElementWithInteger, NttFriendlyFieldElement,
+    },
+    flp::{
+        gadgets::{Mul, ParallelSum},
+        types::{Count, Histogram, Sum, SumVec},
+        Type,
+    },
+    prng::Prng,
+    vdaf::{
+        xof::{IntoFieldVec, Seed, Xof, XofTurboShake128},
+        Aggregatable, Aggregator, Client, Collector, EncodedAggregatable, PrepareTransition,
+        RetractableAggregatable, Vdaf, VdafError,
+    },
+};
+use rand::{rng, Rng};
+use std::{fmt::Debug, io::Cursor, iter};
+use subtle::{Choice, ConstantTimeEq};
+
+const DST_CONSISTENCY_RANDOMNESS: u16 = 8;
+
+/// The smallest algorithm ID of a threshold Prio3 VDAF.
+pub const ALGORITHM_ID_MIN: u32 = 0xFFFF2000;
+
+/// The largest algorithm ID of a threshold Prio3 VDAF.
+pub const ALGORITHM_ID_MAX: u32 = 0xFFFF2FFF;
+
+/// Threshold variant of [`Prio3Count`](super::Prio3Count).
+pub type Prio3ThresholdCount = Prio3Threshold<Count<Field64>, XofTurboShake128, 32>;
+
+impl Prio3ThresholdCount {
+    /// Construct an instance of Prio3ThresholdCount with the given number of aggregators and
+    /// threshold.
+    pub fn new_count(num_aggregators: u8, threshold: u8) -> Result<Self, VdafError> {
+        Prio3Threshold::new(num_aggregators, threshold, 1, 0xFFFF2001, Count::new())
+    }
+}
+
+/// Threshold variant of [`Prio3Sum`](super::Prio3Sum).
+pub type Prio3ThresholdSum = Prio3Threshold<Sum<Field64>, XofTurboShake128, 32>;
+
+impl Prio3ThresholdSum {
+    /// Construct an instance of Prio3ThresholdSum with the given number of aggregators and
+    /// threshold, where each summand must be in the range `[0, max_measurement]`. Errors if
+    /// `max_measurement == 0`.
+    pub fn new_sum(
+        num_aggregators: u8,
+        threshold: u8,
+        max_measurement: <Field64 as FieldElementWithInteger>::Integer,
+    ) -> Result<Self, VdafError> {
+        Prio3Threshold::new(
+            num_aggregators,
+            threshold,
+            1,
+            0xFFFF2002,
+            Sum::new(max_measurement)?,
+        )
+    }
+}
+
+/// Threshold variant of [`Prio3SumVec`](super::Prio3SumVec).
+pub type Prio3ThresholdSumVec =
+    Prio3Threshold<SumVec<Field128, ParallelSum<Field128, Mul<Field128>>>, XofTurboShake128, 32>;
+
+impl Prio3ThresholdSumVec {
+    /// Construct an instance of Prio3ThresholdSumVec with the given number of aggregators and
+    /// threshold. `bits` defines the bit width of each summand of the measurement; `len` defines
+    /// the length of the measurement vector.
+    pub fn new_sum_vec(
+        num_aggregators: u8,
+        threshold: u8,
+        bits: usize,
+        len: usize,
+        chunk_length: usize,
+    ) -> Result<Self, VdafError> {
+        Prio3Threshold::new(
+            num_aggregators,
+            threshold,
+            1,
+            0xFFFF2003,
+            SumVec::new(bits, len, chunk_length)?,
+        )
+    }
+}
+
+/// Threshold variant of [`Prio3Histogram`](super::Prio3Histogram).
+pub type Prio3ThresholdHistogram =
+    Prio3Threshold<Histogram<Field128, ParallelSum<Field128, Mul<Field128>>>, XofTurboShake128, 32>;
+
+impl Prio3ThresholdHistogram {
+    /// Construct an instance of Prio3ThresholdHistogram with the given number of aggregators,
+    /// threshold, number of buckets, and parallel sum gadget chunk length.
+    pub fn new_histogram(
+        num_aggregators: u8,
+        threshold: u8,
+        length: usize,
+        chunk_length: usize,
+    ) -> Result<Self, VdafError> {
+        Prio3Threshold::new(
+            num_aggregators,
+            threshold,
+            1,
+            0xFFFF2004,
+            Histogram::new(length, chunk_length)?,
+        )
+    }
+}
+
+/// The threshold Prio3 VDAF. See the [module documentation](self) for details.
+#[derive(Clone, Debug)]
+pub struct Prio3Threshold<T, P, const SEED_SIZE: usize>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    prio3: Prio3<T, P, SEED_SIZE>,
+    threshold: u8,
+}
+
+impl<T, P, const SEED_SIZE: usize> Prio3Threshold<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    /// Construct an instance of this VDAF with the given number of aggregators, threshold, number
+    /// of proofs to generate and verify, the algorithm ID, and the underlying type.
+    ///
+    /// The threshold must be at least 2 and at most `num_aggregators`, and the algorithm ID must be
+    /// in the range `[ALGORITHM_ID_MIN, ALGORITHM_ID_MAX]`.
+    pub fn new(
+        num_aggregators: u8,
+        threshold: u8,
+        num_proofs: u8,
+        algorithm_id: u32,
+        typ: T,
+    ) -> Result<Self, VdafError> {
+        check_num_aggregators(num_aggregators)?;
+        if threshold < 2 || threshold > num_aggregators {
+            return Err(VdafError::Uncategorized(format!(
+                "threshold must be in [2, {num_aggregators}]; got {threshold}"
+            )));
+        }
+        if !(ALGORITHM_ID_MIN..=ALGORITHM_ID_MAX).contains(&algorithm_id) {
+            return Err(VdafError::Uncategorized(format!(
+                "algorithm ID {algorithm_id:#010x} is not in the threshold Prio3 range"
+            )));
+        }
+
+        Ok(Self {
+            prio3: Prio3::new(num_aggregators, num_proofs, algorithm_id, typ)?,
+            threshold,
+        })
+    }
+
+    /// The number of aggregators needed to finish preparation and unsharding.
+    pub fn threshold(&self) -> usize {
+        self.threshold.into()
+    }
+
+    /// The output length of the underlying FLP.
+    pub fn output_len(&self) -> usize {
+        self.prio3.output_len()
+    }
+
+    /// The verifier length of the underlying FLP.
+    pub fn verifier_len(&self) -> usize {
+        self.prio3.verifier_len()
+    }
+
+    fn proofs_len(&self) -> usize {
+        self.prio3.typ.proof_len() * self.prio3.num_proofs()
+    }
+
+    fn joint_rand_part(
+        &self,
+        ctx: &[u8],
+        joint_rand_blind: &Seed<SEED_SIZE>,
+        agg_id: u8,
+        nonce: &[u8; 16],
+        measurement_share: &[T::Field],
+    ) -> Result<Seed<SEED_SIZE>, VdafError> {
+        let mut xof = P::init(
+            joint_rand_blind.as_ref(),
+            &[
+                &self.prio3.domain_separation_tag(super::DST_JOINT_RAND_PART),
+                ctx,
+            ],
+        );
+        xof.update(&[agg_id]);
+        xof.update(nonce);
+        let mut encoding_buffer = Vec::with_capacity(T::Field::ENCODED_SIZE);
+        for x in measurement_share {
+            encoding_buffer.clear();
+            x.encode(&mut encoding_buffer).map_err(|_| {
+                VdafError::Uncategorized("failed to encode measurement share".to_string())
+            })?;
+            xof.update(&encoding_buffer);
+        }
+        Ok(xof.into_seed())
+    }
+
+    /// Derives the coefficients of the linear combination used to check that the input shares are
+    /// consistent. They depend on the verify key, so they are unknown to the client.
+    fn derive_consistency_rands(
+        &self,
+        verify_key: &[u8; SEED_SIZE],
+        ctx: &[u8],
+        nonce: &[u8; 16],
+    ) -> Vec<T::Field> {
+        P::seed_stream(
+            verify_key,
+            &[
+                &self.prio3.domain_separation_tag(DST_CONSISTENCY_RANDOMNESS),
+                ctx,
+            ],
+            &[nonce],
+        )
+        .into_field_vec(self.prio3.typ.input_len() + self.proofs_len())
+    }
+
+    /// Checks that the aggregator IDs are valid and distinct, and returns their evaluation points.
+    fn eval_points(&self, agg_ids: impl Iterator<Item = u8>) -> Result<Vec<T::Field>, VdafError> {
+        let mut seen = vec![false; self.prio3.num_aggregators()];
+        agg_ids
+            .map(|agg_id| {
+                match seen.get_mut(usize::from(agg_id)) {
+                    Some(seen) if !*seen => *seen = true,
+                    Some(_) => {
+                        return Err(VdafError::Uncategorized(format!(
+                            "duplicate share from aggregator {agg_id}"
+                        )))
+                    }
+                    None => {
+                        return Err(VdafError::Uncategorized("unexpected aggregator id".into()))
+                    }
+                }
+                Ok(eval_point(agg_id))
+            })
+            .collect()
+    }
+
+    /// Checks that there are enough shares to reconstruct the secret.
+    fn check_share_count(&self, count: usize) -> Result<(), VdafError> {
+        if count < self.threshold() {
+            return Err(VdafError::Uncategorized(format!(
+                "unexpected message count: got {count}; want at least {}",
+                self.threshold
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<T, P, const SEED_SIZE: usize> Vdaf for Prio3Threshold<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    type Measurement = T::Measurement;
+    type AggregateResult = T::AggregateResult;
+    type AggregationParam = ();
+    type PublicShare = Prio3PublicShare<SEED_SIZE>;
+    type InputShare = Prio3ThresholdInputShare<T::Field, SEED_SIZE>;
+    type OutputShare = Prio3ThresholdOutputShare<T::Field>;
+    type AggregateShare = Prio3ThresholdAggregateShare<T::Field>;
+
+    fn algorithm_id(&self) -> u32 {
+        self.prio3.algorithm_id
+    }
+
+    fn num_aggregators(&self) -> usize {
+        self.prio3.num_aggregators()
+    }
+}
+
+impl<T, P, const SEED_SIZE: usize> ParameterizedDecode<Prio3Threshold<T, P, SEED_SIZE>>
+    for Prio3PublicShare<SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    fn decode_with_param(
+        decoding_parameter: &Prio3Threshold<T, P, SEED_SIZE>,
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        Self::decode_with_param(&decoding_parameter.prio3, bytes)
+    }
+}
+
+/// Message sent by the [`Client`] to each [`Aggregator`] during the Sharding phase.
+#[derive(Clone, Debug)]
+pub struct Prio3ThresholdInputShare<F, const SEED_SIZE: usize> {
+    /// Shamir share of the encoded measurement.
+    pub measurement_share: Vec<F>,
+
+    /// Shamir share of the FLP proofs.
+    pub proofs_share: Vec<F>,
+
+    /// Shamir share of the random value masking the consistency check.
+    pub mask_share: F,
+
+    /// Blinding seed used by the Aggregator to compute the joint randomness. This field is
+    /// optional because not every [`Type`] requires joint randomness.
+    pub joint_rand_blind: Option<Seed<SEED_SIZE>>,
+}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> PartialEq
+    for Prio3ThresholdInputShare<F, SEED_SIZE>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> Eq for Prio3ThresholdInputShare<F, SEED_SIZE> {}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> ConstantTimeEq
+    for Prio3ThresholdInputShare<F, SEED_SIZE>
+{
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // We allow short-circuiting on the presence or absence of the joint_rand_blind.
+        option_ct_eq(
+            self.joint_rand_blind.as_ref(),
+            other.joint_rand_blind.as_ref(),
+        ) & self.measurement_share.ct_eq(&other.measurement_share)
+            & self.proofs_share.ct_eq(&other.proofs_share)
+            & self.mask_share.ct_eq(&other.mask_share)
+    }
+}
+
+impl<F: NttFriendlyFieldElement, const SEED_SIZE: usize> Encode
+    for Prio3ThresholdInputShare<F, SEED_SIZE>
+{
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        encode_fixlen_items(bytes, &self.measurement_share)?;
+        encode_fixlen_items(bytes, &self.proofs_share)?;
+        self.mask_share.encode(bytes)?;
+        if let Some(ref blind) = self.joint_rand_blind {
+            blind.encode(bytes)?;
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let mut len =
+            (self.measurement_share.len() + self.proofs_share.len() + 1) * F::ENCODED_SIZE;
+        if let Some(ref blind) = self.joint_rand_blind {
+            len += blind.encoded_len()?;
+        }
+        Some(len)
+    }
+}
+
+impl<'a, T, P, const SEED_SIZE: usize>
+    ParameterizedDecode<(&'a Prio3Threshold<T, P, SEED_SIZE>, usize)>
+    for Prio3ThresholdInputShare<T::Field, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    fn decode_with_param(
+        (vdaf, agg_id): &(&'a Prio3Threshold<T, P, SEED_SIZE>, usize),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        vdaf.prio3
+            .role_try_from(*agg_id)
+            .map_err(|e| CodecError::Other(Box::new(e)))?;
+
+        let measurement_share = decode_fieldvec(vdaf.prio3.typ.input_len(), bytes)?;
+        let proofs_share = decode_fieldvec(vdaf.proofs_len(), bytes)?;
+        let mask_share = T::Field::decode(bytes)?;
+        let joint_rand_blind = if vdaf.prio3.typ.joint_rand_len() > 0 {
+            Some(Seed::decode(bytes)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            measurement_share,
+            proofs_share,
+            mask_share,
+            joint_rand_blind,
+        })
+    }
+}
+
+/// Message broadcast by each [`Aggregator`] in each round of the Preparation phase.
+#[derive(Clone, Debug)]
+pub struct Prio3ThresholdPrepareShare<F, const SEED_SIZE: usize> {
+    /// The ID of the aggregator that computed this share.
+    pub agg_id: u8,
+
+    /// A Shamir share of the FLP verifier message. (See [`Type`].)
+    pub verifiers: Vec<F>,
+
+    /// A Shamir share of the masked linear combination of the input shares, used to check that
+    /// the input shares are consistent.
+    pub consistency_check: F,
+
+    /// The joint randomness seed computed by this aggregator.
+    pub joint_rand_seed: Option<Seed<SEED_SIZE>>,
+}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> PartialEq
+    for Prio3ThresholdPrepareShare<F, SEED_SIZE>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> Eq for Prio3ThresholdPrepareShare<F, SEED_SIZE> {}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> ConstantTimeEq
+    for Prio3ThresholdPrepareShare<F, SEED_SIZE>
+{
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // We allow short-circuiting on the aggregator ID and on the presence or absence of the
+        // joint_rand_seed.
+        if self.agg_id != other.agg_id {
+            return Choice::from(0);
+        }
+
+        option_ct_eq(
+            self.joint_rand_seed.as_ref(),
+            other.joint_rand_seed.as_ref(),
+        ) & self.verifiers.ct_eq(&other.verifiers)
+            & self.consistency_check.ct_eq(&other.consistency_check)
+    }
+}
+
+impl<F: NttFriendlyFieldElement, const SEED_SIZE: usize> Encode
+    for Prio3ThresholdPrepareShare<F, SEED_SIZE>
+{
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        self.agg_id.encode(bytes)?;
+        encode_fixlen_items(bytes, &self.verifiers)?;
+        self.consistency_check.encode(bytes)?;
+        if let Some(ref seed) = self.joint_rand_seed {
+            seed.encode(bytes)?;
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let mut len = 1 + F::ENCODED_SIZE * (self.verifiers.len() + 1);
+        if let Some(ref seed) = self.joint_rand_seed {
+            len += seed.encoded_len()?;
+        }
+        Some(len)
+    }
+}
+
+impl<F: NttFriendlyFieldElement, const SEED_SIZE: usize>
+    ParameterizedDecode<Prio3ThresholdPrepareState<F, SEED_SIZE>>
+    for Prio3ThresholdPrepareShare<F, SEED_SIZE>
+{
+    fn decode_with_param(
+        decoding_parameter: &Prio3ThresholdPrepareState<F, SEED_SIZE>,
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        let agg_id = u8::decode(bytes)?;
+        let verifiers = decode_fieldvec(decoding_parameter.verifiers_len, bytes)?;
+        let consistency_check = F::decode(bytes)?;
+        let joint_rand_seed = if decoding_parameter.joint_rand_seed.is_some() {
+            Some(Seed::decode(bytes)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            agg_id,
+            verifiers,
+            consistency_check,
+            joint_rand_seed,
+        })
+    }
+}
+
+impl<F: NttFriendlyFieldElement, const SEED_SIZE: usize>
+    ParameterizedDecode<Prio3ThresholdPrepareState<F, SEED_SIZE>>
+    for Prio3PrepareMessage<SEED_SIZE>
+{
+    fn decode_with_param(
+        decoding_parameter: &Prio3ThresholdPrepareState<F, SEED_SIZE>,
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        let joint_rand_seed = if decoding_parameter.joint_rand_seed.is_some() {
+            Some(Seed::decode(bytes)?)
+        } else {
+            None
+        };
+
+        Ok(Prio3PrepareMessage { joint_rand_seed })
+    }
+}
+
+/// State of each [`Aggregator`] during the Preparation phase.
+#[derive(Clone)]
+pub struct Prio3ThresholdPrepareState<F, const SEED_SIZE: usize> {
+    measurement_share: Vec<F>,
+    joint_rand_seed: Option<Seed<SEED_SIZE>>,
+    agg_id: u8,
+    verifiers_len: usize,
+}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> PartialEq
+    for Prio3ThresholdPrepareState<F, SEED_SIZE>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> Eq for Prio3ThresholdPrepareState<F, SEED_SIZE> {}
+
+impl<F: ConstantTimeEq, const SEED_SIZE: usize> ConstantTimeEq
+    for Prio3ThresholdPrepareState<F, SEED_SIZE>
+{
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // We allow short-circuiting on the presence or absence of the joint_rand_seed, as well as
+        // the aggregator ID & verifier length parameters.
+        if self.agg_id != other.agg_id || self.verifiers_len != other.verifiers_len {
+            return Choice::from(0);
+        }
+
+        option_ct_eq(
+            self.joint_rand_seed.as_ref(),
+            other.joint_rand_seed.as_ref(),
+        ) & self.measurement_share.ct_eq(&other.measurement_share)
+    }
+}
+
+impl<F, const SEED_SIZE: usize> Debug for Prio3ThresholdPrepareState<F, SEED_SIZE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Prio3ThresholdPrepareState")
+            .field("measurement_share", &"[redacted]")
+            .field(
+                "joint_rand_seed",
+                match self.joint_rand_seed {
+                    Some(_) => &"Some([redacted])",
+                    None => &"None",
+                },
+            )
+            .field("agg_id", &self.agg_id)
+            .field("verifiers_len", &self.verifiers_len)
+            .finish()
+    }
+}
+
+impl<F: NttFriendlyFieldElement, const SEED_SIZE: usize> Encode
+    for Prio3ThresholdPrepareState<F, SEED_SIZE>
+{
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        encode_fixlen_items(bytes, &self.measurement_share)?;
+        if let Some(ref seed) = self.joint_rand_seed {
+            seed.encode(bytes)?;
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let mut len = F::ENCODED_SIZE * self.measurement_share.len();
+        if let Some(ref seed) = self.joint_rand_seed {
+            len += seed.encoded_len()?;
+        }
+        Some(len)
+    }
+}
+
+impl<'a, T, P, const SEED_SIZE: usize>
+    ParameterizedDecode<(&'a Prio3Threshold<T, P, SEED_SIZE>, usize)>
+    for Prio3ThresholdPrepareState<T::Field, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    fn decode_with_param(
+        (vdaf, agg_id): &(&'a Prio3Threshold<T, P, SEED_SIZE>, usize),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        let agg_id = vdaf
+            .prio3
+            .role_try_from(*agg_id)
+            .map_err(|e| CodecError::Other(Box::new(e)))?;
+
+        let measurement_share = decode_fieldvec(vdaf.prio3.typ.input_len(), bytes)?;
+        let joint_rand_seed = if vdaf.prio3.typ.joint_rand_len() > 0 {
+            Some(Seed::decode(bytes)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            measurement_share,
+            joint_rand_seed,
+            agg_id,
+            verifiers_len: vdaf.verifier_len() * vdaf.prio3.num_proofs(),
+        })
+    }
+}
+
+/// Output share of an aggregator, tagged with the aggregator's ID.
+#[derive(Clone)]
+pub struct Prio3ThresholdOutputShare<F> {
+    agg_id: u8,
+    share: Vec<F>,
+}
+
+impl<F> Prio3ThresholdOutputShare<F> {
+    /// The ID of the aggregator that computed this share.
+    pub fn agg_id(&self) -> usize {
+        self.agg_id.into()
+    }
+}
+
+impl<F: ConstantTimeEq> PartialEq for Prio3ThresholdOutputShare<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<F: ConstantTimeEq> Eq for Prio3ThresholdOutputShare<F> {}
+
+impl<F: ConstantTimeEq> ConstantTimeEq for Prio3ThresholdOutputShare<F> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        if self.agg_id != other.agg_id {
+            return Choice::from(0);
+        }
+        self.share.ct_eq(&other.share)
+    }
+}
+
+impl<F> AsRef<[F]> for Prio3ThresholdOutputShare<F> {
+    fn as_ref(&self) -> &[F] {
+        &self.share
+    }
+}
+
+impl<F> Debug for Prio3ThresholdOutputShare<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Prio3ThresholdOutputShare")
+            .field("agg_id", &self.agg_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: FieldElement> Encode for Prio3ThresholdOutputShare<F> {
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        self.agg_id.encode(bytes)?;
+        encode_fixlen_items(bytes, &self.share)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(1 + F::ENCODED_SIZE * self.share.len())
+    }
+}
+
+impl<'a, T, P, const SEED_SIZE: usize>
+    ParameterizedDecode<(&'a Prio3Threshold<T, P, SEED_SIZE>, &'a ())>
+    for Prio3ThresholdOutputShare<T::Field>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    fn decode_with_param(
+        (vdaf, _): &(&'a Prio3Threshold<T, P, SEED_SIZE>, &'a ()),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        let agg_id = decode_agg_id(vdaf, bytes)?;
+        let share = decode_fieldvec(vdaf.output_len(), bytes)?;
+        Ok(Self { agg_id, share })
+    }
+}
+
+/// Aggregate share of an aggregator, tagged with the aggregator's ID.
+///
+/// The ID is unknown until the first output share is accumulated, so an aggregate share of an
+/// empty batch has no ID. Such shares are ignored by [`Collector::unshard`].
+#[derive(Clone, Debug)]
+pub struct Prio3ThresholdAggregateShare<F> {
+    agg_id: Option<u8>,
+    share: Vec<F>,
+}
+
+impl<F> Prio3ThresholdAggregateShare<F> {
+    /// The ID of the aggregator that computed this share, if any output share has been
+    /// accumulated into it.
+    pub fn agg_id(&self) -> Option<usize> {
+        self.agg_id.map(usize::from)
+    }
+
+    fn set_agg_id(&mut self, agg_id: u8) -> Result<(), VdafError> {
+        match self.agg_id {
+            Some(own_agg_id) if own_agg_id != agg_id => Err(VdafError::Uncategorized(format!(
+                "cannot combine shares of aggregators {own_agg_id} and {agg_id}"
+            ))),
+            _ => {
+                self.agg_id = Some(agg_id);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<F: ConstantTimeEq> PartialEq for Prio3ThresholdAggregateShare<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<F: ConstantTimeEq> Eq for Prio3ThresholdAggregateShare<F> {}
+
+impl<F: ConstantTimeEq> ConstantTimeEq for Prio3ThresholdAggregateShare<F> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        if self.agg_id != other.agg_id {
+            return Choice::from(0);
+        }
+        self.share.ct_eq(&other.share)
+    }
+}
+
+impl<F> AsRef<[F]> for Prio3ThresholdAggregateShare<F> {
+    fn as_ref(&self) -> &[F] {
+        &self.share
+    }
+}
+
+impl<F> From<Prio3ThresholdOutputShare<F>> for Prio3ThresholdAggregateShare<F> {
+    fn from(other: Prio3ThresholdOutputShare<F>) -> Self {
+        Self {
+            agg_id: Some(other.agg_id),
+            share: other.share,
+        }
+    }
+}
+
+impl<F: FieldElement> Aggregatable for Prio3ThresholdAggregateShare<F> {
+    type OutputShare = Prio3ThresholdOutputShare<F>;
+
+    fn merge(&mut self, agg_share: &Self) -> Result<(), VdafError> {
+        if let Some(agg_id) = agg_share.agg_id {
+            self.set_agg_id(agg_id)?;
+        }
+        merge_vector(&mut self.share, &agg_share.share).map_err(Into::into)
+    }
+
+    fn accumulate(&mut self, output_share: &Self::OutputShare) -> Result<(), VdafError> {
+        self.set_agg_id(output_share.agg_id)?;
+        merge_vector(&mut self.share, &output_share.share).map_err(Into::into)
+    }
+}
+
+impl<F: FieldElement> EncodedAggregatable for Prio3ThresholdAggregateShare<F> {
+    fn accumulate_encoded(&mut self, encoded_output_share: &[u8]) -> Result<(), VdafError> {
+        let (&agg_id, encoded_share) = encoded_output_share
+            .split_first()
+            .ok_or_else(|| VdafError::Uncategorized("encoded output share is empty".to_string()))?;
+        // Restore the aggregator ID if the share is rejected, so that `self` is left unchanged.
+        let prev_agg_id = self.agg_id;
+        self.set_agg_id(agg_id)?;
+        merge_encoded_vector(&mut self.share, encoded_share).map_err(|e| {
+            self.agg_id = prev_agg_id;
+            e.into()
+        })
+    }
+}
+
+impl<F: FieldElement> RetractableAggregatable for Prio3ThresholdAggregateShare<F> {
+    fn subtract(&mut self, agg_share: &Self) -> Result<(), VdafError> {
+        if let Some(agg_id) = agg_share.agg_id {
+            self.set_agg_id(agg_id)?;
+        }
+        unmerge_vector(&mut self.share, &agg_share.share).map_err(Into::into)
+    }
+
+    fn retract(&mut self, output_share: &Prio3ThresholdOutputShare<F>) -> Result<(), VdafError> {
+        self.set_agg_id(output_share.agg_id)?;
+        unmerge_vector(&mut self.share, &output_share.share).map_err(Into::into)
+    }
+}
+
+impl<F: FieldElement> Encode for Prio3ThresholdAggregateShare<F> {
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), CodecError> {
+        match self.agg_id {
+            Some(agg_id) => {
+                1u8.encode(bytes)?;
+                agg_id.encode(bytes)?;
+            }
+            None => 0u8.encode(bytes)?,
+        }
+        encode_fixlen_items(bytes, &self.share)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let agg_id_len = if self.agg_id.is_some() { 2 } else { 1 };
+        Some(agg_id_len + F::ENCODED_SIZE * self.share.len())
+    }
+}
+
+impl<'a, T, P, const SEED_SIZE: usize>
+    ParameterizedDecode<(&'a Prio3Threshold<T, P, SEED_SIZE>, &'a ())>
+    for Prio3ThresholdAggregateShare<T::Field>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    fn decode_with_param(
+        (vdaf, _): &(&'a Prio3Threshold<T, P, SEED_SIZE>, &'a ()),
+        bytes: &mut Cursor<&[u8]>,
+    ) -> Result<Self, CodecError> {
+        let agg_id = match u8::decode(bytes)? {
+            0 => None,
+            1 => Some(decode_agg_id(vdaf, bytes)?),
+            _ => return Err(CodecError::UnexpectedValue),
+        };
+        let share = decode_fieldvec(vdaf.output_len(), bytes)?;
+        Ok(Self { agg_id, share })
+    }
+}
+
+impl<T, P, const SEED_SIZE: usize> Client<16> for Prio3Threshold<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    #[allow(clippy::type_complexity)]
+    fn shard(
+        &self,
+        ctx: &[u8],
+        measurement: &T::Measurement,
+        nonce: &[u8; 16],
+    ) -> Result<
+        (
+            Prio3PublicShare<SEED_SIZE>,
+            Vec<Prio3ThresholdInputShare<T::Field, SEED_SIZE>>,
+        ),
+        VdafError,
+    > {
+        let num_aggregators = self.prio3.num_aggregators();
+        let threshold = self.threshold();
+        let mut rng = rng();
+        let encoded_measurement = self.prio3.typ.encode_measurement(measurement)?;
+        let measurement_shares = shamir_share(&encoded_measurement, threshold, num_aggregators);
+
+        // Compute the joint randomness.
+        let (joint_rand_blinds, public_share) = if self.prio3.typ.joint_rand_len() > 0 {
+            let joint_rand_blinds = iter::repeat_with(|| rng.random::<Seed<SEED_SIZE>>())
+                .take(num_aggregators)
+                .collect::<Vec<_>>();
+            let joint_rand_parts = joint_rand_blinds
+                .iter()
+                .zip(measurement_shares.iter())
+                .enumerate()
+                .map(|(agg_id, (blind, measurement_share))| {
+                    self.joint_rand_part(
+                        ctx,
+                        blind,
+                        u8::try_from(agg_id).unwrap(),
+                        nonce,
+                        measurement_share,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            (
+                joint_rand_blinds.into_iter().map(Some).collect(),
+                Prio3PublicShare {
+                    joint_rand_parts: Some(joint_rand_parts),
+                },
+            )
+        } else {
+            (
+                vec![None; num_aggregators],
+                Prio3PublicShare {
+                    joint_rand_parts: None,
+                },
+            )
+        };
+        let joint_rands = public_share
+            .joint_rand_parts
+            .as_ref()
+            .map(|joint_rand_parts| {
+                self.prio3
+                    .derive_joint_rands(ctx, joint_rand_parts.iter())
+                    .1
+            })
+            .unwrap_or_default();
+
+        // Generate the proofs.
+        let prove_rands = self.prio3.derive_prove_rands(ctx, &rng.random());
+        let prove_rand_len = self.prio3.typ.prove_rand_len();
+        let joint_rand_len = self.prio3.typ.joint_rand_len();
+        let mut proofs = Vec::with_capacity(self.proofs_len());
+        for p in 0..self.prio3.num_proofs() {
+            proofs.append(&mut self.prio3.typ.prove(
+                &encoded_measurement,
+                &prove_rands[p * prove_rand_len..(p + 1) * prove_rand_len],
+                &joint_rands[p * joint_rand_len..(p + 1) * joint_rand_len],
+            )?);
+        }
+        let proofs_shares = shamir_share(&proofs, threshold, num_aggregators);
+
+        // Share a random mask for the consistency check.
+        let mask = Prng::new().take(1).collect::<Vec<_>>();
+        let mask_shares = shamir_share(&mask, threshold, num_aggregators);
+
+        let input_shares = measurement_shares
+            .into_iter()
+            .zip(proofs_shares)
+            .zip(mask_shares)
+            .zip(joint_rand_blinds)
+            .map(
+                |(((measurement_share, proofs_share), mask_share), joint_rand_blind)| {
+                    Prio3ThresholdInputShare {
+                        measurement_share,
+                        proofs_share,
+                        mask_share: mask_share[0],
+                        joint_rand_blind,
+                    }
+                },
+            )
+            .collect();
+
+        Ok((public_share, input_shares))
+    }
+}
+
+impl<T, P, const SEED_SIZE: usize> Aggregator<SEED_SIZE, 16> for Prio3Threshold<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    type PrepareState = Prio3ThresholdPrepareState<T::Field, SEED_SIZE>;
+    type PrepareShare = Prio3ThresholdPrepareShare<T::Field, SEED_SIZE>;
+    type PrepareMessage = Prio3PrepareMessage<SEED_SIZE>;
+
+    /// Begins the Prep process with the other aggregators. The result of this process is
+    /// the aggregator's output share.
+    #[allow(clippy::type_complexity)]
+    fn prepare_init(
+        &self,
+        verify_key: &[u8; SEED_SIZE],
+        ctx: &[u8],
+        agg_id: usize,
+        _agg_param: &Self::AggregationParam,
+        nonce: &[u8; 16],
+        public_share: &Self::PublicShare,
+        msg: &Prio3ThresholdInputShare<T::Field, SEED_SIZE>,
+    ) -> Result<
+        (
+            Prio3ThresholdPrepareState<T::Field, SEED_SIZE>,
+            Prio3ThresholdPrepareShare<T::Field, SEED_SIZE>,
+        ),
+        VdafError,
+    > {
+        let agg_id = self.prio3.role_try_from(agg_id)?;
+        let typ = &self.prio3.typ;
+        if msg.measurement_share.len() != typ.input_len()
+            || msg.proofs_share.len() != self.proofs_len()
+        {
+            return Err(VdafError::Uncategorized(
+                "unexpected input share length".to_string(),
+            ));
+        }
+
+        // Compute the joint randomness. As in Prio3, this aggregator's contribution is computed
+        // from its input share in lieu of the corresponding part from the public share.
+        let (joint_rand_seed, joint_rands) = if typ.joint_rand_len() > 0 {
+            let joint_rand_blind = msg.joint_rand_blind.as_ref().ok_or_else(|| {
+                VdafError::Uncategorized("missing joint randomness blind".to_string())
+            })?;
+            let own_joint_rand_part =
+                self.joint_rand_part(ctx, joint_rand_blind, agg_id, nonce, &msg.measurement_share)?;
+            let corrected_joint_rand_parts = public_share
+                .joint_rand_parts
+                .iter()
+                .flatten()
+                .take(agg_id.into())
+                .chain(iter::once(&own_joint_rand_part))
+                .chain(
+                    public_share
+                        .joint_rand_parts
+                        .iter()
+                        .flatten()
+                        .skip(usize::from(agg_id) + 1),
+                );
+            let (joint_rand_seed, joint_rands) = self
+                .prio3
+                .derive_joint_rands(ctx, corrected_joint_rand_parts);
+            (Some(joint_rand_seed), joint_rands)
+        } else {
+            (None, Vec::new())
+        };
+
+        // Run the query-generation algorithm. The input and proof shares are Shamir shares, so
+        // constants in the validity circuit are added to each share in full.
+        let query_rands = self
+            .prio3
+            .query_rands_seed_stream(verify_key, ctx, nonce)
+            .into_field_vec(typ.query_rand_len() * self.prio3.num_proofs());
+        let mut verifiers = Vec::with_capacity(typ.verifier_len() * self.prio3.num_proofs());
+        for p in 0..self.prio3.num_proofs() {
+            verifiers.append(&mut typ.query(
+                &msg.measurement_share,
+                &msg.proofs_share[p * typ.proof_len()..(p + 1) * typ.proof_len()],
+                &query_rands[p * typ.query_rand_len()..(p + 1) * typ.query_rand_len()],
+                &joint_rands[p * typ.joint_rand_len()..(p + 1) * typ.joint_rand_len()],
+                1,
+            )?);
+        }
+
+        let consistency_check = self
+            .derive_consistency_rands(verify_key, ctx, nonce)
+            .into_iter()
+            .zip(msg.measurement_share.iter().chain(msg.proofs_share.iter()))
+            .fold(msg.mask_share, |acc, (r, x)| acc + r * *x);
+
+        Ok((
+            Prio3ThresholdPrepareState {
+                measurement_share: msg.measurement_share.clone(),
+                joint_rand_seed: joint_rand_seed.clone(),
+                agg_id,
+                verifiers_len: verifiers.len(),
+            },
+            Prio3ThresholdPrepareShare {
+                agg_id,
+                verifiers,
+                consistency_check,
+                joint_rand_seed,
+            },
+        ))
+    }
+
+    /// Combines the prepare shares of at least `threshold` aggregators into the prepare message.
+    ///
+    /// The verifier is interpolated from the first `threshold` prepare shares. Any additional
+    /// prepare shares are used to check that the client's input shares are consistent.
+    fn prepare_shares_to_prepare_message<
+        M: IntoIterator<Item = Prio3ThresholdPrepareShare<T::Field, SEED_SIZE>>,
+    >(
+        &self,
+        _ctx: &[u8],
+        _: &Self::AggregationParam,
+        inputs: M,
+    ) -> Result<Prio3PrepareMessage<SEED_SIZE>, VdafError> {
+        let prep_shares = inputs.into_iter().collect::<Vec<_>>();
+        self.check_share_count(prep_shares.len())?;
+        let points = self.eval_points(prep_shares.iter().map(|share| share.agg_id))?;
+
+        let verifiers_len = self.verifier_len() * self.prio3.num_proofs();
+        let joint_rand_seed = prep_shares[0].joint_rand_seed.clone();
+        let mut shares = Vec::with_capacity(prep_shares.len());
+        for prep_share in prep_shares {
+            if prep_share.verifiers.len() != verifiers_len {
+                return Err(VdafError::Uncategorized(format!(
+                    "unexpected verifier share length: got {}; want {}",
+                    prep_share.verifiers.len(),
+                    verifiers_len,
+                )));
+            }
+
+            // Every aggregator has to agree on the joint randomness.
+            if !bool::from(option_ct_eq(
+                joint_rand_seed.as_ref(),
+                prep_share.joint_rand_seed.as_ref(),
+            )) {
+                return Err(VdafError::Uncategorized(
+                    "joint randomness mismatch".to_string(),
+                ));
+            }
+
+            let mut share = prep_share.verifiers;
+            share.push(prep_share.consistency_check);
+            shares.push(share);
+        }
+
+        let verifiers = interpolate(self.threshold(), &points, &shares)
+            .ok_or_else(|| VdafError::Uncategorized("input shares are inconsistent".to_string()))?;
+
+        // Check the proof verifiers. The last element is the masked consistency check, which is
+        // discarded.
+        for verifier in verifiers[..verifiers_len].chunks(self.verifier_len()) {
+            if !self.prio3.typ.decide(verifier)? {
+                return Err(VdafError::Uncategorized(
+                    "proof verifier check failed".into(),
+                ));
+            }
+        }
+
+        Ok(Prio3PrepareMessage { joint_rand_seed })
+    }
+
+    fn prepare_next(
+        &self,
+        _ctx: &[u8],
+        step: Prio3ThresholdPrepareState<T::Field, SEED_SIZE>,
+        msg: Prio3PrepareMessage<SEED_SIZE>,
+    ) -> Result<PrepareTransition<Self, SEED_SIZE, 16>, VdafError> {
+        // Check that the joint randomness was correct.
+        if !bool::from(option_ct_eq(
+            step.joint_rand_seed.as_ref(),
+            msg.joint_rand_seed.as_ref(),
+        )) {
+            return Err(VdafError::Uncategorized(
+                "joint randomness mismatch".to_string(),
+            ));
+        }
+
+        // Compute the output share. Truncation is linear, so it maps Shamir shares to Shamir
+        // shares.
+        let share = self.prio3.typ.truncate(step.measurement_share)?;
+        Ok(PrepareTransition::Finish(Prio3ThresholdOutputShare {
+            agg_id: step.agg_id,
+            share,
+        }))
+    }
+
+    fn aggregate_init(&self, _agg_param: &Self::AggregationParam) -> Self::AggregateShare {
+        Prio3ThresholdAggregateShare {
+            agg_id: None,
+            share: vec![T::Field::zero(); self.output_len()],
+        }
+    }
+
+    /// Returns `true` iff `prev.is_empty()`
+    fn is_agg_param_valid(_cur: &Self::AggregationParam, prev: &[Self::AggregationParam]) -> bool {
+        prev.is_empty()
+    }
+}
+
+impl<T, P, const SEED_SIZE: usize> Collector for Prio3Threshold<T, P, SEED_SIZE>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    /// Combines the aggregate shares of at least `threshold` aggregators into the aggregate
+    /// result. Aggregate shares without an aggregator ID, i.e., of empty batches, are ignored.
+    fn unshard<It: IntoIterator<Item = Prio3ThresholdAggregateShare<T::Field>>>(
+        &self,
+        _agg_param: &Self::AggregationParam,
+        agg_shares: It,
+        num_measurements: usize,
+    ) -> Result<T::AggregateResult, VdafError> {
+        let (agg_ids, shares): (Vec<_>, Vec<_>) = agg_shares
+            .into_iter()
+            .filter_map(|agg_share| Some((agg_share.agg_id?, agg_share.share)))
+            .unzip();
+
+        let agg = if shares.is_empty() {
+            vec![T::Field::zero(); self.output_len()]
+        } else {
+            self.check_share_count(shares.len())?;
+            let points = self.eval_points(agg_ids.into_iter())?;
+            if shares.iter().any(|share| share.len() != self.output_len()) {
+                return Err(VdafError::Uncategorized(
+                    "unexpected aggregate share length".to_string(),
+                ));
+            }
+            interpolate(self.threshold(), &points, &shares).ok_or_else(|| {
+                VdafError::Uncategorized("aggregate shares are inconsistent".to_string())
+            })?
+        };
+
+        Ok(self.prio3.typ.decode_result(&agg, num_measurements)?)
+    }
+}
+
+/// Decodes an aggregator ID and checks that it is valid for `vdaf`.
+fn decode_agg_id<T, P, const SEED_SIZE: usize>(
+    vdaf: &Prio3Threshold<T, P, SEED_SIZE>,
+    bytes: &mut Cursor<&[u8]>,
+) -> Result<u8, CodecError>
+where
+    T: Type,
+    P: Xof<SEED_SIZE>,
+{
+    vdaf.prio3
+        .role_try_from(u8::decode(bytes)?.into())
+        .map_err(|e| CodecError::Other(Box::new(e)))
+}
+
+/// Returns the point at which the polynomials are evaluated to get the shares of aggregator
+/// `agg_id`.
+fn eval_point<F: FieldElementWithInteger>(agg_id: u8) -> F {
+    F::from(F::Integer::try_from(usize::from(agg_id) + 1).unwrap())
+}
+
+/// Splits `secret` into `num_shares` Shamir shares, any `threshold` of which determine the secret.
+/// The `j`-th share is the evaluation at [`eval_point`]`(j)` of a random polynomial of degree
+/// `threshold - 1` whose constant term is `secret`.
+fn shamir_share<F: FieldElementWithInteger>(
+    secret: &[F],
+    threshold: usize,
+    num_shares: usize,
+) -> Vec<Vec<F>> {
+    let mut prng = Prng::new();
+    let coefficients = iter::repeat_with(|| prng.by_ref().take(secret.len()).collect::<Vec<F>>())
+        .take(threshold - 1)
+        .collect::<Vec<_>>();
+
+    (0..num_shares)
+        .map(|j| {
+            let x = eval_point::<F>(u8::try_from(j).unwrap());
+            // Evaluate the polynomial with Horner's method.
+            let mut share = vec![F::zero(); secret.len()];
+            for coefficient in coefficients
+                .iter()
+                .rev()
+                .chain(iter::once(&secret.to_vec()))
+            {
+                for (y, c) in share.iter_mut().zip(coefficient) {
+                    *y = *y * x + *c;
+                }
+            }
+            share
+        })
+        .collect()
+}
+
+/// Returns the Lagrange coefficients for evaluating, at `at`, the polynomial of degree
+/// `points.len() - 1` that passes through the given points.
+fn lagrange_coefficients<F: FieldElement>(points: &[F], at: F) -> Vec<F> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(j, x_j)| {
+            let (numerator, denominator) = points
+                .iter()
+                .enumerate()
+                .filter(|(m, _)| *m != j)
+                .fold((F::one(), F::one()), |(num, den), (_, x_m)| {
+                    (num * (at - *x_m), den * (*x_j - *x_m))
+                });
+            numerator * denominator.inv()
+        })
+        .collect()
+}
+
+/// Reconstructs the secret from Shamir shares, where `shares[i]` was evaluated at `points[i]`.
+/// The secret is interpolated from the first `threshold` shares. Returns `None` if any of the
+/// remaining shares does not lie on the same polynomial.
+fn interpolate<F: FieldElement>(
+    threshold: usize,
+    points: &[F],
+    shares: &[Vec<F>],
+) -> Option<Vec<F>> {
+    let combine = |coefficients: Vec<F>| {
+        let mut out = vec![F::zero(); shares[0].len()];
+        for (coefficient, share) in coefficients.into_iter().zip(shares) {
+            for (y, x) in out.iter_mut().zip(share) {
+                *y += coefficient * *x;
+            }
+        }
+        out
+    };
+
+    for (point, share) in points.iter().zip(shares).skip(threshold) {
+        let expected = combine(lagrange_coefficients(&points[..threshold], *point));
+        if !bool::from(expected.ct_eq(share)) {
+            return None;
+        }
+    }
+
+    Some(combine(lagrange_coefficients(
+        &points[..threshold],
+        F::zero(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vdaf::test_utils::run_vdaf;
+    use rand::random;
+
+    /// Shards the measurements, then prepares, aggregates and unshards them using only the
+    /// aggregators in `survivors`.
+    fn run_with_survivors<T, P, const SEED_SIZE: usize>(
+        vdaf: &Prio3Threshold<T, P, SEED_SIZE>,
+        measurements: &[T::Measurement],
+        survivors: &[usize],
+    ) -> Result<T::AggregateResult, VdafError>
+    where
+        T: Type,
+        P: Xof<SEED_SIZE>,
+    {
+        let ctx = b"threshold test";
+        let verify_key = random();
+        let mut agg_shares = survivors
+            .iter()
+            .map(|_| vdaf.aggregate_init(&()))
+            .collect::<Vec<_>>();
+        for measurement in measurements {
+            let nonce = random();
+            let (public_share, input_shares) = vdaf.shard(ctx, measurement, &nonce)?;
+            let (states, prep_shares): (Vec<_>, Vec<_>) = survivors
+                .iter()
+                .map(|&agg_id| {
+                    vdaf.prepare_init(
+                        &verify_key,
+                        ctx,
+                        agg_id,
+                        &(),
+                        &nonce,
+                        &public_share,
+                        &input_shares[agg_id],
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .unzip();
+            let prep_msg = vdaf.prepare_shares_to_prepare_message(ctx, &(), prep_shares)?;
+            for (state, agg_share) in states.into_iter().zip(agg_shares.iter_mut()) {
+                match vdaf.prepare_next(ctx, state, prep_msg.clone())? {
+                    PrepareTransition::Finish(output_share) => {
+                        agg_share.accumulate(&output_share)?
+                    }
+                    PrepareTransition::Continue(..) => panic!("unexpected transition"),
+                }
+            }
+        }
+        vdaf.unshard(&(), agg_shares, measurements.len())
+    }
+
+    /// Runs the VDAF for every set of surviving aggregators. Sets of at least `threshold`
+    /// aggregators must compute `expected`, and smaller sets must fail.
+    fn check_all_subsets<T, P, const SEED_SIZE: usize>(
+        vdaf: &Prio3Threshold<T, P, SEED_SIZE>,
+        measurements: &[T::Measurement],
+        expected: &T::AggregateResult,
+    ) where
+        T: Type,
+        P: Xof<SEED_SIZE>,
+        T::AggregateResult: PartialEq,
+    {
+        let num_aggregators = vdaf.num_aggregators();
+        for subset in 1..1u32 << num_aggregators {
+            let survivors = (0..num_aggregators)
+                .filter(|agg_id| subset & (1 << agg_id) != 0)
+                .collect::<Vec<_>>();
+            let result = run_with_survivors(vdaf, measurements, &survivors);
+            if survivors.len() >= vdaf.threshold() {
+                assert_eq!(&result.unwrap(), expected, "survivors {survivors:?}");
+            } else {
+                assert!(result.is_err(), "survivors {survivors:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_prio3_threshold_count() {
+        let vdaf = Prio3ThresholdCount::new_count(4, 2).unwrap();
+        check_all_subsets(&vdaf, &[true, false, true, true], &3);
+
+        let vdaf = Prio3ThresholdCount::new_count(5, 3).unwrap();
+        check_all_subsets(&vdaf, &[true, false, true], &2);
+        assert_eq!(run_vdaf(b"", &vdaf, &(), [true, true]).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_prio3_threshold_sum() {
+        let vdaf = Prio3ThresholdSum::new_sum(3, 2, 100).unwrap();
+        check_all_subsets(&vdaf, &[0, 42, 100], &142);
+    }
+
+    #[test]
+    fn test_prio3_threshold_sum_vec() {
+        let vdaf = Prio3ThresholdSumVec::new_sum_vec(4, 3, 2, 5, 3).unwrap();
+        check_all_subsets(
+            &vdaf,
+            &[vec![1, 2, 3, 0, 1], vec![3, 3, 0, 1, 2]],
+            &vec![4, 5, 3, 1, 3],
+        );
+
+        // Multiple proofs.
+        let vdaf = Prio3Threshold::<_, XofTurboShake128, 32>::new(
+            3,
+            2,
+            2,
+            ALGORITHM_ID_MIN,
+            SumVec::<Field64, ParallelSum<Field64, Mul<Field64>>>::new(1, 4, 2).unwrap(),
+        )
+        .unwrap();
+        check_all_subsets(
+            &vdaf,
+            &[vec![1, 0, 1, 1], vec![0, 0, 1, 1]],
+            &vec![1, 0, 2, 2],
+        );
+    }
+
+    #[test]
+    fn test_prio3_threshold_histogram() {
+        let vdaf = Prio3ThresholdHistogram::new_histogram(4, 2, 4, 2).unwrap();
+        check_all_subsets(&vdaf, &[0, 3, 3, 1], &vec![1, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_prio3_threshold_new() {
+        assert!(Prio3ThresholdCount::new_count(3, 0).is_err());
+        assert!(Prio3ThresholdCount::new_count(3, 1).is_err());
+        assert!(Prio3ThresholdCount::new_count(3, 4).is_err());
+        assert!(Prio3ThresholdCount::new_count(3, 3).is_ok());
+        assert!(Prio3Threshold::<_, XofTurboShake128, 32>::new(
+            3,
+            2,
+            1,
+            0x00000001,
+            Count::<Field64>::new()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_prio3_threshold_malicious_client() {
+        let vdaf = Prio3ThresholdSumVec::new_sum_vec(4, 2, 1, 3, 1).unwrap();
+        let ctx = b"threshold test";
+        let verify_key = random();
+        let nonce = random();
+
+        let prepare = |public_share: &Prio3PublicShare<32>,
+                       input_shares: &[Prio3ThresholdInputShare<Field128, 32>],
+                       survivors: &[usize]| {
+            let prep_shares = survivors
+                .iter()
+                .map(|&agg_id| {
+                    vdaf.prepare_init(
+                        &verify_key,
+                        ctx,
+                        agg_id,
+                        &(),
+                        &nonce,
+                        public_share,
+                        &input_shares[agg_id],
+                    )
+                    .unwrap()
+                    .1
+                })
+                .collect::<Vec<_>>();
+            vdaf.prepare_shares_to_prepare_message(ctx, &(), prep_shares)
+        };
+
+        let (public_share, input_shares) = vdaf.shard(ctx, &vec![1, 0, 1], &nonce).unwrap();
+        assert!(prepare(&public_share, &input_shares, &[0, 1, 2, 3]).is_ok());
+
+        // Shares that are not consistent with each other are detected as long as more than
+        // `threshold` aggregators take part.
+        let mut tampered = input_shares.clone();
+        tampered[3].proofs_share[0] += Field128::one();
+        assert!(prepare(&public_share, &tampered, &[0, 1, 3]).is_err());
+        assert!(prepare(&public_share, &tampered, &[0, 1, 2]).is_ok());
+
+        // Shifting every measurement share by the same amount is consistent, but encodes an invalid
+        // measurement.
+        let mut tampered = input_shares.clone();
+        for input_share in tampered.iter_mut() {
+            input_share.measurement_share[1] += Field128::one();
+        }
+        assert!(prepare(&public_share, &tampered, &[0, 2]).is_err());
+
+        // Duplicate prepare shares do not count towards the threshold.
+        assert!(prepare(&public_share, &input_shares, &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_prio3_threshold_retract() {
+        let output_shares = [1, 2].map(|agg_id| Prio3ThresholdOutputShare {
+            agg_id,
+            share: Field64::random_vector(3),
+        });
+        let mut want = Prio3ThresholdAggregateShare::from(output_shares[0].clone());
+        want.accumulate(&output_shares[0]).unwrap();
+
+        let mut got = want.clone();
+        got.accumulate(&output_shares[0]).unwrap();
+        got.retract(&output_shares[0]).unwrap();
+        assert_eq!(got, want);
+
+        let other = Prio3ThresholdAggregateShare::from(output_shares[0].clone());
+        got.merge(&other).unwrap();
+        got.subtract(&other).unwrap();
+        assert_eq!(got, want);
+
+        // Shares of different aggregators can't be combined.
+        got.retract(&output_shares[1]).unwrap_err();
+        got.subtract(&Prio3ThresholdAggregateShare::from(
+            output_shares[1].clone(),
+        ))
+        .unwrap_err();
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn test_prio3_threshold_accumulate_encoded() {
+        let output_shares = [1, 2].map(|agg_id| Prio3ThresholdOutputShare {
+            agg_id,
+            share: Field64::random_vector(3),
+        });
+        let mut want = Prio3ThresholdAggregateShare::from(output_shares[0].clone());
+        want.accumulate(&output_shares[0]).unwrap();
+
+        let mut got = Prio3ThresholdAggregateShare::from(output_shares[0].clone());
+        got.accumulate_encoded(&output_shares[0].get_encoded().unwrap())
+            .unwrap();
+        assert_eq!(got, want);
+
+        // Shares of different aggregators can't be combined.
+        got.accumulate_encoded(&output_shares[1].get_encoded().unwrap())
+            .unwrap_err();
+        got.accumulate_encoded(&[]).unwrap_err();
+        assert_eq!(got, want);
+
+        // A rejected share doesn't set the aggregator ID.
+        let mut empty = Prio3ThresholdAggregateShare {
+            agg_id: None,
+            share: vec![Field64::zero(); 3],
+        };
+        let mut encoded = output_shares[1].get_encoded().unwrap();
+        encoded.pop();
+        empty.accumulate_encoded(&encoded).unwrap_err();
+        assert_eq!(empty.agg_id, None);
+    }
+
+    #[test]
+    fn test_prio3_threshold_roundtrip() {
+        let vdaf = Prio3ThresholdHistogram::new_histogram(3, 2, 4, 2).unwrap();
+        let ctx = b"threshold test";
+        let verify_key = random();
+        let nonce = random();
+        let (public_share, input_shares) = vdaf.shard(ctx, &2, &nonce).unwrap();
+
+        let encoded = public_share.get_encoded().unwrap();
+        assert_eq!(
+            Prio3PublicShare::get_decoded_with_param(&vdaf, &encoded).unwrap(),
+            public_share
+        );
+
+        for (agg_id, input_share) in input_shares.iter().enumerate() {
+            let encoded = input_share.get_encoded().unwrap();
+            assert_eq!(encoded.len(), input_share.encoded_len().unwrap());
+            assert_eq!(
+                &Prio3ThresholdInputShare::get_decoded_with_param(&(&vdaf, agg_id), &encoded)
+                    .unwrap(),
+                input_share
+            );
+
+            let (state, prep_share) = vdaf
+                .prepare_init(
+                    &verify_key,
+                    ctx,
+                    agg_id,
+                    &(),
+                    &nonce,
+                    &public_share,
+                    input_share,
+                )
+                .unwrap();
+            let encoded = state.get_encoded().unwrap();
+            assert_eq!(
+                Prio3ThresholdPrepareState::get_decoded_with_param(&(&vdaf, agg_id), &encoded)
+                    .unwrap(),
+                state
+            );
+            let encoded = prep_share.get_encoded().unwrap();
+            assert_eq!(encoded.len(), prep_share.encoded_len().unwrap());
+            assert_eq!(
+                Prio3ThresholdPrepareShare::get_decoded_with_param(&state, &encoded).unwrap(),
+                prep_share
+            );
+
+            let mut agg_share = vdaf.aggregate_init(&());
+            let encoded = agg_share.get_encoded().unwrap();
+            assert_eq!(
+                Prio3ThresholdAggregateShare::get_decoded_with_param(&(&vdaf, &()), &encoded)
+                    .unwrap(),
+                agg_share
+            );
+            let output_share = Prio3ThresholdOutputShare {
+                agg_id: agg_id.try_into().unwrap(),
+                share: vec![Field128::one(); vdaf.output_len()],
+            };
+            let encoded = output_share.get_encoded().unwrap();
+            assert_eq!(
+                Prio3ThresholdOutputShare::get_decoded_with_param(&(&vdaf, &()), &encoded).unwrap(),
+                output_share
+            );
+            agg_share.accumulate(&output_share).unwrap();
+            let encoded = agg_share.get_encoded().unwrap();
+            assert_eq!(encoded.len(), agg_share.encoded_len().unwrap());
+            assert_eq!(
+                Prio3ThresholdAggregateShare::get_decoded_with_param(&(&vdaf, &()), &encoded)
+                    .unwrap(),
+                agg_share
+            );
+        }
+    }
+}
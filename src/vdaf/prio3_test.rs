@@ -9,7 +9,10 @@ use crate::{
         types::SumVec,
     },
     vdaf::{
-        prio3::{Prio3, Prio3Count, Prio3Histogram, Prio3MultihotCountVec, Prio3Sum},
+        prio3::{
+            Prio3, Prio3Count, Prio3ExactWeightCountVec, Prio3Histogram, Prio3MultihotCountVec,
+            Prio3OptionalHistogram, Prio3Sum,
+        },
         test_utils::TestVectorVdaf,
         xof::Xof,
     },
@@ -153,8 +156,66 @@ impl TestVectorVdaf for Prio3MultihotCountVec {
     }
 }
 
+impl TestVectorVdaf for Prio3OptionalHistogram {
+    fn new(shares: u8, parameters: &HashMap<String, Value>) -> Self {
+        let length = parameters["length"].as_u64().unwrap().try_into().unwrap();
+        let chunk_length = parameters["chunk_length"]
+            .as_u64()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        Prio3::new_optional_histogram(shares, length, chunk_length).unwrap()
+    }
+
+    fn deserialize_measurement(measurement: &Value) -> Self::Measurement {
+        measurement.as_u64().map(|index| index.try_into().unwrap())
+    }
+
+    fn deserialize_aggregate_result(aggregate_result: &Value) -> Self::AggregateResult {
+        aggregate_result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_u64().unwrap().into())
+            .collect()
+    }
+}
+
+impl TestVectorVdaf for Prio3ExactWeightCountVec {
+    fn new(shares: u8, parameters: &HashMap<String, Value>) -> Self {
+        let length = parameters["length"].as_u64().unwrap().try_into().unwrap();
+        let weight = parameters["weight"].as_u64().unwrap().try_into().unwrap();
+        let chunk_length = parameters["chunk_length"]
+            .as_u64()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        Prio3::new_exact_weight_count_vec(shares, length, weight, chunk_length).unwrap()
+    }
+
+    fn deserialize_measurement(measurement: &Value) -> Self::Measurement {
+        measurement
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_bool().unwrap())
+            .collect()
+    }
+
+    fn deserialize_aggregate_result(aggregate_result: &Value) -> Self::AggregateResult {
+        aggregate_result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_u64().unwrap().into())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "crypto-dependencies")]
+    use crate::vdaf::prio3::Prio3SumVecField64MultiproofHmacSha256Aes128;
     use crate::{
         field::Field64,
         flp::{
@@ -163,7 +224,8 @@ mod tests {
         },
         vdaf::{
             prio3::{
-                Prio3, Prio3Count, Prio3Histogram, Prio3MultihotCountVec, Prio3Sum, Prio3SumVec,
+                Prio3, Prio3Count, Prio3ExactWeightCountVec, Prio3Histogram, Prio3MultihotCountVec,
+                Prio3OptionalHistogram, Prio3Sum, Prio3SumVec,
             },
             test_utils::{check_test_vector, check_test_vector_custom_constructor},
             xof::XofTurboShake128,
@@ -265,4 +327,71 @@ mod tests {
             check_test_vector::<Prio3MultihotCountVec, 32, 16>(&test_vector);
         }
     }
+
+    // These types are not specified by the draft. The following test vectors use the draft's
+    // format and were generated by this implementation, so they guard against regressions in the
+    // encoding rather than checking interoperability.
+
+    #[test]
+    fn test_vec_prio3_optional_histogram() {
+        for test_vector_str in [
+            include_str!("test_vec/custom/Prio3OptionalHistogram_0.json"),
+            include_str!("test_vec/custom/Prio3OptionalHistogram_1.json"),
+            include_str!("test_vec/custom/Prio3OptionalHistogram_2.json"),
+        ] {
+            let test_vector = serde_json::from_str(test_vector_str).unwrap();
+            check_test_vector::<Prio3OptionalHistogram, 32, 16>(&test_vector);
+        }
+    }
+
+    #[test]
+    fn test_vec_prio3_exact_weight_count_vec() {
+        for test_vector_str in [
+            include_str!("test_vec/custom/Prio3ExactWeightCountVec_0.json"),
+            include_str!("test_vec/custom/Prio3ExactWeightCountVec_1.json"),
+        ] {
+            let test_vector = serde_json::from_str(test_vector_str).unwrap();
+            check_test_vector::<Prio3ExactWeightCountVec, 32, 16>(&test_vector);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "crypto-dependencies")]
+    fn test_vec_prio3_sum_vec_field64_multiproof_hmac_sha256_aes128() {
+        // These reuse the inputs of the draft's Prio3SumVecWithMultiproof test vectors, but the
+        // draft does not specify this XOF, so the expected outputs were generated by this
+        // implementation.
+        for test_vector_str in [
+            include_str!("test_vec/custom/Prio3SumVecField64MultiproofHmacSha256Aes128_0.json"),
+            include_str!("test_vec/custom/Prio3SumVecField64MultiproofHmacSha256Aes128_1.json"),
+        ] {
+            let test_vector = serde_json::from_str(test_vector_str).unwrap();
+            check_test_vector_custom_constructor::<
+                Prio3SumVecField64MultiproofHmacSha256Aes128<ParallelSum<Field64, Mul<Field64>>>,
+                32,
+                16,
+            >(&test_vector, |shares, parameters| {
+                let bits = parameters["bits"].as_u64().unwrap().try_into().unwrap();
+                let length = parameters["length"].as_u64().unwrap().try_into().unwrap();
+                let chunk_length = parameters["chunk_length"]
+                    .as_u64()
+                    .unwrap()
+                    .try_into()
+                    .unwrap();
+                let num_proofs = parameters["num_proofs"]
+                    .as_u64()
+                    .unwrap()
+                    .try_into()
+                    .unwrap();
+                Prio3::new_sum_vec_field64_multiproof_hmacsha256_aes128(
+                    shares,
+                    num_proofs,
+                    bits,
+                    length,
+                    chunk_length,
+                )
+                .unwrap()
+            });
+        }
+    }
 }
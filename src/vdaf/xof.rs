@@ -31,7 +31,7 @@ use ctr::Ctr64BE;
 use hmac::{Hmac, Mac};
 use rand_core::{
     impls::{next_u32_via_fill, next_u64_via_fill},
-    RngCore, SeedableRng,
+    CryptoRng, RngCore, SeedableRng,
 };
 
 use rand::distr::{Distribution, StandardUniform};
@@ -192,6 +192,9 @@ impl RngCore for SeedStreamAes128 {
     }
 }
 
+#[cfg(feature = "crypto-dependencies")]
+impl CryptoRng for SeedStreamAes128 {}
+
 #[cfg(feature = "crypto-dependencies")]
 impl Debug for SeedStreamAes128 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -277,6 +280,8 @@ impl RngCore for SeedStreamTurboShake128 {
     }
 }
 
+impl CryptoRng for SeedStreamTurboShake128 {}
+
 /// A `rand`-compatible interface to construct XofTurboShake128 seed streams, with the domain
 /// separation tag and binder string both fixed as the empty string.
 impl SeedableRng for SeedStreamTurboShake128 {
@@ -345,6 +350,41 @@ impl XofFixedKeyAes128Key {
             length_consumed: 0,
         }
     }
+
+    /// Return the first 32 bytes of the stream produced by [`Self::with_seed`] for each seed in
+    /// `seeds`. All the blocks are encrypted in a single call, which allows the AES implementation
+    /// to process several blocks in parallel.
+    pub(crate) fn fill_32_bytes_batch(&self, seeds: &[[u8; 16]]) -> Vec<[u8; 32]> {
+        let sigmas = seeds
+            .iter()
+            .flat_map(|seed| {
+                [0u64, 1].map(|block_counter| {
+                    let mut block = Block::from(*seed);
+                    for (b, i) in block.iter_mut().zip(block_counter.to_le_bytes().iter()) {
+                        *b ^= i;
+                    }
+                    fixed_key_sigma(&block)
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut blocks = sigmas.clone();
+        self.cipher.encrypt_blocks(&mut blocks);
+
+        blocks
+            .chunks_exact(2)
+            .zip(sigmas.chunks_exact(2))
+            .map(|(blocks, sigmas)| {
+                let mut out = [0; 32];
+                for (o, (b, s)) in out
+                    .iter_mut()
+                    .zip(blocks.iter().flatten().zip(sigmas.iter().flatten()))
+                {
+                    *o = b ^ s;
+                }
+                out
+            })
+            .collect()
+    }
 }
 
 /// XofFixedKeyAes128 as specified in [[draft-irtf-cfrg-vdaf-08]]. This XOF is NOT RECOMMENDED for
@@ -427,29 +467,35 @@ pub struct SeedStreamFixedKeyAes128 {
     length_consumed: u64,
 }
 
+/// The orthomorphism applied to each block before it is encrypted with the fixed key.
+#[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
+fn fixed_key_sigma(block: &Block) -> Block {
+    Block::from([
+        // hi
+        block[8],
+        block[9],
+        block[10],
+        block[11],
+        block[12],
+        block[13],
+        block[14],
+        block[15],
+        // xor(hi, lo)
+        block[8] ^ block[0],
+        block[9] ^ block[1],
+        block[10] ^ block[2],
+        block[11] ^ block[3],
+        block[12] ^ block[4],
+        block[13] ^ block[5],
+        block[14] ^ block[6],
+        block[15] ^ block[7],
+    ])
+}
+
 #[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
 impl SeedStreamFixedKeyAes128 {
     fn hash_block(&self, block: &mut Block) {
-        let sigma = Block::from([
-            // hi
-            block[8],
-            block[9],
-            block[10],
-            block[11],
-            block[12],
-            block[13],
-            block[14],
-            block[15],
-            // xor(hi, lo)
-            block[8] ^ block[0],
-            block[9] ^ block[1],
-            block[10] ^ block[2],
-            block[11] ^ block[3],
-            block[12] ^ block[4],
-            block[13] ^ block[5],
-            block[14] ^ block[6],
-            block[15] ^ block[7],
-        ]);
+        let sigma = fixed_key_sigma(block);
         self.cipher.encrypt_block_b2b(&sigma, block);
         for (b, s) in block.iter_mut().zip(sigma.iter()) {
             *b ^= s;
@@ -495,6 +541,9 @@ impl RngCore for SeedStreamFixedKeyAes128 {
     }
 }
 
+#[cfg(all(feature = "crypto-dependencies", feature = "experimental"))]
+impl CryptoRng for SeedStreamFixedKeyAes128 {}
+
 /// XOF based on HMAC-SHA256 and AES128. This XOF is not part of the VDAF spec.
 #[cfg(feature = "crypto-dependencies")]
 #[cfg_attr(docsrs, doc(cfg(feature = "crypto-dependencies")))]
@@ -695,6 +744,11 @@ mod tests {
 
         assert_eq!(output_1_trait_api, output_1_alternate_api);
         assert_eq!(output_2_trait_api, output_2_alternate_api);
+
+        assert_eq!(
+            fixed_key.fill_32_bytes_batch(&[seed_1.0, seed_2.0]),
+            [output_1_trait_api, output_2_trait_api]
+        );
     }
 
     #[test]
@@ -3,11 +3,22 @@
 use num_bigint::{BigInt, BigUint};
 use num_rational::Ratio;
 use num_traits::FromPrimitive;
-use prio::dp::distributions::DiscreteGaussian;
+use prio::dp::distributions::{
+    DiscreteGaussian, EpsilonDeltaDiscreteGaussian, PureDpDiscreteLaplace, ZCdpDiscreteGaussian,
+};
+use prio::dp::estimation::NoiseDistribution;
+use prio::dp::{
+    DifferentialPrivacyStrategy, EpsilonDeltaBudget, PureDpBudget, Rational, ZCdpBudget,
+};
+use prio::field::{Field128, FieldElement, FieldElementWithInteger};
+use prio::flp::gadgets::ParallelSum;
+use prio::flp::types::{Average, Count, MultihotCountVec, Sum};
+use prio::flp::TypeWithNoise;
 use prio::vdaf::xof::SeedStreamTurboShake128;
 use rand::distr::Distribution;
 use rand::SeedableRng;
 use serde::Deserialize;
+use statrs::distribution::{ContinuousCDF, Normal};
 
 /// A test vector of discrete Gaussian samples, produced by the python reference
 /// implementation for [[CKS20]]. The script used to generate the test vector can
@@ -63,3 +74,128 @@ fn discrete_gauss_reference() {
         );
     }
 }
+
+/// Number of noise samples drawn by each statistical test.
+const NUM_SAMPLES: usize = 10_000;
+
+/// Add noise to all-zero aggregate results of `typ` until `NUM_SAMPLES` noise values have been
+/// drawn, lift them out of the field, and check that their empirical mean is consistent with zero
+/// and that their empirical variance matches `expected_variance`.
+fn check_noise_moments<T, S>(typ: &T, dp_strategy: &S, expected_variance: f64, seed: u8)
+where
+    T: TypeWithNoise<S, Field = Field128>,
+    S: DifferentialPrivacyStrategy,
+{
+    let mut rng = SeedStreamTurboShake128::from_seed([seed; 32]);
+    let modulus = Field128::modulus();
+    let mut samples = Vec::with_capacity(NUM_SAMPLES);
+    while samples.len() < NUM_SAMPLES {
+        let mut agg_result = vec![Field128::zero(); typ.output_len()];
+        typ.add_noise_to_result_with_rng(dp_strategy, &mut agg_result, 1, &mut rng)
+            .unwrap();
+        samples.extend(agg_result.into_iter().map(|x| {
+            let x = u128::from(x);
+            if x > modulus / 2 {
+                -((modulus - x) as f64)
+            } else {
+                x as f64
+            }
+        }));
+    }
+    let n = samples.len() as f64;
+
+    // The empirical mean is approximately normally distributed with variance
+    // `expected_variance / n`. Reject if it falls outside the interval that holds it with
+    // probability `1 - 10^-5`.
+    let z_stat = Normal::new(0.0, 1.0)
+        .unwrap()
+        .inverse_cdf(0.00001 / 2.0)
+        .abs();
+    let mean = samples.iter().sum::<f64>() / n;
+    assert!(
+        mean.abs() < z_stat * (expected_variance / n).sqrt(),
+        "empirical mean {mean}, expected 0"
+    );
+
+    let variance = samples.iter().map(|x| x.powi(2)).sum::<f64>() / n;
+    assert!(
+        (variance - expected_variance).abs() < 0.1 * expected_variance,
+        "empirical variance {variance}, expected {expected_variance}"
+    );
+}
+
+/// The variance of the discrete Laplace distribution with scale parameter `scale`.
+fn discrete_laplace_variance(scale: f64) -> f64 {
+    let p = (-1.0 / scale).exp();
+    2.0 * p / (1.0 - p).powi(2)
+}
+
+fn laplace(epsilon: u8) -> PureDpDiscreteLaplace {
+    PureDpDiscreteLaplace::from_budget(
+        PureDpBudget::new(Rational::from_unsigned(epsilon, 1).unwrap()).unwrap(),
+    )
+}
+
+fn gaussian(epsilon: u8) -> ZCdpDiscreteGaussian {
+    ZCdpDiscreteGaussian::from_budget(ZCdpBudget::new(
+        Rational::from_unsigned(epsilon, 1).unwrap(),
+    ))
+}
+
+#[test]
+fn count_noise() {
+    // The sensitivity of a count is one.
+    let count = Count::<Field128>::new();
+    check_noise_moments(&count, &laplace(1), discrete_laplace_variance(1.0), 0);
+    // The variance of the discrete Gaussian is very close to sigma^2 for sigma >= 1.
+    check_noise_moments(&count, &gaussian(1), 1.0, 1);
+}
+
+#[test]
+fn epsilon_delta_count_noise() {
+    // A count is a scalar, so its noise is calibrated with the tighter (epsilon, delta) bound for
+    // one-dimensional queries rather than the one for vectors.
+    let strategy = EpsilonDeltaDiscreteGaussian::from_budget(
+        EpsilonDeltaBudget::new(
+            Rational::from_unsigned(1u32, 1).unwrap(),
+            Rational::from_unsigned(1u32, 100_000).unwrap(),
+        )
+        .unwrap(),
+    );
+    let sensitivity = Ratio::from_integer(BigUint::from(1u8));
+    let scalar_variance = strategy
+        .create_scalar_distribution(sensitivity.clone())
+        .unwrap()
+        .variance();
+    let vector_variance = strategy
+        .create_distribution(sensitivity)
+        .unwrap()
+        .variance();
+    assert!(scalar_variance < 0.9 * vector_variance);
+    check_noise_moments(&Count::<Field128>::new(), &strategy, scalar_variance, 8);
+}
+
+#[test]
+fn sum_noise() {
+    // The sensitivity of a sum is its maximum measurement.
+    let sum = Sum::<Field128>::new(10).unwrap();
+    check_noise_moments(&sum, &laplace(2), discrete_laplace_variance(5.0), 2);
+    check_noise_moments(&sum, &gaussian(2), 25.0, 3);
+}
+
+#[test]
+fn average_noise() {
+    // The average type adds noise to the sum, so it has the same sensitivity as the sum.
+    let average = Average::<Field128>::new(10).unwrap();
+    check_noise_moments(&average, &laplace(1), discrete_laplace_variance(10.0), 4);
+    check_noise_moments(&average, &gaussian(1), 100.0, 5);
+}
+
+#[test]
+fn multihot_count_vec_noise() {
+    // With a maximum weight of two, the L1 sensitivity is four and the L2 sensitivity is two.
+    let multihot: MultihotCountVec<Field128, ParallelSum<_, _>> =
+        MultihotCountVec::new(10, 2, 3).unwrap();
+    check_noise_moments(&multihot, &laplace(1), discrete_laplace_variance(4.0), 6);
+    check_noise_moments(&multihot, &gaussian(1), 4.0, 7);
+}